@@ -2,13 +2,69 @@
 //!
 //! This module handles loading and parsing `.claude/claudifier.json` configuration files.
 
+use crate::credential_cache;
 use crate::error::{NotificationError, Result};
+use crate::matcher::{self, MatchOptions};
+use crate::secrets::{self, DefaultSecretResolver, SecretResolver};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Config path that reads the configuration from stdin instead of a file.
+pub const STDIN_PATH: &str = "-";
+
+/// How many levels deep `include` files may nest.
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// Syntax of a configuration file, chosen by its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// `.json`, and the default for other extensions
+    Json,
+    /// `.yaml` or `.yml`
+    Yaml,
+    /// `.toml`
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Format of the config file at `path`.
+    ///
+    /// `.yaml`/`.yml` and `.toml` files are read as YAML and TOML; anything
+    /// else, stdin included, is read as JSON.
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("toml") => Self::Toml,
+            _ => Self::Json,
+        }
+    }
+
+    /// Parses `content` into the JSON value the config is deserialized from.
+    fn parse(self, content: &str) -> std::result::Result<Value, String> {
+        let parsed = match self {
+            Self::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+            Self::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+            Self::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+        };
+        parsed.map_err(|e| format!("Invalid {}: {}", self, e))
+    }
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Json => "JSON",
+            Self::Yaml => "YAML",
+            Self::Toml => "TOML",
+        })
+    }
+}
 
 /// Project-specific override configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,14 +77,69 @@ pub struct ProjectOverride {
 }
 
 /// Main configuration structure.
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct Config {
     /// List of notification handlers to configure
     pub handlers: Vec<HandlerConfig>,
 
+    /// Config files whose handlers are merged in by `load` (relative to this file)
+    #[serde(default)]
+    pub include: Vec<String>,
+
     /// Optional project-specific overrides based on path patterns
     #[serde(default)]
     pub overrides: Option<Vec<ProjectOverride>>,
+
+    /// Optional fallback handler fired when an event matches no other handler
+    #[serde(default)]
+    pub on_no_match: Option<HandlerConfig>,
+
+    /// Optional caching of file-based secrets in the OS keyring
+    #[serde(default)]
+    pub credential_cache: Option<CredentialCacheConfig>,
+
+    /// Answer the hook right away and run the handlers in a detached background process
+    #[serde(default)]
+    pub fire_and_forget: bool,
+
+    /// Extra fields merged into every hook response (e.g. `{"suppressOutput": true}`);
+    /// fields the hook itself sets always take precedence
+    #[serde(default)]
+    pub response_extra: serde_json::Map<String, Value>,
+
+    /// `path_pattern` of the override applied by `apply_overrides`, if any
+    #[serde(skip)]
+    pub active_override: Option<String>,
+}
+
+/// A problem in the configuration.
+///
+/// Reported for parts skipped by `Config::from_json_lenient`, and for settings
+/// that parse but can't work, such as an `escalate_to` naming no handler.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{location}: {message}")]
+pub struct ConfigError {
+    /// Where the problem is, e.g. `handlers[1] (slack)`
+    pub location: String,
+
+    /// Why it was skipped
+    pub message: String,
+}
+
+/// Settings for caching resolved secrets (requires the `keyring` feature).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialCacheConfig {
+    /// How long a cached secret stays fresh, in seconds (defaults to one hour)
+    #[serde(default = "default_credential_ttl")]
+    pub ttl_secs: u64,
+}
+
+fn default_credential_ttl() -> u64 {
+    3600
+}
+
+fn default_enabled() -> bool {
+    true
 }
 
 impl fmt::Debug for Config {
@@ -41,18 +152,64 @@ impl fmt::Debug for Config {
 }
 
 /// Match type for string matching
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum MatchType {
     /// Exact string match (default)
+    #[default]
     Exact,
     /// Regular expression match
     Regex,
+    /// Semver range match, e.g. `">=1.2.0"` against a version string
+    Semver,
+}
+
+/// Flags for a handler's regexes, written like inline regex flags (e.g. `"i"`).
+///
+/// `i` ignores case, `m` makes `^` and `$` match at line breaks, `s` lets `.`
+/// match newlines and `x` ignores whitespace and `#` comments in patterns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RegexFlags {
+    /// `i`: letters match regardless of case
+    pub case_insensitive: bool,
+    /// `m`: `^` and `$` match at the start and end of lines
+    pub multi_line: bool,
+    /// `s`: `.` also matches `\n`
+    pub dot_matches_new_line: bool,
+    /// `x`: whitespace and `#` comments in the pattern are ignored
+    pub ignore_whitespace: bool,
+}
+
+impl TryFrom<String> for RegexFlags {
+    type Error = String;
+
+    fn try_from(flags: String) -> std::result::Result<Self, Self::Error> {
+        let mut parsed = Self::default();
+        for flag in flags.chars() {
+            match flag {
+                'i' => parsed.case_insensitive = true,
+                'm' => parsed.multi_line = true,
+                's' => parsed.dot_matches_new_line = true,
+                'x' => parsed.ignore_whitespace = true,
+                other => return Err(format!("unknown regex flag `{}` (expected i, m, s or x)", other)),
+            }
+        }
+        Ok(parsed)
+    }
 }
 
-impl Default for MatchType {
-    fn default() -> Self {
-        MatchType::Exact
+impl From<RegexFlags> for String {
+    fn from(flags: RegexFlags) -> Self {
+        [
+            (flags.case_insensitive, 'i'),
+            (flags.multi_line, 'm'),
+            (flags.dot_matches_new_line, 's'),
+            (flags.ignore_whitespace, 'x'),
+        ]
+        .into_iter()
+        .filter_map(|(set, flag)| set.then_some(flag))
+        .collect()
     }
 }
 
@@ -66,16 +223,101 @@ pub struct HandlerConfig {
     #[serde(rename = "type")]
     pub handler_type: String,
 
-    /// Event matching criteria (optional - if None, matches all events)
-    #[serde(default)]
+    /// Set to false to silence the handler without removing it from the config
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Event matching criteria (optional - if None, matches all events).
+    /// Also accepted as `match`.
+    #[serde(default, alias = "match")]
     pub match_rules: Option<MatchRules>,
 
     /// Match type for string fields (defaults to "exact")
     #[serde(default)]
     pub match_type: MatchType,
 
+    /// Treat numeric strings and numbers as equal when matching (defaults to false)
+    #[serde(default)]
+    pub coerce_numbers: bool,
+
+    /// Flags for every regex in the handler's rules, e.g. `"i"` to ignore case
+    #[serde(default)]
+    pub match_flags: RegexFlags,
+
+    /// Rules that decide `{"tool_failed": ...}` in place of the built-in
+    /// failure heuristic (see [`Event::tool_failed`](crate::event::Event::tool_failed))
+    #[serde(default)]
+    pub tool_failed_when: Option<MatchRules>,
+
     /// Handler-specific configuration
     pub config: HashMap<String, Value>,
+
+    /// Alternative configs; the first variant whose rules match is merged over `config`
+    #[serde(default)]
+    pub variants: Vec<HandlerVariant>,
+
+    /// Environment variables that must all match for the handler to run
+    /// (a null value only requires the variable to be set)
+    #[serde(default)]
+    pub when_env: HashMap<String, Option<String>>,
+
+    /// Name of a handler to also fire when this one resolves to critical urgency
+    #[serde(default)]
+    pub escalate_to: Option<String>,
+
+    /// Handlers sharing a channel group run one at a time, in config order,
+    /// until one of them delivers; the rest are suppressed for that event
+    #[serde(default)]
+    pub channel_group: Option<String>,
+
+    /// Short template names for nested event values, as JSON Pointers
+    /// (e.g. `{"cmd": "/tool_input/command"}` makes `{{cmd}}` available)
+    #[serde(default)]
+    pub extract: HashMap<String, String>,
+
+    /// Boolean expression over event fields that must also hold for the handler
+    /// to run (e.g. `tool_name == "Bash" && exit_code != 0`); also accepted as
+    /// `filter`. Requires the `expr` feature.
+    #[serde(default, alias = "filter")]
+    pub expr: Option<String>,
+
+    /// Template for a `systemMessage` shown to the user when this handler
+    /// succeeds on a `Notification` event
+    #[serde(default)]
+    pub system_message: Option<String>,
+
+    /// Template for the reason a `Stop` or `SubagentStop` event is blocked when
+    /// this handler succeeds, keeping Claude working
+    #[serde(default)]
+    pub block_reason: Option<String>,
+
+    /// Template for context added to the prompt of a `UserPromptSubmit` event
+    /// when this handler succeeds
+    #[serde(default)]
+    pub additional_context: Option<String>,
+
+    /// Whether the hook response asks Claude Code to hide the hook's output
+    /// (`"suppressOutput": true`) when this handler succeeds, alongside any
+    /// `system_message`, `block_reason` or `additional_context`; ignored when the
+    /// handler returns a permission decision
+    #[serde(default)]
+    pub suppress_output: bool,
+
+    /// Longest a single call may run before it fails as timed out, in milliseconds
+    /// (defaults to the handler's [`default_timeout`](crate::handlers::Handler::default_timeout))
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Config overrides applied to a handler when an event matches the variant's rules.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandlerVariant {
+    /// Event matching criteria (None matches all events); also accepted as `match`
+    #[serde(default, alias = "match")]
+    pub match_rules: Option<MatchRules>,
+
+    /// Config keys that replace the handler's base config
+    pub config: HashMap<String, Value>,
 }
 
 impl fmt::Debug for HandlerConfig {
@@ -88,6 +330,32 @@ impl fmt::Debug for HandlerConfig {
     }
 }
 
+impl HandlerConfig {
+    /// Builds the matcher options requested by this handler.
+    pub fn match_options(&self) -> MatchOptions {
+        MatchOptions {
+            coerce_numbers: self.coerce_numbers,
+            regex_flags: self.match_flags,
+            tool_failed_when: self.tool_failed_when.clone(),
+            now: None,
+        }
+    }
+
+    /// Returns true if every `when_env` condition holds in the current environment.
+    pub fn env_enabled(&self) -> bool {
+        self.env_enabled_with(|name| std::env::var(name).ok())
+    }
+
+    /// Checks `when_env` against variables looked up with `lookup`.
+    fn env_enabled_with(&self, lookup: impl Fn(&str) -> Option<String>) -> bool {
+        self.when_env.iter().all(|(name, expected)| match (lookup(name), expected) {
+            (Some(actual), Some(expected)) => actual == *expected,
+            (Some(_), None) => true,
+            (None, _) => false,
+        })
+    }
+}
+
 /// Rules for matching events.
 ///
 /// # Serialization
@@ -118,32 +386,279 @@ pub enum MatchRules {
 impl Config {
     /// Loads configuration from a file path and resolves secrets.
     ///
+    /// The file is parsed as YAML or TOML when its extension says so, and as
+    /// JSON otherwise (see [`ConfigFormat::from_path`]). A path of
+    /// [`STDIN_PATH`] (`-`) reads JSON configuration from stdin.
+    ///
     /// # Errors
     ///
     /// Returns an error if the file cannot be read, parsed, or secrets cannot be resolved.
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let content = fs::read_to_string(path.as_ref()).map_err(|e| {
-            NotificationError::InvalidConfig(format!("Failed to read config file: {}", e))
-        })?;
+        let content = read_config(path.as_ref())?;
 
-        let mut config = Self::from_json(&content)?;
+        let mut config = Self::parse_unchecked(&content, ConfigFormat::from_path(path.as_ref()))?;
+        config.resolve_includes(path.as_ref())?;
+        config.check()?;
         config.resolve_secrets()?;
         Ok(config)
     }
 
+    /// Loads configuration like `load`, resolving secrets with a custom resolver.
+    ///
+    /// Use this to support extra `{{scheme.arg}}` secret schemes. The resolver
+    /// fully replaces the default one, so delegate to [`DefaultSecretResolver`]
+    /// to keep the built-in schemes. Each distinct placeholder is resolved only
+    /// once per load, however many handlers use it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, parsed, or secrets cannot be resolved.
+    pub fn load_with_resolver<P: AsRef<Path>>(path: P, resolver: &dyn SecretResolver) -> Result<Self> {
+        let content = read_config(path.as_ref())?;
+
+        let mut config = Self::parse_unchecked(&content, ConfigFormat::from_path(path.as_ref()))?;
+        config.resolve_includes(path.as_ref())?;
+        config.check()?;
+        config.resolve_secrets_with(resolver)?;
+        Ok(config)
+    }
+
     /// Parses configuration from a JSON string.
     ///
     /// # Errors
     ///
-    /// Returns an error if the JSON is invalid.
+    /// Returns an error if the JSON is invalid, or a handler setting can't work
+    /// (see [`Config::problems`]).
     pub fn from_json(json: &str) -> Result<Self> {
-        let config: Config = serde_json::from_str(json).map_err(|e| {
-            NotificationError::InvalidConfig(format!("Invalid JSON: {}", e))
-        })?;
+        Self::parse(json, ConfigFormat::Json)
+    }
 
+    /// Parses configuration written in `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the content is invalid, or a handler setting can't work
+    /// (see [`Config::problems`]).
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<Self> {
+        let config = Self::parse_unchecked(content, format)?;
+        config.check()?;
         Ok(config)
     }
 
+    /// Parses configuration without checking the settings that span handlers.
+    fn parse_unchecked(content: &str, format: ConfigFormat) -> Result<Self> {
+        if format == ConfigFormat::Json {
+            return serde_json::from_str(content)
+                .map_err(|e| NotificationError::InvalidConfig(format!("Invalid JSON: {}", e)));
+        }
+        let value = format.parse(content).map_err(NotificationError::InvalidConfig)?;
+        serde_json::from_value(value)
+            .map_err(|e| NotificationError::InvalidConfig(format!("Invalid {}: {}", format, e)))
+    }
+
+    /// Returns the first of the config's [`problems`](Config::problems) as an error.
+    fn check(&self) -> Result<()> {
+        match self.problems().into_iter().next() {
+            Some(problem) => Err(NotificationError::InvalidConfig(problem.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Finds handler settings that parse but can't work.
+    ///
+    /// Reports `escalate_to` names that match no handler, `expr` conditions
+    /// that don't parse or need the `expr` feature this build lacks, and match
+    /// rule regexes (including variants' and `tool_failed_when`) that don't compile. An
+    /// override's handlers escalate among themselves, since they replace the
+    /// base handlers.
+    pub fn problems(&self) -> Vec<ConfigError> {
+        let mut lists: Vec<(String, &[HandlerConfig], &[HandlerConfig])> =
+            vec![("handlers".to_string(), &self.handlers, &self.handlers)];
+        for (i, project_override) in self.overrides.iter().flatten().enumerate() {
+            let handlers = project_override.handlers.as_slice();
+            lists.push((format!("overrides[{}].handlers", i), handlers, handlers));
+        }
+        lists.extend(
+            self.on_no_match
+                .as_ref()
+                .map(|fallback| ("on_no_match".to_string(), std::slice::from_ref(fallback), self.handlers.as_slice())),
+        );
+
+        let mut problems = Vec::new();
+        for (location, handlers, targets) in lists {
+            for (i, handler) in handlers.iter().enumerate() {
+                let location = match location.as_str() {
+                    "on_no_match" => format!("on_no_match ({})", handler.name),
+                    list => format!("{}[{}] ({})", list, i, handler.name),
+                };
+                let mut report = |message: String| {
+                    problems.push(ConfigError {
+                        location: location.clone(),
+                        message,
+                    })
+                };
+
+                if let Some(target) = &handler.escalate_to {
+                    if !targets.iter().any(|t| &t.name == target) {
+                        report(format!("escalate_to names unknown handler '{}'", target));
+                    }
+                }
+                if let Some(expr) = &handler.expr {
+                    if let Err(e) = check_expr(expr) {
+                        report(e);
+                    }
+                }
+                let rules = [
+                    (&handler.match_rules, &handler.match_type),
+                    (&handler.tool_failed_when, &MatchType::Exact),
+                ];
+                let variant_rules = handler.variants.iter().map(|variant| (&variant.match_rules, &handler.match_type));
+                for (rules, match_type) in rules.into_iter().chain(variant_rules) {
+                    if let Err(e) = matcher::check_regexes(rules, match_type, handler.match_flags) {
+                        report(e);
+                    }
+                }
+            }
+        }
+        problems
+    }
+
+    /// Returns true if any handler template uses `{{prev.*}}` placeholders.
+    ///
+    /// Checks every string setting, including overrides and `on_no_match`, and
+    /// the contents of `body_file` templates. One-shot runs only keep the
+    /// session's previous event on disk when this holds.
+    pub fn uses_previous_event(&self) -> bool {
+        fn uses_prev(value: &Value) -> bool {
+            match value {
+                Value::String(s) => s.contains("{{prev."),
+                Value::Array(items) => items.iter().any(uses_prev),
+                Value::Object(fields) => fields.iter().any(|(key, value)| {
+                    uses_prev(value)
+                        || (key == "body_file"
+                            && value.as_str().is_some_and(|path| {
+                                fs::read_to_string(shellexpand::tilde(path).as_ref())
+                                    .is_ok_and(|body| body.contains("{{prev."))
+                            }))
+                }),
+                _ => false,
+            }
+        }
+        serde_json::to_value(self).is_ok_and(|config| uses_prev(&config))
+    }
+
+    /// Loads configuration like `load`, but skips malformed handlers.
+    ///
+    /// If the `include` files can't be loaded, the config's own handlers are
+    /// still used and the problem is reported as a `ConfigError`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or secrets cannot be resolved.
+    pub fn load_lenient<P: AsRef<Path>>(path: P) -> Result<(Self, Vec<ConfigError>)> {
+        let content = read_config(path.as_ref())?;
+
+        let (mut config, mut errors) = Self::parse_lenient_unchecked(&content, ConfigFormat::from_path(path.as_ref()));
+        if let Err(e) = config.resolve_includes(path.as_ref()) {
+            errors.push(ConfigError {
+                location: "include".to_string(),
+                message: e.to_string(),
+            });
+        }
+        errors.extend(config.problems());
+        config.resolve_secrets()?;
+        Ok((config, errors))
+    }
+
+    /// Parses configuration from a JSON string, deserializing each handler on its own.
+    ///
+    /// Handlers (and overrides) that fail to parse are left out and reported
+    /// alongside the config, so one bad block doesn't disable every notification.
+    /// If the document itself is unusable, an empty config is returned.
+    pub fn from_json_lenient(json: &str) -> (Self, Vec<ConfigError>) {
+        Self::parse_lenient(json, ConfigFormat::Json)
+    }
+
+    /// Parses configuration written in `format` like `from_json_lenient`.
+    ///
+    /// Handlers with [`problems`](Config::problems) are kept, and the problems reported.
+    pub fn parse_lenient(content: &str, format: ConfigFormat) -> (Self, Vec<ConfigError>) {
+        let (config, mut errors) = Self::parse_lenient_unchecked(content, format);
+        errors.extend(config.problems());
+        (config, errors)
+    }
+
+    /// Parses configuration leniently without checking the settings that span handlers.
+    fn parse_lenient_unchecked(content: &str, format: ConfigFormat) -> (Self, Vec<ConfigError>) {
+        let mut errors = Vec::new();
+
+        let mut root = match format.parse(content) {
+            Ok(Value::Object(root)) => root,
+            Ok(other) => {
+                errors.push(ConfigError {
+                    location: "config".to_string(),
+                    message: format!("Invalid {}: expected an object, got {}", format, other),
+                });
+                return (Self::default(), errors);
+            }
+            Err(message) => {
+                errors.push(ConfigError {
+                    location: "config".to_string(),
+                    message,
+                });
+                return (Self::default(), errors);
+            }
+        };
+
+        let handlers = root.insert("handlers".to_string(), Value::Array(Vec::new()));
+        let overrides = root.remove("overrides");
+
+        let mut config: Config = match serde_json::from_value(Value::Object(root)) {
+            Ok(config) => config,
+            Err(e) => {
+                errors.push(ConfigError {
+                    location: "config".to_string(),
+                    message: e.to_string(),
+                });
+                Self::default()
+            }
+        };
+
+        config.handlers = lenient_handlers("handlers", handlers, &mut errors);
+
+        if let Some(Value::Array(entries)) = overrides {
+            let mut parsed = Vec::new();
+            for (i, mut entry) in entries.into_iter().enumerate() {
+                let location = format!("overrides[{}]", i);
+                let handlers = entry
+                    .as_object_mut()
+                    .and_then(|o| o.insert("handlers".to_string(), Value::Array(Vec::new())));
+                match serde_json::from_value::<ProjectOverride>(entry) {
+                    Ok(mut project_override) => {
+                        project_override.handlers = lenient_handlers(
+                            &format!("{}.handlers", location),
+                            handlers,
+                            &mut errors,
+                        );
+                        parsed.push(project_override);
+                    }
+                    Err(e) => errors.push(ConfigError {
+                        location,
+                        message: e.to_string(),
+                    }),
+                }
+            }
+            config.overrides = Some(parsed);
+        } else if let Some(other) = overrides.filter(|v| !v.is_null()) {
+            errors.push(ConfigError {
+                location: "overrides".to_string(),
+                message: format!("expected an array, got {}", other),
+            });
+        }
+
+        (config, errors)
+    }
+
     /// Applies project-specific overrides based on the current project path.
     ///
     /// If multiple patterns match, the last match wins. If a pattern matches,
@@ -162,7 +677,7 @@ impl Config {
         for override_config in overrides {
             if glob::Pattern::new(&override_config.path_pattern)
                 .ok()
-                .and_then(|pattern| Some(pattern.matches(project_path)))
+                .map(|pattern| pattern.matches(project_path))
                 .unwrap_or(false)
             {
                 last_match = Some(override_config);
@@ -172,39 +687,67 @@ impl Config {
         // Apply the last matching override
         if let Some(matched_override) = last_match {
             self.handlers = matched_override.handlers.clone();
+            self.active_override = Some(matched_override.path_pattern.clone());
+        }
+    }
+
+    /// Merges in the handlers of each `include` file, resolved relative to `path`.
+    ///
+    /// Included handlers come first, in `include` order, followed by this
+    /// config's own handlers. On a name clash the config's own handler wins, and
+    /// a later include wins over an earlier one. Included files may include
+    /// others; only their handlers are used.
+    fn resolve_includes(&mut self, path: &Path) -> Result<()> {
+        if self.include.is_empty() {
+            return Ok(());
+        }
+
+        let mut stack = Vec::new();
+        if path != Path::new(STDIN_PATH) {
+            stack.push(fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()));
         }
+        let included = included_handlers(&self.include, &include_base_dir(path), &mut stack)?;
+
+        let local = std::mem::take(&mut self.handlers);
+        self.handlers = merge_handlers(included, local);
+        Ok(())
     }
 
-    /// Resolves secrets in configuration values.
+    /// Resolves secrets in configuration values using the default resolver.
     ///
-    /// Supports:
-    /// - `{{env.VAR_NAME}}` - Environment variables
-    /// - `{{file.path/to/file}}` - Read from file
-    /// - `{{keychain.service/key}}` - System keychain (future)
+    /// See [`DefaultSecretResolver`] for the supported schemes. File secrets are
+    /// cached when `credential_cache` is configured.
     ///
     /// Note: This is called automatically by `load()`. Only use this directly
     /// when working with configurations created via `from_json()`.
     fn resolve_secrets(&mut self) -> Result<()> {
+        let cache = self
+            .credential_cache
+            .as_ref()
+            .and_then(|c| credential_cache::default_cache(c.ttl_secs));
+        self.resolve_secrets_with(&DefaultSecretResolver::with_cache(cache))
+    }
+
+    /// Resolves secrets in all handler configs with the given resolver.
+    fn resolve_secrets_with(&mut self, resolver: &dyn SecretResolver) -> Result<()> {
+        // Shared secrets are looked up once and get one consistent value
+        let resolver = &secrets::MemoizedResolver::new(resolver);
+
         // Resolve secrets in main handlers
         for handler in &mut self.handlers {
-            for value in handler.config.values_mut() {
-                if let Some(s) = value.as_str() {
-                    let resolved = Self::resolve_secret_string(s)?;
-                    *value = Value::String(resolved);
-                }
-            }
+            Self::resolve_handler_secrets(handler, resolver)?;
+        }
+
+        // Resolve secrets in the fallback handler
+        if let Some(handler) = &mut self.on_no_match {
+            Self::resolve_handler_secrets(handler, resolver)?;
         }
 
         // Also resolve secrets in override handlers
         if let Some(overrides) = &mut self.overrides {
             for override_config in overrides {
                 for handler in &mut override_config.handlers {
-                    for value in handler.config.values_mut() {
-                        if let Some(s) = value.as_str() {
-                            let resolved = Self::resolve_secret_string(s)?;
-                            *value = Value::String(resolved);
-                        }
-                    }
+                    Self::resolve_handler_secrets(handler, resolver)?;
                 }
             }
         }
@@ -212,43 +755,181 @@ impl Config {
         Ok(())
     }
 
-    fn resolve_secret_string(s: &str) -> Result<String> {
-        let mut result = s.to_string();
-
-        // Environment variables: {{env.VAR_NAME}}
-        if let Some(start) = result.find("{{env.") {
-            if let Some(end) = result[start..].find("}}") {
-                let var_name = &result[start + 6..start + end];
-                let value = std::env::var(var_name).map_err(|_| {
-                    NotificationError::InvalidConfig(format!(
-                        "Environment variable not found: {}",
-                        var_name
-                    ))
-                })?;
-                result = result.replace(&format!("{{{{env.{}}}}}", var_name), &value);
+    /// Resolves secrets in a handler's config and in each of its variants.
+    fn resolve_handler_secrets(handler: &mut HandlerConfig, resolver: &dyn SecretResolver) -> Result<()> {
+        let variant_configs = handler.variants.iter_mut().map(|v| &mut v.config);
+        for config in std::iter::once(&mut handler.config).chain(variant_configs) {
+            for (key, value) in config.iter_mut() {
+                if let Some(s) = value.as_str() {
+                    // An unset `{{env.LANG}}` selects the default template rather than failing
+                    let resolved = if key == "lang" {
+                        secrets::resolve_placeholders(s, &UnsetEnvAsEmpty(resolver))?
+                    } else {
+                        secrets::resolve_placeholders(s, resolver)?
+                    };
+                    *value = Value::String(resolved);
+                }
             }
         }
+        Ok(())
+    }
 
-        // File: {{file.path/to/file}}
-        if let Some(start) = result.find("{{file.") {
-            if let Some(end) = result[start..].find("}}") {
-                let file_path = &result[start + 7..start + end];
-                let expanded_path = shellexpand::tilde(file_path);
-                let value = fs::read_to_string(expanded_path.as_ref())
-                    .map_err(|e| {
-                        NotificationError::InvalidConfig(format!(
-                            "Failed to read file {}: {}",
-                            file_path, e
-                        ))
-                    })?
-                    .trim()
-                    .to_string();
-                result = result.replace(&format!("{{{{file.{}}}}}", file_path), &value);
-            }
+    #[cfg(test)]
+    fn resolve_secret_string(s: &str) -> Result<String> {
+        secrets::resolve_placeholders(s, &DefaultSecretResolver::new())
+    }
+}
+
+/// Resolver that reads an unset `{{env.*}}` variable as empty instead of failing.
+///
+/// Every other scheme, and any other failure, goes through `.0` unchanged.
+struct UnsetEnvAsEmpty<'a>(&'a dyn SecretResolver);
+
+impl SecretResolver for UnsetEnvAsEmpty<'_> {
+    fn supports(&self, scheme: &str) -> bool {
+        self.0.supports(scheme)
+    }
+
+    fn resolve(&self, scheme: &str, arg: &str) -> Result<String> {
+        match self.0.resolve(scheme, arg) {
+            Err(_) if scheme == "env" && std::env::var_os(arg).is_none() => Ok(String::new()),
+            result => result,
         }
+    }
+}
 
-        Ok(result)
+/// Reads a config file, or stdin for [`STDIN_PATH`].
+fn read_config(path: &Path) -> Result<String> {
+    let content = if path == Path::new(STDIN_PATH) {
+        io::read_to_string(io::stdin())
+    } else {
+        fs::read_to_string(path)
+    };
+    content.map_err(|e| NotificationError::InvalidConfig(format!("Failed to read config file: {}", e)))
+}
+
+/// Directory that `include` paths in the config at `path` are relative to.
+///
+/// For a config read from stdin this is the current directory.
+fn include_base_dir(path: &Path) -> PathBuf {
+    if path == Path::new(STDIN_PATH) {
+        return PathBuf::new();
     }
+    path.parent().map(Path::to_path_buf).unwrap_or_default()
+}
+
+/// Loads the handlers of each included file, following nested includes.
+///
+/// `stack` holds the canonical paths of the files currently being loaded, to
+/// detect cycles and bound the nesting depth.
+fn included_handlers(includes: &[String], base_dir: &Path, stack: &mut Vec<PathBuf>) -> Result<Vec<HandlerConfig>> {
+    let mut handlers = Vec::new();
+
+    for include in includes {
+        let path = base_dir.join(shellexpand::tilde(include).as_ref());
+        let canonical = fs::canonicalize(&path).map_err(|e| {
+            NotificationError::InvalidConfig(format!("Failed to read include {}: {}", path.display(), e))
+        })?;
+
+        if stack.contains(&canonical) {
+            let chain: Vec<String> = stack
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(NotificationError::InvalidConfig(format!(
+                "Include cycle: {}",
+                chain.join(" -> ")
+            )));
+        }
+        if stack.len() > MAX_INCLUDE_DEPTH {
+            return Err(NotificationError::InvalidConfig(format!(
+                "Includes nested more than {} levels deep at {}",
+                MAX_INCLUDE_DEPTH,
+                path.display()
+            )));
+        }
+
+        let content = fs::read_to_string(&canonical).map_err(|e| {
+            NotificationError::InvalidConfig(format!("Failed to read include {}: {}", path.display(), e))
+        })?;
+        let config = Config::parse(&content, ConfigFormat::from_path(&path)).map_err(|e| {
+            NotificationError::InvalidConfig(format!("Invalid include {}: {}", path.display(), e))
+        })?;
+
+        stack.push(canonical);
+        let nested = included_handlers(&config.include, &include_base_dir(&path), stack)?;
+        stack.pop();
+
+        handlers = merge_handlers(handlers, merge_handlers(nested, config.handlers));
+    }
+
+    Ok(handlers)
+}
+
+/// Appends `preferred` to `base`, dropping `base` handlers whose name `preferred` also uses.
+fn merge_handlers(base: Vec<HandlerConfig>, preferred: Vec<HandlerConfig>) -> Vec<HandlerConfig> {
+    let names: std::collections::HashSet<&str> = preferred.iter().map(|h| h.name.as_str()).collect();
+    let mut merged: Vec<HandlerConfig> = base.into_iter().filter(|h| !names.contains(h.name.as_str())).collect();
+    merged.extend(preferred);
+    merged
+}
+
+/// Checks that a handler's `expr` condition can be evaluated.
+#[cfg(feature = "expr")]
+fn check_expr(expr: &str) -> std::result::Result<(), String> {
+    crate::expr::check(expr).map_err(|e| format!("invalid expr '{}': {}", expr, e))
+}
+
+/// Without the `expr` feature no condition can be evaluated.
+#[cfg(not(feature = "expr"))]
+fn check_expr(_expr: &str) -> std::result::Result<(), String> {
+    Err("expr needs boopifier built with the `expr` feature".to_string())
+}
+
+/// Deserializes a `handlers` array entry by entry, recording the ones that fail.
+fn lenient_handlers(
+    location: &str,
+    value: Option<Value>,
+    errors: &mut Vec<ConfigError>,
+) -> Vec<HandlerConfig> {
+    let entries = match value {
+        Some(Value::Array(entries)) => entries,
+        None => {
+            errors.push(ConfigError {
+                location: location.to_string(),
+                message: "missing field `handlers`".to_string(),
+            });
+            return Vec::new();
+        }
+        Some(other) => {
+            errors.push(ConfigError {
+                location: location.to_string(),
+                message: format!("expected an array, got {}", other),
+            });
+            return Vec::new();
+        }
+    };
+
+    entries
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, entry)| {
+            let name = entry.get("name").and_then(Value::as_str).map(str::to_string);
+            serde_json::from_value::<HandlerConfig>(entry)
+                .map_err(|e| {
+                    let location = match name {
+                        Some(name) => format!("{}[{}] ({})", location, i, name),
+                        None => format!("{}[{}]", location, i),
+                    };
+                    errors.push(ConfigError {
+                        location,
+                        message: e.to_string(),
+                    });
+                })
+                .ok()
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -272,6 +953,462 @@ mod tests {
         assert_eq!(config.handlers.len(), 1);
         assert_eq!(config.handlers[0].name, "test-handler");
         assert_eq!(config.handlers[0].handler_type, "desktop");
+        match &config.handlers[0].match_rules {
+            Some(MatchRules::Simple(rules)) => assert_eq!(rules["event_type"], "success"),
+            other => panic!("expected simple match rules, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_match_and_match_rules_keys() {
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "a", "type": "desktop", "match_rules": {"hook_event_name": "Stop"}, "config": {}},
+                {"name": "b", "type": "desktop", "match": {"hook_event_name": "Stop"}, "config": {},
+                 "variants": [{"match": {"tool_name": "Bash"}, "config": {}}]}
+            ]}"#,
+        )
+        .unwrap();
+
+        for handler in &config.handlers {
+            assert!(handler.match_rules.is_some(), "{} lost its rules", handler.name);
+        }
+        assert!(config.handlers[1].variants[0].match_rules.is_some());
+
+        // Giving both spellings is ambiguous
+        assert!(Config::from_json(
+            r#"{"handlers": [{"name": "c", "type": "desktop", "match": {}, "match_rules": {}, "config": {}}]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        let json = r#"{
+            "handlers": [{
+                "name": "stable",
+                "type": "desktop",
+                "match": {"hook_event_name": "Notification"},
+                "match_type": "regex",
+                "coerce_numbers": true,
+                "config": {"urgency": "normal", "body": "{{message}}"},
+                "variants": [{"match_rules": {"tool_name": "Bash"}, "config": {"urgency": "critical"}}],
+                "when_env": {"WORK_MACHINE": "1", "VPN": null},
+                "escalate_to": "phone"
+            }, {
+                "name": "phone",
+                "type": "noop",
+                "config": {}
+            }]
+        }"#;
+
+        let config = Config::from_json(json).unwrap();
+        let serialized = serde_json::to_value(&config).unwrap();
+        assert_eq!(
+            serialized["handlers"][0]["match_rules"],
+            serde_json::json!({"hook_event_name": "Notification"})
+        );
+
+        // Serializing the re-parsed config gives the same JSON
+        let reparsed = Config::from_json(&serialized.to_string()).unwrap();
+        assert_eq!(serde_json::to_value(&reparsed).unwrap(), serialized);
+    }
+
+    #[test]
+    fn test_match_flags() {
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "a", "type": "desktop", "match_type": "regex", "match_flags": "is", "config": {}},
+                {"name": "b", "type": "desktop", "config": {}}
+            ]}"#,
+        )
+        .unwrap();
+        let flags = config.handlers[0].match_options().regex_flags;
+        assert!(flags.case_insensitive && flags.dot_matches_new_line);
+        assert!(!flags.multi_line && !flags.ignore_whitespace);
+        assert_eq!(config.handlers[1].match_flags, RegexFlags::default());
+        assert_eq!(serde_json::to_value(&config.handlers[0]).unwrap()["match_flags"], "is");
+
+        let err = Config::from_json(r#"{"handlers": [{"name": "a", "type": "desktop", "match_flags": "iq", "config": {}}]}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown regex flag `q`"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_on_no_match() {
+        let json = r#"{
+            "handlers": [],
+            "on_no_match": {"name": "unmatched", "type": "desktop", "config": {"body": "{{hook_event_name}}"}}
+        }"#;
+
+        let config = Config::from_json(json).unwrap();
+        let fallback = config.on_no_match.unwrap();
+        assert_eq!(fallback.name, "unmatched");
+        assert_eq!(fallback.handler_type, "desktop");
+    }
+
+    #[test]
+    fn test_from_json_lenient_skips_malformed_handler() {
+        let json = r#"{
+            "handlers": [
+                {"name": "good", "type": "desktop", "config": {}},
+                {"name": "bad", "type": "sound", "match_type": "fuzzy", "config": {}}
+            ],
+            "overrides": [
+                {"path_pattern": "/work/*", "handlers": [{"name": "no-type", "config": {}}]}
+            ]
+        }"#;
+
+        // Strict parsing still rejects the whole file
+        assert!(Config::from_json(json).is_err());
+
+        let (config, errors) = Config::from_json_lenient(json);
+        assert_eq!(config.handlers.len(), 1);
+        assert_eq!(config.handlers[0].name, "good");
+        assert!(config.overrides.as_ref().unwrap()[0].handlers.is_empty());
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].location, "handlers[1] (bad)");
+        assert_eq!(errors[1].location, "overrides[0].handlers[0] (no-type)");
+    }
+
+    #[test]
+    fn test_config_format_from_path() {
+        assert_eq!(ConfigFormat::from_path(Path::new("boopifier.json")), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::from_path(Path::new("a/boopifier.yaml")), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::from_path(Path::new("boopifier.YML")), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::from_path(Path::new("boopifier.toml")), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::from_path(Path::new("boopifier.conf")), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::from_path(Path::new(STDIN_PATH)), ConfigFormat::Json);
+    }
+
+    #[test]
+    fn test_parse_yaml_and_toml() {
+        let json = r#"{
+            "handlers": [
+                {
+                    "name": "done",
+                    "type": "desktop",
+                    "match": {"hook_event_name": "Stop"},
+                    "config": {"summary": "Done", "timeout": 5000}
+                },
+                {"name": "chime", "type": "sound", "enabled": false, "config": {"file": "~/chime.wav"}}
+            ],
+            "include": ["team.json"]
+        }"#;
+        let yaml = r#"
+handlers:
+  - name: done
+    type: desktop
+    match:
+      hook_event_name: Stop
+    config:
+      summary: Done
+      timeout: 5000
+  - name: chime
+    type: sound
+    enabled: false
+    config:
+      file: ~/chime.wav
+include:
+  - team.json
+"#;
+        let toml = r#"
+include = ["team.json"]
+
+[[handlers]]
+name = "done"
+type = "desktop"
+match = { hook_event_name = "Stop" }
+config = { summary = "Done", timeout = 5000 }
+
+[[handlers]]
+name = "chime"
+type = "sound"
+enabled = false
+config = { file = "~/chime.wav" }
+"#;
+
+        let expected = serde_json::to_value(Config::from_json(json).unwrap()).unwrap();
+        for (content, format) in [(yaml, ConfigFormat::Yaml), (toml, ConfigFormat::Toml)] {
+            let config = Config::parse(content, format).unwrap();
+            assert_eq!(serde_json::to_value(&config).unwrap(), expected, "{}", format);
+            assert!(!config.handlers[1].enabled);
+        }
+
+        let err = Config::parse("handlers: [", ConfigFormat::Yaml).unwrap_err().to_string();
+        assert!(err.contains("Invalid YAML"), "{}", err);
+        let err = Config::parse("[[handlers]]\nname = 1", ConfigFormat::Toml).unwrap_err().to_string();
+        assert!(err.contains("Invalid TOML"), "{}", err);
+    }
+
+    #[test]
+    fn test_load_by_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("boopifier.yaml");
+        fs::write(
+            &path,
+            "include: [team.toml]\nhandlers:\n  - {name: local, type: desktop, config: {}}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("team.toml"),
+            "[[handlers]]\nname = \"team\"\ntype = \"webhook\"\nconfig = { url = \"https://example.com\" }\n",
+        )
+        .unwrap();
+
+        let config = Config::load(&path).unwrap();
+        let names: Vec<&str> = config.handlers.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, ["team", "local"]);
+
+        let (config, errors) = Config::load_lenient(&path).unwrap();
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(config.handlers.len(), 2);
+
+        // Lenient loading skips bad handlers in YAML too
+        fs::write(&path, "handlers:\n  - {name: ok, type: desktop, config: {}}\n  - {name: bad, config: {}}\n").unwrap();
+        let (config, errors) = Config::load_lenient(&path).unwrap();
+        assert_eq!(config.handlers.len(), 1);
+        assert_eq!(errors[0].location, "handlers[1] (bad)");
+    }
+
+    #[test]
+    fn test_unknown_escalation_target() {
+        let json = r#"{
+            "handlers": [{"name": "phone", "type": "noop", "config": {}}],
+            "overrides": [{"path_pattern": "/work/*", "handlers": [
+                {"name": "desk", "type": "noop", "escalate_to": "phone", "config": {}}
+            ]}],
+            "on_no_match": {"name": "chime", "type": "noop", "escalate_to": "phone", "config": {}}
+        }"#;
+        let (config, errors) = Config::from_json_lenient(json);
+        assert_eq!(config.overrides.as_ref().unwrap()[0].handlers.len(), 1);
+        let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(errors, ["overrides[0].handlers[0] (desk): escalate_to names unknown handler 'phone'"]);
+        assert!(Config::from_json(json).is_err());
+
+        let json = json.replace(r#""desk", "type": "noop", "escalate_to": "phone""#, r#""desk", "type": "noop""#);
+        assert!(Config::from_json(&json).is_ok());
+    }
+
+    #[test]
+    fn test_uses_previous_event() {
+        let config = |handler: &str| Config::from_json(&format!(r#"{{"handlers": [{}]}}"#, handler)).unwrap();
+        assert!(!config(r#"{"name": "a", "type": "noop", "config": {"body": "{{message}}"}}"#).uses_previous_event());
+        assert!(config(r#"{"name": "a", "type": "noop", "system_message": "was {{prev.message}}", "config": {}}"#)
+            .uses_previous_event());
+
+        let dir = tempfile::tempdir().unwrap();
+        let body = dir.path().join("body.txt");
+        fs::write(&body, "Before: {{prev.tool_name}}").unwrap();
+        let handler = serde_json::json!({"name": "a", "type": "noop", "config": {"body_file": body}});
+        assert!(config(&handler.to_string()).uses_previous_event());
+    }
+
+    #[test]
+    fn test_expr_checked_at_load() {
+        let json = r#"{"handlers": [{"name": "a", "type": "noop", "expr": "(exit_code != 0", "config": {}}]}"#;
+        let (_, errors) = Config::from_json_lenient(json);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().starts_with("handlers[0] (a): "), "{}", errors[0]);
+        assert!(Config::from_json(json).is_err());
+
+        let json = r#"{"handlers": [{"name": "a", "type": "noop", "expr": "exit_code != 0", "config": {}}]}"#;
+        if cfg!(feature = "expr") {
+            assert!(Config::from_json(json).is_ok());
+        } else {
+            let err = Config::from_json(json).unwrap_err().to_string();
+            assert!(err.contains("expr needs boopifier built with the `expr` feature"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_regexes_checked_at_load() {
+        let json = r#"{"handlers": [{"name": "a", "type": "noop", "match_type": "regex",
+            "variants": [{"match": {"message": "(unclosed"}, "config": {}}], "config": {}}]}"#;
+        let err = Config::from_json(json).unwrap_err().to_string();
+        assert!(err.contains("handlers[0] (a): invalid regex '(unclosed' in match rules"), "{}", err);
+
+        let json = r#"{"handlers": [{"name": "a", "type": "noop", "tool_failed_when": {"error": {"regex": "x("}},
+            "config": {}}]}"#;
+        assert!(Config::from_json(json).is_err());
+        assert_eq!(Config::from_json_lenient(json).1.len(), 1);
+    }
+
+    #[test]
+    fn test_from_json_lenient_invalid_json() {
+        let (config, errors) = Config::from_json_lenient("{not json");
+        assert!(config.handlers.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].location, "config");
+    }
+
+    #[test]
+    fn test_unresolved_lang_falls_back() {
+        std::env::remove_var("TEST_UNSET_LANG");
+        let mut config = Config::from_json(
+            r#"{"handlers": [{"name": "done", "type": "noop", "config": {
+                "lang": "{{env.TEST_UNSET_LANG}}", "templates": {"de": "Fertig", "default": "Done"}
+            }}]}"#,
+        )
+        .unwrap();
+
+        config.resolve_secrets().unwrap();
+        let settings = &config.handlers[0].config;
+        assert_eq!(settings["lang"], "");
+        assert_eq!(crate::handlers::message_template(settings, "body"), Some(&Value::from("Done")));
+
+        // Other settings still fail
+        config.handlers[0].config.insert("url".to_string(), Value::from("{{env.TEST_UNSET_LANG}}"));
+        assert!(config.resolve_secrets().is_err());
+
+        // So does a language from a missing file, which is likely a typo
+        config.handlers[0].config.remove("url");
+        config.handlers[0].config.insert("lang".to_string(), Value::from("{{file./nonexistent/boopifier-lang}}"));
+        assert!(config.resolve_secrets().is_err());
+    }
+
+    #[test]
+    fn test_variant_secrets_resolved() {
+        std::env::set_var("TEST_VARIANT_URL", "https://example.com/hook");
+        let mut config = Config::from_json(
+            r#"{"handlers": [{
+                "name": "hook",
+                "type": "webhook",
+                "config": {},
+                "variants": [{"match_rules": {"hook_event_name": "Stop"}, "config": {"url": "{{env.TEST_VARIANT_URL}}"}}]
+            }]}"#,
+        )
+        .unwrap();
+
+        config.resolve_secrets().unwrap();
+        assert_eq!(config.handlers[0].variants[0].config["url"], "https://example.com/hook");
+    }
+
+    #[test]
+    fn test_when_env() {
+        let config = Config::from_json(
+            r#"{"handlers": [{
+                "name": "work",
+                "type": "desktop",
+                "config": {},
+                "when_env": {"WORK_MACHINE": "1", "WORK_VPN": null}
+            }]}"#,
+        )
+        .unwrap();
+        let handler = &config.handlers[0];
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+
+        assert!(handler.env_enabled_with(env(&[("WORK_MACHINE", "1"), ("WORK_VPN", "")])));
+        assert!(!handler.env_enabled_with(env(&[("WORK_MACHINE", "0"), ("WORK_VPN", "on")])));
+        assert!(!handler.env_enabled_with(env(&[("WORK_MACHINE", "1")])));
+        assert!(!handler.env_enabled_with(env(&[])));
+
+        // Handlers without `when_env` always run
+        let config = Config::from_json(r#"{"handlers": [{"name": "a", "type": "desktop", "config": {}}]}"#).unwrap();
+        assert!(config.handlers[0].when_env.is_empty());
+        assert!(config.handlers[0].env_enabled_with(env(&[])));
+    }
+
+    /// Adds a `{{vault.path}}` scheme on top of the built-in ones.
+    struct VaultResolver {
+        builtin: DefaultSecretResolver,
+    }
+
+    impl SecretResolver for VaultResolver {
+        fn supports(&self, scheme: &str) -> bool {
+            scheme == "vault" || self.builtin.supports(scheme)
+        }
+
+        fn resolve(&self, scheme: &str, arg: &str) -> Result<String> {
+            match (scheme, arg) {
+                ("vault", "slack/webhook") => Ok("https://hooks.slack.example/abc".to_string()),
+                ("vault", _) => Err(NotificationError::InvalidConfig(format!("No vault secret {}", arg))),
+                _ => self.builtin.resolve(scheme, arg),
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_with_custom_resolver() {
+        std::env::set_var("TEST_VAULT_CHANNEL", "#alerts");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("boopifier.json");
+        fs::write(
+            &path,
+            r#"{"handlers": [{
+                "name": "slack",
+                "type": "webhook",
+                "config": {
+                    "url": "{{vault.slack/webhook}}",
+                    "channel": "{{env.TEST_VAULT_CHANNEL}}",
+                    "text": "{{capture.tool}} via {{hook_event_name}}"
+                }
+            }]}"#,
+        )
+        .unwrap();
+
+        let resolver = VaultResolver {
+            builtin: DefaultSecretResolver::new(),
+        };
+        let config = Config::load_with_resolver(&path, &resolver).unwrap();
+        let handler = &config.handlers[0].config;
+        assert_eq!(handler["url"], "https://hooks.slack.example/abc");
+        assert_eq!(handler["channel"], "#alerts");
+        assert_eq!(handler["text"], "{{capture.tool}} via {{hook_event_name}}");
+
+        // Without the custom resolver the vault placeholder is left alone
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.handlers[0].config["url"], "{{vault.slack/webhook}}");
+
+        fs::write(&path, r#"{"handlers": [{"name": "x", "type": "webhook", "config": {"url": "{{vault.missing}}"}}]}"#)
+            .unwrap();
+        assert!(Config::load_with_resolver(&path, &resolver).is_err());
+    }
+
+    /// Delegates to the built-in resolver, counting `{{file.*}}` reads.
+    struct FileReadCounter {
+        builtin: DefaultSecretResolver,
+        reads: std::cell::Cell<usize>,
+    }
+
+    impl SecretResolver for FileReadCounter {
+        fn supports(&self, scheme: &str) -> bool {
+            self.builtin.supports(scheme)
+        }
+
+        fn resolve(&self, scheme: &str, arg: &str) -> Result<String> {
+            if scheme == "file" {
+                self.reads.set(self.reads.get() + 1);
+            }
+            self.builtin.resolve(scheme, arg)
+        }
+    }
+
+    #[test]
+    fn test_shared_file_secret_read_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let token = dir.path().join("token");
+        fs::write(&token, "s3cret\n").unwrap();
+        let path = dir.path().join("boopifier.json");
+        let config = serde_json::json!({
+            "handlers": [
+                {"name": "a", "type": "webhook", "config": {"token": format!("{{{{file.{}}}}}", token.display())}},
+                {"name": "b", "type": "webhook", "config": {"auth": format!("Bearer {{{{file.{}}}}}", token.display())}}
+            ]
+        });
+        fs::write(&path, config.to_string()).unwrap();
+
+        let resolver = FileReadCounter {
+            builtin: DefaultSecretResolver::new(),
+            reads: std::cell::Cell::new(0),
+        };
+        let config = Config::load_with_resolver(&path, &resolver).unwrap();
+        assert_eq!(config.handlers[0].config["token"], "s3cret");
+        assert_eq!(config.handlers[1].config["auth"], "Bearer s3cret");
+        assert_eq!(resolver.reads.get(), 1);
     }
 
     #[test]
@@ -281,6 +1418,42 @@ mod tests {
         assert_eq!(resolved, "prefix_secret_value_suffix");
     }
 
+    #[test]
+    fn test_dotenv_resolution() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".env");
+        fs::write(
+            &path,
+            "# secrets\nexport API_TOKEN = abc123\nQUOTED=\"hello world\"\nSINGLE='x=y'\n",
+        )
+        .unwrap();
+        let path = path.display();
+
+        let resolved =
+            Config::resolve_secret_string(&format!("Bearer {{{{dotenv.{}:API_TOKEN}}}}", path))
+                .unwrap();
+        assert_eq!(resolved, "Bearer abc123");
+
+        let resolved =
+            Config::resolve_secret_string(&format!("{{{{dotenv.{}:QUOTED}}}}", path)).unwrap();
+        assert_eq!(resolved, "hello world");
+
+        let resolved =
+            Config::resolve_secret_string(&format!("{{{{dotenv.{}:SINGLE}}}}", path)).unwrap();
+        assert_eq!(resolved, "x=y");
+    }
+
+    #[test]
+    fn test_dotenv_missing_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".env");
+        fs::write(&path, "PRESENT=1\n").unwrap();
+
+        let err = Config::resolve_secret_string(&format!("{{{{dotenv.{}:ABSENT}}}}", path.display()))
+            .unwrap_err();
+        assert!(matches!(err, NotificationError::InvalidConfig(_)));
+    }
+
     #[test]
     fn test_override_exact_match() {
         let json = r#"{
@@ -341,6 +1514,7 @@ mod tests {
         let mut config = Config::from_json(json).unwrap();
         config.apply_overrides("/home/user/work/special");
         assert_eq!(config.handlers[0].name, "work-special");
+        assert_eq!(config.active_override.as_deref(), Some("/home/user/work/special"));
     }
 
     #[test]
@@ -358,6 +1532,7 @@ mod tests {
         let mut config = Config::from_json(json).unwrap();
         config.apply_overrides("/home/user/personal/project");
         assert_eq!(config.handlers[0].name, "base");
+        assert_eq!(config.active_override, None);
     }
 
     #[test]
@@ -370,4 +1545,97 @@ mod tests {
         config.apply_overrides("/any/path");
         assert_eq!(config.handlers[0].name, "base");
     }
+
+    fn handler_json(name: &str, body: &str) -> String {
+        format!(r#"{{"name": "{}", "type": "desktop", "config": {{"body": "{}"}}}}"#, name, body)
+    }
+
+    #[test]
+    fn test_include_merges_handlers() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = dir.path().join("shared");
+        fs::create_dir(&shared).unwrap();
+        fs::write(
+            shared.join("webhooks.json"),
+            format!(
+                r#"{{"include": ["base.json"], "handlers": [{}, {}]}}"#,
+                handler_json("slack", "shared"),
+                handler_json("discord", "shared")
+            ),
+        )
+        .unwrap();
+        fs::write(
+            shared.join("base.json"),
+            format!(r#"{{"handlers": [{}, {}]}}"#, handler_json("sound", "base"), handler_json("slack", "base")),
+        )
+        .unwrap();
+        fs::write(
+            shared.join("later.json"),
+            format!(r#"{{"handlers": [{}]}}"#, handler_json("discord", "later")),
+        )
+        .unwrap();
+
+        let path = dir.path().join("boopifier.json");
+        fs::write(
+            &path,
+            format!(
+                r#"{{"include": ["shared/webhooks.json", "shared/later.json"], "handlers": [{}]}}"#,
+                handler_json("slack", "local")
+            ),
+        )
+        .unwrap();
+
+        let config = Config::load(&path).unwrap();
+        let handlers: Vec<(&str, &str)> = config
+            .handlers
+            .iter()
+            .map(|h| (h.name.as_str(), h.config["body"].as_str().unwrap()))
+            .collect();
+        assert_eq!(handlers, [("sound", "base"), ("discord", "later"), ("slack", "local")]);
+    }
+
+    #[test]
+    fn test_include_cycle_and_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.json");
+        fs::write(&a, r#"{"include": ["b.json"], "handlers": []}"#).unwrap();
+        fs::write(dir.path().join("b.json"), r#"{"include": ["./a.json"], "handlers": []}"#).unwrap();
+
+        let err = Config::load(&a).unwrap_err().to_string();
+        assert!(err.contains("Include cycle"), "{}", err);
+        assert!(err.contains("b.json -> "), "{}", err);
+
+        // A file including itself is a cycle too
+        fs::write(&a, r#"{"include": ["a.json"], "handlers": []}"#).unwrap();
+        assert!(Config::load(&a).unwrap_err().to_string().contains("Include cycle"));
+
+        fs::write(&a, r#"{"include": ["missing.json"], "handlers": []}"#).unwrap();
+        let err = Config::load(&a).unwrap_err().to_string();
+        assert!(err.contains("Failed to read include"), "{}", err);
+
+        // Lenient loading keeps the config's own handlers
+        fs::write(&a, format!(r#"{{"include": ["missing.json"], "handlers": [{}]}}"#, handler_json("own", "x"))).unwrap();
+        let (config, errors) = Config::load_lenient(&a).unwrap();
+        assert_eq!(config.handlers.len(), 1);
+        assert_eq!(errors[0].location, "include");
+    }
+
+    #[test]
+    fn test_include_depth_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..=MAX_INCLUDE_DEPTH + 1 {
+            fs::write(
+                dir.path().join(format!("{}.json", i)),
+                format!(r#"{{"include": ["{}.json"], "handlers": []}}"#, i + 1),
+            )
+            .unwrap();
+        }
+        fs::write(dir.path().join(format!("{}.json", MAX_INCLUDE_DEPTH + 2)), r#"{"handlers": []}"#).unwrap();
+
+        let err = Config::load(dir.path().join("0.json")).unwrap_err().to_string();
+        assert!(err.contains("nested more than"), "{}", err);
+
+        // Exactly at the limit is fine
+        assert!(Config::load(dir.path().join("2.json")).is_ok());
+    }
 }
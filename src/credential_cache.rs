@@ -0,0 +1,207 @@
+//! Credential caching for resolved secrets.
+//!
+//! Reading secrets from files on every invocation is slow and widens the window in
+//! which plaintext is touched. When `credential_cache` is configured, resolved
+//! `{{file.*}}` secrets are cached in a [`CredentialStore`] (the OS keyring with the
+//! `keyring` feature) and re-read from disk only once the cached copy is stale.
+
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Service name used for keyring entries.
+pub const KEYRING_SERVICE: &str = "boopifier";
+
+/// Storage backend for cached credentials.
+pub trait CredentialStore: Send + Sync {
+    /// Gets the raw stored value for a key, if any.
+    fn get(&self, key: &str) -> Option<String>;
+
+    /// Stores a raw value for a key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend rejects the write.
+    fn set(&self, key: &str, value: &str) -> Result<()>;
+}
+
+/// A cached secret together with when it was fetched.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedCredential {
+    value: String,
+    fetched_at: u64,
+}
+
+/// Time-bounded cache for secrets on top of a [`CredentialStore`].
+pub struct CredentialCache {
+    store: Box<dyn CredentialStore>,
+    ttl_secs: u64,
+}
+
+impl CredentialCache {
+    /// Creates a cache that treats entries older than `ttl_secs` as stale.
+    pub fn new(store: Box<dyn CredentialStore>, ttl_secs: u64) -> Self {
+        Self { store, ttl_secs }
+    }
+
+    /// Returns the cached value for `key` if fresh, otherwise calls `fetch` and caches the result.
+    ///
+    /// Failing to write the cache is not an error; the fetched value is still returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from `fetch` on a cache miss.
+    pub fn get_or_fetch<F>(&self, key: &str, fetch: F) -> Result<String>
+    where
+        F: FnOnce() -> Result<String>,
+    {
+        let now = unix_now();
+
+        if let Some(cached) = self
+            .store
+            .get(key)
+            .and_then(|raw| serde_json::from_str::<CachedCredential>(&raw).ok())
+        {
+            if now.saturating_sub(cached.fetched_at) < self.ttl_secs {
+                return Ok(cached.value);
+            }
+        }
+
+        let value = fetch()?;
+        let entry = CachedCredential {
+            value: value.clone(),
+            fetched_at: now,
+        };
+        if let Ok(raw) = serde_json::to_string(&entry) {
+            let _ = self.store.set(key, &raw);
+        }
+        Ok(value)
+    }
+}
+
+/// Credential store backed by the OS keyring.
+#[cfg(feature = "keyring")]
+pub struct KeyringStore;
+
+#[cfg(feature = "keyring")]
+impl CredentialStore for KeyringStore {
+    fn get(&self, key: &str) -> Option<String> {
+        keyring::Entry::new(KEYRING_SERVICE, key)
+            .ok()?
+            .get_password()
+            .ok()
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<()> {
+        keyring::Entry::new(KEYRING_SERVICE, key)
+            .and_then(|entry| entry.set_password(value))
+            .map_err(|e| {
+                crate::error::NotificationError::Handler(format!("Failed to write keyring entry: {}", e))
+            })
+    }
+}
+
+/// Builds the default cache for this build, if one is available.
+///
+/// Returns `None` when boopifier was built without the `keyring` feature.
+pub fn default_cache(ttl_secs: u64) -> Option<CredentialCache> {
+    #[cfg(feature = "keyring")]
+    {
+        Some(CredentialCache::new(Box::new(KeyringStore), ttl_secs))
+    }
+    #[cfg(not(feature = "keyring"))]
+    {
+        let _ = ttl_secs;
+        None
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::NotificationError;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// In-memory store standing in for the OS keyring.
+    #[derive(Clone, Default)]
+    struct MockStore {
+        entries: Arc<Mutex<HashMap<String, String>>>,
+    }
+
+    impl CredentialStore for MockStore {
+        fn get(&self, key: &str) -> Option<String> {
+            self.entries.lock().unwrap().get(key).cloned()
+        }
+
+        fn set(&self, key: &str, value: &str) -> Result<()> {
+            self.entries.lock().unwrap().insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_cache_miss_then_hit() {
+        let store = MockStore::default();
+        let cache = CredentialCache::new(Box::new(store.clone()), 3600);
+        let mut fetches = 0;
+
+        let first = cache
+            .get_or_fetch("file:~/.secret", || {
+                fetches += 1;
+                Ok("s3cret".to_string())
+            })
+            .unwrap();
+        assert_eq!(first, "s3cret");
+
+        let second = cache
+            .get_or_fetch("file:~/.secret", || {
+                fetches += 1;
+                Ok("changed".to_string())
+            })
+            .unwrap();
+        assert_eq!(second, "s3cret");
+        assert_eq!(fetches, 1);
+        assert!(store.get("file:~/.secret").is_some());
+    }
+
+    #[test]
+    fn test_stale_entry_refreshes() {
+        let store = MockStore::default();
+        let stale = serde_json::to_string(&CachedCredential {
+            value: "old".to_string(),
+            fetched_at: unix_now() - 120,
+        })
+        .unwrap();
+        store.set("file:token", &stale).unwrap();
+
+        let cache = CredentialCache::new(Box::new(store.clone()), 60);
+        let value = cache.get_or_fetch("file:token", || Ok("new".to_string())).unwrap();
+        assert_eq!(value, "new");
+
+        // The refreshed value is now cached
+        let value = cache
+            .get_or_fetch("file:token", || panic!("should be cached"))
+            .unwrap();
+        assert_eq!(value, "new");
+    }
+
+    #[test]
+    fn test_fetch_error_not_cached() {
+        let store = MockStore::default();
+        let cache = CredentialCache::new(Box::new(store.clone()), 3600);
+
+        let result = cache.get_or_fetch("file:missing", || {
+            Err(NotificationError::InvalidConfig("missing".to_string()))
+        });
+        assert!(result.is_err());
+        assert!(store.get("file:missing").is_none());
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! This module defines all error types that can occur when using the notification system.
 
+use serde::Serialize;
+use std::fmt;
 use thiserror::Error;
 
 /// Errors that can occur during notification operations.
@@ -42,6 +44,64 @@ pub enum NotificationError {
     /// Handler execution error.
     #[error("Handler error: {0}")]
     Handler(String),
+
+    /// Handler deliberately did nothing for this event (e.g. rate limiting).
+    #[error("Suppressed: {message}")]
+    Suppressed {
+        /// Why the notification was not sent
+        reason: SuppressionReason,
+        /// Human-readable details
+        message: String,
+    },
+}
+
+impl NotificationError {
+    /// Creates a [`NotificationError::Suppressed`] error.
+    pub fn suppressed(reason: SuppressionReason, message: impl Into<String>) -> Self {
+        Self::Suppressed {
+            reason,
+            message: message.into(),
+        }
+    }
+}
+
+/// Why a handler did not send a notification for an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum SuppressionReason {
+    /// The handler's interval or cooldown has not elapsed yet
+    Cooldown,
+    /// There is no device to deliver to (e.g. no audio output)
+    NoDevice,
+    /// None of the handler's routes applies to the event
+    NoRoute,
+    /// Another handler in the same `channel_group` already delivered
+    ChannelGroup,
+    /// The handler needs the network and dispatch is running offline
+    Offline,
+    /// Any other reason given by a handler
+    Other,
+}
+
+impl SuppressionReason {
+    /// The reason's name as used in logs and JSON (e.g. `no_device`).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Cooldown => "cooldown",
+            Self::NoDevice => "no_device",
+            Self::NoRoute => "no_route",
+            Self::ChannelGroup => "channel_group",
+            Self::Offline => "offline",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for SuppressionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// Result type alias for notification operations.
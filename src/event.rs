@@ -2,19 +2,25 @@
 //!
 //! This module defines the event structure received from Claude Code hooks via stdin.
 
+use crate::template::TemplateContext;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::io::Read;
 
 /// A Claude Code event received from stdin.
 ///
 /// Events are flexible JSON objects that can contain any fields.
 /// The event type and other metadata are extracted from the JSON.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Event {
     /// The raw JSON value for flexible matching
     #[serde(flatten)]
     pub data: HashMap<String, Value>,
+
+    /// Runtime metadata for template rendering (not part of the event JSON)
+    #[serde(skip)]
+    pub context: TemplateContext,
 }
 
 impl Event {
@@ -29,27 +35,116 @@ impl Event {
         Ok(event)
     }
 
+    /// Creates a new event by streaming JSON from a reader.
+    ///
+    /// Avoids buffering the whole input into a `String` first, which matters
+    /// for large events read straight off stdin. Reads to EOF: like
+    /// [`from_json`](Self::from_json), anything but whitespace after the event is an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails, the JSON is invalid or followed by more input.
+    pub fn from_reader<R: Read>(reader: R) -> anyhow::Result<Self> {
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let event = serde_path_to_error::deserialize(&mut deserializer)
+            .map_err(|e| anyhow::anyhow!("Failed to parse event JSON: {}", e))?;
+        deserializer.end().map_err(|e| anyhow::anyhow!("Failed to parse event JSON: {}", e))?;
+        Ok(event)
+    }
+
+    /// Reads the next event from a stream of events, leaving the rest of the input unread.
+    ///
+    /// Unlike [`from_reader`](Self::from_reader), returns as soon as the event's
+    /// JSON is complete, without waiting for EOF.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails or the JSON is invalid.
+    pub fn next_from_reader<R: Read>(reader: R) -> anyhow::Result<Self> {
+        let event = serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_reader(reader))
+            .map_err(|e| anyhow::anyhow!("Failed to parse event JSON: {}", e))?;
+        Ok(event)
+    }
+
     /// Gets a field value as a string reference.
     pub fn get_str(&self, key: &str) -> Option<&str> {
         self.data.get(key)?.as_str()
     }
 
+    /// Gets the hook that produced this event (`hook_event_name`).
+    pub fn hook_name(&self) -> Option<&str> {
+        self.get_str("hook_event_name")
+    }
+
+    /// Gets the tool this event refers to, whichever shape the hook uses.
+    ///
+    /// Checks `tool_name`, then `tool.name`, then a plain string `tool`,
+    /// returning the first one present.
+    pub fn tool_name(&self) -> Option<&str> {
+        if let Some(name) = self.get_str("tool_name") {
+            return Some(name);
+        }
+        match self.data.get("tool")? {
+            Value::Object(tool) => tool.get("name")?.as_str(),
+            Value::String(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Guesses whether the tool this event reports on failed.
+    ///
+    /// Checks the event and its `tool_response` object for common failure shapes:
+    /// a non-empty `error`, a non-zero `exit_code` (or `exitCode`), a `status` of
+    /// `"error"`, `"failed"` or `"failure"`, `is_error: true` (or `isError`), and
+    /// `success: false`. Returns `None` when none of these fields is present and
+    /// there is no `tool_response`, as for hooks that don't report tool results.
+    pub fn tool_failed(&self) -> Option<bool> {
+        let response = self.data.get("tool_response");
+        let mut seen = response.is_some();
+        for key in FAILURE_FIELDS {
+            let nested = response.and_then(|r| r.get(key));
+            for value in self.data.get(key).into_iter().chain(nested) {
+                match field_indicates_failure(key, value) {
+                    Some(true) => return Some(true),
+                    Some(false) => seen = true,
+                    None => {}
+                }
+            }
+        }
+        seen.then_some(false)
+    }
+
     /// Gets a field value as a string, with nested path support (e.g., "tool.name").
     pub fn get_nested_str(&self, path: &str) -> Option<String> {
-        let parts: Vec<&str> = path.split('.').collect();
-        let value = Value::Object(
-            self.data
-                .iter()
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect(),
-        );
+        self.get_nested(path)?.as_str().map(|s| s.to_string())
+    }
 
-        let mut current = &value;
+    /// Gets a field value of any type, with nested path support (e.g., "tool_input.files").
+    ///
+    /// Numeric segments index into arrays (`tool_input.files.0`); an index past
+    /// the end yields `None`.
+    pub fn get_nested(&self, path: &str) -> Option<&Value> {
+        let mut parts = path.split('.');
+        let mut current = self.data.get(parts.next()?)?;
         for part in parts {
-            current = current.get(part)?;
+            current = match current {
+                Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+                other => other.get(part)?,
+            };
         }
+        Some(current)
+    }
 
-        current.as_str().map(|s| s.to_string())
+    /// Reports the fields that differ between this event and `other`.
+    ///
+    /// Keys are dotted paths (e.g. `tool.status`), recursing into nested
+    /// objects; arrays and other values are compared whole. Each entry holds
+    /// `(value in self, value in other)`, with `None` where the field is absent,
+    /// so calling `previous.diff(&current)` yields `(old, new)` pairs.
+    pub fn diff(&self, other: &Event) -> HashMap<String, (Option<Value>, Option<Value>)> {
+        let mut changes = HashMap::new();
+        diff_maps("", self.data.iter(), other.data.iter(), &mut changes);
+        changes
     }
 
     /// Gets the entire event data as a reference.
@@ -63,6 +158,59 @@ impl Event {
     }
 }
 
+/// Fields inspected by [`Event::tool_failed`].
+const FAILURE_FIELDS: [&str; 7] = ["error", "exit_code", "exitCode", "status", "is_error", "isError", "success"];
+
+/// Whether one failure field reports a failure; `None` if its value has an unexpected type.
+fn field_indicates_failure(key: &str, value: &Value) -> Option<bool> {
+    match (key, value) {
+        ("error", Value::Null | Value::Bool(false)) => Some(false),
+        ("error", Value::String(message)) => Some(!message.trim().is_empty()),
+        ("error", _) => Some(true),
+        ("exit_code" | "exitCode", code) => code.as_f64().map(|code| code != 0.0),
+        ("status", Value::String(status)) => Some(matches!(
+            status.to_ascii_lowercase().as_str(),
+            "error" | "failed" | "failure"
+        )),
+        ("is_error" | "isError", Value::Bool(is_error)) => Some(*is_error),
+        ("success", Value::Bool(success)) => Some(!success),
+        _ => None,
+    }
+}
+
+/// Collects differing fields of two objects under `prefix` into `changes`.
+fn diff_maps<'a>(
+    prefix: &str,
+    old: impl Iterator<Item = (&'a String, &'a Value)>,
+    new: impl Iterator<Item = (&'a String, &'a Value)>,
+    changes: &mut HashMap<String, (Option<Value>, Option<Value>)>,
+) {
+    let old: HashMap<&String, &Value> = old.collect();
+    let new: HashMap<&String, &Value> = new.collect();
+    let path = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        }
+    };
+
+    for (key, old_value) in &old {
+        match (old_value, new.get(key)) {
+            (Value::Object(a), Some(Value::Object(b))) => diff_maps(&path(key), a.iter(), b.iter(), changes),
+            (a, Some(b)) if a == b => {}
+            (a, b) => {
+                changes.insert(path(key), (Some((*a).clone()), b.map(|v| (*v).clone())));
+            }
+        }
+    }
+    for (key, new_value) in &new {
+        if !old.contains_key(key) {
+            changes.insert(path(key), (None, Some((*new_value).clone())));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,6 +230,138 @@ mod tests {
         assert_eq!(event.get_nested_str("tool.name"), Some("bash".to_string()));
     }
 
+    #[test]
+    fn test_nested_array_index() {
+        let json = r#"{"tool_input": {"files": ["a.rs", {"path": "b.rs"}]}, "args": ["-v"]}"#;
+        let event = Event::from_json(json).unwrap();
+        assert_eq!(event.get_nested_str("tool_input.files.0"), Some("a.rs".to_string()));
+        assert_eq!(event.get_nested_str("tool_input.files.1.path"), Some("b.rs".to_string()));
+        assert_eq!(event.get_nested_str("args.0"), Some("-v".to_string()));
+
+        // Out of bounds and non-numeric segments on arrays
+        assert_eq!(event.get_nested("tool_input.files.2"), None);
+        assert_eq!(event.get_nested("tool_input.files.first"), None);
+        assert_eq!(event.get_nested("tool_input.files.-1"), None);
+    }
+
+    #[test]
+    fn test_hook_name() {
+        let event = Event::from_json(r#"{"hook_event_name": "PreToolUse"}"#).unwrap();
+        assert_eq!(event.hook_name(), Some("PreToolUse"));
+
+        let event = Event::from_json(r#"{"message": "hi"}"#).unwrap();
+        assert_eq!(event.hook_name(), None);
+    }
+
+    #[test]
+    fn test_tool_name_shapes() {
+        let event = Event::from_json(r#"{"tool_name": "Bash"}"#).unwrap();
+        assert_eq!(event.tool_name(), Some("Bash"));
+
+        let event = Event::from_json(r#"{"tool": {"name": "Edit"}}"#).unwrap();
+        assert_eq!(event.tool_name(), Some("Edit"));
+
+        let event = Event::from_json(r#"{"tool": "Read"}"#).unwrap();
+        assert_eq!(event.tool_name(), Some("Read"));
+
+        // tool_name wins when several shapes are present
+        let event = Event::from_json(r#"{"tool_name": "Bash", "tool": "Read"}"#).unwrap();
+        assert_eq!(event.tool_name(), Some("Bash"));
+
+        let event = Event::from_json(r#"{"tool": {"status": "ok"}}"#).unwrap();
+        assert_eq!(event.tool_name(), None);
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+        assert_eq!(event.tool_name(), None);
+    }
+
+    #[test]
+    fn test_diff() {
+        use serde_json::json;
+
+        let previous = Event::from_json(
+            r#"{"hook_event_name": "PreToolUse", "session_id": "abc", "cwd": "/tmp",
+                "tool": {"name": "Bash", "status": "running", "meta": {"pid": 1}}, "args": [1, 2]}"#,
+        )
+        .unwrap();
+        let current = Event::from_json(
+            r#"{"hook_event_name": "PostToolUse", "session_id": "abc", "exit_code": 0,
+                "tool": {"name": "Bash", "status": "done", "meta": {"pid": 2}}, "args": [1, 2]}"#,
+        )
+        .unwrap();
+
+        let diff = previous.diff(&current);
+        assert_eq!(diff.len(), 5);
+        assert_eq!(diff["hook_event_name"], (Some(json!("PreToolUse")), Some(json!("PostToolUse"))));
+        assert_eq!(diff["cwd"], (Some(json!("/tmp")), None));
+        assert_eq!(diff["exit_code"], (None, Some(json!(0))));
+        assert_eq!(diff["tool.status"], (Some(json!("running")), Some(json!("done"))));
+        assert_eq!(diff["tool.meta.pid"], (Some(json!(1)), Some(json!(2))));
+
+        assert!(previous.diff(&previous).is_empty());
+    }
+
+    #[test]
+    fn test_diff_nested_shape_change() {
+        use serde_json::json;
+
+        let previous = Event::from_json(r#"{"tool": "Read", "extra": {"a": 1}}"#).unwrap();
+        let current = Event::from_json(r#"{"tool": {"name": "Read"}}"#).unwrap();
+
+        let diff = previous.diff(&current);
+        assert_eq!(diff["tool"], (Some(json!("Read")), Some(json!({"name": "Read"}))));
+        assert_eq!(diff["extra"], (Some(json!({"a": 1})), None));
+        assert_eq!(diff.len(), 2);
+    }
+
+    #[test]
+    fn test_tool_failed_shapes() {
+        let failed = |json: &str| Event::from_json(json).unwrap().tool_failed();
+
+        // Failures
+        assert_eq!(failed(r#"{"tool_response": {"error": "File not found"}}"#), Some(true));
+        assert_eq!(failed(r#"{"tool_response": {"stdout": "", "exit_code": 2}}"#), Some(true));
+        assert_eq!(failed(r#"{"exitCode": 1}"#), Some(true));
+        assert_eq!(failed(r#"{"status": "Error"}"#), Some(true));
+        assert_eq!(failed(r#"{"tool_response": {"is_error": true, "content": "boom"}}"#), Some(true));
+        assert_eq!(failed(r#"{"tool_response": {"success": false}}"#), Some(true));
+        assert_eq!(failed(r#"{"error": {"code": 500}}"#), Some(true));
+
+        // Successes
+        assert_eq!(failed(r#"{"tool_response": {"stdout": "ok", "exit_code": 0}}"#), Some(false));
+        assert_eq!(failed(r#"{"tool_response": {"success": true, "error": null}}"#), Some(false));
+        assert_eq!(failed(r#"{"tool_response": "done"}"#), Some(false));
+        assert_eq!(failed(r#"{"status": "success", "error": ""}"#), Some(false));
+
+        // Nothing to go on
+        assert_eq!(failed(r#"{"hook_event_name": "Stop"}"#), None);
+        assert_eq!(failed(r#"{"exit_code": "n/a"}"#), None);
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let cursor = std::io::Cursor::new(br#"{"hook_event_name": "Stop", "tool": {"name": "bash"}}"#);
+        let event = Event::from_reader(cursor).unwrap();
+        assert_eq!(event.get_str("hook_event_name"), Some("Stop"));
+        assert_eq!(event.get_nested_str("tool.name"), Some("bash".to_string()));
+    }
+
+    #[test]
+    fn test_from_reader_invalid_json() {
+        let cursor = std::io::Cursor::new(br#"{"invalid": }"#);
+        assert!(Event::from_reader(cursor).is_err());
+
+        let trailing = std::io::Cursor::new(br#"{"hook_event_name": "Stop"} {oops"#);
+        assert!(Event::from_reader(trailing).is_err());
+        assert!(Event::from_reader(std::io::Cursor::new(b"{\"n\": 1}\n  ")).is_ok());
+    }
+
+    #[test]
+    fn test_next_from_reader_leaves_the_rest() {
+        let mut cursor = std::io::Cursor::new(br#"{"n": 1} {"n": 2}"#);
+        assert_eq!(Event::next_from_reader(&mut cursor).unwrap().data["n"], 1);
+        assert_eq!(Event::next_from_reader(&mut cursor).unwrap().data["n"], 2);
+    }
+
     #[test]
     fn test_invalid_json() {
         let json = r#"{"invalid": }"#;
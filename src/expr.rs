@@ -0,0 +1,113 @@
+//! Handler `expr` conditions.
+//!
+//! Evaluates a small boolean expression (see the `evalexpr` crate) against an
+//! event. Top-level fields are variables named after their keys, and nested
+//! object fields are reachable with dots (`tool_input.command`).
+
+use crate::event::Event;
+use evalexpr::{ContextWithMutableVariables, HashMapContext, Value as ExprValue};
+use serde_json::Value;
+
+/// Returns true if `expr` evaluates to `true` for the event.
+///
+/// Expressions that fail to parse, refer to a field the event doesn't have,
+/// compare mismatched types, or produce a non-boolean all count as false.
+///
+/// # Examples
+///
+/// ```
+/// use boopifier::event::Event;
+/// use boopifier::expr::matches;
+///
+/// let event = Event::from_json(r#"{"tool_name": "Bash", "exit_code": 1}"#).unwrap();
+/// assert!(matches(&event, r#"tool_name == "Bash" && exit_code != 0"#));
+/// assert!(!matches(&event, "exit_code == 0"));
+/// ```
+pub fn matches(event: &Event, expr: &str) -> bool {
+    evalexpr::eval_boolean_with_context(expr, &context(event)).unwrap_or(false)
+}
+
+/// Checks that `expr` parses, without evaluating it.
+///
+/// # Errors
+///
+/// Returns the parse error.
+pub fn check(expr: &str) -> Result<(), evalexpr::EvalexprError> {
+    evalexpr::build_operator_tree(expr).map(drop)
+}
+
+/// Binds every scalar event field, including nested ones, as a variable.
+fn context(event: &Event) -> HashMapContext {
+    let mut context = HashMapContext::new();
+    for (key, value) in &event.data {
+        bind(&mut context, key, value);
+    }
+    context
+}
+
+fn bind(context: &mut HashMapContext, name: &str, value: &Value) {
+    if let Value::Object(fields) = value {
+        for (key, value) in fields {
+            bind(context, &format!("{}.{}", name, key), value);
+        }
+    } else if let Some(value) = to_expr_value(value) {
+        // Only fails for type changes of an existing variable, which can't happen here
+        let _ = context.set_value(name.to_string(), value);
+    }
+}
+
+/// Converts a JSON value; arrays become tuples, objects are not representable.
+fn to_expr_value(value: &Value) -> Option<ExprValue> {
+    Some(match value {
+        Value::Null => ExprValue::Empty,
+        Value::Bool(b) => ExprValue::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => ExprValue::Int(i),
+            None => ExprValue::Float(n.as_f64()?),
+        },
+        Value::String(s) => ExprValue::String(s.clone()),
+        Value::Array(items) => ExprValue::Tuple(items.iter().filter_map(to_expr_value).collect()),
+        Value::Object(_) => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> Event {
+        Event::from_json(
+            r#"{
+                "hook_event_name": "PostToolUse",
+                "tool_name": "Bash",
+                "exit_code": 2,
+                "duration": 1.5,
+                "interrupted": false,
+                "tool_input": {"command": "cargo test", "timeout": 600}
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_true_and_false_expressions() {
+        let event = event();
+        assert!(matches(&event, r#"tool_name == "Bash" && exit_code != 0"#));
+        assert!(matches(&event, "duration > 1.0 || interrupted"));
+        assert!(matches(&event, r#"tool_input.command == "cargo test" && tool_input.timeout >= 600"#));
+
+        assert!(!matches(&event, r#"tool_name == "Edit""#));
+        assert!(!matches(&event, "exit_code == 0 && !interrupted"));
+    }
+
+    #[test]
+    fn test_missing_fields_and_bad_expressions_are_false() {
+        let event = event();
+        assert!(!matches(&event, "cwd == \"/tmp\""));
+        assert!(!matches(&event, "!(cwd == \"/tmp\")"));
+        // Not boolean, mismatched types, and unparsable input
+        assert!(!matches(&event, "exit_code + 1"));
+        assert!(!matches(&event, "tool_name > 3"));
+        assert!(!matches(&event, "tool_name == "));
+    }
+}
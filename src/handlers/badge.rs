@@ -0,0 +1,157 @@
+//! Badge counter handler.
+//!
+//! Maintains a persisted count of unacknowledged alerts in a plain text file that
+//! status bar widgets or shell prompts can read. `boopifier ack` resets it.
+
+use crate::event::Event;
+use crate::handlers::{lock_file, write_atomic, Handler, HandlerResult};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Handler that increments a persisted badge counter.
+pub struct BadgeHandler;
+
+#[async_trait]
+impl Handler for BadgeHandler {
+    fn handler_type(&self) -> &str {
+        "badge"
+    }
+
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &["file"]
+    }
+
+    async fn handle(&self, _event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        let path = config
+            .get("file")
+            .and_then(|v| v.as_str())
+            .map(|p| PathBuf::from(shellexpand::tilde(p).as_ref()))
+            .unwrap_or_else(default_badge_path);
+
+        increment_badge(&path)?;
+        Ok(())
+    }
+
+    fn serialize(&self) -> bool {
+        true
+    }
+}
+
+/// Default location of the badge counter file (`~/.claude/boopifier-badge`).
+pub fn default_badge_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".claude/boopifier-badge")
+}
+
+/// Reads the current badge count. A missing or unreadable file counts as zero.
+pub fn read_badge(path: &Path) -> u64 {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Increments the badge count and returns the new value.
+///
+/// Holds the counter's lock file while reading and writing, so concurrent
+/// boopifier processes don't lose increments.
+///
+/// # Errors
+///
+/// Returns an error if the counter file cannot be locked or written.
+pub fn increment_badge(path: &Path) -> HandlerResult<u64> {
+    let _lock = lock_badge(path)?;
+    let count = read_badge(path).saturating_add(1);
+    write_badge(path, count)?;
+    Ok(count)
+}
+
+/// Resets the badge count to zero.
+///
+/// # Errors
+///
+/// Returns an error if the counter file cannot be locked or written.
+pub fn reset_badge(path: &Path) -> HandlerResult<()> {
+    let _lock = lock_badge(path)?;
+    write_badge(path, 0)
+}
+
+/// Locks the counter at `path` via a `.lock` file next to it.
+fn lock_badge(path: &Path) -> HandlerResult<fs::File> {
+    lock_file(&path.with_extension("lock"))
+}
+
+/// Writes the count atomically so readers never see a partial value.
+fn write_badge(path: &Path, count: u64) -> HandlerResult<()> {
+    write_atomic(path, format!("{}\n", count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handler_type() {
+        let handler = BadgeHandler;
+        assert_eq!(handler.handler_type(), "badge");
+        assert!(handler.serialize());
+    }
+
+    #[test]
+    fn test_increment_and_reset() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("badge");
+
+        assert_eq!(read_badge(&path), 0);
+        assert_eq!(increment_badge(&path).unwrap(), 1);
+        assert_eq!(increment_badge(&path).unwrap(), 2);
+        assert_eq!(read_badge(&path), 2);
+
+        reset_badge(&path).unwrap();
+        assert_eq!(read_badge(&path), 0);
+        assert_eq!(increment_badge(&path).unwrap(), 1);
+
+        // No temp files left behind
+        let mut files: Vec<_> = fs::read_dir(dir.path()).unwrap().map(|e| e.unwrap().file_name()).collect();
+        files.sort();
+        assert_eq!(files, ["badge", "badge.lock"]);
+    }
+
+    #[test]
+    fn test_concurrent_increments() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("badge");
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..10 {
+                        increment_badge(&path).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(read_badge(&path), 80);
+    }
+
+    #[tokio::test]
+    async fn test_handle_uses_configured_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("count");
+        let event = Event::from_json(r#"{"hook_event_name": "Notification"}"#).unwrap();
+
+        let mut config = HashMap::new();
+        config.insert("file".to_string(), Value::String(path.to_string_lossy().to_string()));
+
+        BadgeHandler.handle(&event, &config).await.unwrap();
+        BadgeHandler.handle(&event, &config).await.unwrap();
+        assert_eq!(read_badge(&path), 2);
+    }
+}
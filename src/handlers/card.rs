@@ -0,0 +1,421 @@
+//! Notification card handler (requires the `card` feature).
+//!
+//! Renders event text onto a PNG "card" with a built-in 5x7 bitmap font and
+//! saves it, for channels that only take images.
+
+use crate::error::NotificationError;
+use crate::event::Event;
+use crate::handlers::{message_template, write_atomic, Handler, HandlerResult};
+use crate::template;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Text drawn when no `text` template is configured.
+const DEFAULT_TEXT: &str = "Claude Code: {{hook_event_name}}";
+
+/// Largest accepted card width or height, in pixels.
+const MAX_DIMENSION: u64 = 4096;
+
+/// Largest accepted font `scale`.
+const MAX_SCALE: u64 = 16;
+
+/// Width and height of a glyph in the built-in font, in font pixels.
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+
+/// Handler that renders event text into a PNG image.
+pub struct CardHandler;
+
+#[async_trait]
+impl Handler for CardHandler {
+    fn handler_type(&self) -> &str {
+        "card"
+    }
+
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &[
+            "text", "templates", "lang", "file", "width", "height", "background", "foreground", "scale",
+            "padding",
+        ]
+    }
+
+    fn validate(&self, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        CardStyle::from_config(config).map(drop)
+    }
+
+    async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        let style = CardStyle::from_config(config)?;
+        let text = match message_template(config, "text") {
+            Some(Value::String(s)) => template::render(s, event),
+            _ => template::render(DEFAULT_TEXT, event),
+        };
+        let path = config
+            .get("file")
+            .and_then(|v| v.as_str())
+            .map(|p| PathBuf::from(shellexpand::tilde(p).as_ref()))
+            .unwrap_or_else(default_card_path);
+
+        let png = render_card(&text, &style)?;
+        write_atomic(&path, &png)
+            .map_err(|e| NotificationError::Handler(format!("Failed to save card to {}: {}", path.display(), e)))
+    }
+
+    fn serialize(&self) -> bool {
+        true
+    }
+
+    fn runs_first(&self) -> bool {
+        // Email attachments and uploads of the card see this event's image
+        true
+    }
+}
+
+/// Default location of the card image (`~/.claude/boopifier-card.png`).
+pub fn default_card_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".claude/boopifier-card.png")
+}
+
+/// Size and colors of a card.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardStyle {
+    /// Image width in pixels
+    pub width: u32,
+    /// Image height in pixels
+    pub height: u32,
+    /// Background color as RGB
+    pub background: [u8; 3],
+    /// Text color as RGB
+    pub foreground: [u8; 3],
+    /// Size of one font pixel, in image pixels
+    pub scale: u32,
+    /// Empty border around the text, in pixels
+    pub padding: u32,
+}
+
+impl Default for CardStyle {
+    fn default() -> Self {
+        Self {
+            width: 600,
+            height: 200,
+            background: [0x1e, 0x1e, 0x2e],
+            foreground: [0xcd, 0xd6, 0xf4],
+            scale: 2,
+            padding: 16,
+        }
+    }
+}
+
+impl CardStyle {
+    /// Reads `width`, `height`, `background`, `foreground`, `scale` and `padding`
+    /// from a handler config, using the defaults for missing keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotificationError::InvalidConfig` for out-of-range sizes or colors
+    /// that aren't `#rrggbb`.
+    pub fn from_config(config: &HashMap<String, Value>) -> HandlerResult<Self> {
+        let defaults = Self::default();
+        Ok(Self {
+            width: get_bounded(config, "width", defaults.width, 1, MAX_DIMENSION)?,
+            height: get_bounded(config, "height", defaults.height, 1, MAX_DIMENSION)?,
+            background: get_color(config, "background", defaults.background)?,
+            foreground: get_color(config, "foreground", defaults.foreground)?,
+            scale: get_bounded(config, "scale", defaults.scale, 1, MAX_SCALE)?,
+            padding: get_bounded(config, "padding", defaults.padding, 0, MAX_DIMENSION)?,
+        })
+    }
+}
+
+fn get_bounded(config: &HashMap<String, Value>, key: &str, default: u32, min: u64, max: u64) -> HandlerResult<u32> {
+    let Some(value) = config.get(key) else {
+        return Ok(default);
+    };
+    value
+        .as_u64()
+        .filter(|v| (min..=max).contains(v))
+        .map(|v| v as u32)
+        .ok_or_else(|| {
+            NotificationError::InvalidConfig(format!("Card '{}' must be a number from {} to {}", key, min, max))
+        })
+}
+
+fn get_color(config: &HashMap<String, Value>, key: &str, default: [u8; 3]) -> HandlerResult<[u8; 3]> {
+    let Some(value) = config.get(key) else {
+        return Ok(default);
+    };
+    value.as_str().and_then(parse_color).ok_or_else(|| {
+        NotificationError::InvalidConfig(format!("Card '{}' must be a #rrggbb color, got {}", key, value))
+    })
+}
+
+/// Parses a `#rrggbb` (or `rrggbb`) color.
+fn parse_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#').unwrap_or(color);
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Renders `text` onto a card and encodes it as PNG.
+///
+/// Lines are word-wrapped to the card width; lines that don't fit the height
+/// are dropped. Characters outside printable ASCII are drawn as `?`.
+///
+/// # Errors
+///
+/// Returns `NotificationError::Handler` if the image can't be encoded.
+pub fn render_card(text: &str, style: &CardStyle) -> HandlerResult<Vec<u8>> {
+    let (width, height) = (style.width as usize, style.height as usize);
+    let mut pixels: Vec<u8> = style.background.repeat(width * height);
+
+    let cell_width = (GLYPH_WIDTH + 1) * style.scale;
+    let line_height = (GLYPH_HEIGHT + 2) * style.scale;
+    let columns = style.width.saturating_sub(2 * style.padding) / cell_width;
+    let rows = (style.height.saturating_sub(2 * style.padding) + 2 * style.scale) / line_height;
+
+    for (row, line) in wrap(text, columns as usize).iter().take(rows as usize).enumerate() {
+        let y = style.padding + row as u32 * line_height;
+        for (column, c) in line.chars().enumerate() {
+            let x = style.padding + column as u32 * cell_width;
+            draw_glyph(&mut pixels, style, x, y, c);
+        }
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, style.width, style.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|e| NotificationError::Handler(format!("Failed to encode card: {}", e)))?;
+    Ok(png)
+}
+
+/// Splits `text` into lines of at most `columns` characters, breaking at spaces where possible.
+fn wrap(text: &str, columns: usize) -> Vec<String> {
+    if columns == 0 {
+        return Vec::new();
+    }
+
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.replace('\t', " ").split(' ') {
+            let mut word: Vec<char> = word.chars().collect();
+            let line_len = line.chars().count();
+            if line_len > 0 && line_len + 1 + word.len() > columns {
+                lines.push(std::mem::take(&mut line));
+            } else if line_len > 0 {
+                line.push(' ');
+            }
+            // Words longer than a line are split
+            while line.chars().count() + word.len() > columns {
+                let room = columns - line.chars().count();
+                line.extend(word.drain(..room));
+                lines.push(std::mem::take(&mut line));
+            }
+            line.extend(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Draws one character with its top-left corner at (`x`, `y`), clipped to the card.
+fn draw_glyph(pixels: &mut [u8], style: &CardStyle, x: u32, y: u32, c: char) {
+    let index = if (' '..='~').contains(&c) { c as usize - 0x20 } else { '?' as usize - 0x20 };
+    for (gy, bits) in FONT[index].iter().enumerate() {
+        for gx in 0..GLYPH_WIDTH {
+            if bits & (0x10 >> gx) == 0 {
+                continue;
+            }
+            for dy in 0..style.scale {
+                for dx in 0..style.scale {
+                    let px = x + gx * style.scale + dx;
+                    let py = y + gy as u32 * style.scale + dy;
+                    if px < style.width && py < style.height {
+                        let offset = (py as usize * style.width as usize + px as usize) * 3;
+                        pixels[offset..offset + 3].copy_from_slice(&style.foreground);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 5x7 bitmap font for printable ASCII (`' '` to `'~'`); one byte per row, bit 4 is the leftmost column.
+#[rustfmt::skip]
+const FONT: [[u8; 7]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04], // '!'
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // '#'
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // '$'
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // '%'
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // '&'
+    [0x04, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00], // '\''
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // '('
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // ')'
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // '*'
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08], // ','
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // '.'
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // '/'
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // '0'
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // '1'
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // '2'
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // '3'
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // '4'
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // '5'
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // '6'
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // '7'
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // '8'
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ';'
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // '<'
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // '='
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // '>'
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // '?'
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E], // '@'
+    [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // 'A'
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // 'B'
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // 'C'
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // 'D'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // 'E'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // 'F'
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // 'G'
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // 'H'
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // 'I'
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // 'J'
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // 'K'
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // 'L'
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // 'M'
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // 'N'
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // 'O'
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // 'P'
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // 'Q'
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // 'R'
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // 'S'
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // 'T'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // 'U'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // 'V'
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // 'W'
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // 'X'
+    [0x11, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x04], // 'Y'
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // 'Z'
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E], // '['
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00], // '\\'
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E], // ']'
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // '_'
+    [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F], // 'a'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1E], // 'b'
+    [0x00, 0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E], // 'c'
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x0F], // 'd'
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E], // 'e'
+    [0x06, 0x09, 0x08, 0x1C, 0x08, 0x08, 0x08], // 'f'
+    [0x00, 0x0F, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'g'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11], // 'h'
+    [0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E], // 'i'
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0C], // 'j'
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12], // 'k'
+    [0x0C, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // 'l'
+    [0x00, 0x00, 0x1A, 0x15, 0x15, 0x11, 0x11], // 'm'
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11], // 'n'
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E], // 'o'
+    [0x00, 0x00, 0x1E, 0x11, 0x1E, 0x10, 0x10], // 'p'
+    [0x00, 0x00, 0x0D, 0x13, 0x0F, 0x01, 0x01], // 'q'
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10], // 'r'
+    [0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E], // 's'
+    [0x08, 0x08, 0x1C, 0x08, 0x08, 0x09, 0x06], // 't'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D], // 'u'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0A, 0x04], // 'v'
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0A], // 'w'
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11], // 'x'
+    [0x00, 0x00, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'y'
+    [0x00, 0x00, 0x1F, 0x02, 0x04, 0x08, 0x1F], // 'z'
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02], // '{'
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // '|'
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08], // '}'
+    [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00], // '~'
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_handler_type() {
+        let handler = CardHandler;
+        assert_eq!(handler.handler_type(), "card");
+        assert!(!handler.requires_network());
+        assert!(handler.runs_first());
+    }
+
+    #[tokio::test]
+    async fn test_card_png_dimensions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cards/card.png");
+        let mut config = HashMap::new();
+        config.insert("file".to_string(), json!(path.to_string_lossy()));
+        config.insert("text".to_string(), json!("{{hook_event_name}}: {{message}}"));
+        config.insert("width".to_string(), json!(320));
+        config.insert("height".to_string(), json!(90));
+        config.insert("background".to_string(), json!("#000000"));
+        config.insert("foreground".to_string(), json!("#ff8800"));
+
+        let event = Event::from_json(r#"{"hook_event_name": "Notification", "message": "Needs input"}"#).unwrap();
+        CardHandler.handle(&event, &config).await.unwrap();
+
+        let decoder = png::Decoder::new(std::fs::File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (320, 90));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+
+        // Background in the corner, and some text drawn
+        assert_eq!(&pixels[..3], &[0, 0, 0]);
+        assert!(pixels.chunks(3).any(|p| p == [0xff, 0x88, 0x00]));
+    }
+
+    #[test]
+    fn test_style_validation() {
+        assert_eq!(CardStyle::from_config(&HashMap::new()).unwrap(), CardStyle::default());
+
+        for (key, value) in [
+            ("width", json!(0)),
+            ("height", json!(5000)),
+            ("scale", json!("2")),
+            ("background", json!("red")),
+            ("foreground", json!("#12345g")),
+        ] {
+            let config = HashMap::from([(key.to_string(), value)]);
+            assert!(
+                matches!(CardStyle::from_config(&config), Err(NotificationError::InvalidConfig(_))),
+                "{}",
+                key
+            );
+            assert!(CardHandler.validate(&config).is_err(), "{}", key);
+        }
+        assert_eq!(parse_color("#1E1e2e"), Some([0x1e, 0x1e, 0x2e]));
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("Needs your input now", 10), ["Needs your", "input now"]);
+        assert_eq!(wrap("abcdefghijkl mn", 5), ["abcde", "fghij", "kl mn"]);
+        assert_eq!(wrap("one\n\ntwo", 10), ["one", "", "two"]);
+        assert!(wrap("anything", 0).is_empty());
+    }
+}
@@ -0,0 +1,226 @@
+//! Typed access to handler configuration values.
+//!
+//! Wraps a handler's config map so handlers read settings with one call and
+//! report missing or mistyped keys with consistent messages naming the handler.
+
+use crate::error::NotificationError;
+use crate::handlers::HandlerResult;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Typed getters over a handler's config.
+///
+/// Optional getters treat a missing key and `null` alike; a value of the wrong
+/// type is always an error rather than being silently ignored.
+///
+/// # Examples
+///
+/// ```
+/// use boopifier::handlers::ConfigAccess;
+/// use serde_json::json;
+/// use std::collections::HashMap;
+///
+/// let config: HashMap<_, _> = [("smtp_server".to_string(), json!("mail.local"))].into();
+/// let cfg = ConfigAccess::new("Email", &config);
+///
+/// assert_eq!(cfg.str("smtp_server").unwrap(), "mail.local");
+/// assert_eq!(cfg.u16("smtp_port", 25).unwrap(), 25);
+/// assert_eq!(
+///     cfg.str("to").unwrap_err().to_string(),
+///     "Invalid configuration: Email handler requires 'to' configuration"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigAccess<'a> {
+    handler: &'a str,
+    config: &'a HashMap<String, Value>,
+}
+
+impl<'a> ConfigAccess<'a> {
+    /// Wraps `config`; `handler` (e.g. `"Email"`) prefixes error messages.
+    pub fn new(handler: &'a str, config: &'a HashMap<String, Value>) -> Self {
+        Self { handler, config }
+    }
+
+    /// Gets a required string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotificationError::InvalidConfig` if the key is missing or not a string.
+    pub fn str(&self, key: &str) -> HandlerResult<&'a str> {
+        self.opt_str(key)?.ok_or_else(|| self.missing(key))
+    }
+
+    /// Gets an optional string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotificationError::InvalidConfig` if the value is not a string.
+    pub fn opt_str(&self, key: &str) -> HandlerResult<Option<&'a str>> {
+        self.typed(key, "a string", Value::as_str)
+    }
+
+    /// Gets a boolean, or `default` when unset.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotificationError::InvalidConfig` if the value is not a boolean.
+    pub fn bool(&self, key: &str, default: bool) -> HandlerResult<bool> {
+        Ok(self.typed(key, "a boolean", Value::as_bool)?.unwrap_or(default))
+    }
+
+    /// Gets an optional port-sized integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotificationError::InvalidConfig` unless the value is an integer from 0 to 65535.
+    pub fn opt_u16(&self, key: &str) -> HandlerResult<Option<u16>> {
+        self.typed(key, "an integer from 0 to 65535", |v| {
+            v.as_u64().and_then(|n| u16::try_from(n).ok())
+        })
+    }
+
+    /// Gets a port-sized integer, or `default` when unset.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotificationError::InvalidConfig` unless the value is an integer from 0 to 65535.
+    pub fn u16(&self, key: &str, default: u16) -> HandlerResult<u16> {
+        Ok(self.opt_u16(key)?.unwrap_or(default))
+    }
+
+    /// Gets a non-negative integer, or `default` when unset.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotificationError::InvalidConfig` if the value is not a non-negative integer.
+    pub fn u64(&self, key: &str, default: u64) -> HandlerResult<u64> {
+        Ok(self.typed(key, "a non-negative integer", Value::as_u64)?.unwrap_or(default))
+    }
+
+    /// Gets a number, or `default` when unset.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotificationError::InvalidConfig` if the value is not a number.
+    pub fn f64(&self, key: &str, default: f64) -> HandlerResult<f64> {
+        Ok(self.typed(key, "a number", Value::as_f64)?.unwrap_or(default))
+    }
+
+    /// Gets a required array of strings.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotificationError::InvalidConfig` if the key is missing, not an
+    /// array, or has a non-string element.
+    pub fn str_array(&self, key: &str) -> HandlerResult<Vec<&'a str>> {
+        self.opt_str_array(key)?.ok_or_else(|| self.missing(key))
+    }
+
+    /// Gets an optional array of strings.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotificationError::InvalidConfig` if the value is not an array of strings.
+    pub fn opt_str_array(&self, key: &str) -> HandlerResult<Option<Vec<&'a str>>> {
+        self.typed(key, "an array of strings", |v| {
+            v.as_array()?.iter().map(Value::as_str).collect()
+        })
+    }
+
+    /// Looks up `key`, converting it with `convert` or reporting that it must be `expected`.
+    fn typed<T>(&self, key: &str, expected: &str, convert: impl Fn(&'a Value) -> Option<T>) -> HandlerResult<Option<T>> {
+        match self.config.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => convert(value).map(Some).ok_or_else(|| {
+                NotificationError::InvalidConfig(format!(
+                    "{} handler '{}' must be {}, got: {}",
+                    self.handler, key, expected, value
+                ))
+            }),
+        }
+    }
+
+    fn missing(&self, key: &str) -> NotificationError {
+        NotificationError::InvalidConfig(format!("{} handler requires '{}' configuration", self.handler, key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config() -> HashMap<String, Value> {
+        [
+            ("url", json!("https://example.com")),
+            ("port", json!(587)),
+            ("big", json!(70_000)),
+            ("negative", json!(-1)),
+            ("volume", json!(0.5)),
+            ("random", json!(true)),
+            ("files", json!(["a.wav", "b.wav"])),
+            ("mixed", json!(["a.wav", 3])),
+            ("unset", Value::Null),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect()
+    }
+
+    #[test]
+    fn test_strings() {
+        let config = config();
+        let cfg = ConfigAccess::new("Webhook", &config);
+
+        assert_eq!(cfg.str("url").unwrap(), "https://example.com");
+        assert_eq!(cfg.opt_str("missing").unwrap(), None);
+        assert_eq!(cfg.opt_str("unset").unwrap(), None);
+        assert_eq!(
+            cfg.str("missing").unwrap_err().to_string(),
+            "Invalid configuration: Webhook handler requires 'missing' configuration"
+        );
+        assert_eq!(
+            cfg.str("port").unwrap_err().to_string(),
+            "Invalid configuration: Webhook handler 'port' must be a string, got: 587"
+        );
+    }
+
+    #[test]
+    fn test_numbers_and_bools() {
+        let config = config();
+        let cfg = ConfigAccess::new("Email", &config);
+
+        assert_eq!(cfg.u16("port", 25).unwrap(), 587);
+        assert_eq!(cfg.u16("missing", 25).unwrap(), 25);
+        assert_eq!(cfg.opt_u16("unset").unwrap(), None);
+        assert!(cfg.u16("big", 25).unwrap_err().to_string().contains("from 0 to 65535, got: 70000"));
+        assert!(cfg.u16("url", 25).is_err());
+
+        assert_eq!(cfg.u64("big", 0).unwrap(), 70_000);
+        assert!(cfg.u64("negative", 0).is_err());
+        assert_eq!(cfg.f64("volume", 1.0).unwrap(), 0.5);
+        assert_eq!(cfg.f64("port", 1.0).unwrap(), 587.0);
+        assert!(cfg.f64("url", 1.0).is_err());
+
+        assert!(cfg.bool("random", false).unwrap());
+        assert!(!cfg.bool("missing", false).unwrap());
+        assert!(cfg.bool("port", false).unwrap_err().to_string().contains("'port' must be a boolean"));
+    }
+
+    #[test]
+    fn test_string_arrays() {
+        let config = config();
+        let cfg = ConfigAccess::new("Sound", &config);
+
+        assert_eq!(cfg.str_array("files").unwrap(), vec!["a.wav", "b.wav"]);
+        assert_eq!(cfg.opt_str_array("missing").unwrap(), None);
+        assert!(cfg.str_array("missing").unwrap_err().to_string().contains("requires 'missing'"));
+        assert!(cfg
+            .str_array("mixed")
+            .unwrap_err()
+            .to_string()
+            .contains("'mixed' must be an array of strings"));
+        assert!(cfg.str_array("url").is_err());
+    }
+}
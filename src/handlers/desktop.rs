@@ -4,11 +4,21 @@
 
 use crate::error::NotificationError;
 use crate::event::Event;
-use crate::handlers::{Handler, HandlerResult};
+use crate::handlers::{
+    max_body_length, message_template, sanitize_display, Diagnostic, Handler, HandlerResult, DEFAULT_TIMEOUT,
+};
+use crate::hooks::{InteractiveResponse, PermissionDecision};
+use crate::template;
+use crate::text::truncate_chars;
 use async_trait::async_trait;
 use notify_rust::{Notification, Timeout, Urgency};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Handler for desktop notifications.
 pub struct DesktopHandler;
@@ -19,34 +29,348 @@ impl Handler for DesktopHandler {
         "desktop"
     }
 
-    async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
-        // Extract configuration with defaults
-        let summary = get_string(config, "summary", "Claude Code Notification");
-        let body = render_template(config.get("body"), event);
-        let timeout_ms = get_u32(config, "timeout", 5000);
-        let urgency = get_string(config, "urgency", "normal");
-
-        // Build and send notification
-        let mut notification = Notification::new();
-        notification
-            .appname("Claude Code")
-            .summary(&summary)
-            .body(&body)
-            .timeout(Timeout::Milliseconds(timeout_ms));
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &[
+            "summary", "body", "urgency", "timeout", "max_body_length", "templates", "lang",
+            "sounds_by_urgency", "app_id", "approval", "approval_timeout_ms", "approval_default",
+            "persist_missed", "missed_file", "sanitize",
+        ]
+    }
 
-        // Set urgency
-        notification.urgency(match urgency.as_str() {
-            "low" => Urgency::Low,
-            "critical" => Urgency::Critical,
-            _ => Urgency::Normal,
-        });
+    fn supports_hook(&self, hook: &str) -> bool {
+        // Allow/Deny buttons, when `approval` is on
+        hook == "PreToolUse"
+    }
 
-        notification
+    fn default_timeout(&self, config: &HashMap<String, Value>) -> Duration {
+        // Leave approvals time to wait out their own timeout and fall back
+        if config.get("approval").and_then(|v| v.as_bool()).unwrap_or(false) {
+            let timeout_ms = get_u32(config, "approval_timeout_ms", DEFAULT_APPROVAL_TIMEOUT_MS);
+            Duration::from_millis(timeout_ms.into()) + DEFAULT_TIMEOUT
+        } else {
+            DEFAULT_TIMEOUT
+        }
+    }
+
+    async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        build_notification(event, config)?
             .show()
             .map_err(|e| NotificationError::SendFailed(format!("Failed to send desktop notification: {}", e)))?;
 
         Ok(())
     }
+
+    async fn handle_interactive(
+        &self,
+        event: &Event,
+        config: &HashMap<String, Value>,
+    ) -> HandlerResult<Option<InteractiveResponse>> {
+        let approval = config.get("approval").and_then(|v| v.as_bool()).unwrap_or(false);
+        if !approval || event.hook_name() != Some("PreToolUse") {
+            return self.handle(event, config).await.map(|()| None);
+        }
+
+        let fallback = approval_default(config)?;
+        let timeout_ms = get_u32(config, "approval_timeout_ms", DEFAULT_APPROVAL_TIMEOUT_MS);
+
+        let mut notification = build_notification(event, config)?;
+        notification
+            .action(ALLOW_ACTION, "Allow")
+            .action(DENY_ACTION, "Deny")
+            .timeout(Timeout::Milliseconds(timeout_ms));
+
+        let action = wait_for_action(notification, Duration::from_millis(timeout_ms.into())).await?;
+        Ok(Some(approval_response(action.as_deref(), fallback)))
+    }
+
+    async fn diagnose(&self, config: &HashMap<String, Value>) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![notification_server_diagnostic().await];
+        diagnostics.extend(app_id(config).map(|id| app_id_diagnostic(&id)));
+        diagnostics
+    }
+}
+
+/// Builds the notification for an event, recording it first if `persist_missed` is set.
+///
+/// A failure to record is reported on stderr and doesn't stop the notification.
+fn build_notification(event: &Event, config: &HashMap<String, Value>) -> HandlerResult<Notification> {
+    // Extract configuration with defaults
+    let summary = get_string(config, "summary", "Claude Code Notification");
+    let max_body = max_body_length(config, "desktop");
+    let body = sanitize_display(config, render_template(message_template(config, "body"), event));
+    let body = truncate_chars(&body, max_body);
+    let timeout_ms = get_u32(config, "timeout", 5000);
+    let urgency = get_string(config, "urgency", "normal");
+
+    let mut notification = Notification::new();
+    notification
+        .appname("Claude Code")
+        .summary(&summary)
+        .body(&body)
+        .timeout(Timeout::Milliseconds(timeout_ms));
+
+    // Windows groups toasts by AppUserModelID; without one they appear as PowerShell's
+    #[cfg(target_os = "windows")]
+    if let Some(id) = app_id(config) {
+        notification.app_id(&id);
+    }
+
+    // Set urgency
+    notification.urgency(match urgency.as_str() {
+        "low" => Urgency::Low,
+        "critical" => Urgency::Critical,
+        _ => Urgency::Normal,
+    });
+
+    // Pick a sound for this urgency, if configured
+    match urgency_sound(config, &urgency) {
+        Some(UrgencySound::Name(name)) => {
+            notification.sound_name(&name);
+        }
+        #[cfg(all(unix, not(target_os = "macos")))]
+        Some(UrgencySound::File(path)) => {
+            notification.hint(notify_rust::Hint::SoundFile(path));
+        }
+        // Sound files are only supported by freedesktop notification servers
+        #[cfg(not(all(unix, not(target_os = "macos"))))]
+        Some(UrgencySound::File(_)) => {}
+        None => {}
+    }
+
+    // Record the notification so it can be reviewed later if it was missed
+    if config.get("persist_missed").and_then(|v| v.as_bool()).unwrap_or(false) {
+        let path = config
+            .get("missed_file")
+            .and_then(|v| v.as_str())
+            .map(|p| PathBuf::from(shellexpand::tilde(p).as_ref()))
+            .unwrap_or_else(default_missed_path);
+        // A notification that can't be recorded is still worth showing
+        if let Err(e) = append_missed(&path, &summary, &body) {
+            eprintln!("boopifier: could not record missed notification in {}: {}", path.display(), e);
+        }
+    }
+
+    Ok(notification)
+}
+
+/// Action id of the Allow button.
+const ALLOW_ACTION: &str = "allow";
+
+/// Action id of the Deny button.
+const DENY_ACTION: &str = "deny";
+
+/// Default time to wait for an Allow/Deny click.
+const DEFAULT_APPROVAL_TIMEOUT_MS: u32 = 30_000;
+
+/// Reads `approval_default`, the decision used when no button is clicked.
+fn approval_default(config: &HashMap<String, Value>) -> HandlerResult<PermissionDecision> {
+    match config.get("approval_default").and_then(|v| v.as_str()) {
+        None | Some("ask") => Ok(PermissionDecision::Ask),
+        Some("allow") => Ok(PermissionDecision::Allow),
+        Some("deny") => Ok(PermissionDecision::Deny),
+        Some(other) => Err(NotificationError::InvalidConfig(format!(
+            "Desktop approval_default must be allow, deny or ask, got: {}",
+            other
+        ))),
+    }
+}
+
+/// Maps the clicked action id (`None` if nothing was clicked in time) to a decision.
+fn approval_response(action: Option<&str>, fallback: PermissionDecision) -> InteractiveResponse {
+    let (decision, reason) = match action {
+        Some(ALLOW_ACTION) => (PermissionDecision::Allow, "Allowed from desktop notification"),
+        Some(DENY_ACTION) => (PermissionDecision::Deny, "Denied from desktop notification"),
+        _ => (fallback, "No response to desktop notification"),
+    };
+    InteractiveResponse {
+        decision,
+        reason: Some(reason.to_string()),
+    }
+}
+
+/// Shows the notification and waits up to `timeout` for an action click.
+///
+/// Returns `None` when the notification is dismissed or the timeout passes.
+/// On timeout the notification is closed, so its buttons can't be clicked
+/// after the hook has already answered.
+#[cfg(all(unix, not(target_os = "macos")))]
+async fn wait_for_action(notification: Notification, timeout: Duration) -> HandlerResult<Option<String>> {
+    let handle = notification
+        .show()
+        .map_err(|e| NotificationError::SendFailed(format!("Failed to send desktop notification: {}", e)))?;
+    let id = handle.id();
+
+    // Blocks until the notification is clicked or closed, which the daemon
+    // does once the notification's own timeout expires
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    tokio::task::spawn_blocking(move || {
+        handle.wait_for_action(|action| {
+            let _ = tx.send(action.to_string());
+        })
+    });
+
+    match tokio::time::timeout(timeout, rx).await {
+        Ok(Ok(action)) if action != "__closed" => Ok(Some(action)),
+        Ok(_) => Ok(None),
+        Err(_) => {
+            // The handle went to the waiting thread, so close the notification
+            // through a replacement with the same id; closing it also ends that wait
+            let mut replacement = notification;
+            replacement.id(id);
+            let _ = tokio::task::spawn_blocking(move || replacement.show().map(|handle| handle.close())).await;
+            Ok(None)
+        }
+    }
+}
+
+/// Notification actions need a freedesktop server; elsewhere the notification
+/// is shown without buttons and no action is ever reported.
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+async fn wait_for_action(notification: Notification, _timeout: Duration) -> HandlerResult<Option<String>> {
+    notification
+        .show()
+        .map_err(|e| NotificationError::SendFailed(format!("Failed to send desktop notification: {}", e)))?;
+    Ok(None)
+}
+
+/// Checks that a freedesktop notification daemon answers on D-Bus.
+#[cfg(all(unix, not(target_os = "macos")))]
+async fn notification_server_diagnostic() -> Diagnostic {
+    match tokio::task::spawn_blocking(notify_rust::get_server_information).await {
+        Ok(Ok(info)) => Diagnostic::pass(format!("notification daemon reachable: {} {}", info.name, info.version)),
+        Ok(Err(e)) => Diagnostic::fail(format!("no notification daemon reachable: {}", e)),
+        Err(e) => Diagnostic::fail(format!("notification daemon check failed: {}", e)),
+    }
+}
+
+/// macOS and Windows always have a system notification center.
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+async fn notification_server_diagnostic() -> Diagnostic {
+    Diagnostic::pass("using the system notification center")
+}
+
+/// The configured Windows AppUserModelID, if any.
+fn app_id(config: &HashMap<String, Value>) -> Option<String> {
+    config
+        .get("app_id")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(target_os = "windows")]
+fn app_id_diagnostic(id: &str) -> Diagnostic {
+    Diagnostic::pass(format!("toasts use AppUserModelID '{}'", id))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn app_id_diagnostic(_id: &str) -> Diagnostic {
+    Diagnostic::warn("'app_id' only applies on Windows and is ignored here")
+}
+
+/// A desktop notification recorded for later review.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissedNotification {
+    /// Local time the notification was sent
+    pub timestamp: String,
+    /// Notification title
+    pub summary: String,
+    /// Notification body
+    pub body: String,
+}
+
+/// Default location of the missed notifications file (`~/.claude/boopifier-missed.jsonl`).
+pub fn default_missed_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".claude/boopifier-missed.jsonl")
+}
+
+/// Appends a notification to the missed file, one JSON object per line.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created or written.
+pub fn append_missed(path: &Path, summary: &str, body: &str) -> HandlerResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let entry = MissedNotification {
+        timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        summary: summary.to_string(),
+        body: body.to_string(),
+    };
+    let line = serde_json::to_string(&entry)
+        .map_err(|e| NotificationError::Handler(format!("Failed to serialize missed notification: {}", e)))?;
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// Reads all recorded missed notifications. A missing file yields an empty list.
+///
+/// Lines that fail to parse are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn read_missed(path: &Path) -> HandlerResult<Vec<MissedNotification>> {
+    let file = match fs::File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        if let Ok(entry) = serde_json::from_str(&line?) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// Removes all recorded missed notifications.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be removed.
+pub fn clear_missed(path: &Path) -> HandlerResult<()> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Sound to attach to a notification.
+#[derive(Debug, Clone, PartialEq)]
+enum UrgencySound {
+    /// Named system sound (freedesktop `sound-name`, or a macOS/Windows sound name)
+    Name(String),
+    /// Path to a sound file (freedesktop `sound-file`)
+    File(String),
+}
+
+/// Looks up the sound for an urgency in the `sounds_by_urgency` map.
+///
+/// Unknown urgencies use the `normal` entry. Values containing a path separator
+/// or starting with `~` are treated as files; anything else is a sound name.
+fn urgency_sound(config: &HashMap<String, Value>, urgency: &str) -> Option<UrgencySound> {
+    let sounds = config.get("sounds_by_urgency")?.as_object()?;
+    let key = match urgency {
+        "low" | "critical" => urgency,
+        _ => "normal",
+    };
+    let sound = sounds.get(key)?.as_str()?;
+
+    if sound.contains('/') || sound.starts_with('~') {
+        Some(UrgencySound::File(shellexpand::tilde(sound).to_string()))
+    } else {
+        Some(UrgencySound::Name(sound.to_string()))
+    }
 }
 
 /// Helper to get string from config with default.
@@ -74,21 +398,7 @@ fn render_template(template: Option<&Value>, event: &Event) -> String {
         _ => return format!("{:?}", event.data),
     };
 
-    let mut result = template_str.clone();
-
-    // Simple template substitution: {{field_name}}
-    for (key, value) in &event.data {
-        let placeholder = format!("{{{{{}}}}}", key);
-        if result.contains(&placeholder) {
-            let value_str = match value {
-                Value::String(s) => s.clone(),
-                other => other.to_string(),
-            };
-            result = result.replace(&placeholder, &value_str);
-        }
-    }
-
-    result
+    template::render(template_str, event)
 }
 
 #[cfg(test)]
@@ -105,6 +415,147 @@ mod tests {
         assert_eq!(result, "Tool bash completed with status success");
     }
 
+    #[test]
+    fn test_missed_append_read_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/missed.jsonl");
+
+        assert!(read_missed(&path).unwrap().is_empty());
+
+        append_missed(&path, "First", "body one").unwrap();
+        append_missed(&path, "Second", "body\ntwo").unwrap();
+
+        let entries = read_missed(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].summary, "First");
+        assert_eq!(entries[1].body, "body\ntwo");
+
+        clear_missed(&path).unwrap();
+        assert!(read_missed(&path).unwrap().is_empty());
+        // Clearing twice is fine
+        clear_missed(&path).unwrap();
+    }
+
+    #[test]
+    fn test_urgency_sound_mapping() {
+        let mut config = HashMap::new();
+        config.insert(
+            "sounds_by_urgency".to_string(),
+            serde_json::json!({
+                "normal": "message-new-instant",
+                "critical": "/usr/share/sounds/alarm.oga"
+            }),
+        );
+
+        assert_eq!(
+            urgency_sound(&config, "normal"),
+            Some(UrgencySound::Name("message-new-instant".to_string()))
+        );
+        assert_eq!(
+            urgency_sound(&config, "critical"),
+            Some(UrgencySound::File("/usr/share/sounds/alarm.oga".to_string()))
+        );
+        // Unknown urgency falls back to normal; missing entries yield no sound
+        assert_eq!(
+            urgency_sound(&config, "bogus"),
+            Some(UrgencySound::Name("message-new-instant".to_string()))
+        );
+        assert_eq!(urgency_sound(&config, "low"), None);
+        assert_eq!(urgency_sound(&HashMap::new(), "normal"), None);
+    }
+
+    #[test]
+    fn test_approval_response_mapping() {
+        let allow = approval_response(Some("allow"), PermissionDecision::Ask);
+        assert_eq!(allow.decision, PermissionDecision::Allow);
+        assert_eq!(allow.reason.as_deref(), Some("Allowed from desktop notification"));
+
+        assert_eq!(approval_response(Some("deny"), PermissionDecision::Allow).decision, PermissionDecision::Deny);
+
+        // Timeouts and unknown actions (e.g. clicking the notification body) use the fallback
+        let timed_out = approval_response(None, PermissionDecision::Ask);
+        assert_eq!(timed_out.decision, PermissionDecision::Ask);
+        assert_eq!(timed_out.reason.as_deref(), Some("No response to desktop notification"));
+        assert_eq!(approval_response(Some("default"), PermissionDecision::Deny).decision, PermissionDecision::Deny);
+    }
+
+    #[test]
+    fn test_approval_default() {
+        let mut config = HashMap::new();
+        assert_eq!(approval_default(&config).unwrap(), PermissionDecision::Ask);
+
+        config.insert("approval_default".to_string(), Value::from("deny"));
+        assert_eq!(approval_default(&config).unwrap(), PermissionDecision::Deny);
+
+        config.insert("approval_default".to_string(), Value::from("maybe"));
+        assert!(matches!(approval_default(&config), Err(NotificationError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_default_timeout_covers_approval_wait() {
+        let mut config = HashMap::new();
+        assert_eq!(DesktopHandler.default_timeout(&config), DEFAULT_TIMEOUT);
+
+        config.insert("approval".to_string(), Value::Bool(true));
+        config.insert("approval_timeout_ms".to_string(), Value::from(60_000));
+        assert_eq!(DesktopHandler.default_timeout(&config), Duration::from_secs(60) + DEFAULT_TIMEOUT);
+    }
+
+    #[test]
+    fn test_body_sanitized() {
+        let dir = tempfile::tempdir().unwrap();
+        let missed = dir.path().join("missed.jsonl");
+        let event = Event::from_json(r#"{"message": "\u001b[1;31mBuild failed\u001b[0m\r\n\u001b]0;pwned\u0007see log"}"#)
+            .unwrap();
+        let mut config = HashMap::new();
+        config.insert("body".to_string(), Value::from("{{message}}"));
+        config.insert("persist_missed".to_string(), Value::from(true));
+        config.insert("missed_file".to_string(), Value::from(missed.display().to_string()));
+
+        build_notification(&event, &config).unwrap();
+        config.insert("sanitize".to_string(), Value::from(false));
+        build_notification(&event, &config).unwrap();
+
+        let entries = read_missed(&missed).unwrap();
+        assert_eq!(entries[0].body, "Build failed\nsee log");
+        assert!(entries[1].body.starts_with("\u{1b}[1;31mBuild failed"));
+    }
+
+    #[test]
+    fn test_unrecordable_missed_file_still_notifies() {
+        let dir = tempfile::tempdir().unwrap();
+        let event = Event::from_json(r#"{"message": "Build failed"}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert("persist_missed".to_string(), Value::from(true));
+        // A directory can't be appended to
+        config.insert("missed_file".to_string(), Value::from(dir.path().display().to_string()));
+
+        assert!(build_notification(&event, &config).is_ok());
+    }
+
+    #[test]
+    fn test_app_id() {
+        let mut config = HashMap::new();
+        assert_eq!(app_id(&config), None);
+
+        config.insert("app_id".to_string(), Value::from("  "));
+        assert_eq!(app_id(&config), None);
+
+        config.insert("app_id".to_string(), Value::from(" Boopifier.Claude "));
+        assert_eq!(app_id(&config).as_deref(), Some("Boopifier.Claude"));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_windows_notification_with_app_id() {
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert("app_id".to_string(), Value::from("Boopifier.Claude"));
+
+        assert!(build_notification(&event, &config).is_ok());
+        assert_eq!(app_id_diagnostic("Boopifier.Claude").message, "toasts use AppUserModelID 'Boopifier.Claude'");
+    }
+
     #[test]
     fn test_get_string() {
         let mut config = HashMap::new();
@@ -2,15 +2,24 @@
 //!
 //! Sends email notifications using SMTP.
 
-use crate::error::NotificationError;
+use crate::config::{MatchRules, MatchType};
+use crate::error::{NotificationError, SuppressionReason};
 use crate::event::Event;
-use crate::handlers::{Handler, HandlerResult};
+use crate::handlers::{message_template, ConfigAccess, Diagnostic, Handler, HandlerResult};
+use crate::matcher::matches;
+use crate::net;
+use crate::template;
 use async_trait::async_trait;
 use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
 use lettre::transport::smtp::authentication::Credentials;
-use lettre::{Message, SmtpTransport, Transport};
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use once_cell::sync::Lazy;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 /// Handler for email notifications.
 pub struct EmailHandler;
@@ -21,84 +30,291 @@ impl Handler for EmailHandler {
         "email"
     }
 
+    fn required_keys(&self) -> &'static [&'static str] {
+        &["to", "from", "smtp_server"]
+    }
+
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &[
+            "smtp_port", "username", "password", "subject", "body", "body_file", "templates", "lang",
+            "attachments", "routes",
+        ]
+    }
+
+    fn requires_network(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        // With routes, `to` (and `from`, if every route sets it) may be left out
+        let cfg = ConfigAccess::new("Email", config);
+        let routes = parse_routes(config)?;
+        if routes.is_empty() {
+            cfg.str("to")?;
+        }
+        if routes.is_empty() || routes.iter().any(|r| r.from.is_none()) {
+            cfg.str("from")?;
+        }
+        smtp_endpoint(config)?;
+        Ok(())
+    }
+
     async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
-        // Required config
-        let to = config
-            .get("to")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| NotificationError::InvalidConfig("Email handler requires 'to' configuration".to_string()))?;
-
-        let from = config
-            .get("from")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| NotificationError::InvalidConfig("Email handler requires 'from' configuration".to_string()))?;
-
-        let smtp_server = config
-            .get("smtp_server")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| NotificationError::InvalidConfig("Email handler requires 'smtp_server' configuration".to_string()))?;
-
-        let smtp_port = config
-            .get("smtp_port")
-            .and_then(|v| v.as_u64())
-            .map(|v| v as u16)
-            .unwrap_or(25);
+        let cfg = ConfigAccess::new("Email", config);
+
+        // Required config, with `to`/`from`/`subject` possibly chosen by a route
+        let Some(recipient) = select_recipient(event, config)? else {
+            return Err(NotificationError::suppressed(
+                SuppressionReason::NoRoute,
+                "no email route matches this event",
+            ));
+        };
+        let (smtp_server, smtp_port) = smtp_endpoint(config)?;
 
         // Optional config
-        let subject = render_template(
-            config.get("subject"),
-            event,
-            "Claude Code Notification",
-        );
-        let body = render_template(
-            config.get("body"),
-            event,
-            &format!("Event: {:?}", event.data),
-        );
+        let subject = render_template(recipient.subject, event, "Claude Code Notification");
+        let body = match template::render_body_file(config, event)? {
+            Some(body) => body,
+            None => render_template(
+                message_template(config, "body"),
+                event,
+                &format!("Event: {:?}", event.data),
+            ),
+        };
+
+        let attachments = parse_attachments(config, event)?;
 
         // SMTP credentials (optional)
-        let username = config.get("username").and_then(|v| v.as_str());
-        let password = config.get("password").and_then(|v| v.as_str());
+        let username = cfg.opt_str("username")?;
+        let password = cfg.opt_str("password")?;
 
         // Send email
         send_email(
-            from, to, &subject, &body, smtp_server, smtp_port, username, password,
+            recipient.from, recipient.to, &subject, &body, &attachments, smtp_server, smtp_port, username, password,
         )
         .await?;
 
         Ok(())
     }
+
+    async fn diagnose(&self, config: &HashMap<String, Value>) -> Vec<Diagnostic> {
+        // With routes, `to` (and `from`, if every route sets it) may be left out
+        let routes = parse_routes(config);
+        let required: &[&str] = match &routes {
+            Ok(routes) if routes.is_empty() => &["to", "from"],
+            Ok(routes) if routes.iter().all(|r| r.from.is_some()) => &[],
+            _ => &["from"],
+        };
+        let mut diagnostics: Vec<Diagnostic> = required
+            .iter()
+            .filter(|key| config.get(**key).and_then(|v| v.as_str()).is_none())
+            .map(|key| Diagnostic::fail(format!("no '{}' configured", key)))
+            .collect();
+        if let Err(e) = routes {
+            diagnostics.push(Diagnostic::fail(e.to_string()));
+        }
+
+        let (host, port) = match smtp_endpoint(config) {
+            Ok(endpoint) => endpoint,
+            Err(e) => {
+                diagnostics.push(Diagnostic::fail(e.to_string()));
+                return diagnostics;
+            }
+        };
+
+        diagnostics.push(match net::probe_tcp(host, port, net::PROBE_TIMEOUT).await {
+            Ok(()) => Diagnostic::pass(format!("SMTP server {}:{} reachable", host, port)),
+            Err(e) => Diagnostic::fail(format!("cannot connect to SMTP server {}:{}: {}", host, port, e)),
+        });
+        diagnostics
+    }
 }
 
+/// One entry of the `routes` array.
+struct EmailRoute<'a> {
+    rules: Option<MatchRules>,
+    to: &'a str,
+    from: Option<&'a str>,
+    subject: Option<&'a Value>,
+}
+
+/// Addressing for one email: a matching route's settings over the handler's own.
+#[derive(Debug, PartialEq)]
+struct Recipient<'a> {
+    to: &'a str,
+    from: &'a str,
+    subject: Option<&'a Value>,
+}
+
+/// Parses and validates every entry of `routes`, whether or not it will match.
+fn parse_routes(config: &HashMap<String, Value>) -> HandlerResult<Vec<EmailRoute<'_>>> {
+    let Some(routes) = config.get("routes") else {
+        return Ok(Vec::new());
+    };
+    let routes = routes
+        .as_array()
+        .ok_or_else(|| NotificationError::InvalidConfig("Email 'routes' must be an array".to_string()))?;
+
+    routes
+        .iter()
+        .enumerate()
+        .map(|(i, route)| {
+            let invalid = |message: String| NotificationError::InvalidConfig(format!("Email route {}: {}", i, message));
+            let route = route.as_object().ok_or_else(|| invalid("must be an object".to_string()))?;
+
+            let rules = route
+                .get("match_rules")
+                .or_else(|| route.get("match"))
+                .ok_or_else(|| invalid("requires 'match_rules'".to_string()))?;
+            let rules = serde_json::from_value(rules.clone())
+                .map_err(|e| invalid(format!("invalid 'match_rules': {}", e)))?;
+
+            let address = |key: &str| -> HandlerResult<Option<&str>> {
+                let Some(value) = route.get(key) else {
+                    return Ok(None);
+                };
+                let address = value
+                    .as_str()
+                    .ok_or_else(|| invalid(format!("'{}' must be a string", key)))?;
+                address
+                    .parse::<Mailbox>()
+                    .map_err(|e| invalid(format!("invalid '{}' address '{}': {}", key, address, e)))?;
+                Ok(Some(address))
+            };
+
+            Ok(EmailRoute {
+                rules: Some(rules),
+                to: address("to")?.ok_or_else(|| invalid("requires 'to'".to_string()))?,
+                from: address("from")?,
+                subject: route.get("subject"),
+            })
+        })
+        .collect()
+}
+
+/// Picks the first route matching the event, falling back to the top-level settings.
+///
+/// Returns `None` when no route matches and there is no top-level `to`.
+fn select_recipient<'a>(event: &Event, config: &'a HashMap<String, Value>) -> HandlerResult<Option<Recipient<'a>>> {
+    let cfg = ConfigAccess::new("Email", config);
+    let routes = parse_routes(config)?;
+    let has_routes = !routes.is_empty();
+    let route = routes
+        .into_iter()
+        .find(|route| matches(event, &route.rules, &MatchType::Exact));
+
+    let to = match &route {
+        Some(route) => route.to,
+        None if has_routes && cfg.opt_str("to")?.is_none() => return Ok(None),
+        None => cfg.str("to")?,
+    };
+    let from = match route.as_ref().and_then(|r| r.from) {
+        Some(from) => from,
+        None => cfg.str("from")?,
+    };
+    let subject = route.and_then(|r| r.subject).or_else(|| config.get("subject"));
+
+    Ok(Some(Recipient { to, from, subject }))
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn send_email(
     from: &str,
     to: &str,
     subject: &str,
     body: &str,
+    attachments: &[AttachmentSpec],
     smtp_server: &str,
     smtp_port: u16,
     username: Option<&str>,
     password: Option<&str>,
 ) -> HandlerResult<()> {
     // Build the email
-    let email = Message::builder()
-        .from(from.parse().map_err(|e| NotificationError::Email(format!("Invalid 'from' address: {}", e)))?)
-        .to(to.parse().map_err(|e| NotificationError::Email(format!("Invalid 'to' address: {}", e)))?)
-        .subject(subject)
-        .header(ContentType::TEXT_PLAIN)
-        .body(body.to_string())
-        .map_err(|e| NotificationError::Email(format!("Failed to build email: {}", e)))?;
+    let email = build_message(from, to, subject, body, attachments)?;
 
-    // Build SMTP transport - use builder_dangerous for local/test servers
-    let mut mailer = if smtp_port == 1025 || smtp_server == "localhost" || smtp_server == "127.0.0.1" {
+    let mailer = smtp_transport(smtp_server, smtp_port, username, password)?;
+
+    // Send the email
+    mailer
+        .send(email)
+        .await
+        .map_err(|e| NotificationError::Email(format!("Failed to send email: {}", e)))?;
+
+    Ok(())
+}
+
+/// Gets the SMTP transport for a server and login.
+///
+/// In long-running mode (`boopifier serve`) transports are cached, so events
+/// reuse the transport's connection pool instead of reconnecting; one-shot runs
+/// build a fresh transport per send.
+fn smtp_transport(
+    host: &str,
+    port: u16,
+    username: Option<&str>,
+    password: Option<&str>,
+) -> HandlerResult<Arc<SmtpTransport>> {
+    if crate::is_long_running() {
+        cached_transport(host, port, username, password)
+    } else {
+        build_transport(host, port, username, password).map(Arc::new)
+    }
+}
+
+/// The async SMTP transport, so a stalled server can't block the runtime's worker threads.
+type SmtpTransport = AsyncSmtpTransport<Tokio1Executor>;
+
+/// Identifies a cached transport.
+///
+/// Deliberately not `Debug`, so the password can't end up in logs.
+#[derive(PartialEq, Eq, Hash)]
+struct TransportKey {
+    host: String,
+    port: u16,
+    credentials: Option<(String, String)>,
+}
+
+/// SMTP transports built in long-running mode, shared across events.
+static SMTP_TRANSPORTS: Lazy<Mutex<HashMap<TransportKey, Arc<SmtpTransport>>>> = Lazy::new(Default::default);
+
+/// Gets the cached transport for a server and login, building it on first use.
+fn cached_transport(
+    host: &str,
+    port: u16,
+    username: Option<&str>,
+    password: Option<&str>,
+) -> HandlerResult<Arc<SmtpTransport>> {
+    let key = TransportKey {
+        host: host.to_string(),
+        port,
+        credentials: username.zip(password).map(|(u, p)| (u.to_string(), p.to_string())),
+    };
+
+    let mut transports = SMTP_TRANSPORTS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(transport) = transports.get(&key) {
+        return Ok(transport.clone());
+    }
+    let transport = Arc::new(build_transport(host, port, username, password)?);
+    transports.insert(key, transport.clone());
+    Ok(transport)
+}
+
+/// Builds an SMTP transport: plain for local test servers, TLS otherwise.
+fn build_transport(
+    host: &str,
+    port: u16,
+    username: Option<&str>,
+    password: Option<&str>,
+) -> HandlerResult<SmtpTransport> {
+    // Use builder_dangerous for local/test servers
+    let mut mailer = if is_local_test_server(host, port) {
         // Local test server - no TLS
-        SmtpTransport::builder_dangerous(smtp_server)
-            .port(smtp_port)
+        SmtpTransport::builder_dangerous(host).port(port)
     } else {
         // Production server - use relay with TLS
-        SmtpTransport::relay(smtp_server)
+        SmtpTransport::relay(host)
             .map_err(|e| NotificationError::Email(format!("Failed to connect to SMTP server: {}", e)))?
-            .port(smtp_port)
+            .port(port)
     };
 
     // Add credentials if provided
@@ -106,42 +322,156 @@ async fn send_email(
         mailer = mailer.credentials(Credentials::new(user.to_string(), pass.to_string()));
     }
 
-    let mailer = mailer.build();
+    Ok(mailer.build())
+}
 
-    // Send the email
-    mailer
-        .send(&email)
-        .map_err(|e| NotificationError::Email(format!("Failed to send email: {}", e)))?;
+/// Default SMTP port when neither `smtp_port` nor `smtp_server` specify one.
+const DEFAULT_SMTP_PORT: u16 = 25;
 
-    Ok(())
+/// Reads the SMTP host and port from config.
+///
+/// `smtp_server` may include a port (`smtp.example.com:587`, `[::1]:1025`);
+/// an explicit `smtp_port` takes precedence over it.
+fn smtp_endpoint(config: &HashMap<String, Value>) -> HandlerResult<(&str, u16)> {
+    let cfg = ConfigAccess::new("Email", config);
+    let (host, embedded_port) = net::split_host_port(cfg.str("smtp_server")?);
+    let port = cfg.opt_u16("smtp_port")?.or(embedded_port).unwrap_or(DEFAULT_SMTP_PORT);
+
+    Ok((host, port))
 }
 
-fn render_template(template: Option<&Value>, event: &Event, default: &str) -> String {
-    let template_str = match template {
-        Some(Value::String(s)) => s,
-        _ => return default.to_string(),
+/// Local test servers (loopback hosts, or the conventional port 1025) are used without TLS.
+fn is_local_test_server(host: &str, port: u16) -> bool {
+    port == 1025 || net::is_loopback_host(host)
+}
+
+/// A file to attach, with the name and MIME type shown to the recipient.
+#[derive(Debug, Clone, PartialEq)]
+struct AttachmentSpec {
+    path: PathBuf,
+    filename: String,
+    content_type: String,
+}
+
+/// Default MIME type for attachments without an explicit `content_type`.
+const DEFAULT_ATTACHMENT_TYPE: &str = "application/octet-stream";
+
+/// Parses the `attachments` config.
+///
+/// Each entry is either a path string or a `{path, filename, content_type}` object.
+/// Paths and filenames are rendered as templates and paths are tilde-expanded;
+/// `filename` defaults to the path's basename.
+fn parse_attachments(config: &HashMap<String, Value>, event: &Event) -> HandlerResult<Vec<AttachmentSpec>> {
+    let entries = match config.get("attachments") {
+        None | Some(Value::Null) => return Ok(Vec::new()),
+        Some(Value::Array(entries)) => entries,
+        Some(_) => {
+            return Err(NotificationError::InvalidConfig(
+                "Email 'attachments' must be an array".to_string(),
+            ))
+        }
     };
 
-    let mut result = template_str.clone();
+    entries
+        .iter()
+        .map(|entry| {
+            let (path, filename, content_type) = match entry {
+                Value::String(path) => (path.as_str(), None, None),
+                Value::Object(obj) => {
+                    let path = obj.get("path").and_then(|v| v.as_str()).ok_or_else(|| {
+                        NotificationError::InvalidConfig("Email attachment object requires 'path'".to_string())
+                    })?;
+                    (
+                        path,
+                        obj.get("filename").and_then(|v| v.as_str()),
+                        obj.get("content_type").and_then(|v| v.as_str()),
+                    )
+                }
+                _ => {
+                    return Err(NotificationError::InvalidConfig(
+                        "Email attachment must be a path or an object".to_string(),
+                    ))
+                }
+            };
 
-    // Simple template substitution: {{field_name}}
-    for (key, value) in &event.data {
-        let placeholder = format!("{{{{{}}}}}", key);
-        if result.contains(&placeholder) {
-            let value_str = match value {
-                Value::String(s) => s.clone(),
-                other => other.to_string(),
+            // Paths are taken literally, so an event can't pick which local file gets mailed
+            let path = PathBuf::from(shellexpand::tilde(path).as_ref());
+            let filename = match filename {
+                Some(name) => template::render(name, event),
+                None => basename(&path),
             };
-            result = result.replace(&placeholder, &value_str);
+
+            Ok(AttachmentSpec {
+                path,
+                filename,
+                content_type: content_type.unwrap_or(DEFAULT_ATTACHMENT_TYPE).to_string(),
+            })
+        })
+        .collect()
+}
+
+fn basename(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "attachment".to_string())
+}
+
+/// Builds the message, as a plain text body or multipart with attachments.
+fn build_message(
+    from: &str,
+    to: &str,
+    subject: &str,
+    body: &str,
+    attachments: &[AttachmentSpec],
+) -> HandlerResult<Message> {
+    let builder = Message::builder()
+        .from(from.parse().map_err(|e| NotificationError::Email(format!("Invalid 'from' address: {}", e)))?)
+        .to(to.parse().map_err(|e| NotificationError::Email(format!("Invalid 'to' address: {}", e)))?)
+        .subject(subject);
+
+    let message = if attachments.is_empty() {
+        builder
+            .header(ContentType::TEXT_PLAIN)
+            .body(body.to_string())
+    } else {
+        let mut multipart = MultiPart::mixed().singlepart(SinglePart::plain(body.to_string()));
+        for attachment in attachments {
+            let content = fs::read(&attachment.path).map_err(|e| {
+                NotificationError::InvalidConfig(format!(
+                    "Failed to read attachment {}: {}",
+                    attachment.path.display(),
+                    e
+                ))
+            })?;
+            let content_type = ContentType::parse(&attachment.content_type).map_err(|e| {
+                NotificationError::InvalidConfig(format!(
+                    "Invalid attachment content_type '{}': {}",
+                    attachment.content_type, e
+                ))
+            })?;
+            multipart = multipart.singlepart(
+                Attachment::new(attachment.filename.clone()).body(content, content_type),
+            );
         }
-    }
+        builder.multipart(multipart)
+    };
+
+    message.map_err(|e| NotificationError::Email(format!("Failed to build email: {}", e)))
+}
+
+fn render_template(template: Option<&Value>, event: &Event, default: &str) -> String {
+    let template_str = match template {
+        Some(Value::String(s)) => s,
+        _ => return default.to_string(),
+    };
 
-    result
+    template::render(template_str, event)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
 
     #[test]
     fn test_handler_type() {
@@ -158,6 +488,205 @@ mod tests {
         assert_eq!(result, "Task build: success");
     }
 
+    #[test]
+    fn test_parse_attachments_string_and_object() {
+        let event = Event::from_json(r#"{"session_id": "abc"}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert(
+            "attachments".to_string(),
+            serde_json::json!([
+                "/var/log/build.log",
+                "/tmp/{{session_id}}.log",
+                {
+                    "path": "/tmp/run-20260314-0905.log",
+                    "filename": "session-{{session_id}}.log",
+                    "content_type": "text/plain"
+                }
+            ]),
+        );
+
+        let attachments = parse_attachments(&config, &event).unwrap();
+        assert_eq!(
+            attachments,
+            vec![
+                AttachmentSpec {
+                    path: PathBuf::from("/var/log/build.log"),
+                    filename: "build.log".to_string(),
+                    content_type: DEFAULT_ATTACHMENT_TYPE.to_string(),
+                },
+                AttachmentSpec {
+                    path: PathBuf::from("/tmp/{{session_id}}.log"),
+                    filename: "{{session_id}}.log".to_string(),
+                    content_type: DEFAULT_ATTACHMENT_TYPE.to_string(),
+                },
+                AttachmentSpec {
+                    path: PathBuf::from("/tmp/run-20260314-0905.log"),
+                    filename: "session-abc.log".to_string(),
+                    content_type: "text/plain".to_string(),
+                },
+            ]
+        );
+
+        config.insert("attachments".to_string(), serde_json::json!([{"filename": "x"}]));
+        assert!(parse_attachments(&config, &event).is_err());
+    }
+
+    #[test]
+    fn test_build_message_with_named_attachment() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run-20260314-0905.log");
+        fs::write(&path, "all good").unwrap();
+
+        let attachments = vec![AttachmentSpec {
+            path,
+            filename: "build.log".to_string(),
+            content_type: "text/plain".to_string(),
+        }];
+        let message =
+            build_message("bot@example.com", "user@example.com", "Build", "See attached", &attachments).unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+
+        assert!(formatted.contains("Content-Disposition: attachment; filename=\"build.log\""));
+        assert!(formatted.contains("Content-Type: text/plain"));
+        assert!(!formatted.contains("run-20260314-0905"));
+    }
+
+    #[test]
+    fn test_smtp_endpoint() {
+        let mut config = HashMap::new();
+        config.insert("smtp_server".to_string(), Value::String("smtp.example.com".to_string()));
+        assert_eq!(smtp_endpoint(&config).unwrap(), ("smtp.example.com", 25));
+
+        config.insert("smtp_server".to_string(), Value::String("[::1]:2525".to_string()));
+        assert_eq!(smtp_endpoint(&config).unwrap(), ("::1", 2525));
+
+        // Explicit smtp_port wins over an embedded port
+        config.insert("smtp_port".to_string(), Value::from(587));
+        assert_eq!(smtp_endpoint(&config).unwrap(), ("::1", 587));
+
+        config.insert("smtp_port".to_string(), Value::from(70_000));
+        assert!(smtp_endpoint(&config).is_err());
+    }
+
+    #[test]
+    fn test_is_local_test_server() {
+        assert!(is_local_test_server("localhost", 25));
+        assert!(is_local_test_server("127.0.0.1", 587));
+        assert!(is_local_test_server("::1", 2525));
+        assert!(is_local_test_server("smtp.example.com", 1025));
+        assert!(!is_local_test_server("smtp.example.com", 587));
+    }
+
+    #[tokio::test]
+    async fn test_cached_transport_reused_for_identical_config() {
+        let first = cached_transport("localhost", 2526, Some("bot"), Some("secret")).unwrap();
+        let again = cached_transport("localhost", 2526, Some("bot"), Some("secret")).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        // Any difference in server, port or login gets its own transport
+        let other_login = cached_transport("localhost", 2526, Some("bot"), Some("other")).unwrap();
+        let other_port = cached_transport("localhost", 2527, Some("bot"), Some("secret")).unwrap();
+        let anonymous = cached_transport("localhost", 2526, None, None).unwrap();
+        assert!(!Arc::ptr_eq(&first, &other_login));
+        assert!(!Arc::ptr_eq(&first, &other_port));
+        assert!(!Arc::ptr_eq(&first, &anonymous));
+    }
+
+    #[tokio::test]
+    async fn test_one_shot_builds_fresh_transport() {
+        assert!(!crate::is_long_running());
+        let first = smtp_transport("localhost", 2528, None, None).unwrap();
+        let again = smtp_transport("localhost", 2528, None, None).unwrap();
+        assert!(!Arc::ptr_eq(&first, &again));
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_smtp_connectivity() {
+        use crate::handlers::DiagnosticStatus;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let mut config = HashMap::new();
+        config.insert("to".to_string(), Value::String("me@example.com".to_string()));
+        config.insert("smtp_server".to_string(), Value::String(format!("127.0.0.1:{}", port)));
+
+        let diagnostics = EmailHandler.diagnose(&config).await;
+        assert_eq!(diagnostics[0], Diagnostic::fail("no 'from' configured"));
+        assert_eq!(diagnostics[1].status, DiagnosticStatus::Pass);
+
+        drop(listener);
+        let diagnostics = EmailHandler.diagnose(&config).await;
+        assert_eq!(diagnostics[1].status, DiagnosticStatus::Fail);
+        assert!(diagnostics[1].message.contains("cannot connect"));
+    }
+
+    fn routed_config() -> HashMap<String, Value> {
+        let config = json!({
+            "from": "claude@example.com",
+            "to": "me@example.com",
+            "subject": "Claude: {{hook_event_name}}",
+            "routes": [
+                {"match_rules": {"tool_name": "Bash"}, "to": "ops@example.com", "subject": "Shell: {{tool_name}}"},
+                {"match_rules": {"tool_name": {"in": ["Edit", "Write"]}}, "to": "dev@example.com",
+                 "from": "bot@example.com"}
+            ]
+        });
+        serde_json::from_value(config).unwrap()
+    }
+
+    #[test]
+    fn test_routes_select_first_match() {
+        let config = routed_config();
+        let event = |tool: &str| {
+            Event::from_json(&json!({"hook_event_name": "PreToolUse", "tool_name": tool}).to_string()).unwrap()
+        };
+
+        let bash = select_recipient(&event("Bash"), &config).unwrap().unwrap();
+        assert_eq!((bash.to, bash.from), ("ops@example.com", "claude@example.com"));
+        assert_eq!(render_template(bash.subject, &event("Bash"), ""), "Shell: Bash");
+
+        let edit = select_recipient(&event("Write"), &config).unwrap().unwrap();
+        assert_eq!((edit.to, edit.from), ("dev@example.com", "bot@example.com"));
+        assert_eq!(render_template(edit.subject, &event("Write"), ""), "Claude: PreToolUse");
+
+        // No route matches: the handler's own settings
+        let other = select_recipient(&event("Read"), &config).unwrap().unwrap();
+        assert_eq!(other.to, "me@example.com");
+    }
+
+    #[test]
+    fn test_routes_without_default() {
+        let mut config = routed_config();
+        config.remove("to");
+        let event = Event::from_json(r#"{"tool_name": "Read"}"#).unwrap();
+        assert_eq!(select_recipient(&event, &config).unwrap(), None);
+
+        config.remove("routes");
+        assert!(matches!(select_recipient(&event, &config), Err(NotificationError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_routes_validated() {
+        let event = Event::from_json(r#"{"tool_name": "Bash"}"#).unwrap();
+        let error = |routes: Value| {
+            let mut config = routed_config();
+            config.insert("routes".to_string(), routes);
+            select_recipient(&event, &config).unwrap_err().to_string()
+        };
+
+        // Addresses are checked even on routes that don't match
+        let message = error(json!([
+            {"match_rules": {"tool_name": "Bash"}, "to": "ops@example.com"},
+            {"match_rules": {"tool_name": "Edit"}, "to": "not an address"}
+        ]));
+        assert!(message.contains("Email route 1: invalid 'to' address 'not an address'"), "{}", message);
+
+        assert!(error(json!([{"to": "ops@example.com"}])).contains("requires 'match_rules'"));
+        assert!(error(json!([{"match_rules": {}, "from": "ops@example.com"}])).contains("requires 'to'"));
+        assert!(error(json!({"to": "ops@example.com"})).contains("must be an array"));
+    }
+
     #[tokio::test]
     async fn test_missing_config() {
         let handler = EmailHandler;
@@ -0,0 +1,135 @@
+//! Forward handler.
+//!
+//! POSTs the raw event JSON to another machine running `boopifier serve`, which
+//! runs it through that machine's own handlers.
+
+use crate::error::NotificationError;
+use crate::event::Event;
+use crate::handlers::webhook::get_http_client;
+use crate::handlers::{Handler, HandlerResult};
+use async_trait::async_trait;
+use reqwest::Url;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Default time allowed for the remote boopifier to run its handlers.
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+
+/// Handler that forwards events to a remote boopifier.
+pub struct ForwardHandler;
+
+#[async_trait]
+impl Handler for ForwardHandler {
+    fn handler_type(&self) -> &str {
+        "forward"
+    }
+
+    fn required_keys(&self) -> &'static [&'static str] {
+        &["url"]
+    }
+
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &["token", "timeout_ms"]
+    }
+
+    fn requires_network(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        forward_url(config).map(drop)
+    }
+
+    async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        let url = forward_url(config)?;
+        let timeout_ms = config
+            .get("timeout_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_TIMEOUT_MS);
+
+        let mut request = get_http_client()?
+            .post(url)
+            .timeout(Duration::from_millis(timeout_ms))
+            .json(&event.as_value());
+        if let Some(token) = config.get("token").and_then(|v| v.as_str()) {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| NotificationError::Network(format!("Failed to forward event: {}", e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(NotificationError::Network(format!(
+                "Forwarding failed with status {}: {}",
+                status,
+                body.trim()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads and validates the remote server's `url`.
+fn forward_url(config: &HashMap<String, Value>) -> HandlerResult<Url> {
+    let url = config
+        .get("url")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| NotificationError::InvalidConfig("Forward handler requires 'url' configuration".to_string()))?;
+
+    let parsed = Url::parse(url)
+        .map_err(|e| NotificationError::InvalidConfig(format!("Invalid forward url '{}': {}", url, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(NotificationError::InvalidConfig(format!(
+            "Forward url must be http or https: {}",
+            url
+        )));
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_handler_type() {
+        let handler = ForwardHandler;
+        assert_eq!(handler.handler_type(), "forward");
+    }
+
+    #[test]
+    fn test_forward_url_validation() {
+        let mut config = HashMap::new();
+        assert!(forward_url(&config).unwrap_err().to_string().contains("requires 'url'"));
+
+        config.insert("url".to_string(), json!("ftp://desk.local/event"));
+        assert!(forward_url(&config).unwrap_err().to_string().contains("http or https"));
+
+        config.insert("url".to_string(), json!("http://desk.local:7878/event"));
+        assert_eq!(forward_url(&config).unwrap().as_str(), "http://desk.local:7878/event");
+    }
+
+    #[tokio::test]
+    async fn test_connection_failure_is_network_error() {
+        // Bind then drop to get a port with nothing listening
+        let addr = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert("url".to_string(), json!(format!("http://{}/event", addr)));
+
+        assert!(matches!(
+            ForwardHandler.handle(&event, &config).await,
+            Err(NotificationError::Network(_))
+        ));
+    }
+}
@@ -0,0 +1,183 @@
+//! Heartbeat handler.
+//!
+//! Sends an occasional "still working" desktop notification during long sessions.
+//! Boopifier only runs when an event arrives, so the handler checks on each event
+//! how long it has been since the last heartbeat (persisted to a file) and only
+//! fires once `interval_ms` has elapsed. A `Stop` event restarts the clock.
+
+use crate::error::{NotificationError, SuppressionReason};
+use crate::event::Event;
+use crate::handlers::desktop::DesktopHandler;
+use crate::handlers::{lock_file, write_atomic, Handler, HandlerResult};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Default time between heartbeats (15 minutes).
+const DEFAULT_INTERVAL_MS: u64 = 15 * 60 * 1000;
+
+/// Default heartbeat text.
+const DEFAULT_MESSAGE: &str = "Claude is still working";
+
+/// Handler that fires a desktop notification at most once per interval.
+pub struct HeartbeatHandler;
+
+#[async_trait]
+impl Handler for HeartbeatHandler {
+    fn handler_type(&self) -> &str {
+        "heartbeat"
+    }
+
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &["interval_ms", "state_file", "message"]
+    }
+
+    async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        let interval_ms = config
+            .get("interval_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_INTERVAL_MS);
+        let state_path = config
+            .get("state_file")
+            .and_then(|v| v.as_str())
+            .map(|p| PathBuf::from(shellexpand::tilde(p).as_ref()))
+            .unwrap_or_else(default_state_path);
+
+        let is_stop = matches!(event.hook_name(), Some("Stop" | "SubagentStop"));
+        if !heartbeat_due(&state_path, now_ms(), interval_ms, is_stop)? {
+            return Err(NotificationError::suppressed(
+                SuppressionReason::Cooldown,
+                "heartbeat interval has not elapsed",
+            ));
+        }
+
+        // Delegate delivery to the desktop handler, using `message` as the body
+        let mut desktop_config = config.clone();
+        let message = config
+            .get("message")
+            .cloned()
+            .unwrap_or_else(|| Value::String(DEFAULT_MESSAGE.to_string()));
+        desktop_config.insert("body".to_string(), message);
+
+        DesktopHandler.handle(event, &desktop_config).await
+    }
+
+    fn serialize(&self) -> bool {
+        true
+    }
+}
+
+/// Default location of the heartbeat state file (`~/.claude/boopifier-heartbeat`).
+pub fn default_state_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".claude/boopifier-heartbeat")
+}
+
+/// Decides whether a heartbeat should fire at `now_ms`, updating the state file.
+///
+/// The first event (no state yet) and `Stop` events only start the clock.
+/// Otherwise a heartbeat is due once `interval_ms` has passed since the last
+/// one, and the clock restarts when it fires. The state file's `.lock` file is
+/// held throughout, so concurrent boopifier processes fire at most one heartbeat.
+fn heartbeat_due(path: &Path, now_ms: u64, interval_ms: u64, is_stop: bool) -> HandlerResult<bool> {
+    let _lock = lock_file(&path.with_extension("lock"))?;
+    let last = fs::read_to_string(path)
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok());
+
+    let due = match last {
+        Some(last) if !is_stop => now_ms.saturating_sub(last) >= interval_ms,
+        _ => false,
+    };
+
+    if due || is_stop || last.is_none() {
+        write_atomic(path, format!("{}\n", now_ms))?;
+    }
+
+    Ok(due)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handler_type() {
+        let handler = HeartbeatHandler;
+        assert_eq!(handler.handler_type(), "heartbeat");
+        assert!(handler.serialize());
+    }
+
+    #[test]
+    fn test_heartbeat_interval_gating() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("heartbeat");
+
+        // First event only starts the clock
+        assert!(!heartbeat_due(&path, 1_000, 500, false).unwrap());
+        // Within the interval
+        assert!(!heartbeat_due(&path, 1_400, 500, false).unwrap());
+        // Interval elapsed: fires and restarts the clock
+        assert!(heartbeat_due(&path, 1_500, 500, false).unwrap());
+        assert!(!heartbeat_due(&path, 1_900, 500, false).unwrap());
+        assert!(heartbeat_due(&path, 2_000, 500, false).unwrap());
+    }
+
+    #[test]
+    fn test_stop_resets_heartbeat() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("heartbeat");
+
+        heartbeat_due(&path, 1_000, 500, false).unwrap();
+        assert!(!heartbeat_due(&path, 1_600, 500, true).unwrap());
+        assert!(!heartbeat_due(&path, 2_000, 500, false).unwrap());
+        assert!(heartbeat_due(&path, 2_100, 500, false).unwrap());
+    }
+
+    #[test]
+    fn test_concurrent_events_fire_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("heartbeat");
+        heartbeat_due(&path, 1_000, 500, false).unwrap();
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let path = path.clone();
+                std::thread::spawn(move || heartbeat_due(&path, 2_000, 500, false).unwrap())
+            })
+            .collect();
+        let fired = threads.into_iter().map(|t| t.join().unwrap()).filter(|&due| due).count();
+        assert_eq!(fired, 1);
+    }
+
+    #[tokio::test]
+    async fn test_handle_suppressed_within_interval() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = HashMap::new();
+        config.insert(
+            "state_file".to_string(),
+            Value::String(dir.path().join("heartbeat").display().to_string()),
+        );
+        config.insert("interval_ms".to_string(), Value::from(60_000));
+
+        let event = Event::from_json(r#"{"hook_event_name": "PostToolUse"}"#).unwrap();
+        let result = HeartbeatHandler.handle(&event, &config).await;
+        assert!(matches!(
+            result,
+            Err(NotificationError::Suppressed {
+                reason: SuppressionReason::Cooldown,
+                ..
+            })
+        ));
+    }
+}
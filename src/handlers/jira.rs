@@ -0,0 +1,359 @@
+//! Jira notification handler.
+//!
+//! Posts a comment on an issue and/or transitions it, using the Jira Cloud
+//! REST API with basic auth (account email and API token).
+
+use crate::error::NotificationError;
+use crate::event::Event;
+use crate::handlers::webhook::get_http_client;
+use crate::handlers::{ConfigAccess, Handler, HandlerResult};
+use crate::template;
+use async_trait::async_trait;
+use reqwest::Url;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Handler for Jira issue comments and transitions.
+pub struct JiraHandler;
+
+#[async_trait]
+impl Handler for JiraHandler {
+    fn handler_type(&self) -> &str {
+        "jira"
+    }
+
+    fn required_keys(&self) -> &'static [&'static str] {
+        &["base_url", "email", "api_token", "issue_key"]
+    }
+
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &["comment", "transition_id"]
+    }
+
+    fn requires_network(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        // `issue_key` is usually templated, so its format is only checked once rendered
+        let cfg = ConfigAccess::new("Jira", config);
+        for key in ["base_url", "email", "api_token", "issue_key"] {
+            cfg.str(key)?;
+        }
+        if cfg.opt_str("comment")?.is_none() && matches!(config.get("transition_id"), None | Some(Value::Null)) {
+            return Err(NotificationError::InvalidConfig(
+                "Jira handler requires 'comment' or 'transition_id'".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        let settings = JiraSettings::from_config(event, config)?;
+
+        if let Some(comment) = &settings.comment {
+            settings.post("comment", json!({"body": comment})).await?;
+        }
+        if let Some(transition_id) = &settings.transition_id {
+            settings
+                .post("transitions", json!({"transition": {"id": transition_id}}))
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Validated Jira handler settings for one event.
+#[derive(Debug)]
+struct JiraSettings {
+    base_url: Url,
+    email: String,
+    api_token: String,
+    issue_key: String,
+    comment: Option<String>,
+    transition_id: Option<String>,
+}
+
+impl JiraSettings {
+    /// Reads and validates the config, rendering `issue_key` and `comment` against the event.
+    fn from_config(event: &Event, config: &HashMap<String, Value>) -> HandlerResult<Self> {
+        let required = |key: &str| {
+            config
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .ok_or_else(|| NotificationError::InvalidConfig(format!("Jira handler requires '{}' configuration", key)))
+        };
+
+        let base_url = required("base_url")?;
+        let base_url = Url::parse(base_url.trim_end_matches('/'))
+            .map_err(|e| NotificationError::InvalidConfig(format!("Invalid Jira base_url '{}': {}", base_url, e)))?;
+        if !matches!(base_url.scheme(), "http" | "https") {
+            return Err(NotificationError::InvalidConfig(format!(
+                "Jira base_url must be http or https: {}",
+                base_url
+            )));
+        }
+
+        let email = required("email")?.to_string();
+        let api_token = required("api_token")?.to_string();
+
+        let issue_key = template::render(required("issue_key")?, event);
+        if !is_issue_key(&issue_key) {
+            return Err(NotificationError::InvalidConfig(format!(
+                "Jira issue_key must look like PROJ-123, got: {}",
+                issue_key
+            )));
+        }
+
+        let comment = config
+            .get("comment")
+            .and_then(|v| v.as_str())
+            .map(|c| template::render(c, event));
+
+        // Transition ids are numeric strings in Jira, but accept bare numbers too
+        let transition_id = match config.get("transition_id") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(id)) => Some(id.clone()),
+            Some(Value::Number(id)) => Some(id.to_string()),
+            Some(_) => {
+                return Err(NotificationError::InvalidConfig(
+                    "Jira 'transition_id' must be a string or number".to_string(),
+                ))
+            }
+        };
+
+        if comment.is_none() && transition_id.is_none() {
+            return Err(NotificationError::InvalidConfig(
+                "Jira handler requires 'comment' or 'transition_id'".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            base_url,
+            email,
+            api_token,
+            issue_key,
+            comment,
+            transition_id,
+        })
+    }
+
+    /// URL of an issue sub-resource such as `comment` or `transitions`.
+    fn issue_url(&self, resource: &str) -> String {
+        format!(
+            "{}/rest/api/2/issue/{}/{}",
+            self.base_url.as_str().trim_end_matches('/'),
+            self.issue_key,
+            resource
+        )
+    }
+
+    /// POSTs `payload` to an issue sub-resource, surfacing Jira's error messages.
+    async fn post(&self, resource: &str, payload: Value) -> HandlerResult<()> {
+        let response = get_http_client()?
+            .post(self.issue_url(resource))
+            .basic_auth(&self.email, Some(&self.api_token))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| NotificationError::Network(format!("Failed to send Jira {} request: {}", resource, e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(NotificationError::Network(format!(
+                "Jira {} request for {} failed with status {}: {}",
+                resource,
+                self.issue_key,
+                status,
+                jira_error_message(&body)
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns true for keys like `OPS-42`: a project key, a dash, and a number.
+fn is_issue_key(key: &str) -> bool {
+    let Some((project, number)) = key.rsplit_once('-') else {
+        return false;
+    };
+    project.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && project.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Extracts the messages from a Jira error response.
+///
+/// Jira reports failures as `{"errorMessages": [...], "errors": {"field": "..."}}`;
+/// anything else is returned as-is.
+fn jira_error_message(body: &str) -> String {
+    let Ok(json) = serde_json::from_str::<Value>(body) else {
+        return body.trim().to_string();
+    };
+
+    let mut messages: Vec<String> = json
+        .get("errorMessages")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|m| m.as_str().map(|s| s.to_string()))
+        .collect();
+    if let Some(errors) = json.get("errors").and_then(|v| v.as_object()) {
+        messages.extend(
+            errors
+                .iter()
+                .map(|(field, m)| format!("{}: {}", field, m.as_str().unwrap_or(&m.to_string()))),
+        );
+    }
+
+    if messages.is_empty() {
+        body.trim().to_string()
+    } else {
+        messages.join("; ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn base_config() -> HashMap<String, Value> {
+        let mut config = HashMap::new();
+        config.insert("base_url".to_string(), json!("https://example.atlassian.net/"));
+        config.insert("email".to_string(), json!("ops@example.com"));
+        config.insert("api_token".to_string(), json!("token"));
+        config.insert("issue_key".to_string(), json!("{{ticket}}"));
+        config
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config = base_config();
+        assert!(JiraHandler.validate(&config).unwrap_err().to_string().contains("'comment' or 'transition_id'"));
+
+        // A templated issue_key is fine until it renders to something else
+        config.insert("transition_id".to_string(), json!(31));
+        assert!(JiraHandler.validate(&config).is_ok());
+
+        config.remove("email");
+        assert!(JiraHandler.validate(&config).unwrap_err().to_string().contains("requires 'email'"));
+    }
+
+    #[test]
+    fn test_handler_type() {
+        let handler = JiraHandler;
+        assert_eq!(handler.handler_type(), "jira");
+    }
+
+    #[test]
+    fn test_settings_from_config() {
+        let event = Event::from_json(r#"{"ticket": "OPS-42", "status": "failed"}"#).unwrap();
+        let mut config = base_config();
+        config.insert("comment".to_string(), json!("Build {{status}}"));
+        config.insert("transition_id".to_string(), json!(31));
+
+        let settings = JiraSettings::from_config(&event, &config).unwrap();
+        assert_eq!(settings.issue_key, "OPS-42");
+        assert_eq!(settings.comment.as_deref(), Some("Build failed"));
+        assert_eq!(settings.transition_id.as_deref(), Some("31"));
+        assert_eq!(
+            settings.issue_url("comment"),
+            "https://example.atlassian.net/rest/api/2/issue/OPS-42/comment"
+        );
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        let event = Event::from_json(r#"{"ticket": "OPS-42"}"#).unwrap();
+        let error = |config: &HashMap<String, Value>| JiraSettings::from_config(&event, config).unwrap_err().to_string();
+
+        // Neither action configured
+        assert!(error(&base_config()).contains("'comment' or 'transition_id'"));
+
+        let mut config = base_config();
+        config.insert("comment".to_string(), json!("hi"));
+        config.remove("api_token");
+        assert!(error(&config).contains("requires 'api_token'"));
+
+        let mut config = base_config();
+        config.insert("comment".to_string(), json!("hi"));
+        config.insert("issue_key".to_string(), json!("{{missing}}"));
+        assert!(error(&config).contains("issue_key"));
+
+        let mut config = base_config();
+        config.insert("transition_id".to_string(), json!(["31"]));
+        assert!(error(&config).contains("transition_id"));
+    }
+
+    #[test]
+    fn test_is_issue_key() {
+        assert!(is_issue_key("OPS-42"));
+        assert!(is_issue_key("AB_2-1"));
+        assert!(!is_issue_key("OPS"));
+        assert!(!is_issue_key("OPS-"));
+        assert!(!is_issue_key("-42"));
+        assert!(!is_issue_key("OPS-42/../x"));
+    }
+
+    #[test]
+    fn test_jira_error_message() {
+        assert_eq!(
+            jira_error_message(r#"{"errorMessages": ["Issue does not exist"], "errors": {}}"#),
+            "Issue does not exist"
+        );
+        assert_eq!(
+            jira_error_message(r#"{"errorMessages": [], "errors": {"comment": "Comment body can not be empty!"}}"#),
+            "comment: Comment body can not be empty!"
+        );
+        assert_eq!(jira_error_message("Bad Gateway\n"), "Bad Gateway");
+    }
+
+    #[tokio::test]
+    async fn test_surfaces_jira_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            // Read until the JSON body has arrived
+            while !request.ends_with(b"}") {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+
+            let body = r#"{"errorMessages": ["Transition id 99 is not valid for this issue."], "errors": {}}"#;
+            let response = format!(
+                "HTTP/1.1 400 Bad Request\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+
+        let event = Event::from_json(r#"{"ticket": "OPS-42"}"#).unwrap();
+        let mut config = base_config();
+        config.insert("base_url".to_string(), json!(format!("http://{}", addr)));
+        config.insert("transition_id".to_string(), json!("99"));
+
+        let error = JiraHandler.handle(&event, &config).await.unwrap_err().to_string();
+        assert!(error.contains("Transition id 99 is not valid"), "{}", error);
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /rest/api/2/issue/OPS-42/transitions "));
+        assert!(request.to_lowercase().contains("authorization: basic "));
+        assert!(request.contains(r#""id":"99""#));
+    }
+}
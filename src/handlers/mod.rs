@@ -5,19 +5,42 @@
 
 use crate::error::Result;
 use crate::event::Event;
+use crate::hooks::InteractiveResponse;
 use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+pub mod badge;
+#[cfg(feature = "card")]
+pub mod card;
+pub mod config_access;
 pub mod desktop;
 pub mod email;
+pub mod forward;
+pub mod heartbeat;
+pub mod jira;
+pub mod noop;
+pub mod ntfy;
+pub mod policy;
 pub mod signal;
+#[cfg(feature = "aws")]
+pub mod sns;
 pub mod sound;
+pub mod status_file;
 pub mod webhook;
+pub mod websocket;
+pub mod worklog;
+
+pub use config_access::ConfigAccess;
 
 /// Error type for handler operations.
 pub type HandlerResult<T> = Result<T>;
 
+/// How long a handler call may run when neither its config nor the handler sets a limit.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Trait for notification handlers.
 #[async_trait]
 pub trait Handler: Send + Sync {
@@ -31,11 +54,144 @@ pub trait Handler: Send + Sync {
     /// * `event` - The event that triggered this handler
     /// * `config` - Handler-specific configuration from claudifier.json
     async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()>;
+
+    /// Handles an event, returning any decision the user made through the notification.
+    ///
+    /// Handlers that can ask the user (e.g. desktop Allow/Deny buttons on
+    /// `PreToolUse`) override this; the decision feeds the hook response. The
+    /// default runs [`Handler::handle`] and returns no decision.
+    async fn handle_interactive(
+        &self,
+        event: &Event,
+        config: &HashMap<String, Value>,
+    ) -> HandlerResult<Option<InteractiveResponse>> {
+        self.handle(event, config).await.map(|()| None)
+    }
+
+    /// Whether calls for the same configured handler must not overlap.
+    ///
+    /// Stateful handlers (e.g. ones appending to a file) can return `true` so that
+    /// concurrent events are handled one at a time per handler name.
+    fn serialize(&self) -> bool {
+        false
+    }
+
+    /// Whether the handler must finish before the event's other handlers start.
+    ///
+    /// Handlers that produce something other handlers use (e.g. a card image an
+    /// email attaches) return `true`; they still run concurrently with each other.
+    fn runs_first(&self) -> bool {
+        false
+    }
+
+    /// Config keys the handler needs in a minimal config, for tooling such as `--handlers-json`.
+    fn required_keys(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Config keys the handler also understands, besides [`Handler::required_keys`].
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Whether the handler can decide `hook` events interactively.
+    ///
+    /// True when [`Handler::handle_interactive`] may return a decision for that
+    /// hook type (e.g. desktop Allow/Deny buttons on `PreToolUse`).
+    fn supports_hook(&self, _hook: &str) -> bool {
+        false
+    }
+
+    /// Whether delivering needs the network (webhooks, email, push services).
+    ///
+    /// With `--offline`, handlers that return `true` are skipped and reported
+    /// as suppressed instead of waiting for timeouts.
+    fn requires_network(&self) -> bool {
+        false
+    }
+
+    /// How long a call may run when the handler's config sets no `timeout_ms`.
+    ///
+    /// Calls that run longer fail as timed out, so one hung handler can't hold
+    /// up the hook response. Handlers that wait on the user (like desktop
+    /// approvals) return a longer limit.
+    fn default_timeout(&self, _config: &HashMap<String, Value>) -> Duration {
+        DEFAULT_TIMEOUT
+    }
+
+    /// Checks that `config` has the settings this handler needs, for `boopifier --validate`.
+    ///
+    /// Unlike [`Handler::diagnose`], this only looks at the config itself: no
+    /// event templates are rendered and nothing is probed. The default accepts any config.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotificationError::InvalidConfig` describing the first problem found.
+    fn validate(&self, _config: &HashMap<String, Value>) -> HandlerResult<()> {
+        Ok(())
+    }
+
+    /// Checks whether this handler can deliver with the given config, for `boopifier doctor`.
+    ///
+    /// Implementations validate their config and probe what they depend on
+    /// (audio devices, binaries, servers). The default performs no checks.
+    async fn diagnose(&self, _config: &HashMap<String, Value>) -> Vec<Diagnostic> {
+        Vec::new()
+    }
+}
+
+/// Severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DiagnosticStatus {
+    /// The check succeeded
+    Pass,
+    /// Delivery may still work, but something looks off
+    Warn,
+    /// Delivery will fail
+    Fail,
+}
+
+/// Result of one environment check made by [`Handler::diagnose`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// How serious the finding is
+    pub status: DiagnosticStatus,
+    /// Human-readable description of what was checked and found
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Creates a passing check.
+    pub fn pass(message: impl Into<String>) -> Self {
+        Self {
+            status: DiagnosticStatus::Pass,
+            message: message.into(),
+        }
+    }
+
+    /// Creates a warning.
+    pub fn warn(message: impl Into<String>) -> Self {
+        Self {
+            status: DiagnosticStatus::Warn,
+            message: message.into(),
+        }
+    }
+
+    /// Creates a failed check.
+    pub fn fail(message: impl Into<String>) -> Self {
+        Self {
+            status: DiagnosticStatus::Fail,
+            message: message.into(),
+        }
+    }
 }
 
 /// Registry for managing notification handlers.
 pub struct HandlerRegistry {
     handlers: HashMap<String, Box<dyn Handler>>,
+
+    /// Per-handler-name locks for handlers that require serialized execution
+    locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
 }
 
 impl HandlerRegistry {
@@ -43,6 +199,7 @@ impl HandlerRegistry {
     pub fn new() -> Self {
         let mut registry = Self {
             handlers: HashMap::new(),
+            locks: Mutex::new(HashMap::new()),
         };
 
         // Register all built-in handlers
@@ -50,28 +207,166 @@ impl HandlerRegistry {
         registry.register(Box::new(sound::SoundHandler));
         registry.register(Box::new(signal::SignalHandler));
         registry.register(Box::new(webhook::WebhookHandler));
+        registry.register(Box::new(websocket::WebSocketHandler));
         registry.register(Box::new(email::EmailHandler));
+        registry.register(Box::new(badge::BadgeHandler));
+        registry.register(Box::new(heartbeat::HeartbeatHandler));
+        registry.register(Box::new(worklog::WorklogHandler));
+        registry.register(Box::new(jira::JiraHandler));
+        registry.register(Box::new(ntfy::NtfyHandler));
+        registry.register(Box::new(forward::ForwardHandler));
+        registry.register(Box::new(status_file::StatusFileHandler));
+        registry.register(Box::new(noop::NoopHandler));
+        registry.register(Box::new(policy::PolicyHandler));
+        #[cfg(feature = "aws")]
+        registry.register(Box::new(sns::SnsHandler));
+        #[cfg(feature = "card")]
+        registry.register(Box::new(card::CardHandler));
 
         registry
     }
 
     /// Registers a new handler.
+    ///
+    /// Handlers are keyed by [`Handler::handler_type`]. Registering a handler under
+    /// a type that is already present (including a built-in such as `desktop`)
+    /// replaces the existing one, so configs using that type run the new handler.
     pub fn register(&mut self, handler: Box<dyn Handler>) {
         let handler_type = handler.handler_type().to_string();
         self.handlers.insert(handler_type, handler);
     }
 
+    /// Removes the handler registered for a type, returning it if present.
+    ///
+    /// Configured handlers of a removed type fail with an unknown handler type error.
+    pub fn remove(&mut self, handler_type: &str) -> Option<Box<dyn Handler>> {
+        self.handlers.remove(handler_type)
+    }
+
     /// Gets a handler by type.
     pub fn get(&self, handler_type: &str) -> Option<&dyn Handler> {
         self.handlers.get(handler_type).map(|b| b.as_ref())
     }
 
+    /// Gets the lock used to serialize calls for a configured handler name.
+    pub fn handler_lock(&self, name: &str) -> Arc<tokio::sync::Mutex<()>> {
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        locks.entry(name.to_string()).or_default().clone()
+    }
+
     /// Lists all registered handler types.
     pub fn list_types(&self) -> Vec<&str> {
         self.handlers.keys().map(|s| s.as_str()).collect()
     }
 }
 
+/// Gets the message length limit for `service`.
+///
+/// A `max_body_length` in the handler config overrides the service's entry in
+/// [`SERVICE_LIMITS`](crate::text::SERVICE_LIMITS); services with neither are
+/// unlimited.
+pub(crate) fn max_body_length(config: &HashMap<String, Value>, service: &str) -> usize {
+    config
+        .get("max_body_length")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize)
+        .or_else(|| crate::text::service_limit(service))
+        .unwrap_or(usize::MAX)
+}
+
+/// Strips escape sequences and control characters from rendered text a handler displays.
+///
+/// Applied unless the handler's config sets `"sanitize": false`. See
+/// [`strip_control`](crate::text::strip_control).
+pub(crate) fn sanitize_display(config: &HashMap<String, Value>, text: String) -> String {
+    match config.get("sanitize").and_then(|v| v.as_bool()) {
+        Some(false) => text,
+        _ => crate::text::strip_control(&text),
+    }
+}
+
+/// Gets the template for a handler's main message setting `key`, localized if configured.
+///
+/// When the config has a `templates` map keyed by language code, the entry for
+/// `lang` (or the `LANG` environment variable when `lang` is unset) is used,
+/// falling back to its `default` entry and then to `key` itself.
+pub(crate) fn message_template<'a>(config: &'a HashMap<String, Value>, key: &str) -> Option<&'a Value> {
+    let localized = config.get("templates").and_then(|v| v.as_object()).and_then(|templates| {
+        let lang = match config.get("lang").and_then(|v| v.as_str()) {
+            Some(lang) => Some(lang.to_string()),
+            None => std::env::var("LANG").ok(),
+        };
+        crate::text::select_localized(templates, lang.as_deref())
+    });
+    localized.or_else(|| config.get(key))
+}
+
+/// Replaces the file at `path` with `contents` so readers never see a partial write.
+///
+/// Writes a temp file next to `path` and renames it into place, creating
+/// parent directories as needed.
+pub(crate) fn write_atomic(path: &std::path::Path, contents: impl AsRef<[u8]>) -> HandlerResult<()> {
+    write_atomic_with(path, contents.as_ref(), false)
+}
+
+/// Like [`write_atomic`], but the file is only readable by its owner (mode 0600 on Unix).
+pub(crate) fn write_private(path: &std::path::Path, contents: impl AsRef<[u8]>) -> HandlerResult<()> {
+    write_atomic_with(path, contents.as_ref(), true)
+}
+
+fn write_atomic_with(path: &std::path::Path, contents: &[u8], private: bool) -> HandlerResult<()> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let file_name = path.file_name().ok_or_else(|| {
+        crate::error::NotificationError::InvalidConfig(format!("Invalid file path: {:?}", path))
+    })?;
+    // Unique per call, so concurrent writers in one process don't share a temp file
+    static TMP_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let counter = TMP_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let mut tmp_name = file_name.to_os_string();
+    tmp_name.push(format!(".tmp.{}.{}", std::process::id(), counter));
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&tmp_path)?;
+    #[cfg(unix)]
+    if private {
+        // A stale temp file keeps its old mode, so set it explicitly too
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+    file.write_all(contents)?;
+    drop(file);
+
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Opens `path` (creating it if needed) and takes an exclusive lock on it.
+///
+/// Serializes read-modify-write sequences across boopifier processes; the lock
+/// is released when the returned file is dropped.
+pub(crate) fn lock_file(path: &std::path::Path) -> HandlerResult<std::fs::File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = std::fs::OpenOptions::new().create(true).truncate(false).write(true).open(path)?;
+    file.lock()?;
+    Ok(file)
+}
+
 impl Default for HandlerRegistry {
     fn default() -> Self {
         Self::new()
@@ -82,6 +377,58 @@ impl Default for HandlerRegistry {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_config_keys_listed_once() {
+        let registry = HandlerRegistry::new();
+        for handler_type in registry.list_types() {
+            let handler = registry.get(handler_type).unwrap();
+            let keys: Vec<&str> = handler.required_keys().iter().chain(handler.optional_keys()).copied().collect();
+            let unique: std::collections::HashSet<&str> = keys.iter().copied().collect();
+            assert_eq!(unique.len(), keys.len(), "{} lists a key twice", handler_type);
+        }
+    }
+
+    #[test]
+    fn test_required_keys_enforced_by_validate() {
+        let sample = |handler_type: &str, key: &str| -> Value {
+            Value::from(match key {
+                "url" if handler_type == "websocket" => "wss://example.com/feed",
+                "url" => "https://example.com/hook",
+                "base_url" => "https://example.atlassian.net",
+                "to" | "from" | "email" => "me@example.com",
+                "smtp_server" => "mail.example.com",
+                "api_token" => "token",
+                "issue_key" => "PROJ-1",
+                "topic" => "alerts",
+                "recipient" => "+1234567890",
+                "topic_arn" => "arn:aws:sns:us-east-1:123456789012:alerts",
+                "dir" => "/tmp/boopifier-log",
+                other => panic!("no sample value for required key '{}'", other),
+            })
+        };
+
+        let registry = HandlerRegistry::new();
+        for handler_type in registry.list_types() {
+            let handler = registry.get(handler_type).unwrap();
+            if handler.required_keys().is_empty() {
+                continue;
+            }
+            let mut config: HashMap<String, Value> =
+                handler.required_keys().iter().map(|key| (key.to_string(), sample(handler_type, key))).collect();
+            // Settings needed in one of several forms aren't listed as required
+            if handler_type == "jira" {
+                config.insert("comment".to_string(), Value::from("{{message}}"));
+            }
+            handler.validate(&config).unwrap_or_else(|e| panic!("{} rejects its sample config: {}", handler_type, e));
+
+            for key in handler.required_keys() {
+                let mut missing = config.clone();
+                missing.remove(*key);
+                assert!(handler.validate(&missing).is_err(), "{} accepts a config without '{}'", handler_type, key);
+            }
+        }
+    }
+
     #[test]
     fn test_registry_contains_handlers() {
         let registry = HandlerRegistry::new();
@@ -90,6 +437,82 @@ mod tests {
         assert!(registry.get("signal").is_some());
         assert!(registry.get("webhook").is_some());
         assert!(registry.get("email").is_some());
+        assert!(registry.get("badge").is_some());
+    }
+
+    /// Stand-in for the built-in desktop handler.
+    struct CustomDesktop;
+
+    #[async_trait]
+    impl Handler for CustomDesktop {
+        fn handler_type(&self) -> &str {
+            "desktop"
+        }
+
+        async fn handle(&self, _event: &Event, _config: &HashMap<String, Value>) -> HandlerResult<()> {
+            Ok(())
+        }
+
+        fn serialize(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_network_handlers_declared() {
+        let registry = HandlerRegistry::new();
+        let mut network: Vec<&str> = registry
+            .list_types()
+            .into_iter()
+            .filter(|name| registry.get(name).unwrap().requires_network())
+            .collect();
+        network.sort();
+
+        let mut expected = vec!["email", "forward", "jira", "ntfy", "signal", "webhook", "websocket"];
+        if cfg!(feature = "aws") {
+            expected.push("sns");
+            expected.sort();
+        }
+        assert_eq!(network, expected);
+    }
+
+    #[test]
+    fn test_register_overrides_builtin() {
+        let mut registry = HandlerRegistry::new();
+        assert!(!registry.get("desktop").unwrap().serialize());
+
+        registry.register(Box::new(CustomDesktop));
+        assert!(registry.get("desktop").unwrap().serialize());
+        assert_eq!(registry.list_types().iter().filter(|t| **t == "desktop").count(), 1);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut registry = HandlerRegistry::new();
+        let removed = registry.remove("email").unwrap();
+        assert_eq!(removed.handler_type(), "email");
+
+        assert!(registry.get("email").is_none());
+        assert!(!registry.list_types().contains(&"email"));
+        assert!(registry.remove("email").is_none());
+    }
+
+    #[test]
+    fn test_message_template() {
+        let mut config: HashMap<String, Value> = HashMap::new();
+        config.insert("body".to_string(), Value::from("Finished"));
+        assert_eq!(message_template(&config, "body"), Some(&Value::from("Finished")));
+
+        config.insert("templates".to_string(), serde_json::json!({"en": "Done", "de": "Fertig"}));
+        config.insert("lang".to_string(), Value::from("de_DE.UTF-8"));
+        assert_eq!(message_template(&config, "body"), Some(&Value::from("Fertig")));
+
+        // No entry for the language and no default: the handler's own setting
+        config.insert("lang".to_string(), Value::from("fr_FR"));
+        assert_eq!(message_template(&config, "body"), Some(&Value::from("Finished")));
+
+        config.insert("templates".to_string(), serde_json::json!({"en": "Done", "default": "Fini?"}));
+        assert_eq!(message_template(&config, "body"), Some(&Value::from("Fini?")));
     }
 
     #[test]
@@ -0,0 +1,61 @@
+//! No-op handler.
+//!
+//! Matches like any other handler and does nothing, so events that are
+//! intentionally ignored show up as handled rather than as unmatched.
+
+use crate::event::Event;
+use crate::handlers::{Handler, HandlerResult};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Handler that always succeeds without notifying anyone.
+pub struct NoopHandler;
+
+#[async_trait]
+impl Handler for NoopHandler {
+    fn handler_type(&self) -> &str {
+        "noop"
+    }
+
+    async fn handle(&self, _event: &Event, _config: &HashMap<String, Value>) -> HandlerResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::handlers::HandlerRegistry;
+    use crate::hooks::HandlerOutcome;
+
+    #[test]
+    fn test_handler_type() {
+        assert_eq!(NoopHandler.handler_type(), "noop");
+    }
+
+    #[tokio::test]
+    async fn test_ignored_events_count_as_handled() {
+        let config = Config::from_json(
+            r#"{
+                "handlers": [{"name": "ignore-reads", "type": "noop", "match_rules": {"tool_name": "Read"}, "config": {}}],
+                "on_no_match": {"name": "fallback", "type": "noop", "config": {}}
+            }"#,
+        )
+        .unwrap();
+        let registry = HandlerRegistry::new();
+
+        let timed = crate::process_event_with_options(
+            r#"{"hook_event_name": "PreToolUse", "tool_name": "Read"}"#,
+            &config,
+            &registry,
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(timed.len(), 1);
+        assert_eq!(timed[0].name, "ignore-reads");
+        assert!(matches!(timed[0].outcome, HandlerOutcome::Success));
+    }
+}
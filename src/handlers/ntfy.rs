@@ -0,0 +1,325 @@
+//! ntfy notification handler.
+//!
+//! Publishes messages to a topic on [ntfy.sh](https://ntfy.sh) or a self-hosted
+//! ntfy server, with the title, priority and tags sent as headers.
+
+use crate::error::NotificationError;
+use crate::event::Event;
+use crate::handlers::webhook::get_http_client;
+use crate::handlers::{max_body_length, message_template, Handler, HandlerResult};
+use crate::template;
+use crate::text::truncate_chars;
+use async_trait::async_trait;
+use base64::Engine;
+use reqwest::Url;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Server used when the config has no `server`.
+const DEFAULT_SERVER: &str = "https://ntfy.sh";
+
+/// Handler for ntfy push notifications.
+pub struct NtfyHandler;
+
+#[async_trait]
+impl Handler for NtfyHandler {
+    fn handler_type(&self) -> &str {
+        "ntfy"
+    }
+
+    fn required_keys(&self) -> &'static [&'static str] {
+        &["topic"]
+    }
+
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &[
+            "server", "message", "body_file", "templates", "lang", "max_body_length", "title", "priority",
+            "tags", "token",
+        ]
+    }
+
+    fn requires_network(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        NtfySettings::from_config(&Event::default(), config).map(drop)
+    }
+
+    async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        let settings = NtfySettings::from_config(event, config)?;
+
+        let mut request = get_http_client()?.post(settings.topic_url()).body(settings.message);
+        if let Some(title) = &settings.title {
+            request = request.header("Title", encode_header(title));
+        }
+        if let Some(priority) = settings.priority {
+            request = request.header("Priority", priority.to_string());
+        }
+        if !settings.tags.is_empty() {
+            request = request.header("Tags", encode_header(&settings.tags.join(",")));
+        }
+        if let Some(token) = &settings.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| NotificationError::Network(format!("Failed to publish to ntfy: {}", e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(NotificationError::Network(format!(
+                "ntfy publish to '{}' failed with status {}: {}",
+                settings.topic,
+                status,
+                body.trim()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Validated ntfy handler settings for one event.
+#[derive(Debug)]
+struct NtfySettings {
+    server: Url,
+    topic: String,
+    message: String,
+    title: Option<String>,
+    priority: Option<u8>,
+    tags: Vec<String>,
+    token: Option<String>,
+}
+
+impl NtfySettings {
+    /// Reads and validates the config, rendering the message and title against the event.
+    fn from_config(event: &Event, config: &HashMap<String, Value>) -> HandlerResult<Self> {
+        let server = config.get("server").and_then(|v| v.as_str()).unwrap_or(DEFAULT_SERVER);
+        let server = Url::parse(server.trim_end_matches('/'))
+            .map_err(|e| NotificationError::InvalidConfig(format!("Invalid ntfy server '{}': {}", server, e)))?;
+        if !matches!(server.scheme(), "http" | "https") {
+            return Err(NotificationError::InvalidConfig(format!(
+                "ntfy server must be http or https: {}",
+                server
+            )));
+        }
+
+        let topic = config
+            .get("topic")
+            .and_then(|v| v.as_str())
+            .map(|t| template::render(t, event))
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| NotificationError::InvalidConfig("ntfy handler requires 'topic' configuration".to_string()))?;
+        if topic.contains('/') {
+            return Err(NotificationError::InvalidConfig(format!(
+                "ntfy topic must not contain '/': {}",
+                topic
+            )));
+        }
+
+        let message = match template::render_body_file(config, event)? {
+            Some(body) => body,
+            None => render_message(message_template(config, "message"), event),
+        };
+        let message = truncate_chars(&message, max_body_length(config, "ntfy"));
+
+        let title = config
+            .get("title")
+            .and_then(|v| v.as_str())
+            .map(|t| template::render(t, event));
+
+        let priority = match config.get("priority") {
+            None | Some(Value::Null) => None,
+            Some(value) => match value.as_u64() {
+                Some(p @ 1..=5) => Some(p as u8),
+                _ => {
+                    return Err(NotificationError::InvalidConfig(format!(
+                        "ntfy 'priority' must be a number from 1 to 5, got: {}",
+                        value
+                    )))
+                }
+            },
+        };
+
+        let tags = match config.get("tags") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(tags)) => tags
+                .iter()
+                .map(|tag| {
+                    tag.as_str().map(|s| s.to_string()).ok_or_else(|| {
+                        NotificationError::InvalidConfig("ntfy 'tags' must be an array of strings".to_string())
+                    })
+                })
+                .collect::<HandlerResult<_>>()?,
+            Some(_) => {
+                return Err(NotificationError::InvalidConfig(
+                    "ntfy 'tags' must be an array of strings".to_string(),
+                ))
+            }
+        };
+
+        let token = config
+            .get("token")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+
+        Ok(Self {
+            server,
+            topic,
+            message,
+            title,
+            priority,
+            tags,
+            token,
+        })
+    }
+
+    /// URL messages for the topic are published to.
+    fn topic_url(&self) -> String {
+        format!("{}/{}", self.server.as_str().trim_end_matches('/'), self.topic)
+    }
+}
+
+/// Renders the message body template, defaulting to a dump of the event.
+fn render_message(template: Option<&Value>, event: &Event) -> String {
+    let template_str = match template {
+        Some(Value::String(s)) => s,
+        _ => return format!("Claude Code Event: {:?}", event.data),
+    };
+
+    template::render(template_str, event)
+}
+
+/// Makes `value` safe to send as a header.
+///
+/// Header values must be ASCII, so anything else (emoji in a title, say) is
+/// sent as an RFC 2047 encoded word, which ntfy decodes.
+fn encode_header(value: &str) -> String {
+    if value.bytes().all(|b| b.is_ascii() && !b.is_ascii_control()) {
+        value.to_string()
+    } else {
+        format!("=?UTF-8?B?{}?=", base64::engine::general_purpose::STANDARD.encode(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn base_config() -> HashMap<String, Value> {
+        let mut config = HashMap::new();
+        config.insert("topic".to_string(), json!("claude-{{session_id}}"));
+        config.insert("message".to_string(), json!("Finished in {{cwd}}"));
+        config
+    }
+
+    #[test]
+    fn test_handler_type() {
+        let handler = NtfyHandler;
+        assert_eq!(handler.handler_type(), "ntfy");
+    }
+
+    #[test]
+    fn test_settings_from_config() {
+        let event = Event::from_json(r#"{"session_id": "abc", "cwd": "/work"}"#).unwrap();
+        let mut config = base_config();
+        config.insert("title".to_string(), json!("Claude in {{cwd}}"));
+        config.insert("priority".to_string(), json!(4));
+        config.insert("tags".to_string(), json!(["robot", "white_check_mark"]));
+
+        let settings = NtfySettings::from_config(&event, &config).unwrap();
+        assert_eq!(settings.topic_url(), "https://ntfy.sh/claude-abc");
+        assert_eq!(settings.message, "Finished in /work");
+        assert_eq!(settings.title.as_deref(), Some("Claude in /work"));
+        assert_eq!(settings.priority, Some(4));
+        assert_eq!(settings.tags, vec!["robot", "white_check_mark"]);
+        assert!(settings.token.is_none());
+
+        config.insert("server".to_string(), json!("https://ntfy.example.com/"));
+        let settings = NtfySettings::from_config(&event, &config).unwrap();
+        assert_eq!(settings.topic_url(), "https://ntfy.example.com/claude-abc");
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        let event = Event::from_json(r#"{"session_id": "abc"}"#).unwrap();
+        let error = |config: &HashMap<String, Value>| NtfySettings::from_config(&event, config).unwrap_err().to_string();
+
+        let mut config = base_config();
+        config.remove("topic");
+        assert!(error(&config).contains("requires 'topic'"));
+
+        let mut config = base_config();
+        config.insert("topic".to_string(), json!("a/b"));
+        assert!(error(&config).contains("must not contain '/'"));
+
+        let mut config = base_config();
+        config.insert("priority".to_string(), json!(6));
+        assert!(error(&config).contains("1 to 5"));
+
+        let mut config = base_config();
+        config.insert("tags".to_string(), json!("robot"));
+        assert!(error(&config).contains("array of strings"));
+
+        let mut config = base_config();
+        config.insert("server".to_string(), json!("ftp://ntfy.example.com"));
+        assert!(error(&config).contains("http or https"));
+    }
+
+    #[test]
+    fn test_encode_header() {
+        assert_eq!(encode_header("Build done"), "Build done");
+        assert_eq!(encode_header("Done ✅"), "=?UTF-8?B?RG9uZSDinIU=?=");
+    }
+
+    #[tokio::test]
+    async fn test_publishes_with_headers() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            // Read until the message body has arrived
+            while !request.ends_with(b"/work") {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+
+            let response = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}";
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+
+        let event = Event::from_json(r#"{"session_id": "abc", "cwd": "/work"}"#).unwrap();
+        let mut config = base_config();
+        config.insert("server".to_string(), json!(format!("http://{}", addr)));
+        config.insert("title".to_string(), json!("Claude finished"));
+        config.insert("priority".to_string(), json!(5));
+        config.insert("tags".to_string(), json!(["robot", "tada"]));
+        config.insert("token".to_string(), json!("tk_secret"));
+
+        NtfyHandler.handle(&event, &config).await.unwrap();
+
+        let request = server.await.unwrap();
+        let lower = request.to_lowercase();
+        assert!(request.starts_with("POST /claude-abc "), "{}", request);
+        assert!(lower.contains("title: claude finished"));
+        assert!(lower.contains("priority: 5"));
+        assert!(lower.contains("tags: robot,tada"));
+        assert!(lower.contains("authorization: bearer tk_secret"));
+        assert!(request.ends_with("Finished in /work"));
+    }
+}
@@ -4,10 +4,13 @@
 
 use crate::error::NotificationError;
 use crate::event::Event;
-use crate::handlers::{Handler, HandlerResult};
+use crate::handlers::{max_body_length, message_template, ConfigAccess, Diagnostic, Handler, HandlerResult};
+use crate::template;
+use crate::text::truncate_chars;
 use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::time::Duration;
 use tokio::process::Command;
 
 /// Handler for Signal notifications.
@@ -19,6 +22,25 @@ impl Handler for SignalHandler {
         "signal"
     }
 
+    fn required_keys(&self) -> &'static [&'static str] {
+        &["recipient"]
+    }
+
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &[
+            "message", "body_file", "templates", "lang", "max_body_length", "account", "signal_cli_path",
+            "extra_args",
+        ]
+    }
+
+    fn requires_network(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        ConfigAccess::new("Signal", config).str("recipient").map(drop)
+    }
+
     async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
         // Get recipient from config
         let recipient = config
@@ -29,7 +51,11 @@ impl Handler for SignalHandler {
             })?;
 
         // Get message template or use default
-        let message = render_message(config.get("message"), event);
+        let message = match template::render_body_file(config, event)? {
+            Some(body) => body,
+            None => render_message(message_template(config, "message"), event),
+        };
+        let message = truncate_chars(&message, max_body_length(config, "signal"));
 
         // Get optional signal-cli path (defaults to 'signal-cli' in PATH)
         let signal_cli_path = config
@@ -40,28 +66,127 @@ impl Handler for SignalHandler {
         // Get optional account (sender number)
         let account = config.get("account").and_then(|v| v.as_str());
 
+        // Get optional extra signal-cli `send` options
+        let extra_args = extra_args(config)?;
+
         // Send the message
-        send_signal_message(signal_cli_path, account, recipient, &message).await?;
+        send_signal_message(signal_cli_path, account, &extra_args, recipient, &message).await?;
 
         Ok(())
     }
+
+    async fn diagnose(&self, config: &HashMap<String, Value>) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        if config.get("recipient").and_then(|v| v.as_str()).is_none() {
+            diagnostics.push(Diagnostic::fail("no 'recipient' configured"));
+        }
+        if let Err(e) = extra_args(config) {
+            diagnostics.push(Diagnostic::fail(e.to_string()));
+        }
+
+        let signal_cli_path = config
+            .get("signal_cli_path")
+            .and_then(|v| v.as_str())
+            .unwrap_or("signal-cli");
+
+        match run_signal_cli(signal_cli_path, &["--version"]).await {
+            Ok(version) => diagnostics.push(Diagnostic::pass(format!("found {}", version.trim()))),
+            Err(e) => {
+                diagnostics.push(Diagnostic::fail(format!("cannot run '{}': {}", signal_cli_path, e)));
+                return diagnostics;
+            }
+        }
+
+        // signal-cli needs a registered account to send from
+        let account = config.get("account").and_then(|v| v.as_str());
+        diagnostics.push(match run_signal_cli(signal_cli_path, &["listAccounts"]).await {
+            Ok(listing) => account_diagnostic(&listing, account),
+            Err(e) => Diagnostic::warn(format!("could not list signal-cli accounts: {}", e)),
+        });
+
+        diagnostics
+    }
 }
 
-async fn send_signal_message(
+/// Runs signal-cli with `args`, returning its stdout if it succeeds within 10 seconds.
+async fn run_signal_cli(signal_cli_path: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(signal_cli_path).args(args).kill_on_drop(true).output();
+    let output = tokio::time::timeout(Duration::from_secs(10), output)
+        .await
+        .map_err(|_| "timed out".to_string())?
+        .map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Checks `signal-cli listAccounts` output against the configured `account`.
+fn account_diagnostic(listing: &str, account: Option<&str>) -> Diagnostic {
+    let registered: Vec<&str> = listing
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Number:"))
+        .map(str::trim)
+        .collect();
+
+    match (account, registered.len()) {
+        (Some(account), _) if registered.contains(&account) => {
+            Diagnostic::pass(format!("account {} is registered", account))
+        }
+        (Some(account), _) => Diagnostic::fail(format!("account {} is not registered with signal-cli", account)),
+        (None, 0) => Diagnostic::fail("no signal-cli account registered"),
+        (None, 1) => Diagnostic::pass(format!("sending from {}", registered[0])),
+        (None, _) => Diagnostic::warn("several signal-cli accounts registered; set 'account' to pick one"),
+    }
+}
+
+/// Reads `extra_args`, which must be an array of strings if present.
+fn extra_args(config: &HashMap<String, Value>) -> HandlerResult<Vec<String>> {
+    let invalid = || NotificationError::InvalidConfig("Signal 'extra_args' must be an array of strings".to_string());
+    match config.get("extra_args") {
+        None => Ok(Vec::new()),
+        Some(Value::Array(args)) => args
+            .iter()
+            .map(|arg| arg.as_str().map(str::to_string).ok_or_else(invalid))
+            .collect(),
+        Some(_) => Err(invalid()),
+    }
+}
+
+/// Builds `signal-cli [-a account] send -m message [extra_args...] recipient`.
+///
+/// The process is killed if the send is dropped, so a hung signal-cli doesn't
+/// outlive the handler's timeout.
+fn signal_command(
     signal_cli_path: &str,
     account: Option<&str>,
+    extra_args: &[String],
     recipient: &str,
     message: &str,
-) -> HandlerResult<()> {
+) -> Command {
     let mut cmd = Command::new(signal_cli_path);
+    cmd.kill_on_drop(true);
 
     // Add account if specified
     if let Some(acc) = account {
         cmd.arg("-a").arg(acc);
     }
 
-    // Build the command
-    cmd.arg("send").arg("-m").arg(message).arg(recipient);
+    // Build the command, with any extra `send` options just before the recipient
+    cmd.arg("send").arg("-m").arg(message).args(extra_args).arg(recipient);
+    cmd
+}
+
+async fn send_signal_message(
+    signal_cli_path: &str,
+    account: Option<&str>,
+    extra_args: &[String],
+    recipient: &str,
+    message: &str,
+) -> HandlerResult<()> {
+    let mut cmd = signal_command(signal_cli_path, account, extra_args, recipient, message);
 
     // Execute the command
     let output = cmd
@@ -83,21 +208,7 @@ fn render_message(template: Option<&Value>, event: &Event) -> String {
         _ => return format!("Claude Code Event: {:?}", event.data),
     };
 
-    let mut result = template_str.clone();
-
-    // Simple template substitution: {{field_name}}
-    for (key, value) in &event.data {
-        let placeholder = format!("{{{{{}}}}}", key);
-        if result.contains(&placeholder) {
-            let value_str = match value {
-                Value::String(s) => s.clone(),
-                other => other.to_string(),
-            };
-            result = result.replace(&placeholder, &value_str);
-        }
-    }
-
-    result
+    template::render(template_str, event)
 }
 
 #[cfg(test)]
@@ -119,6 +230,78 @@ mod tests {
         assert_eq!(result, "Task build completed: success");
     }
 
+    use crate::handlers::DiagnosticStatus;
+
+    #[test]
+    fn test_account_diagnostic() {
+        let listing = "Number: +15550001\nNumber: +15550002\n";
+        assert_eq!(account_diagnostic(listing, Some("+15550002")).status, DiagnosticStatus::Pass);
+        assert_eq!(account_diagnostic(listing, Some("+15559999")).status, DiagnosticStatus::Fail);
+        assert_eq!(account_diagnostic(listing, None).status, DiagnosticStatus::Warn);
+        assert_eq!(account_diagnostic("Number: +15550001", None).status, DiagnosticStatus::Pass);
+        assert_eq!(account_diagnostic("", None).status, DiagnosticStatus::Fail);
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_missing_signal_cli() {
+        let mut config = HashMap::new();
+        config.insert("signal_cli_path".to_string(), Value::String("/nonexistent/signal-cli".to_string()));
+
+        let diagnostics = SignalHandler.diagnose(&config).await;
+        let statuses: Vec<_> = diagnostics.iter().map(|d| d.status).collect();
+        assert_eq!(statuses, vec![DiagnosticStatus::Fail, DiagnosticStatus::Fail]);
+        assert!(diagnostics[0].message.contains("recipient"));
+        assert!(diagnostics[1].message.contains("/nonexistent/signal-cli"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_diagnose_with_fake_signal_cli() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("signal-cli");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\ncase \"$1\" in\n  --version) echo 'signal-cli 0.13.0' ;;\n  listAccounts) echo 'Number: +15550001' ;;\nesac\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut config = HashMap::new();
+        config.insert("recipient".to_string(), Value::String("+15550002".to_string()));
+        config.insert("signal_cli_path".to_string(), Value::String(script.display().to_string()));
+
+        let diagnostics = SignalHandler.diagnose(&config).await;
+        assert_eq!(diagnostics[0], Diagnostic::pass("found signal-cli 0.13.0"));
+        assert_eq!(diagnostics[1], Diagnostic::pass("sending from +15550001"));
+    }
+
+    #[test]
+    fn test_extra_args_in_command() {
+        let mut config = HashMap::new();
+        config.insert("extra_args".to_string(), serde_json::json!(["--view-once", "--notify-self"]));
+        let extra = extra_args(&config).unwrap();
+
+        let cmd = signal_command("signal-cli", Some("+15550001"), &extra, "+15550002", "hi");
+        let args: Vec<_> = cmd.as_std().get_args().map(|a| a.to_str().unwrap()).collect();
+        assert_eq!(
+            args,
+            ["-a", "+15550001", "send", "-m", "hi", "--view-once", "--notify-self", "+15550002"]
+        );
+    }
+
+    #[test]
+    fn test_extra_args_validated() {
+        assert!(extra_args(&HashMap::new()).unwrap().is_empty());
+
+        for bad in [serde_json::json!("--view-once"), serde_json::json!(["--view-once", 1])] {
+            let mut config = HashMap::new();
+            config.insert("extra_args".to_string(), bad);
+            assert!(matches!(extra_args(&config), Err(NotificationError::InvalidConfig(_))));
+        }
+    }
+
     #[tokio::test]
     async fn test_missing_recipient() {
         let handler = SignalHandler;
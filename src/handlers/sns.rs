@@ -0,0 +1,334 @@
+//! AWS SNS notification handler.
+//!
+//! Publishes messages to an SNS topic using a SigV4-signed request against the
+//! SNS query API. Only built with the `aws` feature.
+
+use crate::error::NotificationError;
+use crate::event::Event;
+use crate::handlers::webhook::get_http_client;
+use crate::handlers::{max_body_length, message_template, ConfigAccess, Handler, HandlerResult};
+use crate::template;
+use crate::text::{split_chars, truncate_chars};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SNS_API_VERSION: &str = "2010-03-31";
+const CONTENT_TYPE: &str = "application/x-www-form-urlencoded; charset=utf-8";
+
+/// Handler for AWS SNS notifications.
+pub struct SnsHandler;
+
+#[async_trait]
+impl Handler for SnsHandler {
+    fn handler_type(&self) -> &str {
+        "sns"
+    }
+
+    fn required_keys(&self) -> &'static [&'static str] {
+        &["topic_arn"]
+    }
+
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &[
+            "region", "message", "templates", "lang", "subject", "sms", "split_sms", "max_body_length",
+            "access_key_id", "secret_access_key", "session_token",
+        ]
+    }
+
+    fn requires_network(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        ConfigAccess::new("SNS", config).str("topic_arn").map(drop)
+    }
+
+    async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        let topic_arn = config
+            .get("topic_arn")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                NotificationError::InvalidConfig("SNS handler requires 'topic_arn' configuration".to_string())
+            })?;
+
+        // Region: explicit config, then the topic ARN, then the environment
+        let region = config
+            .get("region")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .or_else(|| region_from_arn(topic_arn))
+            .or_else(|| std::env::var("AWS_REGION").ok())
+            .or_else(|| std::env::var("AWS_DEFAULT_REGION").ok())
+            .ok_or_else(|| {
+                NotificationError::InvalidConfig("SNS handler could not determine AWS region".to_string())
+            })?;
+
+        let credentials = Credentials::from_config(config)?;
+
+        let message = render_template(message_template(config, "message"), event);
+        let subject = config
+            .get("subject")
+            .and_then(|v| v.as_str())
+            .map(|s| template::render(s, event));
+
+        for message in messages(&message, config) {
+            let mut params = vec![
+                ("Action", "Publish"),
+                ("TopicArn", topic_arn),
+                ("Message", message.as_str()),
+                ("Version", SNS_API_VERSION),
+            ];
+            if let Some(subject) = subject.as_deref() {
+                params.push(("Subject", subject));
+            }
+            let body = form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(params)
+                .finish();
+
+            publish(&region, &credentials, body).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Applies the SMS length limit when the topic delivers to phones.
+///
+/// With `"sms": true` the message is truncated to one SMS segment, or split
+/// into several messages if `"split_sms": true`. Otherwise it is sent as-is.
+fn messages(message: &str, config: &HashMap<String, Value>) -> Vec<String> {
+    let flag = |key: &str| config.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+    if !flag("sms") {
+        return vec![message.to_string()];
+    }
+
+    let max = max_body_length(config, "sms");
+    if flag("split_sms") {
+        split_chars(message, max)
+    } else {
+        vec![truncate_chars(message, max)]
+    }
+}
+
+/// AWS credentials used to sign requests.
+struct Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl Credentials {
+    /// Reads credentials from handler config, falling back to the standard AWS env vars.
+    fn from_config(config: &HashMap<String, Value>) -> HandlerResult<Self> {
+        let lookup = |key: &str, env: &str| {
+            config
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .or_else(|| std::env::var(env).ok())
+        };
+
+        let access_key_id = lookup("access_key_id", "AWS_ACCESS_KEY_ID").ok_or_else(|| {
+            NotificationError::InvalidConfig("SNS handler requires 'access_key_id' or AWS_ACCESS_KEY_ID".to_string())
+        })?;
+        let secret_access_key = lookup("secret_access_key", "AWS_SECRET_ACCESS_KEY").ok_or_else(|| {
+            NotificationError::InvalidConfig(
+                "SNS handler requires 'secret_access_key' or AWS_SECRET_ACCESS_KEY".to_string(),
+            )
+        })?;
+        let session_token = lookup("session_token", "AWS_SESSION_TOKEN");
+
+        Ok(Self {
+            access_key_id,
+            secret_access_key,
+            session_token,
+        })
+    }
+}
+
+/// Extracts the region from an ARN like `arn:aws:sns:us-east-1:123456789012:topic`.
+fn region_from_arn(arn: &str) -> Option<String> {
+    let region = arn.split(':').nth(3)?;
+    if region.is_empty() {
+        None
+    } else {
+        Some(region.to_string())
+    }
+}
+
+async fn publish(region: &str, credentials: &Credentials, body: String) -> HandlerResult<()> {
+    let host = format!("sns.{}.amazonaws.com", region);
+    let now = Utc::now();
+    let authorization = sign_request(&host, region, credentials, &body, now);
+
+    let client = get_http_client()?;
+    let mut request = client
+        .post(format!("https://{}/", host))
+        .header("Content-Type", CONTENT_TYPE)
+        .header("X-Amz-Date", amz_date(now))
+        .header("Authorization", authorization);
+
+    if let Some(token) = &credentials.session_token {
+        request = request.header("X-Amz-Security-Token", token);
+    }
+
+    let response = request
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| NotificationError::Network(format!("Failed to publish to SNS: {}", e)))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(NotificationError::Network(format!(
+            "SNS publish failed with status {}: {}",
+            status, text
+        )));
+    }
+
+    Ok(())
+}
+
+/// Builds the SigV4 `Authorization` header for an SNS POST request.
+fn sign_request(
+    host: &str,
+    region: &str,
+    credentials: &Credentials,
+    body: &str,
+    now: DateTime<Utc>,
+) -> String {
+    let amz_date = amz_date(now);
+    let date_stamp = now.format("%Y%m%d").to_string();
+
+    let mut canonical_headers = format!(
+        "content-type:{}\nhost:{}\nx-amz-date:{}\n",
+        CONTENT_TYPE, host, amz_date
+    );
+    let mut signed_headers = "content-type;host;x-amz-date".to_string();
+    if let Some(token) = &credentials.session_token {
+        canonical_headers.push_str(&format!("x-amz-security-token:{}\n", token));
+        signed_headers.push_str(";x-amz-security-token");
+    }
+
+    let canonical_request = format!(
+        "POST\n/\n\n{}\n{}\n{}",
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(body.as_bytes()))
+    );
+
+    let scope = format!("{}/{}/sns/aws4_request", date_stamp, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = signing_key(&credentials.secret_access_key, &date_stamp, region, "sns");
+    let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature
+    )
+}
+
+/// Derives the SigV4 signing key for a date, region, and service.
+fn signing_key(secret: &str, date_stamp: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac(format!("AWS4{}", secret).as_bytes(), date_stamp.as_bytes());
+    let k_region = hmac(&k_date, region.as_bytes());
+    let k_service = hmac(&k_region, service.as_bytes());
+    hmac(&k_service, b"aws4_request")
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn amz_date(now: DateTime<Utc>) -> String {
+    now.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn render_template(template: Option<&Value>, event: &Event) -> String {
+    let template_str = match template {
+        Some(Value::String(s)) => s,
+        _ => return format!("Claude Code Event: {:?}", event.data),
+    };
+
+    template::render(template_str, event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handler_type() {
+        let handler = SnsHandler;
+        assert_eq!(handler.handler_type(), "sns");
+    }
+
+    #[test]
+    fn test_region_from_arn() {
+        assert_eq!(
+            region_from_arn("arn:aws:sns:eu-west-1:123456789012:alerts"),
+            Some("eu-west-1".to_string())
+        );
+        assert_eq!(region_from_arn("not-an-arn"), None);
+    }
+
+    #[test]
+    fn test_signing_key() {
+        // Example from the AWS SigV4 documentation
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_sms_messages_at_limit() {
+        let mut config = HashMap::new();
+        let exact = "a".repeat(160);
+        let over = "a".repeat(161);
+
+        // Topics without SMS subscribers are unlimited
+        assert_eq!(messages(&over, &config), vec![over.clone()]);
+
+        config.insert("sms".to_string(), Value::Bool(true));
+        assert_eq!(messages(&exact, &config), vec![exact.clone()]);
+        let truncated = messages(&over, &config);
+        assert_eq!(truncated.len(), 1);
+        assert_eq!(truncated[0].chars().count(), 160);
+        assert!(truncated[0].ends_with('…'));
+
+        config.insert("split_sms".to_string(), Value::Bool(true));
+        assert_eq!(messages(&over, &config), vec![exact, "a".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_topic_arn() {
+        let handler = SnsHandler;
+        let event = Event::from_json(r#"{"test": "data"}"#).unwrap();
+        let config = HashMap::new();
+
+        let result = handler.handle(&event, &config).await;
+        assert!(result.unwrap_err().to_string().contains("requires 'topic_arn'"));
+    }
+}
@@ -2,16 +2,18 @@
 //!
 //! Plays audio files using rodio.
 
-use crate::error::NotificationError;
+use crate::error::{NotificationError, SuppressionReason};
 use crate::event::Event;
-use crate::handlers::{Handler, HandlerResult};
+use crate::handlers::{ConfigAccess, Diagnostic, Handler, HandlerResult, DEFAULT_TIMEOUT};
 use async_trait::async_trait;
 use rand::seq::SliceRandom;
+use rand::Rng;
 use rodio::{Decoder, OutputStream, Sink};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::BufReader;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 // Suppress ALSA warnings on Linux (unless debug mode is enabled)
@@ -52,30 +54,82 @@ impl Handler for SoundHandler {
         "sound"
     }
 
-    async fn handle(&self, _event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &[
+            "file", "files", "random", "volume", "skip_if_no_device", "decode_timeout_ms",
+            "output_timeout_ms", "play_timeout_ms",
+        ]
+    }
+
+    fn default_timeout(&self, config: &HashMap<String, Value>) -> Duration {
+        // Leave playback time to run into its own limits, which cut the sound off cleanly
+        match PlaybackTimeouts::from_config(config) {
+            Ok(timeouts) => (timeouts.total() + Duration::from_secs(1)).max(DEFAULT_TIMEOUT),
+            Err(_) => DEFAULT_TIMEOUT,
+        }
+    }
+
+    fn validate(&self, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        get_sound_file(config, None)?;
+        ConfigAccess::new("Sound", config).f64("volume", 1.0)?;
+        PlaybackTimeouts::from_config(config).map(drop)
+    }
+
+    async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
         // Determine which file to play
-        let file_path = get_sound_file(config)?;
+        let state_path = default_state_path();
+        let state = event.context.handler_name.as_deref().map(|name| (state_path.as_path(), name));
+        let file_path = get_sound_file(config, state)?;
 
         // Expand tilde in path
         let expanded_path = shellexpand::tilde(&file_path).to_string();
 
         // Get optional volume (0.0 to 1.0, default 1.0)
-        let volume = config
-            .get("volume")
-            .and_then(|v| v.as_f64())
-            .unwrap_or(1.0) as f32;
-
-        // Play the sound in a blocking task to avoid blocking the async runtime
-        tokio::task::spawn_blocking(move || {
-            // Suppress ALSA warnings in this thread before initializing audio
+        let volume = ConfigAccess::new("Sound", config).f64("volume", 1.0)? as f32;
+        let skip = skip_if_no_device(config, std::env::var(CI_ENV).ok().as_deref())?;
+        let timeouts = PlaybackTimeouts::from_config(config)?;
+
+        let result = play_sound(expanded_path, volume, timeouts).await;
+        playback_result(result, skip)
+    }
+
+    async fn diagnose(&self, config: &HashMap<String, Value>) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        // Every configured file must exist, not just the one that would be picked
+        let files: Vec<&str> = match (config.get("file"), config.get("files")) {
+            (Some(file), _) => file.as_str().into_iter().collect(),
+            (None, Some(files)) => files
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str())
+                .collect(),
+            (None, None) => Vec::new(),
+        };
+        if files.is_empty() {
+            diagnostics.push(Diagnostic::fail("no sound files configured ('file' or 'files')"));
+        }
+        for file in files {
+            if Path::new(shellexpand::tilde(file).as_ref()).is_file() {
+                diagnostics.push(Diagnostic::pass(format!("sound file found: {}", file)));
+            } else {
+                diagnostics.push(Diagnostic::fail(format!("sound file not found: {}", file)));
+            }
+        }
+
+        let output = tokio::task::spawn_blocking(|| {
             suppress_alsa_errors_if_not_debug();
-            play_sound(&expanded_path, volume)
+            OutputStream::try_default().map(drop).map_err(|e| e.to_string())
         })
-            .await
-            .map_err(|e| NotificationError::Audio(format!("Sound playback task failed: {}", e)))?
-            .map_err(|e| NotificationError::Audio(format!("Sound playback failed: {}", e)))?;
-
-        Ok(())
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+        diagnostics.push(match output {
+            Ok(()) => Diagnostic::pass("audio output device available"),
+            Err(e) => Diagnostic::fail(format!("no audio output device: {}", e)),
+        });
+
+        diagnostics
     }
 }
 
@@ -85,43 +139,37 @@ impl Handler for SoundHandler {
 /// - Single file: `"file": "path/to/sound.wav"`
 /// - Multiple files: `"files": ["sound1.wav", "sound2.wav"]`
 /// - Random selection: `"random": true` (picks randomly from files array)
-fn get_sound_file(config: &HashMap<String, Value>) -> HandlerResult<String> {
+///
+/// With random selection, the last file played is remembered in the `state` file
+/// under the handler's name (if given) so the same sound is not picked twice in a row.
+fn get_sound_file(config: &HashMap<String, Value>, state: Option<(&Path, &str)>) -> HandlerResult<String> {
+    let cfg = ConfigAccess::new("Sound", config);
+
     // Check for single file
-    if let Some(file) = config.get("file").and_then(|v| v.as_str()) {
+    if let Some(file) = cfg.opt_str("file")? {
         return Ok(file.to_string());
     }
 
     // Check for files array
-    if let Some(files_value) = config.get("files") {
-        let files: Vec<String> = match files_value {
-            Value::Array(arr) => arr
-                .iter()
-                .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                .collect(),
-            _ => {
-                return Err(NotificationError::InvalidConfig(
-                    "Sound handler 'files' must be an array of strings".to_string()
-                ))
-            }
-        };
+    if let Some(files) = cfg.opt_str_array("files")? {
+        let files: Vec<String> = files.into_iter().map(|f| f.to_string()).collect();
 
         if files.is_empty() {
             return Err(NotificationError::InvalidConfig("Sound handler 'files' array is empty".to_string()));
         }
 
         // Check if random selection is enabled
-        let random = config
-            .get("random")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
-
-        if random {
-            // Randomly select one file
+        if cfg.bool("random", false)? {
+            // Randomly select one file, avoiding an immediate repeat
+            let last = state.and_then(|(path, name)| read_last_played(path, name));
             let mut rng = rand::thread_rng();
-            files
-                .choose(&mut rng)
-                .cloned()
-                .ok_or_else(|| NotificationError::Audio("Failed to randomly select sound file".to_string()))
+            let choice = choose_sound(&files, last.as_deref(), &mut rng)
+                .ok_or_else(|| NotificationError::Audio("Failed to randomly select sound file".to_string()))?;
+
+            if let Some((path, name)) = state {
+                write_last_played(path, name, &choice);
+            }
+            Ok(choice)
         } else {
             // Use first file if random not enabled
             Ok(files[0].clone())
@@ -133,36 +181,201 @@ fn get_sound_file(config: &HashMap<String, Value>) -> HandlerResult<String> {
     }
 }
 
-fn play_sound(file_path: &str, volume: f32) -> HandlerResult<()> {
-    // Get output stream and stream handle
-    let (_stream, stream_handle) = OutputStream::try_default()
-        .map_err(|e| NotificationError::Audio(format!("Failed to get audio output stream: {}", e)))?;
+/// Randomly picks a file, excluding `last` when there is more than one candidate.
+fn choose_sound<R: Rng>(files: &[String], last: Option<&str>, rng: &mut R) -> Option<String> {
+    let candidates: Vec<&String> = match last {
+        Some(last) if files.len() > 1 => files.iter().filter(|f| f.as_str() != last).collect(),
+        _ => files.iter().collect(),
+    };
 
-    // Create a sink for audio playback
-    let sink = Sink::try_new(&stream_handle)
-        .map_err(|e| NotificationError::Audio(format!("Failed to create audio sink: {}", e)))?;
+    // Every file was the last one played (duplicates); fall back to the full list
+    let candidates = if candidates.is_empty() {
+        files.iter().collect()
+    } else {
+        candidates
+    };
+
+    candidates.choose(rng).map(|f| (*f).clone())
+}
 
-    // Open the audio file
-    let file = File::open(file_path)
-        .map_err(|e| NotificationError::Audio(format!("Failed to open audio file '{}': {}", file_path, e)))?;
+/// Location of the random-selection state file (`~/.claude/boopifier-sound-state.json`).
+fn default_state_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".claude/boopifier-sound-state.json")
+}
+
+/// Reads the last file played by the named handler from the state file.
+fn read_last_played(path: &Path, name: &str) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let state: HashMap<String, String> = serde_json::from_str(&content).ok()?;
+    state.get(name).cloned()
+}
+
+/// Records the last file played by the named handler. Failures are ignored,
+/// since losing the state only means a possible repeat.
+fn write_last_played(path: &Path, name: &str, file: &str) {
+    let mut state: HashMap<String, String> = fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    state.insert(name.to_string(), file.to_string());
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string(&state) {
+        let _ = fs::write(path, json);
+    }
+}
+
+/// Environment variable that, when set, makes `skip_if_no_device` default to true.
+const CI_ENV: &str = "CI";
+
+/// Why a sound could not be played.
+#[derive(Debug)]
+enum PlaybackError {
+    /// No usable audio output device (e.g. in CI or over SSH)
+    NoDevice(String),
+    /// Any other failure, such as an unreadable or undecodable file
+    Failed(NotificationError),
+}
+
+impl From<NotificationError> for PlaybackError {
+    fn from(e: NotificationError) -> Self {
+        PlaybackError::Failed(e)
+    }
+}
+
+/// Reads `skip_if_no_device`, defaulting to true when the `CI` variable is set.
+fn skip_if_no_device(config: &HashMap<String, Value>, ci: Option<&str>) -> HandlerResult<bool> {
+    let running_in_ci = ci.is_some_and(|v| !v.is_empty() && v != "0" && !v.eq_ignore_ascii_case("false"));
+    ConfigAccess::new("Sound", config).bool("skip_if_no_device", running_in_ci)
+}
+
+/// Converts a playback result into the handler's result.
+///
+/// A missing device is reported as suppressed when `skip_if_no_device` is set,
+/// so headless machines don't log an error for every sound.
+fn playback_result(result: Result<(), PlaybackError>, skip_if_no_device: bool) -> HandlerResult<()> {
+    match result {
+        Ok(()) => Ok(()),
+        Err(PlaybackError::NoDevice(e)) if skip_if_no_device => {
+            Err(NotificationError::suppressed(
+                SuppressionReason::NoDevice,
+                format!("no audio output device: {}", e),
+            ))
+        }
+        Err(PlaybackError::NoDevice(e)) => {
+            Err(NotificationError::Audio(format!("Sound playback failed: no audio output device: {}", e)))
+        }
+        Err(PlaybackError::Failed(e)) => Err(NotificationError::Audio(format!("Sound playback failed: {}", e))),
+    }
+}
+
+/// Limits for each step of playing a sound, so a bad file or a stuck device
+/// fails quickly instead of holding up the handler.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PlaybackTimeouts {
+    /// Opening and decoding the file (`decode_timeout_ms`, default 2s)
+    decode: Duration,
+    /// Acquiring the audio output device (`output_timeout_ms`, default 2s)
+    output: Duration,
+    /// Playing; longer sounds are cut off (`play_timeout_ms`, default 5s)
+    play: Duration,
+}
+
+impl PlaybackTimeouts {
+    fn from_config(config: &HashMap<String, Value>) -> HandlerResult<Self> {
+        let cfg = ConfigAccess::new("Sound", config);
+        Ok(Self {
+            decode: Duration::from_millis(cfg.u64("decode_timeout_ms", 2_000)?),
+            output: Duration::from_millis(cfg.u64("output_timeout_ms", 2_000)?),
+            play: Duration::from_millis(cfg.u64("play_timeout_ms", 5_000)?),
+        })
+    }
+
+    /// The longest decoding and playing can take.
+    fn total(&self) -> Duration {
+        self.decode + self.output + self.play
+    }
+}
 
-    let source = Decoder::new(BufReader::new(file))
-        .map_err(|e| NotificationError::Audio(format!("Failed to decode audio file: {}", e)))?;
+/// Decodes the file, then plays it on a blocking thread, bounding each step.
+///
+/// The file is decoded before the output device is touched, so a malformed
+/// file fails without waiting on audio initialization. A step that times out
+/// leaves its blocking thread to finish on its own.
+async fn play_sound(file_path: String, volume: f32, timeouts: PlaybackTimeouts) -> Result<(), PlaybackError> {
+    let source = decode_sound(file_path, timeouts.decode).await?;
+
+    // The output stream can't move between threads, so it is opened on the thread
+    // that plays, which reports back once the device is ready
+    let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+    let playback = tokio::task::spawn_blocking(move || {
+        // Suppress ALSA warnings in this thread before initializing audio
+        suppress_alsa_errors_if_not_debug();
+        let output = OutputStream::try_default()
+            .map_err(|e| e.to_string())
+            .and_then(|(stream, handle)| Ok((stream, Sink::try_new(&handle).map_err(|e| e.to_string())?)));
+        let (_stream, sink) = match output {
+            Ok(output) => {
+                let _ = ready_tx.send(Ok(()));
+                output
+            }
+            Err(e) => {
+                let _ = ready_tx.send(Err(e));
+                return;
+            }
+        };
 
-    // Set volume and append to sink
-    sink.set_volume(volume.clamp(0.0, 1.0));
-    sink.append(source);
+        sink.set_volume(volume.clamp(0.0, 1.0));
+        sink.append(source);
 
-    // Wait for sound to finish with a timeout (max 5 seconds)
-    // This prevents hanging if there are audio device issues
-    let timeout = Duration::from_secs(5);
-    let start = std::time::Instant::now();
+        // Wait for the sound to finish, cutting it off at the play timeout
+        let start = std::time::Instant::now();
+        while !sink.empty() && start.elapsed() < timeouts.play {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    });
 
-    while !sink.empty() && start.elapsed() < timeout {
-        std::thread::sleep(Duration::from_millis(100));
+    match tokio::time::timeout(timeouts.output, ready_rx).await {
+        Ok(Ok(Ok(()))) => {}
+        Ok(Ok(Err(e))) => return Err(PlaybackError::NoDevice(e)),
+        // The thread ended without reporting back; its join error says why
+        Ok(Err(_)) => {}
+        Err(_) => {
+            return Err(PlaybackError::NoDevice(format!(
+                "timed out opening audio output after {}ms",
+                timeouts.output.as_millis()
+            )))
+        }
     }
 
-    Ok(())
+    playback
+        .await
+        .map_err(|e| NotificationError::Audio(format!("Sound playback task failed: {}", e)).into())
+}
+
+/// Opens and decodes the audio file on a blocking thread, within `timeout`.
+async fn decode_sound(file_path: String, timeout: Duration) -> Result<Decoder<BufReader<File>>, PlaybackError> {
+    let path = file_path.clone();
+    let decode = tokio::task::spawn_blocking(move || {
+        let file = File::open(&path)
+            .map_err(|e| NotificationError::Audio(format!("Failed to open audio file '{}': {}", path, e)))?;
+        Decoder::new(BufReader::new(file))
+            .map_err(|e| NotificationError::Audio(format!("Failed to decode audio file '{}': {}", path, e)))
+    });
+
+    match tokio::time::timeout(timeout, decode).await {
+        Ok(Ok(result)) => Ok(result?),
+        Ok(Err(e)) => Err(NotificationError::Audio(format!("Sound decoding task failed: {}", e)).into()),
+        Err(_) => Err(NotificationError::Audio(format!(
+            "Timed out decoding audio file '{}' after {}ms",
+            file_path,
+            timeout.as_millis()
+        ))
+        .into()),
+    }
 }
 
 #[cfg(test)]
@@ -175,6 +388,101 @@ mod tests {
         assert_eq!(handler.handler_type(), "sound");
     }
 
+    #[test]
+    fn test_skip_if_no_device_defaults_on_in_ci() {
+        let mut config = HashMap::new();
+        assert!(!skip_if_no_device(&config, None).unwrap());
+        assert!(skip_if_no_device(&config, Some("true")).unwrap());
+        assert!(!skip_if_no_device(&config, Some("false")).unwrap());
+        assert!(!skip_if_no_device(&config, Some("")).unwrap());
+
+        // An explicit setting wins over CI detection
+        config.insert("skip_if_no_device".to_string(), serde_json::json!(false));
+        assert!(!skip_if_no_device(&config, Some("true")).unwrap());
+        config.insert("skip_if_no_device".to_string(), serde_json::json!(true));
+        assert!(skip_if_no_device(&config, None).unwrap());
+    }
+
+    #[test]
+    fn test_missing_device_suppressed_when_skipping() {
+        let no_device = || Err(PlaybackError::NoDevice("NoDevice".to_string()));
+
+        let skipped = playback_result(no_device(), true).unwrap_err();
+        assert!(matches!(
+            &skipped,
+            NotificationError::Suppressed { reason: SuppressionReason::NoDevice, message }
+                if message.contains("no audio output device")
+        ));
+        assert!(matches!(playback_result(no_device(), false), Err(NotificationError::Audio(_))));
+
+        // Other failures are errors even when skipping
+        let bad_file = Err(PlaybackError::Failed(NotificationError::Audio("Failed to decode".to_string())));
+        assert!(matches!(playback_result(bad_file, true), Err(NotificationError::Audio(_))));
+        assert!(playback_result(Ok(()), true).is_ok());
+    }
+
+    #[test]
+    fn test_playback_timeouts_from_config() {
+        let mut config = HashMap::new();
+        let defaults = PlaybackTimeouts::from_config(&config).unwrap();
+        assert_eq!(defaults.decode, Duration::from_secs(2));
+        assert_eq!(defaults.output, Duration::from_secs(2));
+        assert_eq!(defaults.play, Duration::from_secs(5));
+
+        config.insert("decode_timeout_ms".to_string(), serde_json::json!(250));
+        config.insert("play_timeout_ms".to_string(), serde_json::json!(10_000));
+        let timeouts = PlaybackTimeouts::from_config(&config).unwrap();
+        assert_eq!(timeouts.decode, Duration::from_millis(250));
+        assert_eq!(timeouts.output, Duration::from_secs(2));
+        assert_eq!(timeouts.play, Duration::from_secs(10));
+
+        // Long sounds get a handler timeout beyond their playback limits
+        assert_eq!(SoundHandler.default_timeout(&HashMap::new()), DEFAULT_TIMEOUT);
+        config.insert("play_timeout_ms".to_string(), serde_json::json!(30_000));
+        assert_eq!(SoundHandler.default_timeout(&config), Duration::from_millis(250 + 2_000 + 30_000 + 1_000));
+
+        config.insert("output_timeout_ms".to_string(), serde_json::json!("soon"));
+        assert!(PlaybackTimeouts::from_config(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_file_fails_to_decode_promptly() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corrupt.wav");
+        fs::write(&path, b"RIFF\x10\x00\x00\x00WAVEnot really audio").unwrap();
+
+        let start = std::time::Instant::now();
+        let timeouts = PlaybackTimeouts::from_config(&HashMap::new()).unwrap();
+        let result = play_sound(path.display().to_string(), 1.0, timeouts).await;
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        // Decoding fails before any audio device is needed
+        let error = playback_result(result, true).unwrap_err().to_string();
+        assert!(error.contains("Failed to decode audio file"), "{}", error);
+        assert!(error.contains("corrupt.wav"), "{}", error);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_decode_timeout() {
+        use std::io::Write;
+
+        // Opening a FIFO blocks until something opens it for writing
+        let dir = tempfile::tempdir().unwrap();
+        let fifo = dir.path().join("stuck.wav");
+        let status = std::process::Command::new("mkfifo").arg(&fifo).status().unwrap();
+        assert!(status.success());
+
+        let result = decode_sound(fifo.display().to_string(), Duration::from_millis(100)).await;
+        let Err(PlaybackError::Failed(error)) = result else {
+            panic!("expected a decode timeout");
+        };
+        assert!(error.to_string().contains("Timed out decoding audio file"), "{}", error);
+
+        // Unblock the reader so the runtime can shut down
+        fs::OpenOptions::new().write(true).open(&fifo).unwrap().flush().unwrap();
+    }
+
     #[test]
     fn test_missing_file_config() {
         let handler = SoundHandler;
@@ -195,7 +503,7 @@ mod tests {
         let mut config = HashMap::new();
         config.insert("file".to_string(), Value::String("/path/to/sound.wav".to_string()));
 
-        let result = get_sound_file(&config).unwrap();
+        let result = get_sound_file(&config, None).unwrap();
         assert_eq!(result, "/path/to/sound.wav");
     }
 
@@ -210,7 +518,7 @@ mod tests {
             ]),
         );
 
-        let result = get_sound_file(&config).unwrap();
+        let result = get_sound_file(&config, None).unwrap();
         // Without random, should return first file
         assert_eq!(result, "sound1.wav");
     }
@@ -230,7 +538,7 @@ mod tests {
 
         // Test multiple times to ensure it returns one of the files
         for _ in 0..10 {
-            let result = get_sound_file(&config).unwrap();
+            let result = get_sound_file(&config, None).unwrap();
             assert!(
                 result == "sound1.wav" || result == "sound2.wav" || result == "sound3.wav",
                 "Got unexpected file: {}",
@@ -244,8 +552,59 @@ mod tests {
         let mut config = HashMap::new();
         config.insert("files".to_string(), Value::Array(vec![]));
 
-        let result = get_sound_file(&config);
+        let result = get_sound_file(&config, None);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("empty"));
     }
+
+    #[test]
+    fn test_choose_sound_no_immediate_repeat() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let files = vec!["a.wav".to_string(), "b.wav".to_string(), "c.wav".to_string()];
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let mut last = choose_sound(&files, None, &mut rng).unwrap();
+        for _ in 0..50 {
+            let next = choose_sound(&files, Some(&last), &mut rng).unwrap();
+            assert_ne!(next, last);
+            last = next;
+        }
+    }
+
+    #[test]
+    fn test_choose_sound_single_file_repeats() {
+        let files = vec!["only.wav".to_string()];
+        let result = choose_sound(&files, Some("only.wav"), &mut rand::thread_rng());
+        assert_eq!(result.as_deref(), Some("only.wav"));
+    }
+
+    #[test]
+    fn test_random_selection_uses_state_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = dir.path().join("state.json");
+
+        let mut config = HashMap::new();
+        config.insert(
+            "files".to_string(),
+            Value::Array(vec![
+                Value::String("sound1.wav".to_string()),
+                Value::String("sound2.wav".to_string()),
+            ]),
+        );
+        config.insert("random".to_string(), Value::Bool(true));
+
+        let mut last = get_sound_file(&config, Some((&state, "chime"))).unwrap();
+        for _ in 0..10 {
+            let next = get_sound_file(&config, Some((&state, "chime"))).unwrap();
+            assert_ne!(next, last);
+            last = next;
+        }
+
+        // State is kept per handler, even for the same files
+        let other = get_sound_file(&config, Some((&state, "other"))).unwrap();
+        assert_eq!(read_last_played(&state, "chime"), Some(last));
+        assert_eq!(read_last_played(&state, "other"), Some(other));
+    }
 }
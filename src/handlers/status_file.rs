@@ -0,0 +1,134 @@
+//! Status file handler.
+//!
+//! Writes a short rendered status line (e.g. "⏳ running") to a file that shell
+//! prompts or status bars can read, and empties it on events matching `clear_on`.
+
+use crate::config::{MatchRules, MatchType};
+use crate::error::NotificationError;
+use crate::event::Event;
+use crate::handlers::{sanitize_display, write_atomic, Handler, HandlerResult};
+use crate::matcher::matches;
+use crate::template;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Status written when no `status` template is configured.
+const DEFAULT_STATUS: &str = "{{hook_event_name}}";
+
+/// Handler that keeps a one-line status file up to date.
+pub struct StatusFileHandler;
+
+#[async_trait]
+impl Handler for StatusFileHandler {
+    fn handler_type(&self) -> &str {
+        "status_file"
+    }
+
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &["file", "status", "clear_on", "sanitize"]
+    }
+
+    async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        let path = config
+            .get("file")
+            .and_then(|v| v.as_str())
+            .map(|p| PathBuf::from(shellexpand::tilde(p).as_ref()))
+            .unwrap_or_else(default_status_path);
+
+        if clears(event, config)? {
+            return write_atomic(&path, "");
+        }
+
+        let status = config.get("status").and_then(|v| v.as_str()).unwrap_or(DEFAULT_STATUS);
+        // Prompts expect a single line, and must not receive escape sequences
+        let status = sanitize_display(config, template::render(status, event).replace(['\n', '\r'], " "));
+        write_atomic(&path, format!("{}\n", status.trim()))
+    }
+
+    fn serialize(&self) -> bool {
+        true
+    }
+}
+
+/// Default location of the status file (`~/.claude/boopifier-status`).
+pub fn default_status_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".claude/boopifier-status")
+}
+
+/// Whether the event matches the `clear_on` rules, if any.
+fn clears(event: &Event, config: &HashMap<String, Value>) -> HandlerResult<bool> {
+    let Some(rules) = config.get("clear_on") else {
+        return Ok(false);
+    };
+    let rules: MatchRules = serde_json::from_value(rules.clone()).map_err(|e| {
+        NotificationError::InvalidConfig(format!("Status file 'clear_on' must be match rules: {}", e))
+    })?;
+    Ok(matches(event, &Some(rules), &MatchType::Exact))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn test_handler_type() {
+        let handler = StatusFileHandler;
+        assert_eq!(handler.handler_type(), "status_file");
+        assert!(handler.serialize());
+    }
+
+    #[tokio::test]
+    async fn test_write_and_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prompt/status");
+
+        let mut config = HashMap::new();
+        config.insert("file".to_string(), json!(path.to_string_lossy()));
+        config.insert("status".to_string(), json!("⏳ {{tool_name}}"));
+        config.insert("clear_on".to_string(), json!({"hook_event_name": "Stop"}));
+
+        let running = Event::from_json(r#"{"hook_event_name": "PreToolUse", "tool_name": "Bash"}"#).unwrap();
+        StatusFileHandler.handle(&running, &config).await.unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "⏳ Bash\n");
+
+        let stop = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+        StatusFileHandler.handle(&stop, &config).await.unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+
+        // No temp files left behind
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_status_is_one_line_and_clear_on_validated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("status");
+        let event = Event::from_json(r#"{"hook_event_name": "Notification", "message": "Needs\ninput"}"#).unwrap();
+
+        let mut config = HashMap::new();
+        config.insert("file".to_string(), json!(path.to_string_lossy()));
+        config.insert("status".to_string(), json!("🔔 {{message}}"));
+        StatusFileHandler.handle(&event, &config).await.unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "🔔 Needs input\n");
+
+        // Escape sequences from terminal output never reach the prompt, unless opted out
+        let event = Event::from_json(r#"{"message": "\u001b]0;owned\u0007\u001b[31mred\u001b[0m"}"#).unwrap();
+        StatusFileHandler.handle(&event, &config).await.unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "🔔 red\n");
+        config.insert("sanitize".to_string(), json!(false));
+        StatusFileHandler.handle(&event, &config).await.unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "🔔 \u{1b}]0;owned\u{7}\u{1b}[31mred\u{1b}[0m\n");
+        config.remove("sanitize");
+
+        config.insert("clear_on".to_string(), json!("Stop"));
+        assert!(matches!(
+            StatusFileHandler.handle(&event, &config).await,
+            Err(NotificationError::InvalidConfig(_))
+        ));
+    }
+}
@@ -4,19 +4,34 @@
 
 use crate::error::NotificationError;
 use crate::event::Event;
-use crate::handlers::{Handler, HandlerResult};
+use crate::handlers::{
+    lock_file, max_body_length, message_template, write_atomic, ConfigAccess, Diagnostic, Handler, HandlerResult,
+};
+use crate::net;
+use crate::template;
+use crate::text::truncate_chars;
 use async_trait::async_trait;
 use once_cell::sync::OnceCell;
-use reqwest::Client;
+use reqwest::{Client, Url};
 use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::time::Duration;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Deepest object/array nesting a custom payload template may have.
+const MAX_PAYLOAD_DEPTH: usize = 32;
+
+/// Most bytes of rendered string content a custom payload template may produce.
+const MAX_PAYLOAD_BYTES: usize = 1024 * 1024;
 
 /// Shared HTTP client with connection pooling.
 static HTTP_CLIENT: OnceCell<Client> = OnceCell::new();
 
 /// Initialize or get the HTTP client.
-fn get_http_client() -> HandlerResult<&'static Client> {
+pub(crate) fn get_http_client() -> HandlerResult<&'static Client> {
     HTTP_CLIENT.get_or_try_init(|| {
         Client::builder()
             .timeout(Duration::from_secs(30))
@@ -40,14 +55,39 @@ impl Handler for WebhookHandler {
         "webhook"
     }
 
+    fn required_keys(&self) -> &'static [&'static str] {
+        &["url"]
+    }
+
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &[
+            "type", "text", "content", "payload", "channel", "username", "mrkdwn", "body_file", "templates",
+            "lang", "max_body_length", "form", "content_type", "batch", "allowed_hosts",
+        ]
+    }
+
+    fn requires_network(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        // Templated URLs are only checked once rendered against an event
+        let cfg = ConfigAccess::new("Webhook", config);
+        let url = cfg.str("url")?;
+        cfg.opt_str_array("allowed_hosts")?;
+        if !url.contains("{{") {
+            resolve_url(&Event::default(), config)?;
+        }
+        batch_settings(config)?;
+        match config.get("type").and_then(|v| v.as_str()).unwrap_or("json") {
+            "slack" | "discord" | "json" => Ok(()),
+            other => Err(NotificationError::InvalidConfig(format!("Unknown webhook type: {}", other))),
+        }
+    }
+
     async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
-        // Get webhook URL
-        let url = config
-            .get("url")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| {
-                NotificationError::InvalidConfig("Webhook handler requires 'url' configuration".to_string())
-            })?;
+        // Get webhook URL (may contain template placeholders)
+        let url = resolve_url(event, config)?;
 
         // Get payload type (slack, discord, json, or custom)
         let payload_type = config
@@ -57,12 +97,292 @@ impl Handler for WebhookHandler {
 
         // Build the payload
         let payload = build_payload(payload_type, event, config)?;
+        let form = config.get("form").and_then(|v| v.as_bool()).unwrap_or(false);
+        let content_type = config.get("content_type").and_then(|v| v.as_str());
+
+        // Batch mode: queue the payload and only send once a threshold is reached
+        if let Some(settings) = batch_settings(config)? {
+            let queue = BatchQueue::for_url(&default_batch_dir(), url.as_str());
+            return send_batched(url, &queue, &settings, payload, content_type).await;
+        }
+
+        // Body encoding: JSON by default, or form fields for legacy endpoints
+        let body = if form {
+            WebhookBody::Form(form_fields(&payload)?)
+        } else {
+            WebhookBody::Json(payload)
+        };
 
         // Send the webhook
-        send_webhook(url, &payload).await?;
+        send_webhook(url, &body, content_type).await?;
 
         Ok(())
     }
+
+    async fn diagnose(&self, config: &HashMap<String, Value>) -> Vec<Diagnostic> {
+        // Templated URLs can only be resolved once a real event arrives
+        if config.get("url").and_then(|v| v.as_str()).is_some_and(|u| u.contains("{{")) {
+            return vec![Diagnostic::warn("URL depends on event fields; reachability not checked")];
+        }
+
+        let url = match resolve_url(&Event::default(), config) {
+            Ok(url) => url,
+            Err(e) => return vec![Diagnostic::fail(e.to_string())],
+        };
+        let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+            return vec![Diagnostic::fail(format!("webhook URL has no host: {}", url))];
+        };
+
+        vec![match net::probe_tcp(host, port, net::PROBE_TIMEOUT).await {
+            Ok(()) => Diagnostic::pass(format!("webhook host {}:{} reachable", host, port)),
+            Err(e) => Diagnostic::fail(format!("cannot connect to webhook host {}:{}: {}", host, port, e)),
+        }]
+    }
+}
+
+/// Reads the `batch` settings, if batching is configured.
+///
+/// # Errors
+///
+/// Returns `NotificationError::InvalidConfig` if the settings are invalid or
+/// `batch` is combined with `form`.
+fn batch_settings(config: &HashMap<String, Value>) -> HandlerResult<Option<BatchSettings>> {
+    let Some(batch) = config.get("batch") else {
+        return Ok(None);
+    };
+    if config.get("form").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return Err(NotificationError::InvalidConfig(
+            "Webhook 'batch' cannot be combined with 'form'".to_string(),
+        ));
+    }
+    BatchSettings::from_value(batch).map(Some)
+}
+
+/// Thresholds for flushing a batched webhook queue.
+#[derive(Debug, Clone, PartialEq)]
+struct BatchSettings {
+    /// Flush once this many payloads are queued
+    max_items: usize,
+    /// Flush once the oldest queued payload is this old
+    max_age_ms: u64,
+}
+
+impl BatchSettings {
+    fn from_value(value: &Value) -> HandlerResult<Self> {
+        let obj = value.as_object().ok_or_else(|| {
+            NotificationError::InvalidConfig("Webhook 'batch' must be an object".to_string())
+        })?;
+
+        let setting = |key: &str, default: u64| match obj.get(key) {
+            None => Ok(default),
+            Some(value) => value.as_u64().ok_or_else(|| {
+                NotificationError::InvalidConfig(format!("Webhook 'batch.{}' must be a non-negative integer", key))
+            }),
+        };
+        let max_items = setting("max_items", 10)? as usize;
+        let max_age_ms = setting("max_age_ms", 60_000)?;
+
+        if max_items == 0 {
+            return Err(NotificationError::InvalidConfig(
+                "Webhook 'batch.max_items' must be at least 1".to_string(),
+            ));
+        }
+
+        Ok(Self { max_items, max_age_ms })
+    }
+
+    /// Whether the queued entries should be flushed now.
+    fn should_flush(&self, entries: &[BatchEntry], now_ms: u64) -> bool {
+        if entries.len() >= self.max_items {
+            return true;
+        }
+        entries
+            .iter()
+            .map(|e| e.queued_at_ms)
+            .min()
+            .is_some_and(|oldest| now_ms.saturating_sub(oldest) >= self.max_age_ms)
+    }
+}
+
+/// A payload waiting in a batch queue.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct BatchEntry {
+    queued_at_ms: u64,
+    payload: Value,
+}
+
+/// On-disk queue of payloads for one webhook endpoint, one JSON entry per line.
+struct BatchQueue {
+    path: PathBuf,
+}
+
+impl BatchQueue {
+    /// Queue file for a URL inside `dir`. The URL is hashed so secrets in it
+    /// don't end up in file names.
+    fn for_url(dir: &Path, url: &str) -> Self {
+        Self {
+            path: dir.join(format!("{:016x}.jsonl", fnv1a(url.as_bytes()))),
+        }
+    }
+
+    fn append(&self, payload: &Value, now_ms: u64) -> HandlerResult<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let entry = BatchEntry {
+            queued_at_ms: now_ms,
+            payload: payload.clone(),
+        };
+        let line = serde_json::to_string(&entry)
+            .map_err(|e| NotificationError::Handler(format!("Failed to queue webhook payload: {}", e)))?;
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+
+    fn entries(&self) -> HandlerResult<Vec<BatchEntry>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    fn clear(&self) -> HandlerResult<()> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Locks the queue against other processes until the returned file is dropped.
+    fn lock(&self) -> HandlerResult<fs::File> {
+        lock_file(&self.path.with_extension("lock"))
+    }
+
+    /// Moves the queued entries to a file of their own, for this process alone to send.
+    ///
+    /// Must be called with the queue locked, so no append is half-written.
+    fn claim(&self) -> HandlerResult<BatchQueue> {
+        static CLAIMS: AtomicU64 = AtomicU64::new(0);
+        let suffix = format!("{}-{}.sending", std::process::id(), CLAIMS.fetch_add(1, Ordering::Relaxed));
+        let claimed = BatchQueue {
+            path: self.path.with_extension(suffix),
+        };
+        fs::rename(&self.path, &claimed.path)?;
+        Ok(claimed)
+    }
+
+    /// Puts the entries of a claimed queue that failed to send back in front of this queue.
+    fn restore(&self, claimed: &BatchQueue) -> HandlerResult<()> {
+        let _lock = self.lock()?;
+        let mut lines = String::new();
+        for entry in claimed.entries()?.into_iter().chain(self.entries()?) {
+            let line = serde_json::to_string(&entry)
+                .map_err(|e| NotificationError::Handler(format!("Failed to queue webhook payload: {}", e)))?;
+            lines.push_str(&line);
+            lines.push('\n');
+        }
+        write_atomic(&self.path, lines)?;
+        claimed.clear()
+    }
+}
+
+/// Default directory for batch queues (`~/.claude/boopifier-batches`).
+fn default_batch_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".claude/boopifier-batches")
+}
+
+/// 64-bit FNV-1a hash, stable across runs and Rust versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Queues a payload and sends the whole queue as a JSON array once it's due.
+///
+/// Since boopifier runs once per event, a due queue is only flushed when the
+/// next event for the same endpoint arrives. The process that finds the queue
+/// due claims it under a lock, so concurrent runs never send an entry twice or
+/// drop one appended mid-flush. On send failure the entries are put back.
+async fn send_batched(
+    url: Url,
+    queue: &BatchQueue,
+    settings: &BatchSettings,
+    payload: Value,
+    content_type: Option<&str>,
+) -> HandlerResult<()> {
+    let now = now_ms();
+    let claimed = {
+        let _lock = queue.lock()?;
+        queue.append(&payload, now)?;
+        if !settings.should_flush(&queue.entries()?, now) {
+            return Ok(());
+        }
+        queue.claim()?
+    };
+
+    let batch = Value::Array(claimed.entries()?.into_iter().map(|e| e.payload).collect());
+    if let Err(e) = send_webhook(url, &WebhookBody::Json(batch), content_type).await {
+        queue.restore(&claimed)?;
+        return Err(e);
+    }
+    claimed.clear()
+}
+
+/// Renders the configured `url` against the event and validates the result.
+///
+/// Event values are percent-encoded as they're substituted, so they can only
+/// fill in path segments, query values and the like. The rendered host must
+/// still be the template's own host, or one listed in `allowed_hosts`.
+fn resolve_url(event: &Event, config: &HashMap<String, Value>) -> HandlerResult<Url> {
+    let cfg = ConfigAccess::new("Webhook", config);
+    let template_str = cfg.str("url")?;
+
+    let rendered = template::render_escaped(template_str, event, percent_encode);
+    let url = Url::parse(&rendered).map_err(|e| {
+        NotificationError::InvalidConfig(format!("Invalid webhook URL '{}': {}", rendered, e))
+    })?;
+
+    // Hosts are compared normalized, so `[::1]` and `0:0::1` or `Example.com` and `example.com` agree
+    let host = net::normalize_host(url.host_str().unwrap_or_default());
+    let template_host = Url::parse(template_str).ok().and_then(|u| u.host_str().map(net::normalize_host));
+    let allowed = cfg.opt_str_array("allowed_hosts")?.unwrap_or_default();
+    let listed = allowed.iter().any(|entry| net::normalize_host(net::split_host_port(entry).0) == host);
+    if template_host.as_deref() != Some(host.as_str()) && !listed {
+        return Err(NotificationError::InvalidConfig(format!(
+            "Webhook URL host '{}' is not the configured host; list it in allowed_hosts to permit it",
+            url.host_str().unwrap_or_default()
+        )));
+    }
+    Ok(url)
+}
+
+/// Percent-encodes everything except RFC 3986 unreserved characters.
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
 }
 
 fn build_payload(
@@ -82,7 +402,15 @@ fn build_payload(
 }
 
 fn build_slack_payload(event: &Event, config: &HashMap<String, Value>) -> HandlerResult<Value> {
-    let text = render_template(config.get("text"), event);
+    // Slack treats `&`, `<` and `>` as control characters, so event values (and the
+    // captures, extracted values and previous event taken from events) are escaped;
+    // the template itself may still use `<url|label>` links and mentions
+    let event = &slack_escaped_event(event);
+    let text = match template::render_body_file(config, event)? {
+        Some(body) => body,
+        None => render_template(message_template(config, "text"), event),
+    };
+    let text = truncate_chars(&text, max_body_length(config, "slack"));
 
     let mut payload = json!({
         "text": text,
@@ -98,11 +426,51 @@ fn build_slack_payload(event: &Event, config: &HashMap<String, Value>) -> Handle
         payload["username"] = Value::String(username.to_string());
     }
 
+    // Optional: send plain text, without *bold*, _italic_ or `code` formatting
+    if config.get("mrkdwn").and_then(|v| v.as_bool()) == Some(false) {
+        payload["mrkdwn"] = Value::Bool(false);
+    }
+
     Ok(payload)
 }
 
+/// Copy of `event` with `&`, `<` and `>` escaped in every string value,
+/// including the captures, extracted values and previous event in its context.
+fn slack_escaped_event(event: &Event) -> Event {
+    fn escape_text(s: &str) -> String {
+        s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+    }
+
+    fn escape(value: &Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(escape_text(s)),
+            Value::Array(items) => Value::Array(items.iter().map(escape).collect()),
+            Value::Object(fields) => Value::Object(
+                fields.iter().map(|(k, v)| (k.clone(), escape(v))).collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    let context = &event.context;
+    Event {
+        data: event.data.iter().map(|(k, v)| (k.clone(), escape(v))).collect(),
+        context: template::TemplateContext {
+            override_pattern: context.override_pattern.clone(),
+            captures: context.captures.iter().map(|(k, v)| (k.clone(), escape_text(v))).collect(),
+            extracted: context.extracted.iter().map(|(k, v)| (k.clone(), escape(v))).collect(),
+            previous: context.previous.as_deref().map(|previous| std::sync::Arc::new(escape(previous))),
+            handler_name: context.handler_name.clone(),
+        },
+    }
+}
+
 fn build_discord_payload(event: &Event, config: &HashMap<String, Value>) -> HandlerResult<Value> {
-    let content = render_template(config.get("content"), event);
+    let content = match template::render_body_file(config, event)? {
+        Some(body) => body,
+        None => render_template(message_template(config, "content"), event),
+    };
+    let content = truncate_chars(&content, max_body_length(config, "discord"));
 
     let mut payload = json!({
         "content": content,
@@ -119,7 +487,7 @@ fn build_discord_payload(event: &Event, config: &HashMap<String, Value>) -> Hand
 fn build_json_payload(event: &Event, config: &HashMap<String, Value>) -> HandlerResult<Value> {
     // Check if custom payload is provided
     if let Some(custom) = config.get("payload") {
-        return Ok(render_payload_template(custom, event));
+        return render_payload_template(custom, event);
     }
 
     // Default: send the entire event data
@@ -127,65 +495,114 @@ fn build_json_payload(event: &Event, config: &HashMap<String, Value>) -> Handler
 }
 
 /// Recursively render template variables in a JSON value
-fn render_payload_template(value: &Value, event: &Event) -> Value {
+///
+/// # Errors
+///
+/// Returns `NotificationError::Template` if the payload nests deeper than
+/// `MAX_PAYLOAD_DEPTH` or renders more than `MAX_PAYLOAD_BYTES` of strings.
+pub(crate) fn render_payload_template(value: &Value, event: &Event) -> HandlerResult<Value> {
+    let mut budget = MAX_PAYLOAD_BYTES;
+    render_payload_value(value, event, 0, &mut budget)
+}
+
+fn render_payload_value(value: &Value, event: &Event, depth: usize, budget: &mut usize) -> HandlerResult<Value> {
+    if depth > MAX_PAYLOAD_DEPTH {
+        return Err(NotificationError::Template(format!(
+            "payload nests deeper than {} levels",
+            MAX_PAYLOAD_DEPTH
+        )));
+    }
+
     match value {
         Value::String(s) => {
-            let mut result = s.clone();
-            for (key, val) in &event.data {
-                let placeholder = format!("{{{{{}}}}}", key);
-                if result.contains(&placeholder) {
-                    let value_str = match val {
-                        Value::String(s) => s.clone(),
-                        other => other.to_string(),
-                    };
-                    result = result.replace(&placeholder, &value_str);
-                }
-            }
-            Value::String(result)
+            let rendered = template::render(s, event);
+            spend(budget, rendered.len())?;
+            Ok(Value::String(rendered))
         }
         Value::Object(map) => {
             let mut new_map = serde_json::Map::new();
             for (k, v) in map {
-                new_map.insert(k.clone(), render_payload_template(v, event));
+                spend(budget, k.len())?;
+                new_map.insert(k.clone(), render_payload_value(v, event, depth + 1, budget)?);
             }
-            Value::Object(new_map)
+            Ok(Value::Object(new_map))
         }
-        Value::Array(arr) => {
-            Value::Array(arr.iter().map(|v| render_payload_template(v, event)).collect())
-        }
-        other => other.clone(),
+        Value::Array(arr) => arr
+            .iter()
+            .map(|v| render_payload_value(v, event, depth + 1, budget))
+            .collect::<HandlerResult<Vec<_>>>()
+            .map(Value::Array),
+        other => Ok(other.clone()),
     }
 }
 
+/// Deducts `len` bytes from the remaining payload budget.
+fn spend(budget: &mut usize, len: usize) -> HandlerResult<()> {
+    *budget = budget.checked_sub(len).ok_or_else(|| {
+        NotificationError::Template(format!("rendered payload exceeds {} bytes", MAX_PAYLOAD_BYTES))
+    })?;
+    Ok(())
+}
+
 fn render_template(template: Option<&Value>, event: &Event) -> String {
     let template_str = match template {
         Some(Value::String(s)) => s,
         _ => return format!("Claude Code Event: {:?}", event.data),
     };
 
-    let mut result = template_str.clone();
+    template::render(template_str, event)
+}
 
-    // Simple template substitution: {{field_name}}
-    for (key, value) in &event.data {
-        let placeholder = format!("{{{{{}}}}}", key);
-        if result.contains(&placeholder) {
-            let value_str = match value {
+/// Encoded request body for a webhook.
+enum WebhookBody {
+    /// JSON body (`application/json`)
+    Json(Value),
+    /// Form fields (`application/x-www-form-urlencoded`)
+    Form(Vec<(String, String)>),
+}
+
+/// Flattens a payload object into form fields.
+///
+/// Only flat maps are supported: values must be strings, numbers, booleans, or null.
+fn form_fields(payload: &Value) -> HandlerResult<Vec<(String, String)>> {
+    let map = payload.as_object().ok_or_else(|| {
+        NotificationError::InvalidConfig("Form-encoded webhook payload must be an object".to_string())
+    })?;
+
+    map.iter()
+        .map(|(key, value)| {
+            let field = match value {
                 Value::String(s) => s.clone(),
-                other => other.to_string(),
+                Value::Null => String::new(),
+                Value::Number(_) | Value::Bool(_) => value.to_string(),
+                Value::Array(_) | Value::Object(_) => {
+                    return Err(NotificationError::InvalidConfig(format!(
+                        "Form-encoded webhook payload must be a flat map, but '{}' is nested",
+                        key
+                    )))
+                }
             };
-            result = result.replace(&placeholder, &value_str);
-        }
-    }
-
-    result
+            Ok((key.clone(), field))
+        })
+        .collect()
 }
 
-async fn send_webhook(url: &str, payload: &Value) -> HandlerResult<()> {
+async fn send_webhook(url: Url, body: &WebhookBody, content_type: Option<&str>) -> HandlerResult<()> {
     let client = get_http_client()?;
 
-    let response = client
-        .post(url)
-        .json(payload)
+    let mut request = client.post(url);
+
+    // Set an explicit content type first so the body encoder doesn't add its own
+    if let Some(content_type) = content_type {
+        request = request.header(reqwest::header::CONTENT_TYPE, content_type);
+    }
+
+    request = match body {
+        WebhookBody::Json(payload) => request.json(payload),
+        WebhookBody::Form(fields) => request.form(fields),
+    };
+
+    let response = request
         .send()
         .await
         .map_err(|e| NotificationError::Network(format!("Failed to send webhook: {}", e)))?;
@@ -223,6 +640,69 @@ mod tests {
         assert_eq!(payload["text"], "Status: success");
     }
 
+    #[test]
+    fn test_slack_payload_escapes_event_values() {
+        let event = Event::from_json(
+            r#"{"tool_input": {"command": "cargo test && echo <done>"}, "url": "https://ci.example/run?id=1&tab=log"}"#,
+        )
+        .unwrap();
+        let mut config = HashMap::new();
+        config.insert(
+            "text".to_string(),
+            json!("<https://ci.example|CI>: {{tool_input.command}} ({{url}})"),
+        );
+
+        let payload = build_slack_payload(&event, &config).unwrap();
+        assert_eq!(
+            payload["text"],
+            "<https://ci.example|CI>: cargo test &amp;&amp; echo &lt;done&gt; (https://ci.example/run?id=1&amp;tab=log)"
+        );
+        assert!(payload.get("mrkdwn").is_none());
+    }
+
+    #[test]
+    fn test_slack_payload_escapes_captures_and_extracted_values() {
+        let mut event = Event::from_json(r#"{"message": "build <main> failed"}"#).unwrap();
+        event.context.captures.insert("branch".to_string(), "<!channel>".to_string());
+        event.context.extracted.insert("cmd".to_string(), json!("a && b"));
+        event.context.previous = Some(std::sync::Arc::new(json!({"message": "<@U123>"})));
+        let mut config = HashMap::new();
+        config.insert("text".to_string(), json!("<!here> {{capture.branch}} {{cmd}} {{prev.message}}"));
+
+        let payload = build_slack_payload(&event, &config).unwrap();
+        assert_eq!(payload["text"], "<!here> &lt;!channel&gt; a &amp;&amp; b &lt;@U123&gt;");
+    }
+
+    #[test]
+    fn test_slack_payload_mrkdwn_disabled() {
+        let event = Event::from_json(r#"{"msg": "*not bold* a<b"}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert("text".to_string(), json!("{{msg}}"));
+        config.insert("mrkdwn".to_string(), json!(false));
+
+        let payload = build_slack_payload(&event, &config).unwrap();
+        assert_eq!(payload["mrkdwn"], false);
+        assert_eq!(payload["text"], "*not bold* a&lt;b");
+    }
+
+    #[test]
+    fn test_build_slack_payload_body_file_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("slack.txt");
+        std::fs::write(&path, "From file: {{status}}").unwrap();
+
+        let event = Event::from_json(r#"{"status": "success"}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert("text".to_string(), Value::String("inline".to_string()));
+        config.insert(
+            "body_file".to_string(),
+            Value::String(path.display().to_string()),
+        );
+
+        let payload = build_slack_payload(&event, &config).unwrap();
+        assert_eq!(payload["text"], "From file: success");
+    }
+
     #[test]
     fn test_build_discord_payload() {
         let event = Event::from_json(r#"{"task": "build"}"#).unwrap();
@@ -236,6 +716,359 @@ mod tests {
         assert_eq!(payload["content"], "Task: build");
     }
 
+    #[test]
+    fn test_templated_url() {
+        let event = Event::from_json(r#"{"project": "boopifier"}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert(
+            "url".to_string(),
+            Value::String("https://hooks.example/{{project}}".to_string()),
+        );
+
+        let url = resolve_url(&event, &config).unwrap();
+        assert_eq!(url.as_str(), "https://hooks.example/boopifier");
+        assert_eq!(url.host_str(), Some("hooks.example"));
+    }
+
+    #[test]
+    fn test_templated_url_encodes_values() {
+        let event = Event::from_json(r#"{"project": "a/../b?x=1#frag", "user": "evil.example@"}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert("url".to_string(), json!("https://hooks.example/{{project}}?q={{project}}"));
+
+        let url = resolve_url(&event, &config).unwrap();
+        assert_eq!(url.as_str(), "https://hooks.example/a%2F..%2Fb%3Fx%3D1%23frag?q=a%2F..%2Fb%3Fx%3D1%23frag");
+        assert_eq!(url.query(), Some("q=a%2F..%2Fb%3Fx%3D1%23frag"));
+        assert_eq!(url.fragment(), None);
+
+        // A value can't smuggle in userinfo to move the request to another host
+        config.insert("url".to_string(), json!("https://{{user}}hooks.example/"));
+        assert!(resolve_url(&event, &config).is_err());
+    }
+
+    #[test]
+    fn test_templated_url_host() {
+        let event = Event::from_json(r#"{"region": "eu"}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert("url".to_string(), json!("https://{{region}}.hooks.example/"));
+        let err = resolve_url(&event, &config).unwrap_err().to_string();
+        assert!(err.contains("host 'eu.hooks.example' is not the configured host"), "{}", err);
+
+        config.insert("allowed_hosts".to_string(), json!(["eu.hooks.example", "us.hooks.example"]));
+        assert_eq!(resolve_url(&event, &config).unwrap().host_str(), Some("eu.hooks.example"));
+
+        let event = Event::from_json(r#"{"region": "attacker.example/"}"#).unwrap();
+        assert!(resolve_url(&event, &config).is_err());
+    }
+
+    #[test]
+    fn test_allowed_hosts_normalized() {
+        let event = Event::from_json(r#"{"region": "eu"}"#).unwrap();
+        // Entries are matched ignoring case, a trailing dot, brackets and any port
+        for allowed in ["EU.Hooks.Example", "eu.hooks.example.", "eu.hooks.example:443"] {
+            let mut config = HashMap::new();
+            config.insert("url".to_string(), json!("https://{{region}}.hooks.example/"));
+            config.insert("allowed_hosts".to_string(), json!([allowed]));
+            assert!(resolve_url(&event, &config).is_ok(), "{}", allowed);
+        }
+
+        // A literal IPv6 host still matches the template when written differently
+        let mut config = HashMap::new();
+        config.insert("url".to_string(), json!("http://[0:0::1]:8080/{{region}}"));
+        assert_eq!(resolve_url(&event, &config).unwrap().host_str(), Some("[::1]"));
+    }
+
+    #[test]
+    fn test_ipv6_url_with_port() {
+        let event = Event::from_json(r#"{}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert(
+            "url".to_string(),
+            Value::String("http://[::1]:8080/hook".to_string()),
+        );
+
+        let url = resolve_url(&event, &config).unwrap();
+        assert_eq!(url.host_str(), Some("[::1]"));
+        assert_eq!(url.port_or_known_default(), Some(8080));
+        assert!(crate::net::is_loopback_host(url.host_str().unwrap()));
+    }
+
+    #[test]
+    fn test_templated_url_invalid() {
+        let event = Event::from_json(r#"{"host": "not a host"}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert("url".to_string(), Value::String("{{host}}".to_string()));
+
+        let result = resolve_url(&event, &config);
+        assert!(result.unwrap_err().to_string().contains("Invalid webhook URL"));
+    }
+
+    #[test]
+    fn test_form_fields_flat() {
+        let payload = json!({"text": "done", "count": 3, "ok": true, "none": null});
+        let mut fields = form_fields(&payload).unwrap();
+        fields.sort();
+
+        assert_eq!(
+            fields,
+            vec![
+                ("count".to_string(), "3".to_string()),
+                ("none".to_string(), String::new()),
+                ("ok".to_string(), "true".to_string()),
+                ("text".to_string(), "done".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_form_fields_rejects_nested() {
+        let payload = json!({"text": "done", "extra": {"nested": true}});
+        let result = form_fields(&payload);
+        assert!(result.unwrap_err().to_string().contains("flat map"));
+
+        assert!(form_fields(&json!(["not", "an", "object"])).is_err());
+    }
+
+    #[test]
+    fn test_form_body_from_templated_payload() {
+        let event = Event::from_json(r#"{"status": "success"}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert("payload".to_string(), json!({"message": "Status: {{status}}"}));
+
+        let payload = build_json_payload(&event, &config).unwrap();
+        let fields = form_fields(&payload).unwrap();
+        assert_eq!(fields, vec![("message".to_string(), "Status: success".to_string())]);
+    }
+
+    #[test]
+    fn test_payload_template_depth_limit() {
+        let event = Event::from_json(r#"{"status": "ok"}"#).unwrap();
+        let nest = |levels: usize| (0..levels).fold(json!("{{status}}"), |inner, _| json!({"a": [inner]}));
+
+        // Each level adds an object and an array
+        let deepest = nest(MAX_PAYLOAD_DEPTH / 2);
+        assert!(render_payload_template(&deepest, &event).is_ok());
+
+        let too_deep = nest(MAX_PAYLOAD_DEPTH / 2 + 1);
+        assert!(matches!(
+            render_payload_template(&too_deep, &event),
+            Err(NotificationError::Template(_))
+        ));
+    }
+
+    #[test]
+    fn test_payload_template_size_limit() {
+        // Small template, large event field repeated by the template
+        let big = "x".repeat(MAX_PAYLOAD_BYTES / 4);
+        let event = Event::from_json(&json!({ "big": big }).to_string()).unwrap();
+
+        let fits = json!({"text": "{{big}}{{big}}"});
+        assert!(render_payload_template(&fits, &event).is_ok());
+
+        let oversized = json!({"text": "{{big}}{{big}}", "copies": ["{{big}}", "{{big}}{{big}}"]});
+        let error = render_payload_template(&oversized, &event).unwrap_err();
+        assert!(matches!(error, NotificationError::Template(_)));
+        assert!(error.to_string().contains("exceeds"));
+    }
+
+    #[test]
+    fn test_batch_accumulates_until_max_items() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = BatchQueue::for_url(dir.path(), "https://hooks.example/a");
+        let settings = BatchSettings {
+            max_items: 3,
+            max_age_ms: 60_000,
+        };
+
+        queue.append(&json!({"n": 1}), 1_000).unwrap();
+        queue.append(&json!({"n": 2}), 1_100).unwrap();
+        let entries = queue.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(!settings.should_flush(&entries, 1_200));
+
+        queue.append(&json!({"n": 3}), 1_200).unwrap();
+        let entries = queue.entries().unwrap();
+        assert!(settings.should_flush(&entries, 1_200));
+        assert_eq!(entries[2].payload, json!({"n": 3}));
+
+        queue.clear().unwrap();
+        assert!(queue.entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_batch_flushes_on_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = BatchQueue::for_url(dir.path(), "https://hooks.example/a");
+        let settings = BatchSettings {
+            max_items: 100,
+            max_age_ms: 5_000,
+        };
+
+        queue.append(&json!({"n": 1}), 10_000).unwrap();
+        let entries = queue.entries().unwrap();
+        assert!(!settings.should_flush(&entries, 14_999));
+        assert!(settings.should_flush(&entries, 15_000));
+    }
+
+    #[test]
+    fn test_batch_queue_per_endpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = BatchQueue::for_url(dir.path(), "https://hooks.example/a");
+        let b = BatchQueue::for_url(dir.path(), "https://hooks.example/b");
+
+        a.append(&json!({"n": 1}), 0).unwrap();
+        assert_eq!(a.entries().unwrap().len(), 1);
+        assert!(b.entries().unwrap().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_flushes_send_each_entry_once() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Endpoint recording the `n` of every payload it receives
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}/hook", listener.local_addr().unwrap())).unwrap();
+        let received = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let server_received = received.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let received = server_received.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0; 4096];
+                    while !request.ends_with(b"]") {
+                        let n = stream.read(&mut buf).await.unwrap();
+                        if n == 0 {
+                            break;
+                        }
+                        request.extend_from_slice(&buf[..n]);
+                    }
+                    let request = String::from_utf8_lossy(&request);
+                    let body: Value = serde_json::from_str(&request[request.find("\r\n\r\n").unwrap() + 4..]).unwrap();
+                    received.lock().unwrap().extend(body.as_array().unwrap().iter().map(|p| p["n"].as_u64().unwrap()));
+                    let response = "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let queue = BatchQueue::for_url(dir.path(), url.as_str());
+        let settings = BatchSettings {
+            max_items: 1,
+            max_age_ms: 60_000,
+        };
+
+        // Every run finds the queue due, so the flushes race each other
+        let runs = (0..8u64).map(|n| {
+            let (url, queue, settings) = (url.clone(), BatchQueue { path: queue.path.clone() }, settings.clone());
+            tokio::spawn(async move { send_batched(url, &queue, &settings, json!({"n": n}), None).await })
+        });
+        for run in futures::future::join_all(runs).await {
+            run.unwrap().unwrap();
+        }
+
+        let mut received = received.lock().unwrap().clone();
+        received.sort();
+        assert_eq!(received, (0..8).collect::<Vec<_>>());
+        assert!(queue.entries().unwrap().is_empty());
+        assert!(!fs::read_dir(dir.path()).unwrap().any(|e| e.unwrap().path().to_string_lossy().ends_with(".sending")));
+    }
+
+    #[test]
+    fn test_batch_settings() {
+        let settings = BatchSettings::from_value(&json!({"max_items": 5})).unwrap();
+        assert_eq!(settings.max_items, 5);
+        assert_eq!(settings.max_age_ms, 60_000);
+
+        assert!(BatchSettings::from_value(&json!({"max_items": 0})).is_err());
+        assert!(BatchSettings::from_value(&json!(true)).is_err());
+        for batch in [json!({"max_items": "5"}), json!({"max_items": -1}), json!({"max_age_ms": 1.5})] {
+            let err = BatchSettings::from_value(&batch).unwrap_err().to_string();
+            assert!(err.contains("must be a non-negative integer"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_validate_checks_batch() {
+        let mut config = HashMap::new();
+        config.insert("url".to_string(), json!("https://hooks.example/a"));
+        config.insert("batch".to_string(), json!({"max_items": 5}));
+        assert!(WebhookHandler.validate(&config).is_ok());
+
+        config.insert("form".to_string(), json!(true));
+        let err = WebhookHandler.validate(&config).unwrap_err().to_string();
+        assert!(err.contains("cannot be combined with 'form'"), "{}", err);
+
+        config.remove("form");
+        config.insert("batch".to_string(), json!({"max_age_ms": "60000"}));
+        assert!(WebhookHandler.validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_discord_content_truncated() {
+        let long = "🚀".repeat(2_010);
+        let event = Event::from_json(&json!({"msg": long}).to_string()).unwrap();
+        let mut config = HashMap::new();
+        config.insert("content".to_string(), Value::String("{{msg}}".to_string()));
+
+        let payload = build_discord_payload(&event, &config).unwrap();
+        let content = payload["content"].as_str().unwrap();
+        assert_eq!(content.chars().count(), 2_000);
+        assert!(content.ends_with('…'));
+    }
+
+    #[test]
+    fn test_discord_content_at_limit() {
+        let mut config = HashMap::new();
+        config.insert("content".to_string(), Value::String("{{msg}}".to_string()));
+
+        let exact = "é".repeat(2_000);
+        let event = Event::from_json(&json!({"msg": exact}).to_string()).unwrap();
+        let payload = build_discord_payload(&event, &config).unwrap();
+        assert_eq!(payload["content"], exact);
+
+        let over = "é".repeat(2_001);
+        let event = Event::from_json(&json!({"msg": over}).to_string()).unwrap();
+        let payload = build_discord_payload(&event, &config).unwrap();
+        let content = payload["content"].as_str().unwrap();
+        assert_eq!(content.chars().count(), 2_000);
+        assert!(content.ends_with('…'));
+    }
+
+    #[test]
+    fn test_slack_max_body_length_override() {
+        let event = Event::from_json(r#"{"msg": "abcdefghij"}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert("text".to_string(), json!("{{msg}}"));
+        config.insert("max_body_length".to_string(), json!(5));
+
+        let payload = build_slack_payload(&event, &config).unwrap();
+        assert_eq!(payload["text"], "abcd…");
+    }
+
+    #[tokio::test]
+    async fn test_diagnose() {
+        use crate::handlers::DiagnosticStatus;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let mut config = HashMap::new();
+        config.insert("url".to_string(), json!(format!("http://{}/hook", addr)));
+        let diagnostics = WebhookHandler.diagnose(&config).await;
+        assert_eq!(diagnostics[0].status, DiagnosticStatus::Pass);
+
+        config.insert("url".to_string(), json!("https://hooks.example.com/{{session_id}}"));
+        let diagnostics = WebhookHandler.diagnose(&config).await;
+        assert_eq!(diagnostics[0].status, DiagnosticStatus::Warn);
+
+        config.insert("url".to_string(), json!("not a url"));
+        let diagnostics = WebhookHandler.diagnose(&config).await;
+        assert_eq!(diagnostics[0].status, DiagnosticStatus::Fail);
+    }
+
     #[tokio::test]
     async fn test_missing_url() {
         let handler = WebhookHandler;
@@ -0,0 +1,234 @@
+//! WebSocket push handler.
+//!
+//! Connects to a `ws://` or `wss://` URL, sends one JSON message, and closes,
+//! all within a timeout. Suited to live dashboards listening for events.
+
+use crate::error::NotificationError;
+use crate::event::Event;
+use crate::handlers::webhook::render_payload_template;
+use crate::handlers::{ConfigAccess, Handler, HandlerResult};
+use crate::template;
+use async_trait::async_trait;
+use futures::SinkExt;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue, Request};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Default time allowed for connect, send and close together.
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+
+/// Handler that pushes a message over a WebSocket.
+pub struct WebSocketHandler;
+
+#[async_trait]
+impl Handler for WebSocketHandler {
+    fn handler_type(&self) -> &str {
+        "websocket"
+    }
+
+    fn required_keys(&self) -> &'static [&'static str] {
+        &["url"]
+    }
+
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &["message", "headers", "bearer_token", "timeout_ms"]
+    }
+
+    fn requires_network(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        // Templated URLs are only checked once rendered against an event
+        let url = ConfigAccess::new("WebSocket", config).str("url")?;
+        if !url.contains("{{") {
+            build_request(&Event::default(), config)?;
+        }
+        Ok(())
+    }
+
+    async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        let request = build_request(event, config)?;
+        let message = build_message(event, config)?;
+        let timeout_ms = config
+            .get("timeout_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_TIMEOUT_MS);
+
+        tokio::time::timeout(Duration::from_millis(timeout_ms), push(request, message))
+            .await
+            .map_err(|_| NotificationError::Network(format!("WebSocket push timed out after {}ms", timeout_ms)))?
+    }
+}
+
+/// Builds the handshake request from the templated `url` and optional `headers`.
+fn build_request(event: &Event, config: &HashMap<String, Value>) -> HandlerResult<Request<()>> {
+    let url = config
+        .get("url")
+        .and_then(|v| v.as_str())
+        .map(|u| template::render(u, event))
+        .ok_or_else(|| {
+            NotificationError::InvalidConfig("WebSocket handler requires 'url' configuration".to_string())
+        })?;
+
+    if !(url.starts_with("ws://") || url.starts_with("wss://")) {
+        return Err(NotificationError::InvalidConfig(format!(
+            "WebSocket URL must start with ws:// or wss://: {}",
+            url
+        )));
+    }
+
+    let mut request = url.as_str().into_client_request().map_err(|e| {
+        NotificationError::InvalidConfig(format!("Invalid WebSocket URL '{}': {}", url, e))
+    })?;
+
+    if let Some(headers) = config.get("headers").and_then(|v| v.as_object()) {
+        for (name, value) in headers {
+            let value = value.as_str().ok_or_else(|| {
+                NotificationError::InvalidConfig(format!("WebSocket header '{}' must be a string", name))
+            })?;
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+                NotificationError::InvalidConfig(format!("Invalid WebSocket header name '{}': {}", name, e))
+            })?;
+            let value = HeaderValue::from_str(&template::render(value, event)).map_err(|e| {
+                NotificationError::InvalidConfig(format!("Invalid value for WebSocket header '{}': {}", name, e))
+            })?;
+            request.headers_mut().insert(name, value);
+        }
+    }
+
+    // Top-level so `{{env.*}}` / `{{file.*}}` secrets are resolved for it
+    if let Some(token) = config.get("bearer_token").and_then(|v| v.as_str()) {
+        let value = HeaderValue::from_str(&format!("Bearer {}", token)).map_err(|e| {
+            NotificationError::InvalidConfig(format!("Invalid WebSocket bearer_token: {}", e))
+        })?;
+        request.headers_mut().insert("authorization", value);
+    }
+
+    Ok(request)
+}
+
+/// Renders the `message` template, defaulting to the whole event.
+fn build_message(event: &Event, config: &HashMap<String, Value>) -> HandlerResult<String> {
+    let payload = match config.get("message") {
+        Some(template) => render_payload_template(template, event)?,
+        None => event.as_value(),
+    };
+    Ok(payload.to_string())
+}
+
+/// Connects, sends the message as a text frame, and closes the connection.
+async fn push(request: Request<()>, message: String) -> HandlerResult<()> {
+    let (mut socket, _response) = tokio_tungstenite::connect_async(request)
+        .await
+        .map_err(|e| NotificationError::Network(format!("WebSocket handshake failed: {}", e)))?;
+
+    socket
+        .send(Message::text(message))
+        .await
+        .map_err(|e| NotificationError::Network(format!("WebSocket send failed: {}", e)))?;
+
+    socket
+        .close(None)
+        .await
+        .map_err(|e| NotificationError::Network(format!("WebSocket close failed: {}", e)))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::handshake::server;
+
+    #[test]
+    fn test_handler_type() {
+        let handler = WebSocketHandler;
+        assert_eq!(handler.handler_type(), "websocket");
+    }
+
+    #[test]
+    fn test_rejects_non_ws_url() {
+        let event = Event::from_json(r#"{}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert("url".to_string(), json!("https://example.com"));
+
+        assert!(matches!(
+            build_request(&event, &config),
+            Err(NotificationError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_bearer_token_header() {
+        let event = Event::from_json(r#"{}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert("url".to_string(), json!("wss://dash.example/ws"));
+        config.insert("headers".to_string(), json!({"X-Client": "boopifier"}));
+        config.insert("bearer_token".to_string(), json!("s3cret"));
+
+        let request = build_request(&event, &config).unwrap();
+        assert_eq!(request.headers()["authorization"], "Bearer s3cret");
+        assert_eq!(request.headers()["x-client"], "boopifier");
+    }
+
+    #[tokio::test]
+    async fn test_push_to_local_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Echo server that also reports the auth header it saw during the handshake
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut auth = None;
+            // The callback signature is fixed by tungstenite
+            #[allow(clippy::result_large_err)]
+            let callback = |req: &server::Request, resp: server::Response| {
+                auth = req
+                    .headers()
+                    .get("authorization")
+                    .map(|v| v.to_str().unwrap().to_string());
+                Ok(resp)
+            };
+            let mut ws = tokio_tungstenite::accept_hdr_async(stream, callback).await.unwrap();
+
+            let received = match ws.next().await {
+                Some(Ok(Message::Text(text))) => text.to_string(),
+                other => panic!("unexpected frame: {:?}", other),
+            };
+            let _ = ws.send(Message::text(received.clone())).await;
+            (auth, received)
+        });
+
+        let event = Event::from_json(r#"{"hook_event_name": "Stop", "session_id": "abc"}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert("url".to_string(), json!(format!("ws://{}/events", addr)));
+        config.insert("headers".to_string(), json!({"Authorization": "Bearer {{session_id}}"}));
+        config.insert("message".to_string(), json!({"event": "{{hook_event_name}}"}));
+
+        WebSocketHandler.handle(&event, &config).await.unwrap();
+
+        let (auth, received) = server.await.unwrap();
+        assert_eq!(auth.as_deref(), Some("Bearer abc"));
+        assert_eq!(serde_json::from_str::<Value>(&received).unwrap(), json!({"event": "Stop"}));
+    }
+
+    #[tokio::test]
+    async fn test_connection_failure_is_network_error() {
+        // Bind then drop to get a port with nothing listening
+        let addr = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+
+        let event = Event::from_json(r#"{}"#).unwrap();
+        let mut config = HashMap::new();
+        config.insert("url".to_string(), json!(format!("ws://{}", addr)));
+
+        let result = WebSocketHandler.handle(&event, &config).await;
+        assert!(matches!(result, Err(NotificationError::Network(_))));
+    }
+}
@@ -0,0 +1,149 @@
+//! Markdown worklog handler.
+//!
+//! Appends a timestamped bullet per event to `{dir}/{YYYY-MM-DD}.md`, one file
+//! per day, starting each new file with a date heading.
+
+use crate::event::Event;
+use crate::handlers::{lock_file, ConfigAccess, Handler, HandlerResult};
+use crate::template;
+use async_trait::async_trait;
+use chrono::{DateTime, Local, TimeZone};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Default bullet text when no `message` is configured.
+const DEFAULT_MESSAGE: &str = "{{hook_event_name}}";
+
+/// Lock file in the worklog directory, held while appending.
+const LOCK_FILE: &str = ".boopifier-worklog.lock";
+
+/// Handler that appends events to a daily markdown worklog.
+pub struct WorklogHandler;
+
+#[async_trait]
+impl Handler for WorklogHandler {
+    fn handler_type(&self) -> &str {
+        "worklog"
+    }
+
+    fn required_keys(&self) -> &'static [&'static str] {
+        &["dir"]
+    }
+
+    fn optional_keys(&self) -> &'static [&'static str] {
+        &["message"]
+    }
+
+    fn validate(&self, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        ConfigAccess::new("Worklog", config).str("dir").map(drop)
+    }
+
+    async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        let cfg = ConfigAccess::new("Worklog", config);
+        let dir = PathBuf::from(shellexpand::tilde(cfg.str("dir")?).as_ref());
+        let message = cfg.opt_str("message")?.unwrap_or(DEFAULT_MESSAGE);
+        let text = template::render(message, event);
+
+        append_entry(&dir, &Local::now(), &text)?;
+        Ok(())
+    }
+
+    fn serialize(&self) -> bool {
+        true
+    }
+}
+
+/// Appends a bullet for `text` to the worklog file for `now`'s date.
+///
+/// Creates the directory and the day's file (with a `# YYYY-MM-DD` heading)
+/// when needed. A `.boopifier-worklog.lock` file in `dir` is held while
+/// appending, so concurrent boopifier processes write the heading only once.
+/// Returns the path written to.
+///
+/// # Errors
+///
+/// Returns `NotificationError::Io` if the file cannot be created or written.
+pub fn append_entry<Tz: TimeZone>(dir: &Path, now: &DateTime<Tz>, text: &str) -> HandlerResult<PathBuf>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    fs::create_dir_all(dir)?;
+
+    let date = now.format("%Y-%m-%d").to_string();
+    let path = dir.join(format!("{}.md", date));
+
+    let _lock = lock_file(&dir.join(LOCK_FILE))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    if file.metadata()?.len() == 0 {
+        writeln!(file, "# {}\n", date)?;
+    }
+    // Keep each entry on a single bullet line
+    let text = text.replace('\n', " ");
+    writeln!(file, "- {} {}", now.format("%H:%M:%S"), text.trim())?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_handler_type() {
+        let handler = WorklogHandler;
+        assert_eq!(handler.handler_type(), "worklog");
+        assert!(handler.serialize());
+    }
+
+    #[test]
+    fn test_first_write_creates_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 3, 14, 9, 5, 0).unwrap();
+
+        let path = append_entry(&dir.path().join("log"), &now, "Started").unwrap();
+        assert_eq!(path.file_name().unwrap(), "2026-03-14.md");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# 2026-03-14\n\n- 09:05:00 Started\n"
+        );
+    }
+
+    #[test]
+    fn test_concurrent_first_writes_share_one_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 3, 14, 9, 5, 0).unwrap();
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let dir = dir.path().to_path_buf();
+                std::thread::spawn(move || append_entry(&dir, &now, "Started").unwrap())
+            })
+            .collect();
+        let path = threads.into_iter().map(|t| t.join().unwrap()).last().unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.matches("# 2026-03-14").count(), 1, "{}", contents);
+        assert_eq!(contents.lines().filter(|line| line.starts_with("- ")).count(), 8);
+    }
+
+    #[test]
+    fn test_subsequent_appends_and_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = Utc.with_ymd_and_hms(2026, 3, 14, 9, 5, 0).unwrap();
+        let second = Utc.with_ymd_and_hms(2026, 3, 14, 17, 30, 15).unwrap();
+        let next_day = Utc.with_ymd_and_hms(2026, 3, 15, 8, 0, 0).unwrap();
+
+        append_entry(dir.path(), &first, "Started").unwrap();
+        let path = append_entry(dir.path(), &second, "Done:\nall tests pass").unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# 2026-03-14\n\n- 09:05:00 Started\n- 17:30:15 Done: all tests pass\n"
+        );
+
+        let path = append_entry(dir.path(), &next_day, "Back").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# 2026-03-15\n\n- 08:00:00 Back\n");
+    }
+}
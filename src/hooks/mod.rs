@@ -11,6 +11,7 @@ pub mod prompt;
 pub mod session;
 pub mod compact;
 
+use crate::error::SuppressionReason;
 use crate::event::Event;
 use anyhow::{bail, Result};
 use serde_json::Value;
@@ -22,26 +23,85 @@ pub enum HandlerOutcome {
     Success,
     /// Handler failed with an error
     Error(String),
-    /// Handler requires user interaction (future: for PreToolUse)
-    #[allow(dead_code)]
+    /// Handler chose not to notify for this event
+    Suppressed {
+        /// Why the notification was not sent
+        reason: SuppressionReason,
+        /// Human-readable details, prefixed with the handler name
+        message: String,
+    },
+    /// Handler succeeded and asked for this message to be shown to the user (for Notification)
+    SystemMessage(String),
+    /// Handler collected a decision from the user (for PreToolUse)
     Interactive(InteractiveResponse),
+    /// Handler asked for the hook's output to be hidden from the transcript;
+    /// wraps the outcome it would otherwise have had
+    SuppressOutput(Box<HandlerOutcome>),
+    /// Handler succeeded and asked Claude to keep working, for this reason (for Stop)
+    Block(String),
+    /// Handler succeeded and supplied context to add to the prompt (for UserPromptSubmit)
+    AdditionalContext(String),
+}
+
+impl HandlerOutcome {
+    /// Whether the handler delivered its notification.
+    pub fn is_success(&self) -> bool {
+        match self {
+            Self::SuppressOutput(outcome) => outcome.is_success(),
+            other => matches!(
+                other,
+                Self::Success
+                    | Self::SystemMessage(_)
+                    | Self::Interactive(_)
+                    | Self::Block(_)
+                    | Self::AdditionalContext(_)
+            ),
+        }
+    }
+
+    /// The outcome without a `SuppressOutput` wrapper.
+    pub fn unwrapped(&self) -> &HandlerOutcome {
+        match self {
+            Self::SuppressOutput(outcome) => outcome.unwrapped(),
+            other => other,
+        }
+    }
 }
 
 /// Interactive response from a handler (for PreToolUse hooks)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct InteractiveResponse {
     pub decision: PermissionDecision,
     pub reason: Option<String>,
 }
 
 /// Permission decision for tool use
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PermissionDecision {
     Allow,
     Deny,
     Ask,
 }
 
+/// Picks the decision to answer with when several handlers decided.
+///
+/// The most cautious decision wins: deny over ask over allow. Among equal
+/// decisions, the first handler's response (and reason) is used.
+pub(crate) fn combined_decision(outcomes: &[HandlerOutcome]) -> Option<&InteractiveResponse> {
+    let rank = |decision: PermissionDecision| match decision {
+        PermissionDecision::Deny => 0,
+        PermissionDecision::Ask => 1,
+        PermissionDecision::Allow => 2,
+    };
+    outcomes
+        .iter()
+        .filter_map(|outcome| match outcome {
+            HandlerOutcome::Interactive(response) => Some(response),
+            _ => None,
+        })
+        .min_by_key(|response| rank(response.decision))
+}
+
 /// Trait for Claude Code hook types.
 ///
 /// Each hook type knows how to generate its own JSON response format.
@@ -53,11 +113,36 @@ pub trait Hook: Send + Sync {
     fn generate_response(&self, outcomes: &[HandlerOutcome]) -> Value;
 }
 
+/// Serializes a hook response, pretty-printed (indented, multi-line) if `pretty`.
+///
+/// Claude Code accepts either form; pretty output is easier to read in logs.
+pub fn format_response(response: &Value, pretty: bool) -> String {
+    let formatted = if pretty {
+        serde_json::to_string_pretty(response)
+    } else {
+        serde_json::to_string(response)
+    };
+    // Serializing a `Value` cannot fail
+    formatted.unwrap_or_default()
+}
+
+/// Every `hook_event_name` that [`hook_from_event`] understands.
+pub const HOOK_TYPES: &[&str] = &[
+    "Stop",
+    "SubagentStop",
+    "Notification",
+    "PreToolUse",
+    "PostToolUse",
+    "PermissionRequest",
+    "UserPromptSubmit",
+    "SessionStart",
+    "SessionEnd",
+    "PreCompact",
+];
+
 /// Create a Hook instance from an event by parsing the hook_event_name
 pub fn hook_from_event(event: &Event) -> Result<Box<dyn Hook>> {
-    let hook_event_name = event
-        .get_str("hook_event_name")
-        .unwrap_or("unknown");
+    let hook_event_name = event.hook_name().unwrap_or("unknown");
 
     match hook_event_name {
         "Stop" | "SubagentStop" => Ok(Box::new(stop::StopHook::new(hook_event_name))),
@@ -72,3 +157,34 @@ pub fn hook_from_event(event: &Event) -> Result<Box<dyn Hook>> {
         _ => bail!("Unknown hook type: {}", hook_event_name),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_hook_types_all_known() {
+        for hook in HOOK_TYPES {
+            let event = Event::from_json(&json!({"hook_event_name": hook}).to_string()).unwrap();
+            assert_eq!(hook_from_event(&event).unwrap().hook_type(), *hook);
+        }
+    }
+
+    #[test]
+    fn test_pretty_response_matches_minified() {
+        let response = json!({
+            "hookSpecificOutput": {"hookEventName": "PreToolUse", "permissionDecision": "deny"},
+            "systemMessage": "line one\nline two"
+        });
+
+        let minified = format_response(&response, false);
+        let pretty = format_response(&response, true);
+        assert!(!minified.contains('\n'));
+        assert!(pretty.lines().count() > 1);
+        assert_eq!(
+            serde_json::from_str::<Value>(&pretty).unwrap(),
+            serde_json::from_str::<Value>(&minified).unwrap()
+        );
+    }
+}
@@ -6,7 +6,8 @@ use serde_json::{json, Value};
 /// Handler for Notification hooks.
 ///
 /// These hooks fire when Claude Code wants to notify about an event.
-/// We return an empty object {} to indicate passive observation.
+/// We return an empty object {} to indicate passive observation, unless
+/// handlers configured with `system_message` supply text to show the user.
 pub struct NotificationHook;
 
 impl Hook for NotificationHook {
@@ -14,10 +15,21 @@ impl Hook for NotificationHook {
         "Notification"
     }
 
-    fn generate_response(&self, _outcomes: &[HandlerOutcome]) -> Value {
-        // Return empty object - just observing notifications
-        // Could add systemMessage here if needed to show warnings to user
-        json!({})
+    fn generate_response(&self, outcomes: &[HandlerOutcome]) -> Value {
+        let messages: Vec<&str> = outcomes
+            .iter()
+            .filter_map(|outcome| match outcome {
+                HandlerOutcome::SystemMessage(message) => Some(message.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        if messages.is_empty() {
+            // Return empty object - just observing notifications
+            json!({})
+        } else {
+            json!({ "systemMessage": messages.join("\n") })
+        }
     }
 }
 
@@ -33,4 +45,23 @@ mod tests {
         let response = hook.generate_response(&[]);
         assert_eq!(response, json!({}));
     }
+
+    #[test]
+    fn test_system_message_passthrough() {
+        let hook = NotificationHook;
+
+        let suppressed = HandlerOutcome::Suppressed {
+            reason: crate::error::SuppressionReason::Cooldown,
+            message: "quiet".into(),
+        };
+        let plain = hook.generate_response(&[HandlerOutcome::Success, suppressed]);
+        assert_eq!(plain, json!({}));
+
+        let response = hook.generate_response(&[
+            HandlerOutcome::SystemMessage("🔔 Needs input".to_string()),
+            HandlerOutcome::Success,
+            HandlerOutcome::SystemMessage("Also sent to phone".to_string()),
+        ]);
+        assert_eq!(response, json!({"systemMessage": "🔔 Needs input\nAlso sent to phone"}));
+    }
 }
@@ -1,13 +1,16 @@
 //! PermissionRequest hook implementation.
 
-use super::{HandlerOutcome, Hook};
+use super::{combined_decision, HandlerOutcome, Hook, PermissionDecision};
 use serde_json::{json, Value};
 
 /// Handler for PermissionRequest hooks.
 ///
 /// These hooks fire when Claude Code asks for permission to perform an action.
-/// Currently returns an empty object {} to allow passive observation.
-/// Future: Could implement automatic approval logic based on handler outcomes.
+/// We return an empty object {} so Claude Code asks as usual, unless an
+/// interactive handler (such as `policy`) allows or denies the request. If
+/// several handlers decide, deny wins over ask, and ask over allow. The
+/// decision goes in `hookSpecificOutput.decision.behavior`, with a deny's
+/// reason as its `message`.
 pub struct PermissionRequestHook;
 
 impl Hook for PermissionRequestHook {
@@ -15,17 +18,37 @@ impl Hook for PermissionRequestHook {
         "PermissionRequest"
     }
 
-    fn generate_response(&self, _outcomes: &[HandlerOutcome]) -> Value {
-        // Return empty object - currently just observing
-        // Future: Could return {"decision": "allow"} or {"decision": "deny"}
-        // based on handler outcomes to enable automated permission workflows
-        json!({})
+    fn generate_response(&self, outcomes: &[HandlerOutcome]) -> Value {
+        let decision = match combined_decision(outcomes) {
+            Some(response) if response.decision == PermissionDecision::Allow => json!({"behavior": "allow"}),
+            Some(response) if response.decision == PermissionDecision::Deny => match &response.reason {
+                Some(reason) => json!({"behavior": "deny", "message": reason}),
+                None => json!({"behavior": "deny"}),
+            },
+            // Nobody decided, or someone wants to be asked: leave it to Claude Code's own prompt
+            _ => return json!({}),
+        };
+
+        json!({
+            "hookSpecificOutput": {
+                "hookEventName": "PermissionRequest",
+                "decision": decision
+            }
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hooks::InteractiveResponse;
+
+    fn interactive(decision: PermissionDecision, reason: &str) -> HandlerOutcome {
+        HandlerOutcome::Interactive(InteractiveResponse {
+            decision,
+            reason: Some(reason.to_string()),
+        })
+    }
 
     #[test]
     fn test_permission_request_hook_response() {
@@ -35,4 +58,36 @@ mod tests {
         let response = hook.generate_response(&[]);
         assert_eq!(response, json!({}));
     }
+
+    fn decision(behavior: Value) -> Value {
+        json!({"hookSpecificOutput": {"hookEventName": "PermissionRequest", "decision": behavior}})
+    }
+
+    #[test]
+    fn test_allow_and_deny() {
+        let hook = PermissionRequestHook;
+
+        let allow = interactive(PermissionDecision::Allow, "safe tool");
+        let response = hook.generate_response(&[HandlerOutcome::Success, allow.clone()]);
+        assert_eq!(response, decision(json!({"behavior": "allow"})));
+
+        let deny = interactive(PermissionDecision::Deny, "dangerous command");
+        let response = hook.generate_response(&[allow, deny]);
+        assert_eq!(response, decision(json!({"behavior": "deny", "message": "dangerous command"})));
+    }
+
+    #[test]
+    fn test_ask_falls_through() {
+        let hook = PermissionRequestHook;
+        let response = hook.generate_response(&[interactive(PermissionDecision::Ask, "no rule matched")]);
+        assert_eq!(response, json!({}));
+
+        // Asking wins over an approval, but not over a denial
+        let allow = interactive(PermissionDecision::Allow, "safe tool");
+        let ask = interactive(PermissionDecision::Ask, "unsure");
+        assert_eq!(hook.generate_response(&[allow.clone(), ask.clone()]), json!({}));
+        let deny = interactive(PermissionDecision::Deny, "no");
+        let response = hook.generate_response(&[allow, ask, deny]);
+        assert_eq!(response, decision(json!({"behavior": "deny", "message": "no"})));
+    }
 }
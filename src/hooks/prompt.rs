@@ -6,8 +6,8 @@ use serde_json::{json, Value};
 /// Handler for UserPromptSubmit hooks.
 ///
 /// These hooks fire when the user submits a prompt to Claude Code.
-/// Returns an empty object {} to allow passive observation.
-/// Future: Could implement prompt transformation or validation logic.
+/// We return an empty object {} to indicate passive observation, unless
+/// handlers configured with `additional_context` supply text to add to the prompt.
 pub struct UserPromptSubmitHook;
 
 impl Hook for UserPromptSubmitHook {
@@ -15,11 +15,26 @@ impl Hook for UserPromptSubmitHook {
         "UserPromptSubmit"
     }
 
-    fn generate_response(&self, _outcomes: &[HandlerOutcome]) -> Value {
-        // Return empty object - currently just observing
-        // Future: Could modify the prompt or add system messages
-        // based on handler outcomes
-        json!({})
+    fn generate_response(&self, outcomes: &[HandlerOutcome]) -> Value {
+        let contexts: Vec<&str> = outcomes
+            .iter()
+            .filter_map(|outcome| match outcome {
+                HandlerOutcome::AdditionalContext(context) => Some(context.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        if contexts.is_empty() {
+            // Return empty object - just observing prompts
+            json!({})
+        } else {
+            json!({
+                "hookSpecificOutput": {
+                    "hookEventName": "UserPromptSubmit",
+                    "additionalContext": contexts.join("\n")
+                }
+            })
+        }
     }
 }
 
@@ -34,5 +49,27 @@ mod tests {
 
         let response = hook.generate_response(&[]);
         assert_eq!(response, json!({}));
+
+        let response = hook.generate_response(&[HandlerOutcome::Success, HandlerOutcome::Error("x".to_string())]);
+        assert_eq!(response, json!({}));
+    }
+
+    #[test]
+    fn test_additional_context_joined() {
+        let hook = UserPromptSubmitHook;
+        let response = hook.generate_response(&[
+            HandlerOutcome::AdditionalContext("Branch: main".to_string()),
+            HandlerOutcome::Success,
+            HandlerOutcome::AdditionalContext("Deploys go through `make deploy`".to_string()),
+        ]);
+        assert_eq!(
+            response,
+            json!({
+                "hookSpecificOutput": {
+                    "hookEventName": "UserPromptSubmit",
+                    "additionalContext": "Branch: main\nDeploys go through `make deploy`"
+                }
+            })
+        );
     }
 }
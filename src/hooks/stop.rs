@@ -5,8 +5,9 @@ use serde_json::{json, Value};
 
 /// Handler for Stop and SubagentStop hooks.
 ///
-/// These hooks fire when Claude Code is about to stop execution.
-/// We return an empty object {} to indicate passive observation (no decision).
+/// These hooks fire when Claude Code is about to stop execution. Handlers
+/// configured with `block_reason` can block stopping to keep Claude working;
+/// otherwise we return an empty object {} (passive observation, no decision).
 pub struct StopHook {
     hook_name: String,
 }
@@ -24,11 +25,17 @@ impl Hook for StopHook {
         &self.hook_name
     }
 
-    fn generate_response(&self, _outcomes: &[HandlerOutcome]) -> Value {
-        // Return empty object - we're just observing events, not controlling behavior
-        // To block stopping, we would return:
-        // {"decision": "block", "reason": "...", "continue": false}
-        json!({})
+    fn generate_response(&self, outcomes: &[HandlerOutcome]) -> Value {
+        // The first blocking handler's reason is what Claude is told to work on
+        let reason = outcomes.iter().find_map(|outcome| match outcome {
+            HandlerOutcome::Block(reason) => Some(reason),
+            _ => None,
+        });
+
+        match reason {
+            Some(reason) => json!({"decision": "block", "reason": reason}),
+            None => json!({}),
+        }
     }
 }
 
@@ -53,4 +60,23 @@ mod tests {
         let response = hook.generate_response(&[]);
         assert_eq!(response, json!({}));
     }
+
+    #[test]
+    fn test_stop_hook_blocks_with_first_reason() {
+        let hook = StopHook::new("Stop");
+        let response = hook.generate_response(&[
+            HandlerOutcome::Success,
+            HandlerOutcome::Block("Tests are failing".to_string()),
+            HandlerOutcome::Block("Lint errors".to_string()),
+        ]);
+        assert_eq!(response, json!({"decision": "block", "reason": "Tests are failing"}));
+
+        // Failures and other outcomes never block
+        let response = hook.generate_response(&[
+            HandlerOutcome::Success,
+            HandlerOutcome::Error("check: boom".to_string()),
+            HandlerOutcome::SystemMessage("hi".to_string()),
+        ]);
+        assert_eq!(response, json!({}));
+    }
 }
@@ -1,6 +1,6 @@
 //! PreToolUse and PostToolUse hook implementations.
 
-use super::{HandlerOutcome, Hook, PermissionDecision};
+use super::{combined_decision, HandlerOutcome, Hook, PermissionDecision};
 use crate::event::Event;
 use anyhow::Result;
 use serde_json::{json, Value};
@@ -8,8 +8,9 @@ use serde_json::{json, Value};
 /// Handler for PreToolUse hooks.
 ///
 /// These hooks fire before a tool is executed, allowing for approval/denial.
-/// Currently auto-approves all tools (passive mode), but can be extended
-/// to support interactive handlers that ask the user for permission.
+/// Auto-approves tools (passive mode) unless an interactive handler, such as a
+/// desktop notification with Allow/Deny buttons, returns the user's decision.
+/// If several handlers decide, deny wins over ask, and ask over allow.
 pub struct PreToolUseHook {
     #[allow(dead_code)]
     tool_name: String,
@@ -17,10 +18,7 @@ pub struct PreToolUseHook {
 
 impl PreToolUseHook {
     pub fn from_event(event: &Event) -> Result<Self> {
-        let tool_name = event
-            .get_str("tool_name")
-            .unwrap_or("unknown")
-            .to_string();
+        let tool_name = event.tool_name().unwrap_or("unknown").to_string();
 
         Ok(Self { tool_name })
     }
@@ -33,15 +31,7 @@ impl Hook for PreToolUseHook {
 
     fn generate_response(&self, outcomes: &[HandlerOutcome]) -> Value {
         // Check if any handler provided an interactive response
-        let interactive = outcomes.iter().find_map(|outcome| {
-            if let HandlerOutcome::Interactive(response) = outcome {
-                Some(response)
-            } else {
-                None
-            }
-        });
-
-        let (decision, reason) = if let Some(response) = interactive {
+        let (decision, reason) = if let Some(response) = combined_decision(outcomes) {
             // Use the interactive handler's decision
             let decision_str = match response.decision {
                 PermissionDecision::Allow => "allow",
@@ -138,6 +128,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pre_tool_use_most_cautious_decision_wins() {
+        use crate::hooks::InteractiveResponse;
+
+        let event = Event::from_json(r#"{"hook_event_name": "PreToolUse", "tool_name": "Bash"}"#).unwrap();
+        let hook = PreToolUseHook::from_event(&event).unwrap();
+        let interactive = |decision, reason: &str| {
+            HandlerOutcome::Interactive(InteractiveResponse {
+                decision,
+                reason: Some(reason.to_string()),
+            })
+        };
+        let allow = interactive(PermissionDecision::Allow, "allowed");
+        let ask = interactive(PermissionDecision::Ask, "asked");
+        let deny = interactive(PermissionDecision::Deny, "denied");
+        let decided = |outcomes: &[HandlerOutcome]| {
+            let response = hook.generate_response(outcomes);
+            let output = &response["hookSpecificOutput"];
+            (output["permissionDecision"].clone(), output["permissionDecisionReason"].clone())
+        };
+
+        assert_eq!(decided(&[allow.clone(), deny.clone(), ask.clone()]), (json!("deny"), json!("denied")));
+        assert_eq!(decided(&[allow.clone(), ask.clone()]), (json!("ask"), json!("asked")));
+        assert_eq!(decided(&[HandlerOutcome::Success, allow]), (json!("allow"), json!("allowed")));
+    }
+
     #[test]
     fn test_post_tool_use_response() {
         let hook = PostToolUseHook;
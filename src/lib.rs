@@ -30,13 +30,30 @@
 //! ```
 
 pub mod config;
+pub mod credential_cache;
 pub mod error;
 pub mod event;
+#[cfg(feature = "expr")]
+pub mod expr;
 pub mod handlers;
 pub mod hooks;
 pub mod matcher;
+pub mod net;
+pub mod previous;
+pub mod secrets;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod template;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod text;
 
+use futures::FutureExt;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 /// Global debug mode flag - set once at startup, read from handlers
 static DEBUG_MODE: AtomicBool = AtomicBool::new(false);
@@ -51,13 +68,27 @@ pub fn is_debug_mode() -> bool {
     DEBUG_MODE.load(Ordering::Relaxed)
 }
 
+/// Global long-running flag - set by `boopifier serve`, read from handlers
+static LONG_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Mark the process as long-running, so handlers may keep connections between events
+pub fn set_long_running(enabled: bool) {
+    LONG_RUNNING.store(enabled, Ordering::Relaxed);
+}
+
+/// Check if the process handles many events (serve mode) rather than one
+pub fn is_long_running() -> bool {
+    LONG_RUNNING.load(Ordering::Relaxed)
+}
+
 // Re-export commonly used types at the crate root
-pub use config::{Config, HandlerConfig, MatchRules, MatchType, ProjectOverride};
-pub use error::{NotificationError, Result};
+pub use config::{Config, ConfigError, ConfigFormat, HandlerConfig, HandlerVariant, MatchRules, MatchType, ProjectOverride};
+pub use error::{NotificationError, Result, SuppressionReason};
 pub use event::Event;
 pub use handlers::HandlerRegistry;
 pub use hooks::{hook_from_event, HandlerOutcome, Hook};
-pub use matcher::matches;
+pub use matcher::{matches, matches_with_captures, matches_with_options, Captures, MatchOptions};
+pub use secrets::{DefaultSecretResolver, SecretResolver};
 
 /// Processes a single event through the configured handlers.
 ///
@@ -72,48 +103,1516 @@ pub async fn process_event(
     config: &Config,
     registry: &HandlerRegistry,
 ) -> anyhow::Result<Vec<HandlerOutcome>> {
-    use futures::future::join_all;
+    let event = Event::from_json(event_json)?;
+    dispatch_event(&event, config, registry).await
+}
+
+/// Processes a single event and builds the hook response Claude Code expects for it.
+///
+/// Combines [`process_event`] with [`hook_from_event`]: the returned response is ready
+/// to print (see [`hooks::format_response`]), alongside the same outcomes
+/// [`process_event`] returns.
+///
+/// # Errors
+///
+/// Returns an error if event parsing fails or the event's hook type is unknown, in
+/// which case no handlers run.
+pub async fn process_event_full(
+    event_json: &str,
+    config: &Config,
+    registry: &HandlerRegistry,
+) -> anyhow::Result<(Value, Vec<HandlerOutcome>)> {
+    let event = Event::from_json(event_json)?;
+    let hook = hook_from_event(&event)?;
+    let outcomes = dispatch_event(&event, config, registry).await?;
+    Ok((build_hook_response(hook.as_ref(), &outcomes, config), outcomes))
+}
+
+/// Builds the hook response for an event's outcomes, with the config's
+/// `response_extra` fields merged in.
+///
+/// Fields the hook generates (such as `hookSpecificOutput.permissionDecision`)
+/// are never overwritten; nested objects are merged field by field. When any
+/// handler with `suppress_output` succeeded, the response carries
+/// `"suppressOutput": true`.
+pub fn build_hook_response(hook: &dyn Hook, outcomes: &[HandlerOutcome], config: &Config) -> Value {
+    let unwrapped: Vec<HandlerOutcome> = outcomes.iter().map(|o| o.unwrapped().clone()).collect();
+    let mut response = hook.generate_response(&unwrapped);
+    if outcomes.iter().any(|o| matches!(o, HandlerOutcome::SuppressOutput(_))) {
+        if let Value::Object(fields) = &mut response {
+            fields.insert("suppressOutput".to_string(), Value::Bool(true));
+        }
+    }
+    merge_missing_fields(&mut response, &config.response_extra);
+    response
+}
 
+/// Adds each field of `extra` that `target` doesn't already have, recursing into objects.
+fn merge_missing_fields(target: &mut Value, extra: &serde_json::Map<String, Value>) {
+    let Value::Object(target) = target else {
+        return;
+    };
+    for (key, value) in extra {
+        match (target.get_mut(key), value) {
+            (None, _) => {
+                target.insert(key.clone(), value.clone());
+            }
+            (Some(existing @ Value::Object(_)), Value::Object(nested)) => merge_missing_fields(existing, nested),
+            (Some(_), _) => {}
+        }
+    }
+}
+
+/// Dispatches an already-parsed event through the configured handlers.
+///
+/// This is the same as [`process_event`] but skips JSON parsing, which is useful
+/// when the event was read with [`Event::from_reader`].
+///
+/// # Errors
+///
+/// Currently infallible; the `Result` mirrors [`process_event`].
+pub async fn dispatch_event(
+    event: &Event,
+    config: &Config,
+    registry: &HandlerRegistry,
+) -> anyhow::Result<Vec<HandlerOutcome>> {
+    let timed = dispatch_event_timed(event, config, registry).await?;
+    Ok(timed.into_iter().map(|t| t.outcome).collect())
+}
+
+/// Options that change how an event is dispatched.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessOptions {
+    /// Run every configured handler, including `on_no_match`, regardless of its
+    /// match rules (used by `boopifier test-all`)
+    pub ignore_match: bool,
+
+    /// Skip handlers that need the network, reporting them as suppressed
+    /// (used by `boopifier --offline`)
+    pub offline: bool,
+}
+
+/// Processes a single event like [`process_event`], with dispatch options.
+///
+/// # Errors
+///
+/// Returns an error if event parsing fails.
+pub async fn process_event_with_options(
+    event_json: &str,
+    config: &Config,
+    registry: &HandlerRegistry,
+    options: &ProcessOptions,
+) -> anyhow::Result<Vec<TimedOutcome>> {
     let event = Event::from_json(event_json)?;
+    dispatch_event_with_options(&event, config, registry, options).await
+}
+
+/// Outcome of a single handler along with how long it took to run.
+#[derive(Debug, Clone)]
+pub struct TimedOutcome {
+    /// Configured handler name
+    pub name: String,
+    /// Result of running the handler
+    pub outcome: HandlerOutcome,
+    /// Wall-clock time spent in the handler (including waiting for its lock)
+    pub duration: Duration,
+}
+
+/// Dispatches an event like [`dispatch_event`], also recording per-handler timings.
+///
+/// # Errors
+///
+/// Currently infallible; the `Result` mirrors [`process_event`].
+pub async fn dispatch_event_timed(
+    event: &Event,
+    config: &Config,
+    registry: &HandlerRegistry,
+) -> anyhow::Result<Vec<TimedOutcome>> {
+    dispatch_event_with_options(event, config, registry, &ProcessOptions::default()).await
+}
+
+/// Dispatches an event like [`dispatch_event_timed`], with dispatch options.
+///
+/// # Errors
+///
+/// Currently infallible; the `Result` mirrors [`process_event`].
+pub async fn dispatch_event_with_options(
+    event: &Event,
+    config: &Config,
+    registry: &HandlerRegistry,
+    options: &ProcessOptions,
+) -> anyhow::Result<Vec<TimedOutcome>> {
+    use futures::future::join_all;
 
-    // Collect futures for all matching handlers
-    let mut handler_futures = Vec::new();
+    let (planned, unresolved) = plan_handlers(event, config, options);
+
+    // Collect futures for all selected handlers, gathering each channel group's
+    // members in order; every ungrouped handler is a group of its own
+    let mut groups: Vec<(Option<&str>, bool, Vec<_>)> = Vec::new();
+    for (index, PlannedHandler { handler_config, event, settings }) in planned.into_iter().enumerate() {
+        let future = match handler_future(event, handler_config, settings, registry, options.offline) {
+            Ok(future) => future,
+            Err(outcome) => return Ok(vec![outcome]),
+        };
+        let first = registry.get(&handler_config.handler_type).is_some_and(|h| h.runs_first());
+        let member = (index, handler_config.name.as_str(), future);
+        let group = handler_config.channel_group.as_deref();
+        match groups.iter_mut().find(|(name, _, _)| group.is_some() && *name == group) {
+            Some((_, runs_first, members)) => {
+                *runs_first |= first;
+                members.push(member);
+            }
+            None => groups.push((group, first, vec![member])),
+        }
+    }
+
+    // Execute the groups concurrently, those that must run first before the
+    // rest, then restore the planned order
+    let (first, rest): (Vec<_>, Vec<_>) = groups.into_iter().partition(|(_, first, _)| *first);
+    let mut outcomes: Vec<(usize, TimedOutcome)> = Vec::new();
+    for phase in [first, rest] {
+        let phase = join_all(phase.into_iter().map(|(group, _, members)| run_channel_group(group, members))).await;
+        outcomes.extend(phase.into_iter().flatten());
+    }
+    outcomes.sort_by_key(|(index, _)| *index);
+
+    Ok(outcomes.into_iter().map(|(_, outcome)| outcome).chain(unresolved).collect())
+}
+
+/// Runs a channel group's handlers one at a time until one succeeds.
+///
+/// Members after the first success are not run and report `Suppressed`.
+/// Outcomes keep each member's index in the dispatch plan.
+async fn run_channel_group<F>(group: Option<&str>, members: Vec<(usize, &str, F)>) -> Vec<(usize, TimedOutcome)>
+where
+    F: std::future::Future<Output = TimedOutcome>,
+{
+    let mut outcomes = Vec::with_capacity(members.len());
+    let mut delivered_by: Option<String> = None;
+    for (index, name, future) in members {
+        let outcome = match (&delivered_by, group) {
+            (Some(delivered_by), Some(group)) => TimedOutcome {
+                name: name.to_string(),
+                outcome: HandlerOutcome::Suppressed {
+                    reason: SuppressionReason::ChannelGroup,
+                    message: format!("{}: channel group '{}' already delivered by {}", name, group, delivered_by),
+                },
+                duration: Duration::ZERO,
+            },
+            _ => future.await,
+        };
+        if delivered_by.is_none() && outcome.outcome.is_success() {
+            delivered_by = Some(outcome.name.clone());
+        }
+        outcomes.push((index, outcome));
+    }
+    outcomes
+}
+
+/// Names of the handlers that would run for an event, without running them.
+///
+/// Applies the same selection as [`dispatch_event_with_options`]: match rules,
+/// `when_env`, muted tools, the `on_no_match` fallback and escalations.
+///
+/// # Errors
+///
+/// Returns an error if a selected handler escalates to an unknown handler.
+pub fn matching_handlers(event: &Event, config: &Config, options: &ProcessOptions) -> anyhow::Result<Vec<String>> {
+    let (planned, unresolved) = plan_handlers(event, config, options);
+    match unresolved.into_iter().next() {
+        Some(TimedOutcome {
+            outcome: HandlerOutcome::Error(message),
+            ..
+        }) => anyhow::bail!(message),
+        Some(outcome) => anyhow::bail!("{}: {:?}", outcome.name, outcome.outcome),
+        None => Ok(planned.into_iter().map(|p| p.handler_config.name.clone()).collect()),
+    }
+}
+
+/// A handler selected to run, with the event and config to run it with.
+struct PlannedHandler<'a> {
+    handler_config: &'a HandlerConfig,
+    event: Event,
+    settings: HashMap<String, Value>,
+}
+
+/// Selects the handlers to run for an event, in order.
+///
+/// Also returns an error outcome for each escalation that names an unknown
+/// handler; the other handlers still run.
+fn plan_handlers<'a>(
+    event: &Event,
+    config: &'a Config,
+    options: &ProcessOptions,
+) -> (Vec<PlannedHandler<'a>>, Vec<TimedOutcome>) {
+    // Globally muted tools skip every handler
+    if is_tool_muted(event, std::env::var(MUTE_TOOLS_ENV).ok().as_deref()) {
+        return (Vec::new(), Vec::new());
+    }
+
+    // Expose override metadata to handler templates
+    let mut event = event.clone();
+    event.context.override_pattern = config.active_override.clone();
+    let event = &event;
+
+    let mut planned = Vec::new();
+    let mut matched_any = false;
+
+    let mut fired = HashSet::new();
+    let mut escalations = Vec::new();
 
     for handler_config in &config.handlers {
         // Check if event matches the handler's rules
-        if !matches(&event, &handler_config.match_rules, &handler_config.match_type) {
+        let Some((handler_event, settings)) = prepare_handler(event, handler_config, options) else {
             continue;
+        };
+        matched_any = true;
+        fired.insert(handler_config.name.as_str());
+        if let Some(escalation) = escalation(handler_config, &handler_event, &settings) {
+            escalations.push(escalation);
         }
+        planned.push(PlannedHandler {
+            handler_config,
+            event: handler_event,
+            settings,
+        });
+    }
 
-        // Get the handler
-        let handler = match registry.get(&handler_config.handler_type) {
-            Some(h) => h,
-            None => {
-                return Ok(vec![HandlerOutcome::Error(format!(
-                    "{}: Unknown handler type: {}",
-                    handler_config.name, handler_config.handler_type
-                ))]);
+    // Fire the fallback handler when nothing else matched
+    if !matched_any || options.ignore_match {
+        if let Some(fallback) = &config.on_no_match {
+            if let Some((handler_event, settings)) = prepare_handler(event, fallback, options) {
+                if let Some(escalation) = escalation(fallback, &handler_event, &settings) {
+                    escalations.push(escalation);
+                }
+                planned.push(PlannedHandler {
+                    handler_config: fallback,
+                    event: handler_event,
+                    settings,
+                });
             }
+        }
+    }
+
+    // Critical events also go to each escalation target, once, even if its rules don't match
+    let mut unresolved = Vec::new();
+    for (target_name, source_event) in escalations {
+        if !fired.insert(target_name) {
+            continue;
+        }
+        let Some(target) = config.handlers.iter().find(|h| h.name == target_name) else {
+            unresolved.push(TimedOutcome {
+                name: target_name.to_string(),
+                outcome: HandlerOutcome::Error(format!("escalate_to: unknown handler '{}'", target_name)),
+                duration: Duration::ZERO,
+            });
+            continue;
         };
+        if !target.enabled || !target.env_enabled() {
+            continue;
+        }
+
+        // The target sees the source handler's captures and extracted values
+        let captures = source_event.context.captures.clone();
+        let (mut handler_event, settings) = configure_handler(&source_event, target, captures);
+        for (name, value) in &source_event.context.extracted {
+            handler_event.context.extracted.entry(name.clone()).or_insert_with(|| value.clone());
+        }
+        planned.push(PlannedHandler {
+            handler_config: target,
+            event: handler_event,
+            settings,
+        });
+    }
+
+    (planned, unresolved)
+}
+
+/// Environment variable listing tools (comma-separated) whose events run no handlers.
+pub const MUTE_TOOLS_ENV: &str = "BOOPIFIER_MUTE_TOOLS";
+
+/// Returns true if the event's tool appears in the comma-separated `mute_list`.
+///
+/// Tool names are compared case-insensitively; events without a tool are never muted.
+fn is_tool_muted(event: &Event, mute_list: Option<&str>) -> bool {
+    let (Some(tool), Some(list)) = (event.tool_name(), mute_list) else {
+        return false;
+    };
+    list.split(',')
+        .map(str::trim)
+        .any(|muted| !muted.is_empty() && muted.eq_ignore_ascii_case(tool))
+}
+
+/// Matches a handler against the event, returning the event and config to run it with.
+///
+/// Returns `None` if the handler is disabled, or its `when_env` conditions, rules or `expr` don't match
+/// (rules are skipped with `ignore_match`). Otherwise the event carries
+/// the regex captures bound by the rules, and the first matching variant's config
+/// (if any) is merged over the handler's base config.
+fn prepare_handler(
+    event: &Event,
+    handler_config: &HandlerConfig,
+    process_options: &ProcessOptions,
+) -> Option<(Event, HashMap<String, Value>)> {
+    if !handler_config.enabled || !handler_config.env_enabled() {
+        return None;
+    }
+    if process_options.ignore_match {
+        return Some(configure_handler(event, handler_config, Captures::new()));
+    }
 
-        // Clone data for this handler future
-        let event_clone = event.clone();
-        let config_clone = handler_config.config.clone();
-        let name = handler_config.name.clone();
+    let options = handler_config.match_options();
+    let captures = matches_with_captures(event, &handler_config.match_rules, &handler_config.match_type, &options)?;
+    if !handler_config.expr.as_deref().is_none_or(|expr| expr_matches(event, expr)) {
+        return None;
+    }
+
+    Some(configure_handler(event, handler_config, captures))
+}
+
+/// Evaluates a handler's `expr` condition against the event.
+#[cfg(feature = "expr")]
+fn expr_matches(event: &Event, expr: &str) -> bool {
+    expr::matches(event, expr)
+}
+
+/// Without the `expr` feature, handlers with an `expr` condition never run
+/// (configs with one only load leniently).
+#[cfg(not(feature = "expr"))]
+fn expr_matches(_event: &Event, _expr: &str) -> bool {
+    false
+}
+
+/// Builds the event and config to run a handler with, given its rule captures.
+///
+/// The first matching variant's config (if any) is merged over the handler's
+/// base config, and its captures are added to `captures`.
+fn configure_handler(
+    event: &Event,
+    handler_config: &HandlerConfig,
+    mut captures: Captures,
+) -> (Event, HashMap<String, Value>) {
+    let options = handler_config.match_options();
+    let mut settings = handler_config.config.clone();
+    let variant = handler_config.variants.iter().find_map(|variant| {
+        matches_with_captures(event, &variant.match_rules, &handler_config.match_type, &options)
+            .map(|variant_captures| (variant, variant_captures))
+    });
+    if let Some((variant, variant_captures)) = variant {
+        settings.extend(variant.config.clone());
+        captures.extend(variant_captures);
+    }
 
-        // Create a future for this handler
-        let future = async move {
-            match handler.handle(&event_clone, &config_clone).await {
-                Ok(()) => HandlerOutcome::Success,
-                Err(e) => HandlerOutcome::Error(format!("{}: {}", name, e)),
+    // Expose regex captures and extracted values to this handler's templates
+    let mut handler_event = event.clone();
+    handler_event.context.captures = captures;
+    handler_event.context.extracted = template::TemplateContext::extract(event, &handler_config.extract);
+    handler_event.context.handler_name = Some(handler_config.name.clone());
+
+    (handler_event, settings)
+}
+
+/// Returns the handler's escalation target if its resolved `urgency` is `critical`.
+///
+/// `settings` already has any matching variant merged in, so a variant can raise
+/// the urgency for particular events. The target receives the same event,
+/// including this handler's captures.
+fn escalation<'a>(
+    handler_config: &'a HandlerConfig,
+    event: &Event,
+    settings: &HashMap<String, Value>,
+) -> Option<(&'a str, Event)> {
+    let target = handler_config.escalate_to.as_deref()?;
+    let critical = settings.get("urgency").and_then(|v| v.as_str()) == Some("critical");
+    critical.then(|| (target, event.clone()))
+}
+
+/// Builds the future that runs one configured handler against an event.
+///
+/// When `offline`, handlers that need the network resolve to `Suppressed`
+/// without running. Returns an error outcome if the handler type is not registered.
+fn handler_future<'a>(
+    event: Event,
+    handler_config: &HandlerConfig,
+    settings: HashMap<String, Value>,
+    registry: &'a HandlerRegistry,
+    offline: bool,
+) -> std::result::Result<impl std::future::Future<Output = TimedOutcome> + 'a, TimedOutcome> {
+    // Get the handler
+    let handler = registry.get(&handler_config.handler_type).ok_or_else(|| TimedOutcome {
+        name: handler_config.name.clone(),
+        outcome: HandlerOutcome::Error(format!(
+            "{}: Unknown handler type: {}",
+            handler_config.name, handler_config.handler_type
+        )),
+        duration: Duration::ZERO,
+    })?;
+
+    let name = handler_config.name.clone();
+    let system_message = handler_config.system_message.clone();
+    let block_reason = handler_config.block_reason.clone();
+    let additional_context = handler_config.additional_context.clone();
+    let suppress_output = handler_config.suppress_output;
+    let timeout = handler_config
+        .timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or_else(|| handler.default_timeout(&settings));
+
+    // Stateful handlers get a per-name lock so their calls never overlap
+    let lock = handler.serialize().then(|| registry.handler_lock(&name));
+    let skip_offline = offline && handler.requires_network();
+
+    // Create a future for this handler
+    Ok(async move {
+        if skip_offline {
+            return TimedOutcome {
+                outcome: HandlerOutcome::Suppressed {
+                    reason: SuppressionReason::Offline,
+                    message: format!("{}: needs the network and running offline", name),
+                },
+                name,
+                duration: Duration::ZERO,
+            };
+        }
+
+        let start = Instant::now();
+        let _guard = match &lock {
+            Some(lock) => Some(lock.lock().await),
+            None => None,
+        };
+
+        // A panicking handler (or native library) fails on its own instead of taking down the event,
+        // and a hung one gives up rather than holding back the hook response
+        let result = tokio::time::timeout(
+            timeout,
+            AssertUnwindSafe(handler.handle_interactive(&event, &settings)).catch_unwind(),
+        )
+        .await;
+
+        let outcome = match result {
+            Ok(Ok(Ok(None))) => {
+                let outcome = success_outcome(&event, &block_reason, &additional_context, &system_message);
+                if suppress_output {
+                    HandlerOutcome::SuppressOutput(Box::new(outcome))
+                } else {
+                    outcome
+                }
             }
+            Ok(Ok(Ok(Some(response)))) => HandlerOutcome::Interactive(response),
+            Ok(Ok(Err(NotificationError::Suppressed { reason, message }))) => HandlerOutcome::Suppressed {
+                reason,
+                message: format!("{}: {}", name, message),
+            },
+            Ok(Ok(Err(e))) => HandlerOutcome::Error(format!("{}: {}", name, e)),
+            Ok(Err(payload)) => HandlerOutcome::Error(format!("{}: panicked: {}", name, panic_message(&*payload))),
+            Err(_) => HandlerOutcome::Error(format!("{}: timed out after {}ms", name, timeout.as_millis())),
         };
 
-        handler_futures.push(future);
+        TimedOutcome {
+            name,
+            outcome,
+            duration: start.elapsed(),
+        }
+    })
+}
+
+/// Outcome of a handler that ran without deciding anything itself.
+///
+/// Each hook reads one of the response settings: `block_reason` for Stop and
+/// SubagentStop, `additional_context` for UserPromptSubmit and `system_message`
+/// for Notification. A setting the event's hook doesn't read is ignored.
+fn success_outcome(
+    event: &Event,
+    block_reason: &Option<String>,
+    additional_context: &Option<String>,
+    system_message: &Option<String>,
+) -> HandlerOutcome {
+    match (event.hook_name(), block_reason, additional_context, system_message) {
+        (Some("Stop" | "SubagentStop"), Some(reason), _, _) => HandlerOutcome::Block(template::render(reason, event)),
+        (Some("UserPromptSubmit"), _, Some(context), _) => {
+            HandlerOutcome::AdditionalContext(template::render(context, event))
+        }
+        (Some("Notification"), _, _, Some(message)) => HandlerOutcome::SystemMessage(template::render(message, event)),
+        _ => HandlerOutcome::Success,
     }
+}
 
-    // Execute all handler futures concurrently
-    let outcomes = join_all(handler_futures).await;
+/// Text of a caught panic's payload.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::{Handler, HandlerResult};
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    /// Test handler that records the name of every handler config it runs for.
+    struct RecordingHandler {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Handler for RecordingHandler {
+        fn handler_type(&self) -> &str {
+            "recording"
+        }
+
+        async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+            let label = config.get("label").and_then(|v| v.as_str()).unwrap_or("");
+            let label = crate::template::render(label, event);
+            self.calls.lock().unwrap().push(label);
+            if let Some(message) = config.get("panic").and_then(Value::as_str) {
+                panic!("{}", message);
+            }
+            if let Some(delay_ms) = config.get("delay_ms").and_then(Value::as_u64) {
+                tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+            }
+            match config.get("fail") {
+                Some(_) => Err(NotificationError::SendFailed("unavailable".to_string())),
+                None => Ok(()),
+            }
+        }
+    }
+
+    /// Test handler that fails if it is ever entered while already running.
+    struct ExclusiveHandler {
+        in_use: Arc<std::sync::atomic::AtomicBool>,
+        calls: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Handler for ExclusiveHandler {
+        fn handler_type(&self) -> &str {
+            "exclusive"
+        }
+
+        async fn handle(&self, _event: &Event, _config: &HashMap<String, Value>) -> HandlerResult<()> {
+            use std::sync::atomic::Ordering;
 
-    Ok(outcomes)
+            assert!(!self.in_use.swap(true, Ordering::SeqCst), "handler re-entered");
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            self.in_use.store(false, Ordering::SeqCst);
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn serialize(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serialized_handler_never_reentered() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut registry = HandlerRegistry::new();
+        registry.register(Box::new(ExclusiveHandler {
+            in_use: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            calls: calls.clone(),
+        }));
+
+        let config = Config::from_json(
+            r#"{"handlers": [{"name": "exclusive", "type": "exclusive", "config": {}}]}"#,
+        )
+        .unwrap();
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+
+        let (a, b, c) = tokio::join!(
+            dispatch_event(&event, &config, &registry),
+            dispatch_event(&event, &config, &registry),
+            dispatch_event(&event, &config, &registry),
+        );
+
+        for outcomes in [a, b, c] {
+            assert!(matches!(outcomes.unwrap()[..], [HandlerOutcome::Success]));
+        }
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    /// Test handler that records its label after a delay, and runs before other handlers.
+    struct ProducerHandler {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Handler for ProducerHandler {
+        fn handler_type(&self) -> &str {
+            "producer"
+        }
+
+        async fn handle(&self, _event: &Event, _config: &HashMap<String, Value>) -> HandlerResult<()> {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            self.calls.lock().unwrap().push("card".to_string());
+            Ok(())
+        }
+
+        fn runs_first(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runs_first_handlers_finish_before_the_rest() {
+        let (mut registry, calls) = recording_registry();
+        registry.register(Box::new(ProducerHandler { calls: calls.clone() }));
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "email", "type": "recording", "config": {"label": "email"}},
+                {"name": "card", "type": "producer", "config": {}}
+            ]}"#,
+        )
+        .unwrap();
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+
+        let outcomes = dispatch_event_timed(&event, &config, &registry).await.unwrap();
+        let names: Vec<&str> = outcomes.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, ["email", "card"]);
+        assert_eq!(*calls.lock().unwrap(), ["card", "email"]);
+    }
+
+    /// Test handler that sleeps for the configured number of milliseconds.
+    struct SleepHandler;
+
+    #[async_trait]
+    impl Handler for SleepHandler {
+        fn handler_type(&self) -> &str {
+            "sleep"
+        }
+
+        async fn handle(&self, _event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+            let ms = config.get("ms").and_then(|v| v.as_u64()).unwrap_or(0);
+            tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_records_timings() {
+        let mut registry = HandlerRegistry::new();
+        registry.register(Box::new(SleepHandler));
+
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "fast", "type": "sleep", "config": {"ms": 0}},
+                {"name": "slow", "type": "sleep", "config": {"ms": 30}}
+            ]}"#,
+        )
+        .unwrap();
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+
+        let timed = dispatch_event_timed(&event, &config, &registry).await.unwrap();
+        assert_eq!(timed.len(), 2);
+
+        let slow = timed.iter().find(|t| t.name == "slow").unwrap();
+        let fast = timed.iter().find(|t| t.name == "fast").unwrap();
+        assert!(slow.duration >= std::time::Duration::from_millis(30));
+        assert!(fast.duration < slow.duration);
+        assert!(timed.iter().all(|t| matches!(t.outcome, HandlerOutcome::Success)));
+    }
+
+    /// Test handler that "clicks" the action named in its config.
+    struct ApprovalHandler;
+
+    #[async_trait]
+    impl Handler for ApprovalHandler {
+        fn handler_type(&self) -> &str {
+            "approval"
+        }
+
+        async fn handle(&self, _event: &Event, _config: &HashMap<String, Value>) -> HandlerResult<()> {
+            Ok(())
+        }
+
+        async fn handle_interactive(
+            &self,
+            _event: &Event,
+            config: &HashMap<String, Value>,
+        ) -> HandlerResult<Option<hooks::InteractiveResponse>> {
+            Ok(config.get("click").map(|_| hooks::InteractiveResponse {
+                decision: hooks::PermissionDecision::Deny,
+                reason: Some("clicked Deny".to_string()),
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interactive_decision_reaches_hook_response() {
+        let mut registry = HandlerRegistry::new();
+        registry.register(Box::new(ApprovalHandler));
+
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "ask", "type": "approval", "config": {"click": true}},
+                {"name": "passive", "type": "approval", "config": {}}
+            ]}"#,
+        )
+        .unwrap();
+        let event = Event::from_json(r#"{"hook_event_name": "PreToolUse", "tool_name": "Bash"}"#).unwrap();
+
+        let outcomes = dispatch_event(&event, &config, &registry).await.unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes.iter().any(|o| matches!(o, HandlerOutcome::Success)));
+
+        let response = hook_from_event(&event).unwrap().generate_response(&outcomes);
+        assert_eq!(response["hookSpecificOutput"]["permissionDecision"], "deny");
+        assert_eq!(response["hookSpecificOutput"]["permissionDecisionReason"], "clicked Deny");
+    }
+
+    #[tokio::test]
+    async fn test_process_event_full_builds_hook_response() {
+        let mut registry = HandlerRegistry::new();
+        registry.register(Box::new(ApprovalHandler));
+        let config = Config::from_json(r#"{"handlers": [{"name": "ask", "type": "approval", "config": {"click": true}}]}"#).unwrap();
+
+        let (response, outcomes) = process_event_full(r#"{"hook_event_name": "Stop"}"#, &config, &registry)
+            .await
+            .unwrap();
+        assert_eq!(response, serde_json::json!({}));
+        assert_eq!(outcomes.len(), 1);
+
+        let (response, outcomes) = process_event_full(
+            r#"{"hook_event_name": "PreToolUse", "tool_name": "Bash"}"#,
+            &config,
+            &registry,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response["hookSpecificOutput"]["hookEventName"], "PreToolUse");
+        assert_eq!(response["hookSpecificOutput"]["permissionDecision"], "deny");
+        assert!(matches!(outcomes[..], [HandlerOutcome::Interactive(_)]));
+
+        assert!(process_event_full(r#"{"hook_event_name": "Bogus"}"#, &config, &registry)
+            .await
+            .is_err());
+    }
+
+    fn recording_registry() -> (HandlerRegistry, Arc<Mutex<Vec<String>>>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = HandlerRegistry::new();
+        registry.register(Box::new(RecordingHandler { calls: calls.clone() }));
+        (registry, calls)
+    }
+
+    #[tokio::test]
+    async fn test_channel_group_first_success_suppresses_rest() {
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "desktop", "type": "recording", "channel_group": "alert", "config": {"label": "desktop"}},
+                {"name": "log", "type": "recording", "config": {"label": "log"}},
+                {"name": "push", "type": "recording", "channel_group": "alert", "config": {"label": "push"}}
+            ]}"#,
+        )
+        .unwrap();
+        let (registry, calls) = recording_registry();
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+
+        let outcomes = dispatch_event_timed(&event, &config, &registry).await.unwrap();
+        let names: Vec<&str> = outcomes.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, ["desktop", "log", "push"]);
+        assert!(outcomes[0].outcome.is_success());
+        assert!(
+            matches!(&outcomes[2].outcome, HandlerOutcome::Suppressed { reason: SuppressionReason::ChannelGroup, message }
+                if message.contains("already delivered by desktop"))
+        );
+
+        let mut calls = calls.lock().unwrap().clone();
+        calls.sort();
+        assert_eq!(calls, ["desktop", "log"]);
+    }
+
+    #[tokio::test]
+    async fn test_channel_group_failure_falls_through() {
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "desktop", "type": "recording", "channel_group": "alert", "config": {"label": "desktop", "fail": true}},
+                {"name": "push", "type": "recording", "channel_group": "alert", "config": {"label": "push"}},
+                {"name": "email", "type": "recording", "channel_group": "alert", "config": {"label": "email"}}
+            ]}"#,
+        )
+        .unwrap();
+        let (registry, calls) = recording_registry();
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+
+        let outcomes = dispatch_event(&event, &config, &registry).await.unwrap();
+        assert!(matches!(outcomes[0], HandlerOutcome::Error(_)));
+        assert!(outcomes[1].is_success());
+        assert!(matches!(outcomes[2], HandlerOutcome::Suppressed { .. }));
+        assert_eq!(*calls.lock().unwrap(), ["desktop", "push"]);
+    }
+
+    #[tokio::test]
+    async fn test_handler_panic_becomes_error() {
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "boom", "type": "recording", "config": {"label": "boom", "panic": "no audio device"}},
+                {"name": "ok", "type": "recording", "system_message": "still here", "config": {"label": "ok"}}
+            ]}"#,
+        )
+        .unwrap();
+        let (registry, calls) = recording_registry();
+
+        let (response, outcomes) = process_event_full(r#"{"hook_event_name": "Notification"}"#, &config, &registry)
+            .await
+            .unwrap();
+        assert!(matches!(&outcomes[0], HandlerOutcome::Error(e) if e == "boom: panicked: no audio device"));
+        assert!(outcomes[1].is_success());
+        assert_eq!(response["systemMessage"], "still here");
+        assert_eq!(*calls.lock().unwrap(), ["boom", "ok"]);
+    }
+
+    #[tokio::test]
+    async fn test_slow_handler_times_out() {
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "hung", "type": "recording", "timeout_ms": 50, "config": {"label": "hung", "delay_ms": 60000}},
+                {"name": "ok", "type": "recording", "timeout_ms": 5000, "config": {"label": "ok", "delay_ms": 10}}
+            ]}"#,
+        )
+        .unwrap();
+        let (registry, _calls) = recording_registry();
+
+        let start = Instant::now();
+        let outcomes = process_event(r#"{"hook_event_name": "Stop"}"#, &config, &registry)
+            .await
+            .unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(matches!(&outcomes[0], HandlerOutcome::Error(e) if e == "hung: timed out after 50ms"));
+        assert!(outcomes[1].is_success());
+    }
+
+    #[tokio::test]
+    async fn test_offline_skips_network_handlers() {
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "local", "type": "recording", "config": {"label": "local"}},
+                {"name": "hook", "type": "webhook", "config": {"url": "http://192.0.2.1/never"}}
+            ]}"#,
+        )
+        .unwrap();
+        let (mut registry, calls) = recording_registry();
+        registry.register(Box::new(handlers::webhook::WebhookHandler));
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+
+        let options = ProcessOptions {
+            offline: true,
+            ..Default::default()
+        };
+        let outcomes = dispatch_event_with_options(&event, &config, &registry, &options).await.unwrap();
+        assert!(outcomes[0].outcome.is_success());
+        assert!(matches!(
+            &outcomes[1].outcome,
+            HandlerOutcome::Suppressed { reason: SuppressionReason::Offline, message } if message.starts_with("hook:")
+        ));
+        assert_eq!(outcomes[1].duration, Duration::ZERO);
+        assert_eq!(*calls.lock().unwrap(), ["local"]);
+    }
+
+    #[tokio::test]
+    async fn test_response_extra_merged_without_overriding_hook_fields() {
+        let mut registry = HandlerRegistry::new();
+        registry.register(Box::new(ApprovalHandler));
+        let config = Config::from_json(
+            r#"{
+                "handlers": [{"name": "ask", "type": "approval", "config": {"click": true}}],
+                "response_extra": {
+                    "suppressOutput": true,
+                    "hookSpecificOutput": {"permissionDecision": "allow", "note": "from config"}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let (response, _) = process_event_full(r#"{"hook_event_name": "Stop"}"#, &config, &registry)
+            .await
+            .unwrap();
+        assert_eq!(response["suppressOutput"], true);
+
+        let (response, _) = process_event_full(
+            r#"{"hook_event_name": "PreToolUse", "tool_name": "Bash"}"#,
+            &config,
+            &registry,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            response,
+            serde_json::json!({
+                "suppressOutput": true,
+                "hookSpecificOutput": {
+                    "hookEventName": "PreToolUse",
+                    "permissionDecision": "deny",
+                    "permissionDecisionReason": "clicked Deny",
+                    "note": "from config"
+                }
+            })
+        );
+    }
+
+    #[cfg(feature = "expr")]
+    #[tokio::test]
+    async fn test_expr_anded_with_match_rules() {
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "failed-bash", "type": "recording", "match_rules": {"hook_event_name": "PostToolUse"},
+                 "expr": "tool_name == \"Bash\" && exit_code != 0", "config": {"label": "failed"}},
+                {"name": "stop", "type": "recording", "match_rules": {"hook_event_name": "Stop"},
+                 "filter": "true", "config": {"label": "stop"}}
+            ]}"#,
+        )
+        .unwrap();
+        let (registry, calls) = recording_registry();
+
+        for event in [
+            r#"{"hook_event_name": "PostToolUse", "tool_name": "Bash", "exit_code": 1}"#,
+            r#"{"hook_event_name": "PostToolUse", "tool_name": "Bash", "exit_code": 0}"#,
+            r#"{"hook_event_name": "PostToolUse", "tool_name": "Bash"}"#,
+            r#"{"hook_event_name": "Notification", "tool_name": "Bash", "exit_code": 1}"#,
+            r#"{"hook_event_name": "Stop"}"#,
+        ] {
+            let event = Event::from_json(event).unwrap();
+            dispatch_event(&event, &config, &registry).await.unwrap();
+        }
+
+        assert_eq!(*calls.lock().unwrap(), vec!["failed".to_string(), "stop".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_system_message_only_when_configured() {
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "echo", "type": "recording", "system_message": "Claude says: {{message}}", "config": {}},
+                {"name": "plain", "type": "recording", "config": {}}
+            ]}"#,
+        )
+        .unwrap();
+        let (registry, _calls) = recording_registry();
+        let event = Event::from_json(r#"{"hook_event_name": "Notification", "message": "Waiting for input"}"#).unwrap();
+
+        let outcomes = dispatch_event(&event, &config, &registry).await.unwrap();
+        assert!(matches!(
+            &outcomes[..],
+            [HandlerOutcome::SystemMessage(m), HandlerOutcome::Success] if m == "Claude says: Waiting for input"
+        ));
+
+        let response = hook_from_event(&event).unwrap().generate_response(&outcomes);
+        assert_eq!(response, serde_json::json!({"systemMessage": "Claude says: Waiting for input"}));
+    }
+
+    #[tokio::test]
+    async fn test_suppress_output_only_when_requested() {
+        let (registry, _calls) = recording_registry();
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+        let hook = hook_from_event(&event).unwrap();
+
+        let config = Config::from_json(r#"{"handlers": [{"name": "plain", "type": "recording", "config": {}}]}"#).unwrap();
+        let outcomes = dispatch_event(&event, &config, &registry).await.unwrap();
+        assert_eq!(build_hook_response(hook.as_ref(), &outcomes, &config), serde_json::json!({}));
+
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "quiet", "type": "recording", "suppress_output": true, "config": {}},
+                {"name": "plain", "type": "recording", "config": {}}
+            ]}"#,
+        )
+        .unwrap();
+        let outcomes = dispatch_event(&event, &config, &registry).await.unwrap();
+        assert!(matches!(
+            &outcomes[..],
+            [HandlerOutcome::SuppressOutput(o), HandlerOutcome::Success] if matches!(**o, HandlerOutcome::Success)
+        ));
+        assert_eq!(
+            build_hook_response(hook.as_ref(), &outcomes, &config),
+            serde_json::json!({"suppressOutput": true})
+        );
+
+        // A failed handler doesn't hide the output
+        let outcomes = [HandlerOutcome::Error("quiet: boom".to_string())];
+        assert_eq!(build_hook_response(hook.as_ref(), &outcomes, &config), serde_json::json!({}));
+    }
+
+    #[tokio::test]
+    async fn test_response_settings_follow_the_hook() {
+        let config = Config::from_json(
+            r#"{"handlers": [{
+                "name": "all", "type": "recording", "suppress_output": true,
+                "system_message": "Heads up", "block_reason": "Keep going", "additional_context": "Branch: main",
+                "config": {}
+            }]}"#,
+        )
+        .unwrap();
+        let (registry, _calls) = recording_registry();
+        let respond = |hook: &str| {
+            let event = serde_json::json!({"hook_event_name": hook, "prompt": "hi"}).to_string();
+            let (config, registry) = (&config, &registry);
+            async move { process_event_full(&event, config, registry).await.unwrap().0 }
+        };
+
+        assert_eq!(
+            respond("Notification").await,
+            serde_json::json!({"systemMessage": "Heads up", "suppressOutput": true})
+        );
+        assert_eq!(
+            respond("Stop").await,
+            serde_json::json!({"decision": "block", "reason": "Keep going", "suppressOutput": true})
+        );
+        assert_eq!(
+            respond("UserPromptSubmit").await,
+            serde_json::json!({
+                "hookSpecificOutput": {"hookEventName": "UserPromptSubmit", "additionalContext": "Branch: main"},
+                "suppressOutput": true
+            })
+        );
+        assert_eq!(respond("PostToolUse").await, serde_json::json!({"suppressOutput": true}));
+    }
+
+    #[tokio::test]
+    async fn test_block_reason_blocks_stop() {
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "plain", "type": "recording", "config": {}},
+                {"name": "keep-going", "type": "recording", "block_reason": "Finish the work in {{cwd}}",
+                 "match_rules": {"stop_hook_active": false}, "config": {}}
+            ]}"#,
+        )
+        .unwrap();
+        let (registry, _calls) = recording_registry();
+
+        let event = r#"{"hook_event_name": "Stop", "cwd": "/work", "stop_hook_active": false}"#;
+        let (response, outcomes) = process_event_full(event, &config, &registry).await.unwrap();
+        assert!(matches!(
+            &outcomes[..],
+            [HandlerOutcome::Success, HandlerOutcome::Block(r)] if r == "Finish the work in /work"
+        ));
+        assert_eq!(response, serde_json::json!({"decision": "block", "reason": "Finish the work in /work"}));
+
+        // Once Claude is continuing because of a block, nothing matches and the stop goes ahead
+        let event = r#"{"hook_event_name": "Stop", "cwd": "/work", "stop_hook_active": true}"#;
+        let (response, _) = process_event_full(event, &config, &registry).await.unwrap();
+        assert_eq!(response, serde_json::json!({}));
+    }
+
+    #[tokio::test]
+    async fn test_additional_context_for_prompts() {
+        let config = Config::from_json(
+            r#"{"handlers": [
+                {"name": "deploys", "type": "recording", "match_rules": {"prompt": {"contains": "deploy"}},
+                 "additional_context": "Deploys from {{cwd}} go through make deploy", "config": {}},
+                {"name": "log", "type": "recording", "config": {}}
+            ]}"#,
+        )
+        .unwrap();
+        let (registry, _calls) = recording_registry();
+
+        let event = r#"{"hook_event_name": "UserPromptSubmit", "cwd": "/work", "prompt": "deploy it"}"#;
+        let (response, _) = process_event_full(event, &config, &registry).await.unwrap();
+        assert_eq!(
+            response,
+            serde_json::json!({"hookSpecificOutput": {
+                "hookEventName": "UserPromptSubmit",
+                "additionalContext": "Deploys from /work go through make deploy"
+            }})
+        );
+
+        let event = r#"{"hook_event_name": "UserPromptSubmit", "cwd": "/work", "prompt": "fix the tests"}"#;
+        let (response, _) = process_event_full(event, &config, &registry).await.unwrap();
+        assert_eq!(response, serde_json::json!({}));
+    }
+
+    #[tokio::test]
+    async fn test_extract_binds_template_names() {
+        let config = Config::from_json(
+            r#"{
+                "handlers": [{
+                    "name": "bash",
+                    "type": "recording",
+                    "extract": {"cmd": "/tool_input/command", "timeout": "/tool_input/timeout"},
+                    "config": {"label": "ran {{cmd}} ({{timeout}})"}
+                }]
+            }"#,
+        )
+        .unwrap();
+        let (registry, calls) = recording_registry();
+
+        process_event(
+            r#"{"hook_event_name": "PreToolUse", "tool_input": {"command": "ls -la"}}"#,
+            &config,
+            &registry,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), vec!["ran ls -la ({{timeout}})".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_ignore_match_runs_every_handler() {
+        let config = Config::from_json(
+            r#"{
+                "handlers": [
+                    {"name": "stop", "type": "recording", "match_rules": {"hook_event_name": "Stop"}, "config": {"label": "stop"}},
+                    {"name": "never", "type": "recording", "match_rules": {"not": {"hook_event_name": "Notification"}}, "config": {"label": "never"}},
+                    {"name": "bash", "type": "recording", "match_rules": {"tool_name": "Bash"}, "config": {"label": "bash"}}
+                ],
+                "on_no_match": {"name": "unmatched", "type": "recording", "config": {"label": "fallback"}}
+            }"#,
+        )
+        .unwrap();
+        let (registry, calls) = recording_registry();
+        let event = r#"{"hook_event_name": "Notification"}"#;
+
+        // Normally nothing matches, so only the fallback runs
+        process_event(event, &config, &registry).await.unwrap();
+        assert_eq!(*calls.lock().unwrap(), vec!["fallback".to_string()]);
+        calls.lock().unwrap().clear();
+
+        let options = ProcessOptions {
+            ignore_match: true,
+            ..Default::default()
+        };
+        let timed = process_event_with_options(event, &config, &registry, &options)
+            .await
+            .unwrap();
+
+        let mut names: Vec<&str> = timed.iter().map(|t| t.name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["bash", "never", "stop", "unmatched"]);
+        assert_eq!(calls.lock().unwrap().len(), 4);
+    }
+
+    const FALLBACK_CONFIG: &str = r#"{
+        "handlers": [
+            {"name": "stop", "type": "recording", "match_rules": {"hook_event_name": "Stop"}, "config": {"label": "stop"}}
+        ],
+        "on_no_match": {"name": "unmatched", "type": "recording", "config": {"label": "fallback"}}
+    }"#;
+
+    #[tokio::test]
+    async fn test_on_no_match_fires_when_nothing_matches() {
+        let config = Config::from_json(FALLBACK_CONFIG).unwrap();
+        let (registry, calls) = recording_registry();
+
+        let outcomes = process_event(r#"{"hook_event_name": "Notification"}"#, &config, &registry)
+            .await
+            .unwrap();
+
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(*calls.lock().unwrap(), vec!["fallback".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_override_pattern_in_template() {
+        let mut config = Config::from_json(
+            r#"{
+                "handlers": [],
+                "overrides": [{
+                    "path_pattern": "/work/*",
+                    "handlers": [{"name": "work", "type": "recording", "config": {"label": "{{override.pattern}}: {{hook_event_name}}"}}]
+                }]
+            }"#,
+        )
+        .unwrap();
+        config.apply_overrides("/work/project");
+        let (registry, calls) = recording_registry();
+
+        process_event(r#"{"hook_event_name": "Stop"}"#, &config, &registry)
+            .await
+            .unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), vec!["/work/*: Stop".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_bound_capture_in_template() {
+        let config = Config::from_json(
+            r#"{
+                "handlers": [{
+                    "name": "permission",
+                    "type": "recording",
+                    "match_rules": {"message": {"regex": "use (?P<tool>\\w+)", "bind": true}},
+                    "config": {"label": "Approve {{capture.tool}}?"}
+                }]
+            }"#,
+        )
+        .unwrap();
+        let (registry, calls) = recording_registry();
+
+        process_event(
+            r#"{"hook_event_name": "Notification", "message": "Claude needs your permission to use Bash"}"#,
+            &config,
+            &registry,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), vec!["Approve Bash?".to_string()]);
+    }
+
+    const VARIANT_CONFIG: &str = r#"{
+        "handlers": [{
+            "name": "status",
+            "type": "recording",
+            "config": {"label": "event: {{hook_event_name}}"},
+            "variants": [
+                {"match_rules": {"hook_event_name": "Stop"}, "config": {"label": "finished"}},
+                {"match_rules": {"error": {"regex": "(?P<kind>\\w+) failed", "bind": true}}, "config": {"label": "error: {{capture.kind}}"}},
+                {"match_rules": {"hook_event_name": "Stop"}, "config": {"label": "never used"}}
+            ]
+        }]
+    }"#;
+
+    #[tokio::test]
+    async fn test_variants_select_first_match() {
+        let config = Config::from_json(VARIANT_CONFIG).unwrap();
+        let (registry, calls) = recording_registry();
+
+        for event in [
+            r#"{"hook_event_name": "Stop"}"#,
+            r#"{"hook_event_name": "PostToolUse", "error": "build failed"}"#,
+            r#"{"hook_event_name": "Notification"}"#,
+        ] {
+            process_event(event, &config, &registry).await.unwrap();
+        }
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "finished".to_string(),
+                "error: build".to_string(),
+                "event: Notification".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_when_env_gates_handlers() {
+        std::env::set_var("BOOPIFIER_TEST_WHEN_ENV", "work");
+        let config = Config::from_json(
+            r#"{
+                "handlers": [
+                    {"name": "work", "type": "recording", "when_env": {"BOOPIFIER_TEST_WHEN_ENV": "work"}, "config": {"label": "work"}},
+                    {"name": "home", "type": "recording", "when_env": {"BOOPIFIER_TEST_WHEN_ENV": "home"}, "config": {"label": "home"}},
+                    {"name": "set", "type": "recording", "when_env": {"BOOPIFIER_TEST_WHEN_ENV": null}, "config": {"label": "set"}},
+                    {"name": "unset", "type": "recording", "when_env": {"BOOPIFIER_TEST_WHEN_ENV_UNSET": null}, "config": {"label": "unset"}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let (registry, calls) = recording_registry();
+
+        let outcomes = process_event(r#"{"hook_event_name": "Stop"}"#, &config, &registry)
+            .await
+            .unwrap();
+
+        assert_eq!(outcomes.len(), 2);
+        let mut labels = calls.lock().unwrap().clone();
+        labels.sort();
+        assert_eq!(labels, vec!["set".to_string(), "work".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_handler_never_runs() {
+        let config = Config::from_json(
+            r#"{
+                "handlers": [
+                    {"name": "on", "type": "recording", "match_rules": {"hook_event_name": "Stop"}, "config": {"label": "on"}},
+                    {"name": "off", "type": "recording", "enabled": false, "match_rules": {"hook_event_name": "Stop"}, "config": {"label": "off"}},
+                    {"name": "phone", "type": "recording", "enabled": false, "config": {"label": "phone"}}
+                ],
+                "on_no_match": {"name": "fallback", "type": "recording", "config": {"label": "fallback"}}
+            }"#,
+        )
+        .unwrap();
+        assert!(config.handlers[0].enabled);
+        let (registry, calls) = recording_registry();
+
+        let outcomes = process_event(r#"{"hook_event_name": "Stop"}"#, &config, &registry)
+            .await
+            .unwrap();
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(*calls.lock().unwrap(), vec!["on".to_string()]);
+
+        // Disabled handlers count as not matching, so the fallback fires
+        calls.lock().unwrap().clear();
+        process_event(r#"{"hook_event_name": "Notification"}"#, &config, &registry)
+            .await
+            .unwrap();
+        assert_eq!(*calls.lock().unwrap(), vec!["fallback".to_string()]);
+    }
+
+    const ESCALATION_CONFIG: &str = r#"{
+        "handlers": [
+            {
+                "name": "desktop",
+                "type": "recording",
+                "escalate_to": "phone",
+                "config": {"label": "desktop {{hook_event_name}}", "urgency": "normal"},
+                "variants": [{"match_rules": {"hook_event_name": "Notification"}, "config": {"urgency": "critical"}}]
+            },
+            {
+                "name": "phone",
+                "type": "recording",
+                "match_rules": {"hook_event_name": "NeverSent"},
+                "config": {"label": "phone {{hook_event_name}}"}
+            }
+        ]
+    }"#;
+
+    #[tokio::test]
+    async fn test_critical_event_escalates() {
+        let config = Config::from_json(ESCALATION_CONFIG).unwrap();
+        let (registry, calls) = recording_registry();
+
+        let outcomes = process_event(r#"{"hook_event_name": "Notification"}"#, &config, &registry)
+            .await
+            .unwrap();
+
+        assert_eq!(outcomes.len(), 2);
+        let mut labels = calls.lock().unwrap().clone();
+        labels.sort();
+        assert_eq!(
+            labels,
+            vec!["desktop Notification".to_string(), "phone Notification".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_non_critical_event_does_not_escalate() {
+        let config = Config::from_json(ESCALATION_CONFIG).unwrap();
+        let (registry, calls) = recording_registry();
+
+        process_event(r#"{"hook_event_name": "Stop"}"#, &config, &registry)
+            .await
+            .unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), vec!["desktop Stop".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_escalation_target_fires_once() {
+        let config = Config::from_json(
+            r#"{
+                "handlers": [
+                    {"name": "a", "type": "recording", "escalate_to": "phone", "config": {"label": "a", "urgency": "critical"}},
+                    {"name": "b", "type": "recording", "escalate_to": "phone", "config": {"label": "b", "urgency": "critical"}},
+                    {"name": "phone", "type": "recording", "config": {"label": "phone"}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let (registry, calls) = recording_registry();
+
+        process_event(r#"{"hook_event_name": "Stop"}"#, &config, &registry)
+            .await
+            .unwrap();
+
+        let mut labels = calls.lock().unwrap().clone();
+        labels.sort();
+        assert_eq!(labels, vec!["a".to_string(), "b".to_string(), "phone".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_escalation_to_unknown_handler() {
+        let json = r#"{"handlers": [
+            {"name": "a", "type": "recording", "escalate_to": "missing", "config": {"label": "a", "urgency": "critical"}},
+            {"name": "b", "type": "recording", "config": {"label": "b"}}
+        ]}"#;
+        let err = Config::from_json(json).unwrap_err().to_string();
+        assert!(err.contains("handlers[0] (a): escalate_to names unknown handler 'missing'"), "{}", err);
+
+        // Loaded leniently, the other handlers still run
+        let (config, problems) = Config::from_json_lenient(json);
+        assert_eq!(problems.len(), 1);
+        let (registry, calls) = recording_registry();
+        let outcomes = process_event(r#"{}"#, &config, &registry).await.unwrap();
+        assert!(matches!(
+            &outcomes[..],
+            [HandlerOutcome::Success, HandlerOutcome::Success, HandlerOutcome::Error(e)] if e.contains("unknown handler 'missing'")
+        ));
+        assert_eq!(*calls.lock().unwrap(), vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_escalation_keeps_captures() {
+        let config = Config::from_json(
+            r#"{
+                "handlers": [
+                    {
+                        "name": "desktop",
+                        "type": "recording",
+                        "escalate_to": "phone",
+                        "match_rules": {"message": {"regex": "^(?P<what>\\w+) failed$", "bind": true}},
+                        "extract": {"cmd": "/tool_input/command"},
+                        "config": {"label": "desktop", "urgency": "critical"}
+                    },
+                    {
+                        "name": "phone",
+                        "type": "recording",
+                        "match_rules": {"hook_event_name": "NeverSent"},
+                        "config": {"label": "phone {{capture.what}} {{cmd}}"}
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let (registry, calls) = recording_registry();
+
+        let event = r#"{"message": "deploy failed", "tool_input": {"command": "make deploy"}}"#;
+        process_event(event, &config, &registry).await.unwrap();
+
+        let mut labels = calls.lock().unwrap().clone();
+        labels.sort();
+        assert_eq!(labels, vec!["desktop".to_string(), "phone deploy make deploy".to_string()]);
+    }
+
+    #[test]
+    fn test_is_tool_muted() {
+        let event = Event::from_json(r#"{"tool_name": "Bash"}"#).unwrap();
+        assert!(is_tool_muted(&event, Some("Read, bash")));
+        assert!(!is_tool_muted(&event, Some("Read,Edit")));
+        assert!(!is_tool_muted(&event, Some("")));
+        assert!(!is_tool_muted(&event, None));
+
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+        assert!(!is_tool_muted(&event, Some("Bash")));
+    }
+
+    #[tokio::test]
+    async fn test_mute_tools_env() {
+        let config = Config::from_json(
+            r#"{
+                "handlers": [{"name": "all", "type": "recording", "config": {"label": "{{tool_name}}"}}],
+                "on_no_match": {"name": "unmatched", "type": "recording", "config": {"label": "fallback"}}
+            }"#,
+        )
+        .unwrap();
+        let (registry, calls) = recording_registry();
+        let event = r#"{"hook_event_name": "PostToolUse", "tool_name": "MuteTestTool"}"#;
+
+        std::env::set_var(MUTE_TOOLS_ENV, "OtherTool,MuteTestTool");
+        let outcomes = process_event(event, &config, &registry).await.unwrap();
+        assert!(outcomes.is_empty());
+
+        std::env::remove_var(MUTE_TOOLS_ENV);
+        let outcomes = process_event(event, &config, &registry).await.unwrap();
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(*calls.lock().unwrap(), vec!["MuteTestTool".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_on_no_match_skipped_when_handler_matches() {
+        let config = Config::from_json(FALLBACK_CONFIG).unwrap();
+        let (registry, calls) = recording_registry();
+
+        process_event(r#"{"hook_event_name": "Stop"}"#, &config, &registry)
+            .await
+            .unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), vec!["stop".to_string()]);
+    }
 }
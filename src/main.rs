@@ -2,30 +2,154 @@
 //!
 //! Reads JSON events from stdin and dispatches them to configured handlers.
 
-use clap::Parser;
-use boopifier::{hook_from_event, process_event, Config, Event, HandlerOutcome, HandlerRegistry};
+use clap::{Parser, Subcommand};
+use boopifier::handlers::{Diagnostic, DiagnosticStatus};
+use boopifier::hooks::{format_response, HOOK_TYPES};
+use boopifier::{
+    build_hook_response, dispatch_event_timed, dispatch_event_with_options, hook_from_event, matching_handlers, Config,
+    ConfigError, Event, HandlerConfig, HandlerOutcome, HandlerRegistry, ProcessOptions, TimedOutcome,
+};
 use serde_json::json;
+use std::ffi::OsString;
 use std::fs::OpenOptions;
 use std::io::{self, BufRead, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "boopifier")]
 #[command(author, version, about)]
 #[command(about = "Universal notification handler for Claude Code events")]
 struct Cli {
-    /// Path to the configuration file (overrides auto-detection)
+    /// Path to the configuration file (overrides auto-detection); `-` reads it from stdin
     #[arg(short, long)]
     config: Option<PathBuf>,
 
+    /// Read the event from this file instead of stdin (required with `--config -`)
+    #[arg(long)]
+    event_file: Option<PathBuf>,
+
     /// Enable debug logging to /tmp/boopifier.log
     #[arg(short, long)]
     debug: bool,
 
+    /// Also print debug log lines to stderr, colored when it is a terminal (implies --debug)
+    #[arg(long)]
+    debug_stderr: bool,
+
     /// List available handler types
     #[arg(long)]
     list_handlers: bool,
+
+    /// Print each handler type's config keys and capabilities as JSON
+    #[arg(long)]
+    handlers_json: bool,
+
+    /// Print version, git commit, enabled features and handler types as JSON
+    #[arg(long)]
+    version_json: bool,
+
+    /// Skip malformed handlers instead of failing the whole config
+    #[arg(long)]
+    lenient: bool,
+
+    /// Check the config for unknown handler types and missing settings, then exit (1 if any are found)
+    #[arg(long)]
+    validate: bool,
+
+    /// Give up waiting for an event on stdin after this many milliseconds (0 waits forever)
+    #[arg(long, default_value_t = 5000)]
+    stdin_timeout_ms: u64,
+
+    /// Handle every event piped to stdin, one after another, instead of answering the first and exiting
+    #[arg(long)]
+    multi_event: bool,
+
+    /// Print per-handler execution times to stderr after processing
+    #[arg(long)]
+    profile: bool,
+
+    /// Pretty-print the JSON hook response (and `serve` HTTP replies)
+    #[arg(long)]
+    pretty: bool,
+
+    /// On fatal errors (bad config, unreadable event), tell Claude Code to stop instead of continuing
+    #[arg(long)]
+    fail_closed: bool,
+
+    /// Skip handlers that need the network (webhooks, email, push), reporting them as suppressed
+    #[arg(long)]
+    offline: bool,
+
+    /// Run as the detached sender started for `fire_and_forget` (internal)
+    #[arg(long, hide = true)]
+    background: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Show desktop notifications recorded with `persist_missed`
+    Missed {
+        /// Clear the recorded notifications after showing them
+        #[arg(long)]
+        clear: bool,
+
+        /// Path to the missed notifications file (defaults to ~/.claude/boopifier-missed.jsonl)
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+
+    /// Acknowledge pending alerts by resetting the badge counter
+    Ack {
+        /// Path to the badge counter file (defaults to ~/.claude/boopifier-badge)
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+
+    /// Check that the configured handlers can deliver notifications on this machine
+    Doctor,
+
+    /// Send a test event through every configured handler, ignoring match rules
+    TestAll,
+
+    /// Re-run a JSONL log of events through the current config
+    Replay {
+        /// File with one event per line (or objects wrapping it in an `event` field)
+        file: PathBuf,
+
+        /// Only report which handlers would fire (the default)
+        #[arg(long, overrides_with = "no_dry_run")]
+        dry_run: bool,
+
+        /// Run the handlers, sending real notifications
+        #[arg(long, overrides_with = "dry_run")]
+        no_dry_run: bool,
+    },
+
+    /// Accept events over HTTP (`POST /event`) and run them through the configured handlers
+    #[cfg(feature = "server")]
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = boopifier::server::DEFAULT_PORT)]
+        port: u16,
+
+        /// Address to bind; anything but loopback requires a token
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: std::net::IpAddr,
+
+        /// Bearer token clients must send (defaults to $BOOPIFIER_SERVE_TOKEN)
+        #[arg(long)]
+        token: Option<String>,
+
+        /// Also accept newline-delimited JSON events on this Unix socket
+        #[cfg(unix)]
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
 }
 
 #[cfg(target_os = "linux")]
@@ -54,9 +178,42 @@ static ALSA_INIT: extern "C" fn() = {
     alsa_init
 };
 
+/// What a debug log line is about, used to color it on stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogKind {
+    /// General progress
+    Info,
+    /// An event was received
+    Event,
+    /// A handler matched the event
+    Match,
+    /// Handlers ran successfully
+    Success,
+    /// Something failed
+    Failure,
+}
+
+impl LogKind {
+    fn style(self) -> anstyle::Style {
+        use anstyle::AnsiColor;
+
+        match self {
+            LogKind::Info => anstyle::Style::new().dimmed(),
+            LogKind::Event => AnsiColor::Cyan.on_default().bold(),
+            LogKind::Match => AnsiColor::Blue.on_default(),
+            LogKind::Success => AnsiColor::Green.on_default(),
+            LogKind::Failure => AnsiColor::Red.on_default().bold(),
+        }
+    }
+}
+
 struct DebugLogger {
     enabled: bool,
     log_path: PathBuf,
+    /// Mirror log lines to stderr
+    to_stderr: bool,
+    /// Color the stderr lines (never the file)
+    color: bool,
 }
 
 impl DebugLogger {
@@ -64,36 +221,71 @@ impl DebugLogger {
         Self {
             enabled,
             log_path: PathBuf::from("/tmp/boopifier.log"),
+            to_stderr: false,
+            color: false,
         }
     }
 
+    /// Mirrors log lines to stderr, colored only if stderr is a terminal and `NO_COLOR` is unset.
+    fn with_stderr(mut self, to_stderr: bool) -> Self {
+        use std::io::IsTerminal;
+
+        self.to_stderr = to_stderr;
+        self.color = use_color(io::stderr().is_terminal(), std::env::var_os("NO_COLOR").is_some());
+        self
+    }
+
     fn log(&self, message: &str) {
+        self.log_kind(LogKind::Info, message);
+    }
+
+    fn log_kind(&self, kind: LogKind, message: &str) {
         if !self.enabled {
             return;
         }
 
+        let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
         if let Ok(mut file) = OpenOptions::new()
             .create(true)
             .append(true)
             .open(&self.log_path)
         {
-            let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
             let _ = writeln!(file, "[{}] {}", timestamp, message);
         }
+
+        if self.to_stderr {
+            eprintln!("{}", format_stderr_line(kind, &timestamp.to_string(), message, self.color));
+        }
+    }
+}
+
+/// Whether stderr output should be colored.
+fn use_color(is_terminal: bool, no_color: bool) -> bool {
+    is_terminal && !no_color
+}
+
+/// Formats a log line for stderr, wrapping the message in its kind's color if `color`.
+fn format_stderr_line(kind: LogKind, timestamp: &str, message: &str, color: bool) -> String {
+    if color {
+        let style = kind.style();
+        format!("[{}] {}{}{}", timestamp, style.render(), message, style.render_reset())
+    } else {
+        format!("[{}] {}", timestamp, message)
     }
 }
 
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
-    let logger = DebugLogger::new(cli.debug);
+    let debug = cli.debug || cli.debug_stderr;
+    let logger = DebugLogger::new(debug).with_stderr(cli.debug_stderr);
 
     // Set global debug mode for handlers
-    boopifier::set_debug_mode(cli.debug);
+    boopifier::set_debug_mode(debug);
 
     // Suppress ALSA errors early (before any audio initialization)
     #[cfg(target_os = "linux")]
-    if !cli.debug {
+    if !debug {
         suppress_alsa_warnings();
     }
 
@@ -103,6 +295,58 @@ async fn main() {
         return;
     }
 
+    if cli.handlers_json {
+        println!("{}", format_response(&handler_catalog(&HandlerRegistry::new()), cli.pretty));
+        return;
+    }
+
+    if cli.version_json {
+        println!("{}", version_info(&HandlerRegistry::new()));
+        return;
+    }
+
+    if cli.validate {
+        let config_path = cli.config.clone().unwrap_or_else(resolve_config_path);
+        let valid = run_validate(&config_path, &HandlerRegistry::new());
+        process::exit(if valid { 0 } else { 1 });
+    }
+
+    // Run a subcommand instead of processing an event
+    if let Some(command) = &cli.command {
+        match command {
+            Command::Missed { clear, file } => show_missed(file.as_deref(), *clear),
+            Command::Ack { file } => ack_badge(file.as_deref()),
+            Command::Doctor => {
+                let config_path = cli.config.clone().unwrap_or_else(resolve_config_path);
+                let healthy = run_doctor(&config_path, &HandlerRegistry::new()).await;
+                process::exit(if healthy { 0 } else { 1 });
+            }
+            Command::TestAll => {
+                let config_path = cli.config.clone().unwrap_or_else(resolve_config_path);
+                let all_ok = run_test_all(&config_path, &HandlerRegistry::new()).await;
+                process::exit(if all_ok { 0 } else { 1 });
+            }
+            Command::Replay { file, no_dry_run, .. } => {
+                let config_path = cli.config.clone().unwrap_or_else(resolve_config_path);
+                let all_ok = run_replay(&config_path, file, !*no_dry_run, &HandlerRegistry::new()).await;
+                process::exit(if all_ok { 0 } else { 1 });
+            }
+            #[cfg(feature = "server")]
+            Command::Serve { port, bind, token, #[cfg(unix)] socket } => {
+                let config_path = cli.config.clone().unwrap_or_else(resolve_config_path);
+                let token = token.clone().or_else(|| std::env::var(boopifier::server::TOKEN_ENV).ok());
+                let addr = std::net::SocketAddr::new(*bind, *port);
+                #[cfg(not(unix))]
+                let socket: Option<PathBuf> = None;
+                if let Err(e) = run_serve(&config_path, addr, token, socket.clone(), cli.pretty).await {
+                    eprintln!("boopifier: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        return;
+    }
+
     logger.log("Boopifier starting");
 
     // Resolve config file path
@@ -118,24 +362,48 @@ async fn main() {
         }
     };
 
+    // Config on stdin leaves nowhere else to read the event from
+    if config_path.as_os_str() == boopifier::config::STDIN_PATH && cli.event_file.is_none() {
+        logger.log_kind(LogKind::Failure, "--config - given without --event-file");
+        output_hook_error(
+            cli.pretty,
+            cli.fail_closed,
+            "--config - reads the config from stdin, so pass the event with --event-file",
+        );
+        process::exit(0); // Exit 0 for hook compatibility
+    }
+
     // Load configuration (secrets are resolved automatically)
-    let mut config = match Config::load(&config_path) {
+    let loaded = if cli.lenient {
+        Config::load_lenient(&config_path).map(|(cfg, errors)| {
+            for error in &errors {
+                logger.log(&format!("Skipping invalid config entry: {}", error));
+                eprintln!("boopifier: skipping invalid config entry: {}", error);
+            }
+            cfg
+        })
+    } else {
+        Config::load(&config_path)
+    };
+    let mut config = match loaded {
         Ok(cfg) => cfg,
         Err(e) => {
-            logger.log(&format!("Failed to load config: {}", e));
-            output_hook_error(&format!("Failed to load config from {:?}: {}", config_path, e));
+            logger.log_kind(LogKind::Failure, &format!("Failed to load config: {}", e));
+            output_hook_error(cli.pretty, cli.fail_closed, &format!("Failed to load config from {:?}: {}", config_path, e));
             process::exit(0); // Exit 0 for hook compatibility
         }
     };
 
     // Apply project-specific overrides if using global config
-    if let Ok(project_dir) = std::env::var("CLAUDE_PROJECT_DIR") {
-        // Only apply overrides if we're not using a project-specific config
-        let project_config_path = PathBuf::from(&project_dir).join(".claude/boopifier.json");
-        if !project_config_path.exists() {
-            logger.log(&format!("Checking overrides for project: {}", project_dir));
-            config.apply_overrides(&project_dir);
-        }
+    if let Some(project_dir) = missing_project_dir() {
+        logger.log(&format!(
+            "Warning: CLAUDE_PROJECT_DIR {} does not exist; skipping project overrides",
+            project_dir
+        ));
+    }
+    if let Some(project_dir) = override_project_dir() {
+        logger.log(&format!("Checking overrides for project: {}", project_dir));
+        config.apply_overrides(&project_dir);
     }
 
     logger.log(&format!("Loaded config with {} handlers", config.handlers.len()));
@@ -143,107 +411,385 @@ async fn main() {
     // Create handler registry
     let registry = HandlerRegistry::new();
 
-    // Read one event from stdin (Claude Code sends one event per invocation)
-    let stdin = io::stdin();
-    let mut reader = stdin.lock();
-    let mut event_json = String::new();
+    // Read the event from stdin or --event-file. Claude Code sends one per
+    // invocation and waits for the answer, so unless --multi-event is given we
+    // respond as soon as one complete event has arrived, without waiting for EOF
+    let timeout = (cli.stdin_timeout_ms > 0).then(|| Duration::from_millis(cli.stdin_timeout_ms));
+    let mut inputs = match &cli.event_file {
+        Some(path) => match std::fs::File::open(path) {
+            Ok(file) => InputStream::spawn(file, !cli.multi_event),
+            Err(e) => InputStream::failed(e),
+        },
+        None => InputStream::spawn(io::stdin(), !cli.multi_event),
+    };
+    let source = match &cli.event_file {
+        Some(path) => path.display().to_string(),
+        None => "stdin".to_string(),
+    };
 
-    match reader.read_line(&mut event_json) {
-        Ok(_) => {
-            if event_json.trim().is_empty() {
+    let mut handled = 0;
+    loop {
+        match inputs.next(timeout).await {
+            Input::Event(event) => {
+                handle_event(event, &cli, &config, &config_path, &registry, &logger).await;
+                handled += 1;
+                if !cli.multi_event {
+                    break;
+                }
+            }
+            Input::Empty if handled > 0 => {
+                logger.log(&format!("Processed {} events, exiting", handled));
+                break;
+            }
+            Input::Empty => {
                 logger.log("No input received");
                 println!("{{}}");
-                return;
+                break;
             }
+            Input::TimedOut => {
+                logger.log(&format!("Timed out waiting for {} after {}ms", source, cli.stdin_timeout_ms));
+                if handled == 0 {
+                    println!("{{}}");
+                }
+                break;
+            }
+            Input::InvalidJson(e) => {
+                logger.log_kind(LogKind::Failure, &format!("Failed to parse event JSON: {}", e));
+                // Earlier events were already answered; trailing junk gets no response of its own
+                if handled == 0 {
+                    output_hook_error(cli.pretty, cli.fail_closed, &format!("Invalid JSON: {}", e));
+                }
+                break;
+            }
+            Input::ReadError(e) => {
+                logger.log_kind(LogKind::Failure, &format!("Error reading {}: {}", source, e));
+                output_hook_error(cli.pretty, cli.fail_closed, &format!("Error reading {}: {}", source, e));
+                break;
+            }
+        }
+    }
 
-            logger.log(&format!("Received event: {}", event_json.trim()));
+    // Explicitly exit to avoid hanging on background threads (rodio/tokio cleanup)
+    process::exit(0);
+}
 
-            // Parse the event to determine hook type
-            let event = match Event::from_json(&event_json) {
-                Ok(e) => e,
-                Err(e) => {
-                    logger.log(&format!("Failed to parse event JSON: {}", e));
-                    output_hook_error(&format!("Invalid JSON: {}", e));
+/// Runs one event through the handlers and prints the hook response.
+async fn handle_event(
+    mut event: Event,
+    cli: &Cli,
+    config: &Config,
+    config_path: &Path,
+    registry: &HandlerRegistry,
+    logger: &DebugLogger,
+) {
+    logger.log_kind(LogKind::Event, &format!("Received event: {}", event.as_value()));
+
+    // Create the appropriate hook type
+    let hook = match hook_from_event(&event) {
+        Ok(h) => {
+            logger.log(&format!("Hook type: {}", h.hook_type()));
+            h
+        }
+        Err(e) => {
+            // Newer Claude Code versions add hooks; an event we don't know is no
+            // reason to stop Claude, even with --fail-closed
+            logger.log_kind(LogKind::Failure, &format!("Unknown hook type: {}", e));
+            println!("{}", format_response(&json!({}), cli.pretty));
+            return;
+        }
+    };
+
+    // With fire_and_forget, a detached copy of boopifier runs the handlers
+    // and Claude Code gets its response without waiting for them
+    if config.fire_and_forget && !cli.background {
+        if config_path.as_os_str() == boopifier::config::STDIN_PATH {
+            logger.log("fire_and_forget needs a config file, not --config -; running handlers in the foreground");
+        } else {
+            match spawn_background_sender(cli, config_path, &event) {
+                Ok(pid) => {
+                    logger.log(&format!("Handed the event to background sender (pid {})", pid));
+                    let response = build_hook_response(hook.as_ref(), &[], config);
+                    println!("{}", format_response(&response, cli.pretty));
                     return;
                 }
-            };
+                Err(e) => logger.log_kind(
+                    LogKind::Failure,
+                    &format!("Failed to start background sender, running handlers in the foreground: {}", e),
+                ),
+            }
+        }
+    }
 
-            // Create the appropriate hook type
-            let hook = match hook_from_event(&event) {
-                Ok(h) => {
-                    logger.log(&format!("Hook type: {}", h.hook_type()));
-                    h
-                }
-                Err(e) => {
-                    logger.log(&format!("Unknown hook type: {}", e));
-                    output_hook_error(&format!("Unknown hook: {}", e));
-                    return;
+    // Give templates the session's previous event, and keep this one for the next run
+    if config.uses_previous_event() {
+        let previous_dir = boopifier::previous::default_dir();
+        event.context.previous = boopifier::previous::load_persisted(&previous_dir, &event);
+        if let Err(e) = boopifier::previous::persist(&previous_dir, &event) {
+            logger.log_kind(LogKind::Failure, &format!("Failed to save previous event: {}", e));
+        }
+    }
+
+    // Process the event through handlers
+    let options = ProcessOptions {
+        offline: cli.offline,
+        ..Default::default()
+    };
+    match dispatch_event_with_options(&event, config, registry, &options).await {
+        Ok(timed) => {
+            if cli.profile {
+                report_profile(&timed, logger);
+            }
+            for entry in &timed {
+                logger.log_kind(LogKind::Match, &format!("Matched handler: {}", entry.name));
+            }
+
+            // Record which handlers didn't notify, and why
+            for outcome in &timed {
+                if let HandlerOutcome::Suppressed { reason, message } = &outcome.outcome {
+                    logger.log(&format!("Handler suppressed ({}): {}", reason, message));
                 }
-            };
+            }
+            if let Some(summary) = suppression_summary(&timed) {
+                logger.log(&format!("Suppressed: {}", summary));
+            }
+            let outcomes: Vec<HandlerOutcome> = timed.into_iter().map(|t| t.outcome).collect();
 
-            // Process the event through handlers
-            match process_event(&event_json, &config, &registry).await {
-                Ok(outcomes) => {
-                    // Log handler outcomes
-                    let successes = outcomes.iter().filter(|o| matches!(o, HandlerOutcome::Success)).count();
-                    let errors = outcomes.iter().filter(|o| matches!(o, HandlerOutcome::Error(_))).count();
-
-                    if errors == 0 {
-                        logger.log(&format!("Event processed successfully ({} handlers)", successes));
-                    } else {
-                        logger.log(&format!("Event processed: {} succeeded, {} failed", successes, errors));
-                        for outcome in &outcomes {
-                            if let HandlerOutcome::Error(msg) = outcome {
-                                logger.log(&format!("Handler error: {}", msg));
-                            }
-                        }
-                    }
+            // Log handler outcomes
+            let successes = outcomes.iter().filter(|o| o.is_success()).count();
+            let errors = outcomes.iter().filter(|o| matches!(o, HandlerOutcome::Error(_))).count();
 
-                    // Generate hook-specific response
-                    let response = hook.generate_response(&outcomes);
-                    if let Ok(json_str) = serde_json::to_string(&response) {
-                        println!("{}", json_str);
+            if errors == 0 {
+                logger.log_kind(LogKind::Success, &format!("Event processed successfully ({} handlers)", successes));
+            } else {
+                logger.log_kind(LogKind::Failure, &format!("Event processed: {} succeeded, {} failed", successes, errors));
+                for outcome in &outcomes {
+                    if let HandlerOutcome::Error(msg) = outcome {
+                        logger.log_kind(LogKind::Failure, &format!("Handler error: {}", msg));
                     }
                 }
-                Err(e) => {
-                    logger.log(&format!("Error processing event: {}", e));
-                    // Still output a valid response (empty object)
-                    println!("{{}}");
-                }
             }
 
-            logger.log("Event processed, exiting");
+            // Generate hook-specific response
+            let response = build_hook_response(hook.as_ref(), &outcomes, config);
+            println!("{}", format_response(&response, cli.pretty));
         }
         Err(e) => {
-            logger.log(&format!("Error reading stdin: {}", e));
-            output_hook_error(&format!("Error reading stdin: {}", e));
+            logger.log_kind(LogKind::Failure, &format!("Error processing event: {}", e));
+            // Still output a valid response (empty object)
+            println!("{{}}");
         }
     }
 
-    // Explicitly exit to avoid hanging on background threads (rodio/tokio cleanup)
-    process::exit(0);
+    logger.log("Event processed");
 }
 
+/// Config file names looked for in a `.claude` directory, in order of preference.
+const CONFIG_FILE_NAMES: [&str; 4] = ["boopifier.json", "boopifier.yaml", "boopifier.yml", "boopifier.toml"];
+
 /// Resolve the config file path using Claude Code conventions.
 ///
 /// Resolution order:
 /// 1. $CLAUDE_PROJECT_DIR/.claude/boopifier.json (if CLAUDE_PROJECT_DIR is set and file exists)
 /// 2. ~/.claude/boopifier.json (global fallback, may include path-based overrides)
 ///
+/// In either directory a `boopifier.yaml`, `boopifier.yml` or `boopifier.toml`
+/// is used when there is no `boopifier.json`.
+///
 /// Note: When using the global config, project-specific overrides will be applied
 /// based on glob pattern matching against $CLAUDE_PROJECT_DIR.
 fn resolve_config_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    resolve_config_path_from(std::env::var("CLAUDE_PROJECT_DIR").ok().as_deref(), &home)
+}
+
+/// Like [`resolve_config_path`], for given `CLAUDE_PROJECT_DIR` and `HOME` values.
+fn resolve_config_path_from(project_dir: Option<&str>, home: &str) -> PathBuf {
     // Try project-specific config if CLAUDE_PROJECT_DIR is set
-    if let Ok(project_dir) = std::env::var("CLAUDE_PROJECT_DIR") {
-        let project_config = PathBuf::from(project_dir).join(".claude/boopifier.json");
-        if project_config.exists() {
+    if let Some(project_dir) = project_dir {
+        if let Some(project_config) = find_config(&PathBuf::from(project_dir).join(".claude")) {
             return project_config;
         }
     }
 
     // Fall back to global config
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home).join(".claude/boopifier.json")
+    let global_dir = PathBuf::from(home).join(".claude");
+    find_config(&global_dir).unwrap_or_else(|| global_dir.join(CONFIG_FILE_NAMES[0]))
+}
+
+/// The existing config file in `dir` that is preferred by [`CONFIG_FILE_NAMES`].
+fn find_config(dir: &Path) -> Option<PathBuf> {
+    CONFIG_FILE_NAMES.iter().map(|name| dir.join(name)).find(|path| path.exists())
+}
+
+/// Project directory whose overrides apply to the global config.
+///
+/// `None` unless `CLAUDE_PROJECT_DIR` is set and the project has no config of its own.
+fn override_project_dir() -> Option<String> {
+    override_project_dir_from(std::env::var("CLAUDE_PROJECT_DIR").ok())
+}
+
+/// Like [`override_project_dir`], for a given `CLAUDE_PROJECT_DIR` value.
+///
+/// A directory that doesn't exist (e.g. a stale `CLAUDE_PROJECT_DIR`) gets no
+/// overrides, so it can't match a pattern by its path alone.
+fn override_project_dir_from(project_dir: Option<String>) -> Option<String> {
+    let project_dir = project_dir?;
+    let path = PathBuf::from(&project_dir);
+    (path.is_dir() && find_config(&path.join(".claude")).is_none()).then_some(project_dir)
+}
+
+/// `CLAUDE_PROJECT_DIR`, if it is set but not an existing directory.
+fn missing_project_dir() -> Option<String> {
+    std::env::var("CLAUDE_PROJECT_DIR")
+        .ok()
+        .filter(|dir| !PathBuf::from(dir).is_dir())
+}
+
+/// Print handler timings, slowest first, to stderr and the debug log.
+fn report_profile(timed: &[TimedOutcome], logger: &DebugLogger) {
+    let mut sorted: Vec<&TimedOutcome> = timed.iter().collect();
+    sorted.sort_by_key(|entry| std::cmp::Reverse(entry.duration));
+
+    eprintln!("Handler timings ({} handlers):", sorted.len());
+    for entry in sorted {
+        let status = match &entry.outcome {
+            HandlerOutcome::Error(_) => "error".to_string(),
+            HandlerOutcome::Suppressed { reason, .. } => format!("suppressed: {}", reason),
+            _ => "ok".to_string(),
+        };
+        let line = format!("{:>10.3}ms  {} ({})", entry.duration.as_secs_f64() * 1000.0, entry.name, status);
+        eprintln!("  {}", line);
+        logger.log(&format!("Profile: {}", line.trim_start()));
+    }
+}
+
+/// What was read from stdin.
+enum Input {
+    /// A parsed event
+    Event(Event),
+    /// Nothing but whitespace before EOF
+    Empty,
+    /// Input that is not a valid event
+    InvalidJson(String),
+    /// Reading stdin failed
+    ReadError(io::Error),
+    /// No complete event arrived within the timeout
+    TimedOut,
+}
+
+/// Reads and parses the next event, blocking until EOF or a complete JSON value.
+///
+/// With `single`, the input must hold just this event. Only bytes already read
+/// are checked for anything after it, so the hook is answered without waiting for EOF.
+fn read_input<R: io::Read>(reader: &mut io::BufReader<R>, single: bool) -> Input {
+    match has_input(reader) {
+        Ok(true) => match Event::next_from_reader(&mut *reader) {
+            Ok(_) if single && !reader.buffer().iter().all(u8::is_ascii_whitespace) => {
+                Input::InvalidJson("trailing characters after the event".to_string())
+            }
+            Ok(event) => Input::Event(event),
+            Err(e) => Input::InvalidJson(e.to_string()),
+        },
+        Ok(false) => Input::Empty,
+        Err(e) => Input::ReadError(e),
+    }
+}
+
+/// Events read on a separate thread, so a stalled pipe can't hang the hook.
+///
+/// A plain thread is used rather than `spawn_blocking` because a read that never
+/// returns would otherwise keep the runtime from shutting down.
+struct InputStream {
+    receiver: tokio::sync::mpsc::UnboundedReceiver<Input>,
+}
+
+impl InputStream {
+    /// Starts reading events from `reader`, one after another, until EOF or an error.
+    ///
+    /// With `single`, reading stops after the first event.
+    fn spawn<R>(reader: R, single: bool) -> Self
+    where
+        R: io::Read + Send + 'static,
+    {
+        let (tx, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut reader = io::BufReader::new(reader);
+        std::thread::spawn(move || loop {
+            let input = read_input(&mut reader, single);
+            let more = !single && matches!(input, Input::Event(_));
+            if tx.send(input).is_err() || !more {
+                break;
+            }
+        });
+        Self { receiver }
+    }
+
+    /// A stream whose only input is `error`.
+    fn failed(error: io::Error) -> Self {
+        let (tx, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let _ = tx.send(Input::ReadError(error));
+        Self { receiver }
+    }
+
+    /// Waits for the next input; after the last event this is [`Input::Empty`].
+    async fn next(&mut self, timeout: Option<Duration>) -> Input {
+        let received = match timeout {
+            Some(limit) => match tokio::time::timeout(limit, self.receiver.recv()).await {
+                Ok(received) => received,
+                Err(_) => return Input::TimedOut,
+            },
+            None => self.receiver.recv().await,
+        };
+        received.unwrap_or_else(|| Input::ReadError(io::Error::other("stdin reader thread exited")))
+    }
+}
+
+/// Skip leading whitespace on stdin and report whether any input remains.
+fn has_input<R: BufRead>(reader: &mut R) -> io::Result<bool> {
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(false);
+        }
+
+        let whitespace = buf.iter().take_while(|b| b.is_ascii_whitespace()).count();
+        if whitespace < buf.len() {
+            return Ok(true);
+        }
+        reader.consume(whitespace);
+    }
+}
+
+/// Build and runtime details for bug reports (`--version-json`).
+fn version_info(registry: &HandlerRegistry) -> serde_json::Value {
+    let mut features = Vec::new();
+    if cfg!(feature = "aws") {
+        features.push("aws");
+    }
+    if cfg!(feature = "keyring") {
+        features.push("keyring");
+    }
+    if cfg!(feature = "card") {
+        features.push("card");
+    }
+    if cfg!(feature = "expr") {
+        features.push("expr");
+    }
+    if cfg!(feature = "server") {
+        features.push("server");
+    }
+    if cfg!(feature = "testing") {
+        features.push("testing");
+    }
+
+    let mut handlers = registry.list_types();
+    handlers.sort_unstable();
+
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_commit": env!("BOOPIFIER_GIT_COMMIT"),
+        "features": features,
+        "handlers": handlers,
+    })
 }
 
 fn list_available_handlers() {
@@ -254,14 +800,1063 @@ fn list_available_handlers() {
     }
 }
 
-/// Output error hook response in Claude Code format (still continues)
-fn output_hook_error(error_message: &str) {
-    let response = json!({
-        "continue": true,
-        "systemMessage": format!("Boopifier warning: {}", error_message)
+/// Describes every registered handler type for tooling such as config editors.
+///
+/// Lists each type's required and optional config keys, whether it needs the
+/// network, and the hooks it can answer interactively, sorted by type.
+fn handler_catalog(registry: &HandlerRegistry) -> serde_json::Value {
+    let mut types = registry.list_types();
+    types.sort_unstable();
+
+    let handlers: Vec<_> = types
+        .into_iter()
+        .filter_map(|handler_type| {
+            let handler = registry.get(handler_type)?;
+            let interactive_hooks: Vec<&str> =
+                HOOK_TYPES.iter().copied().filter(|hook| handler.supports_hook(hook)).collect();
+            Some(json!({
+                "type": handler_type,
+                "required_keys": handler.required_keys(),
+                "optional_keys": handler.optional_keys(),
+                "requires_network": handler.requires_network(),
+                "interactive_hooks": interactive_hooks,
+            }))
+        })
+        .collect();
+    json!({ "handlers": handlers })
+}
+
+fn show_missed(file: Option<&std::path::Path>, clear: bool) {
+    use boopifier::handlers::desktop::{clear_missed, default_missed_path, read_missed};
+
+    let path = file.map(PathBuf::from).unwrap_or_else(default_missed_path);
+
+    match read_missed(&path) {
+        Ok(entries) if entries.is_empty() => println!("No missed notifications."),
+        Ok(entries) => {
+            println!("Missed notifications ({}):", entries.len());
+            for entry in &entries {
+                println!("  [{}] {}: {}", entry.timestamp, entry.summary, entry.body);
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to read {:?}: {}", path, e);
+            process::exit(1);
+        }
+    }
+
+    if clear {
+        if let Err(e) = clear_missed(&path) {
+            eprintln!("Failed to clear {:?}: {}", path, e);
+            process::exit(1);
+        }
+        println!("Cleared.");
+    }
+}
+
+fn ack_badge(file: Option<&std::path::Path>) {
+    use boopifier::handlers::badge::{default_badge_path, read_badge, reset_badge};
+
+    let path = file.map(PathBuf::from).unwrap_or_else(default_badge_path);
+    let pending = read_badge(&path);
+
+    if let Err(e) = reset_badge(&path) {
+        eprintln!("Failed to reset {:?}: {}", path, e);
+        process::exit(1);
+    }
+    println!("Acknowledged {} pending alert(s).", pending);
+}
+
+/// Checks the config and every configured handler, printing a pass/warn/fail report.
+///
+/// Returns false if any check failed.
+async fn run_doctor(config_path: &std::path::Path, registry: &HandlerRegistry) -> bool {
+    let mut sections = Vec::new();
+
+    let mut config_checks = Vec::new();
+    let config = match Config::load_lenient(config_path) {
+        Ok((mut config, errors)) => {
+            if let Some(project_dir) = override_project_dir() {
+                config.apply_overrides(&project_dir);
+            }
+            config_checks.push(Diagnostic::pass(format!(
+                "loaded {} handler(s) from {}",
+                config.handlers.len(),
+                config_path.display()
+            )));
+            config_checks.extend(errors.iter().map(|e| Diagnostic::fail(format!("invalid entry: {}", e))));
+            Some(config)
+        }
+        Err(e) => {
+            config_checks.push(Diagnostic::fail(format!("cannot load {}: {}", config_path.display(), e)));
+            None
+        }
+    };
+    sections.push(("config".to_string(), config_checks));
+
+    if let Some(config) = &config {
+        sections.extend(diagnose_handlers(config, registry).await);
+    }
+
+    let report = format_doctor_report(&sections);
+    print!("{}", report);
+    !sections.iter().flat_map(|(_, checks)| checks).any(|d| d.status == DiagnosticStatus::Fail)
+}
+
+/// Runs every configured handler's diagnostics concurrently, ignoring match rules.
+async fn diagnose_handlers(config: &Config, registry: &HandlerRegistry) -> Vec<(String, Vec<Diagnostic>)> {
+    let handler_configs = config.handlers.iter().chain(config.on_no_match.as_ref());
+    let checks = handler_configs.map(|hc| async move {
+        let title = format!("{} ({})", hc.name, hc.handler_type);
+        let diagnostics = match registry.get(&hc.handler_type) {
+            Some(handler) => handler.diagnose(&hc.config).await,
+            None => vec![Diagnostic::fail(format!("unknown handler type: {}", hc.handler_type))],
+        };
+        (title, diagnostics)
     });
+    futures::future::join_all(checks).await
+}
+
+/// Formats doctor results as indented sections followed by a summary line.
+fn format_doctor_report(sections: &[(String, Vec<Diagnostic>)]) -> String {
+    let mut report = String::new();
+    let mut counts = [0; 3];
+
+    for (title, diagnostics) in sections {
+        report.push_str(&format!("{}\n", title));
+        if diagnostics.is_empty() {
+            report.push_str("  [----] no checks for this handler type\n");
+        }
+        for diagnostic in diagnostics {
+            let (label, slot) = match diagnostic.status {
+                DiagnosticStatus::Pass => ("pass", 0),
+                DiagnosticStatus::Warn => ("warn", 1),
+                DiagnosticStatus::Fail => ("FAIL", 2),
+            };
+            counts[slot] += 1;
+            report.push_str(&format!("  [{}] {}\n", label, diagnostic.message));
+        }
+    }
+
+    report.push_str(&format!(
+        "\n{} passed, {} warning(s), {} failed\n",
+        counts[0], counts[1], counts[2]
+    ));
+    report
+}
+
+/// Checks every configured handler for an unknown type or missing settings, printing a report.
+///
+/// Returns false if the config could not be loaded or has any problem.
+fn run_validate(config_path: &Path, registry: &HandlerRegistry) -> bool {
+    let (config, mut problems) = match Config::load_lenient(config_path) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            println!("{}: cannot load config: {}", config_path.display(), e);
+            return false;
+        }
+    };
+    problems.extend(validate_handlers(&config, registry));
+
+    print!("{}", format_validation_report(config_path, &problems));
+    problems.is_empty()
+}
+
+/// Validates the handlers, the override handlers and `on_no_match` against the registry.
+///
+/// Unlike doctor, every override is checked, whatever the current project.
+/// Handlers are named rather than indexed, since lenient loading may have
+/// dropped entries before them. Each variant is checked with its config merged
+/// over the base config, as it runs.
+fn validate_handlers(config: &Config, registry: &HandlerRegistry) -> Vec<ConfigError> {
+    let mut handlers: Vec<(Option<String>, &HandlerConfig)> = config.handlers.iter().map(|hc| (None, hc)).collect();
+    for (i, project_override) in config.overrides.iter().flatten().enumerate() {
+        handlers.extend(project_override.handlers.iter().map(|hc| (Some(format!("overrides[{}]", i)), hc)));
+    }
+    handlers.extend(config.on_no_match.iter().map(|hc| (Some("on_no_match".to_string()), hc)));
+
+    let mut types = registry.list_types();
+    types.sort_unstable();
+
+    let mut problems = Vec::new();
+    for (location, hc) in handlers {
+        let handler = format!("{} ({})", hc.name, hc.handler_type);
+        let location = match location {
+            Some(location) => format!("{} in {}", handler, location),
+            None => handler,
+        };
+        let Some(handler) = registry.get(&hc.handler_type) else {
+            problems.push(ConfigError {
+                location,
+                message: format!("unknown handler type '{}' (available: {})", hc.handler_type, types.join(", ")),
+            });
+            continue;
+        };
+
+        if let Err(e) = handler.validate(&hc.config) {
+            problems.push(ConfigError {
+                location: location.clone(),
+                message: e.to_string(),
+            });
+        }
+        // Each variant runs with its config merged over the base config
+        for (i, variant) in hc.variants.iter().enumerate() {
+            let mut config = hc.config.clone();
+            config.extend(variant.config.clone());
+            if let Err(e) = handler.validate(&config) {
+                problems.push(ConfigError {
+                    location: format!("{} variants[{}]", location, i),
+                    message: e.to_string(),
+                });
+            }
+        }
+    }
+    problems
+}
+
+/// Formats `--validate` results as a summary line followed by one line per problem.
+fn format_validation_report(config_path: &Path, problems: &[ConfigError]) -> String {
+    if problems.is_empty() {
+        return format!("{}: OK\n", config_path.display());
+    }
+
+    let mut report = format!("{}: {} problem(s)\n", config_path.display(), problems.len());
+    for problem in problems {
+        report.push_str(&format!("  {}\n", problem));
+    }
+    report
+}
+
+/// Sends a synthetic event through every configured handler and prints the outcomes.
+///
+/// Returns false if the config could not be loaded or any handler failed.
+async fn run_test_all(config_path: &std::path::Path, registry: &HandlerRegistry) -> bool {
+    let mut config = match Config::load(config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Failed to load config from {}: {}", config_path.display(), e);
+            return false;
+        }
+    };
+    if let Some(project_dir) = override_project_dir() {
+        config.apply_overrides(&project_dir);
+    }
+
+    let options = ProcessOptions {
+        ignore_match: true,
+        ..Default::default()
+    };
+    let timed = match dispatch_event_with_options(&test_event(), &config, registry, &options).await {
+        Ok(timed) => timed,
+        Err(e) => {
+            eprintln!("Failed to dispatch test event: {}", e);
+            return false;
+        }
+    };
+
+    print!("{}", format_outcome_table(&timed));
+    !timed.iter().any(|t| matches!(t.outcome, HandlerOutcome::Error(_)))
+}
+
+/// Replays a log of events through the config, printing what fired for each.
+///
+/// Returns false if the config or log could not be read, or any line or handler failed.
+async fn run_replay(
+    config_path: &std::path::Path,
+    log_path: &std::path::Path,
+    dry_run: bool,
+    registry: &HandlerRegistry,
+) -> bool {
+    let mut config = match Config::load(config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Failed to load config from {}: {}", config_path.display(), e);
+            return false;
+        }
+    };
+    if let Some(project_dir) = override_project_dir() {
+        config.apply_overrides(&project_dir);
+    }
+
+    let file = match std::fs::File::open(log_path) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("Failed to open {}: {}", log_path.display(), e);
+            return false;
+        }
+    };
+
+    let (report, all_ok) = replay(io::BufReader::new(file), &config, registry, dry_run).await;
+    print!("{}", report);
+    all_ok
+}
+
+/// Replays each event in `reader` (one JSON object per line) and formats a report.
+///
+/// In a dry run only the would-fire handlers are listed; otherwise they run
+/// and their outcomes are shown. Blank lines are skipped.
+async fn replay<R: BufRead>(reader: R, config: &Config, registry: &HandlerRegistry, dry_run: bool) -> (String, bool) {
+    let mut report = String::new();
+    let mut all_ok = true;
+    let (mut events, mut fired) = (0, 0);
+
+    for (index, line) in reader.lines().enumerate() {
+        let line_no = index + 1;
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                report.push_str(&format!("line {}: read error: {}\n", line_no, e));
+                all_ok = false;
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let event = match replay_event(&line) {
+            Ok(event) => event,
+            Err(e) => {
+                report.push_str(&format!("line {}: invalid event: {}\n", line_no, e));
+                all_ok = false;
+                continue;
+            }
+        };
+        events += 1;
+        let hook = event.hook_name().unwrap_or("unknown");
+
+        if dry_run {
+            match matching_handlers(&event, config, &ProcessOptions::default()) {
+                Ok(names) if names.is_empty() => report.push_str(&format!("line {}: {} -> (none)\n", line_no, hook)),
+                Ok(names) => {
+                    fired += 1;
+                    report.push_str(&format!("line {}: {} -> {}\n", line_no, hook, names.join(", ")));
+                }
+                Err(e) => {
+                    all_ok = false;
+                    report.push_str(&format!("line {}: {} -> error: {}\n", line_no, hook, e));
+                }
+            }
+            continue;
+        }
+
+        report.push_str(&format!("line {}: {}\n", line_no, hook));
+        match dispatch_event_timed(&event, config, registry).await {
+            Ok(timed) => {
+                all_ok &= !timed.iter().any(|t| matches!(t.outcome, HandlerOutcome::Error(_)));
+                if timed.is_empty() {
+                    report.push_str("  (none)\n");
+                    continue;
+                }
+                fired += 1;
+                for row in format_outcome_table(&timed).lines() {
+                    report.push_str(&format!("  {}\n", row));
+                }
+            }
+            Err(e) => {
+                all_ok = false;
+                report.push_str(&format!("  error: {}\n", e));
+            }
+        }
+    }
+
+    report.push_str(&format!(
+        "\n{} event(s) replayed{}, {} fired at least one handler\n",
+        events,
+        if dry_run { " (dry run)" } else { "" },
+        fired
+    ));
+    (report, all_ok)
+}
+
+/// Parses a replayed line, unwrapping an `event` field if the line records one.
+fn replay_event(line: &str) -> anyhow::Result<Event> {
+    let value: serde_json::Value = serde_json::from_str(line)?;
+    let event = match value.get("event") {
+        Some(inner) if inner.is_object() => inner.clone(),
+        _ => value,
+    };
+    Ok(serde_json::from_value(event)?)
+}
+
+/// Runs `boopifier serve` until it receives Ctrl-C or SIGTERM.
+#[cfg(feature = "server")]
+async fn run_serve(
+    config_path: &std::path::Path,
+    addr: std::net::SocketAddr,
+    token: Option<String>,
+    socket: Option<PathBuf>,
+    pretty: bool,
+) -> anyhow::Result<()> {
+    let token = token.filter(|t| !t.is_empty());
+    if token.is_none() && !addr.ip().is_loopback() {
+        anyhow::bail!(
+            "refusing to serve on {} without a token; pass --token or set {}",
+            addr,
+            boopifier::server::TOKEN_ENV
+        );
+    }
+
+    let project_dir = override_project_dir();
+    let mut config = Config::load(config_path)
+        .map_err(|e| anyhow::anyhow!("failed to load config from {:?}: {}", config_path, e))?;
+    if let Some(project_dir) = &project_dir {
+        config.apply_overrides(project_dir);
+    }
+
+    boopifier::set_long_running(true);
+    let mut server = boopifier::server::Server::new(config, HandlerRegistry::new())
+        .with_token(token)
+        .with_pretty(pretty)
+        .with_reload(config_path, project_dir);
+    #[cfg(unix)]
+    if let Some(socket) = &socket {
+        server = server
+            .with_socket(socket)
+            .map_err(|e| anyhow::anyhow!("failed to bind {}: {}", socket.display(), e))?;
+        eprintln!("boopifier: accepting events on {}", socket.display());
+    }
+    #[cfg(not(unix))]
+    let _ = socket;
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    eprintln!("boopifier: serving on http://{}/event", listener.local_addr()?);
+    server.serve(listener, shutdown_signal()).await?;
+    eprintln!("boopifier: stopped");
+    Ok(())
+}
+
+/// Completes on Ctrl-C or, on Unix, SIGTERM.
+#[cfg(feature = "server")]
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+            return;
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// The synthetic event sent by `test-all`.
+fn test_event() -> Event {
+    let cwd = std::env::current_dir().map(|d| d.display().to_string()).unwrap_or_default();
+    let event = json!({
+        "hook_event_name": "Notification",
+        "message": "Test notification from boopifier",
+        "session_id": "boopifier-test-all",
+        "cwd": cwd,
+    });
+    serde_json::from_value(event).expect("test event is a JSON object")
+}
+
+/// One line naming each suppressed handler with its reason, e.g. `heartbeat (cooldown), push (channel_group)`.
+///
+/// Returns `None` if no handler was suppressed.
+fn suppression_summary(timed: &[TimedOutcome]) -> Option<String> {
+    let entries: Vec<String> = timed
+        .iter()
+        .filter_map(|entry| match &entry.outcome {
+            HandlerOutcome::Suppressed { reason, .. } => Some(format!("{} ({})", entry.name, reason)),
+            _ => None,
+        })
+        .collect();
+    (!entries.is_empty()).then(|| entries.join(", "))
+}
+
+/// Formats handler outcomes as a table, in config order.
+fn format_outcome_table(timed: &[TimedOutcome]) -> String {
+    if timed.is_empty() {
+        return "No handlers configured.\n".to_string();
+    }
+
+    let width = timed.iter().map(|t| t.name.len()).max().unwrap_or(0).max("HANDLER".len());
+    let mut table = format!("{:<width$}  {:>10}  RESULT\n", "HANDLER", "TIME", width = width);
+    for entry in timed {
+        let result = match entry.outcome.unwrapped() {
+            HandlerOutcome::Success
+            | HandlerOutcome::SystemMessage(_)
+            | HandlerOutcome::Interactive(_)
+            | HandlerOutcome::SuppressOutput(_)
+            | HandlerOutcome::AdditionalContext(_) => "ok".to_string(),
+            HandlerOutcome::Block(reason) => format!("blocked: {}", reason),
+            HandlerOutcome::Suppressed { reason, message } => format!("suppressed ({}): {}", reason, message),
+            HandlerOutcome::Error(message) => format!("error: {}", message),
+        };
+        table.push_str(&format!(
+            "{:<width$}  {:>8.1}ms  {}\n",
+            entry.name,
+            entry.duration.as_secs_f64() * 1000.0,
+            result,
+            width = width
+        ));
+    }
+    table
+}
+
+/// Build the error hook response in Claude Code format.
+///
+/// Claude continues by default; with `fail_closed` it is stopped and shown the reason.
+fn hook_error_response(fail_closed: bool, error_message: &str) -> serde_json::Value {
+    if fail_closed {
+        let message = format!("Boopifier error: {}", error_message);
+        json!({
+            "continue": false,
+            "stopReason": message,
+            "systemMessage": message
+        })
+    } else {
+        json!({
+            "continue": true,
+            "systemMessage": format!("Boopifier warning: {}", error_message)
+        })
+    }
+}
+
+/// Arguments for the detached sender that runs an event's handlers for `fire_and_forget`.
+///
+/// The sender reads the event from stdin and loads the same config with the
+/// same handler-affecting flags; its output is discarded.
+fn background_args(cli: &Cli, config_path: &Path) -> Vec<OsString> {
+    let mut args: Vec<OsString> = vec!["--background".into(), "--config".into(), config_path.into()];
+    for (enabled, flag) in [
+        (cli.debug || cli.debug_stderr, "--debug"),
+        (cli.lenient, "--lenient"),
+        (cli.offline, "--offline"),
+    ] {
+        if enabled {
+            args.push(flag.into());
+        }
+    }
+    args
+}
+
+/// Starts a detached copy of boopifier that runs the handlers for `event`.
+///
+/// The sender gets its own process group, so it keeps running after this
+/// process exits and isn't killed along with the hook. Returns its pid.
+fn spawn_background_sender(cli: &Cli, config_path: &Path, event: &Event) -> io::Result<u32> {
+    let mut command = std::process::Command::new(std::env::current_exe()?);
+    command
+        .args(background_args(cli, config_path))
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+
+    let mut child = command.spawn()?;
+    let mut stdin = child.stdin.take().expect("sender stdin is piped");
+    writeln!(stdin, "{}", event.as_value())?;
+    Ok(child.id())
+}
+
+/// Output error hook response in Claude Code format
+fn output_hook_error(pretty: bool, fail_closed: bool, error_message: &str) {
+    println!("{}", format_response(&hook_error_response(fail_closed, error_message), pretty));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    /// Reader that produces no data until well after the test's timeout.
+    struct StalledReader;
+
+    impl Read for StalledReader {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            std::thread::sleep(Duration::from_secs(2));
+            Ok(0)
+        }
+    }
+
+    #[test]
+    fn test_override_project_dir_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().display().to_string();
+        assert_eq!(override_project_dir_from(Some(project.clone())), Some(project.clone()));
+
+        // A stale CLAUDE_PROJECT_DIR gets no overrides, but the global config still loads
+        let missing = dir.path().join("deleted-project").display().to_string();
+        assert_eq!(override_project_dir_from(Some(missing.clone())), None);
+        assert_eq!(override_project_dir_from(None), None);
+        assert_eq!(
+            resolve_config_path_from(Some(&missing), "/home/user"),
+            PathBuf::from("/home/user/.claude/boopifier.json")
+        );
+
+        // Projects with their own config don't use the global overrides
+        std::fs::create_dir(dir.path().join(".claude")).unwrap();
+        std::fs::write(dir.path().join(".claude/boopifier.json"), "{}").unwrap();
+        assert_eq!(override_project_dir_from(Some(project.clone())), None);
+        assert_eq!(
+            resolve_config_path_from(Some(&project), "/home/user"),
+            dir.path().join(".claude/boopifier.json")
+        );
+    }
+
+    #[test]
+    fn test_resolve_yaml_and_toml_configs() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        let home = dir.path().join("home");
+        std::fs::create_dir_all(project.join(".claude")).unwrap();
+        std::fs::create_dir_all(home.join(".claude")).unwrap();
+        let project_str = project.display().to_string();
+        let home_str = home.display().to_string();
+
+        std::fs::write(home.join(".claude/boopifier.toml"), "handlers = []").unwrap();
+        assert_eq!(resolve_config_path_from(None, &home_str), home.join(".claude/boopifier.toml"));
+        assert_eq!(
+            resolve_config_path_from(Some(&project_str), &home_str),
+            home.join(".claude/boopifier.toml")
+        );
+
+        std::fs::write(project.join(".claude/boopifier.yml"), "handlers: []").unwrap();
+        assert_eq!(
+            resolve_config_path_from(Some(&project_str), &home_str),
+            project.join(".claude/boopifier.yml")
+        );
+        assert_eq!(override_project_dir_from(Some(project_str.clone())), None);
+
+        // JSON wins when a directory has more than one
+        std::fs::write(project.join(".claude/boopifier.json"), "{}").unwrap();
+        assert_eq!(
+            resolve_config_path_from(Some(&project_str), &home_str),
+            project.join(".claude/boopifier.json")
+        );
+    }
+
+    #[test]
+    fn test_handler_catalog() {
+        let registry = HandlerRegistry::new();
+        let catalog = handler_catalog(&registry);
+        let handlers = catalog["handlers"].as_array().unwrap();
+
+        let mut types = registry.list_types();
+        types.sort_unstable();
+        let listed: Vec<&str> = handlers.iter().map(|h| h["type"].as_str().unwrap()).collect();
+        assert_eq!(listed, types);
+        for builtin in ["badge", "desktop", "email", "forward", "heartbeat", "jira", "noop", "ntfy", "signal", "sound"] {
+            assert!(listed.contains(&builtin), "{} missing", builtin);
+        }
+
+        let entry = |handler_type: &str| handlers.iter().find(|h| h["type"] == handler_type).unwrap().clone();
+        assert_eq!(
+            entry("email"),
+            json!({
+                "type": "email",
+                "required_keys": ["to", "from", "smtp_server"],
+                "optional_keys": [
+                    "smtp_port", "username", "password", "subject", "body", "body_file", "templates", "lang",
+                    "attachments", "routes"
+                ],
+                "requires_network": true,
+                "interactive_hooks": [],
+            })
+        );
+        assert_eq!(entry("webhook")["required_keys"], json!(["url"]));
+        assert_eq!(entry("desktop")["interactive_hooks"], json!(["PreToolUse"]));
+        assert_eq!(entry("desktop")["requires_network"], false);
+        assert_eq!(entry("noop")["optional_keys"], json!([]));
+    }
+
+    #[test]
+    fn test_version_info() {
+        let info = version_info(&HandlerRegistry::new());
+        assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+        assert!(info["git_commit"].as_str().is_some_and(|c| !c.is_empty()));
+
+        let handlers: Vec<&str> = info["handlers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|h| h.as_str())
+            .collect();
+        assert!(handlers.contains(&"desktop"));
+        assert!(handlers.contains(&"webhook"));
+        assert_eq!(handlers.contains(&"sns"), cfg!(feature = "aws"));
+        assert!(handlers.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        let features = info["features"].as_array().unwrap();
+        assert_eq!(features.contains(&json!("testing")), cfg!(feature = "testing"));
+        assert_eq!(features.contains(&json!("server")), cfg!(feature = "server"));
+    }
+
+    /// Handler that reports a fixed set of diagnostics.
+    struct Checked;
+
+    #[async_trait::async_trait]
+    impl boopifier::handlers::Handler for Checked {
+        fn handler_type(&self) -> &str {
+            "checked"
+        }
+
+        async fn handle(
+            &self,
+            _event: &Event,
+            _config: &std::collections::HashMap<String, serde_json::Value>,
+        ) -> boopifier::Result<()> {
+            Ok(())
+        }
+
+        async fn diagnose(&self, config: &std::collections::HashMap<String, serde_json::Value>) -> Vec<Diagnostic> {
+            match config.get("ok").and_then(|v| v.as_bool()) {
+                Some(true) => vec![Diagnostic::pass("all good")],
+                _ => vec![Diagnostic::warn("looks odd"), Diagnostic::fail("broken")],
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_doctor_checks_every_handler() {
+        let config = Config::from_json(
+            r#"{
+                "handlers": [
+                    {"name": "good", "type": "checked", "match_rules": {"hook_event_name": "Never"}, "config": {"ok": true}},
+                    {"name": "bad", "type": "checked", "config": {}},
+                    {"name": "log", "type": "worklog", "config": {}},
+                    {"name": "typo", "type": "deskop", "config": {}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let mut registry = HandlerRegistry::new();
+        registry.register(Box::new(Checked));
+
+        let sections = diagnose_handlers(&config, &registry).await;
+        let report = format_doctor_report(&sections);
+        assert_eq!(
+            report,
+            "good (checked)\n  [pass] all good\n\
+             bad (checked)\n  [warn] looks odd\n  [FAIL] broken\n\
+             log (worklog)\n  [----] no checks for this handler type\n\
+             typo (deskop)\n  [FAIL] unknown handler type: deskop\n\
+             \n1 passed, 1 warning(s), 2 failed\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_doctor_reports_invalid_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("boopifier.json");
+        std::fs::write(&path, r#"{"handlers": [{"name": "x"}]}"#).unwrap();
+        assert!(!run_doctor(&path, &HandlerRegistry::new()).await);
+
+        assert!(!run_doctor(&dir.path().join("missing.json"), &HandlerRegistry::new()).await);
+
+        std::fs::write(&path, r#"{"handlers": []}"#).unwrap();
+        assert!(run_doctor(&path, &HandlerRegistry::new()).await);
+    }
+
+    #[test]
+    fn test_validate_handlers() {
+        let registry = HandlerRegistry::new();
+        let config = Config::from_json(
+            r#"{
+                "handlers": [
+                    {"name": "done", "type": "desktop", "config": {}},
+                    {"name": "slack", "type": "webhook", "config": {"url": "https://hooks.slack.com/x", "type": "slack"}},
+                    {"name": "per-session", "type": "webhook", "config": {"url": "https://example.com/{{session_id}}"}},
+                    {"name": "mail", "type": "email", "config": {"to": "a@example.com", "from": "b@example.com", "smtp_server": "mail.local"}}
+                ],
+                "on_no_match": {"name": "chime", "type": "sound", "config": {"file": "~/chime.wav"}}
+            }"#,
+        )
+        .unwrap();
+        assert!(validate_handlers(&config, &registry).is_empty());
+
+        let config = Config::from_json(
+            r#"{
+                "handlers": [
+                    {"name": "typo", "type": "deskop", "config": {}},
+                    {"name": "hook", "type": "webhook", "config": {}},
+                    {"name": "relative", "type": "webhook", "config": {"url": "/hook"}},
+                    {"name": "mail", "type": "email", "config": {"to": "a@example.com", "from": "b@example.com"}}
+                ],
+                "overrides": [
+                    {"path_pattern": "/work/*", "handlers": [{"name": "mail", "type": "email", "config": {"smtp_server": "x"}}]}
+                ],
+                "on_no_match": {"name": "chime", "type": "sound", "config": {}}
+            }"#,
+        )
+        .unwrap();
+        let problems: Vec<String> = validate_handlers(&config, &registry).iter().map(|p| p.to_string()).collect();
+        assert_eq!(problems.len(), 6, "{:#?}", problems);
+        assert!(problems[0].starts_with("typo (deskop): unknown handler type 'deskop' (available: "));
+        assert!(problems[0].contains("desktop, "));
+        assert!(problems[1].starts_with("hook (webhook): ") && problems[1].contains("requires 'url'"));
+        assert!(problems[2].contains("Invalid webhook URL '/hook'"));
+        assert!(problems[3].starts_with("mail (email): ") && problems[3].contains("requires 'smtp_server'"));
+        assert!(problems[4].starts_with("mail (email) in overrides[0]: ") && problems[4].contains("requires 'to'"));
+        assert!(problems[5].starts_with("chime (sound) in on_no_match: ") && problems[5].contains("'file' or 'files'"));
+
+        // Variants are checked merged over the base config
+        let config = Config::from_json(
+            r#"{"handlers": [{"name": "hook", "type": "webhook", "config": {"url": "https://example.com/hook"},
+                "variants": [
+                    {"match": {"hook_event_name": "Stop"}, "config": {}},
+                    {"match": {"tool_name": "Bash"}, "config": {"url": "/bash"}}
+                ]}]}"#,
+        )
+        .unwrap();
+        let problems: Vec<String> = validate_handlers(&config, &registry).iter().map(|p| p.to_string()).collect();
+        assert_eq!(problems.len(), 1, "{:#?}", problems);
+        assert!(problems[0].starts_with("hook (webhook) variants[1]: ") && problems[0].contains("'/bash'"));
+    }
+
+    #[cfg(feature = "card")]
+    #[test]
+    fn test_validate_card_style() {
+        let config = r##"{"handlers": [{"name": "card", "type": "card", "config": {"background": "#12345g"}}]}"##;
+        let config = Config::from_json(config).unwrap();
+        let problems = validate_handlers(&config, &HandlerRegistry::new());
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].to_string().starts_with("card (card): "));
+    }
+
+    #[test]
+    fn test_validation_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("boopifier.json");
+        let registry = HandlerRegistry::new();
+
+        std::fs::write(&path, r#"{"handlers": [{"name": "log", "type": "worklog", "config": {"dir": "/tmp/log"}}]}"#)
+            .unwrap();
+        assert!(run_validate(&path, &registry));
+        assert_eq!(format_validation_report(&path, &[]), format!("{}: OK\n", path.display()));
+
+        // Malformed entries are reported alongside the handlers that fail validation
+        std::fs::write(
+            &path,
+            r#"{"handlers": [{"name": "no-type", "config": {}}, {"name": "log", "type": "worklog", "config": {}}]}"#,
+        )
+        .unwrap();
+        assert!(!run_validate(&path, &registry));
+        let (config, mut problems) = Config::load_lenient(&path).unwrap();
+        problems.extend(validate_handlers(&config, &registry));
+        assert_eq!(
+            format_validation_report(&path, &problems),
+            format!(
+                "{}: 2 problem(s)\n  handlers[0] (no-type): missing field `type`\n  \
+                 log (worklog): Invalid configuration: Worklog handler requires 'dir' configuration\n",
+                path.display()
+            )
+        );
+
+        // So are settings that span handlers
+        std::fs::write(
+            &path,
+            r#"{"handlers": [{"name": "log", "type": "worklog", "escalate_to": "pager", "config": {"dir": "/tmp/log"}}]}"#,
+        )
+        .unwrap();
+        assert!(!run_validate(&path, &registry));
+        let (_, problems) = Config::load_lenient(&path).unwrap();
+        assert_eq!(problems[0].to_string(), "handlers[0] (log): escalate_to names unknown handler 'pager'");
+
+        // Match rule regexes and expr conditions are checked too
+        std::fs::write(
+            &path,
+            r#"{"handlers": [{"name": "log", "type": "worklog", "match_type": "regex", "expr": "(exit_code != 0",
+                "match_rules": {"tool_name": "Bash("}, "config": {"dir": "/tmp/log"}}]}"#,
+        )
+        .unwrap();
+        assert!(!run_validate(&path, &registry));
+        let (_, problems) = Config::load_lenient(&path).unwrap();
+        assert_eq!(problems.len(), 2, "{:#?}", problems);
+        assert!(problems[0].to_string().starts_with("handlers[0] (log): "));
+        assert!(problems[1].to_string().starts_with("handlers[0] (log): invalid regex 'Bash(' in match rules: "));
+
+        assert!(!run_validate(&dir.path().join("missing.json"), &registry));
+    }
+
+    #[test]
+    fn test_no_color_without_tty() {
+        assert!(!use_color(false, false));
+        assert!(!use_color(true, true));
+        assert!(use_color(true, false));
+
+        let color = use_color(false, false);
+        for kind in [LogKind::Info, LogKind::Event, LogKind::Match, LogKind::Success, LogKind::Failure] {
+            let line = format_stderr_line(kind, "2026-01-01 00:00:00.000", "Handler error: boom", color);
+            assert_eq!(line, "[2026-01-01 00:00:00.000] Handler error: boom");
+            assert!(!line.contains('\x1b'));
+        }
+
+        let colored = format_stderr_line(LogKind::Failure, "t", "boom", true);
+        assert!(colored.contains("\x1b[") && colored.contains("boom"));
+    }
+
+    #[test]
+    fn test_format_outcome_table() {
+        let timed = vec![
+            TimedOutcome {
+                name: "desktop".to_string(),
+                outcome: HandlerOutcome::Success,
+                duration: Duration::from_micros(12_340),
+            },
+            TimedOutcome {
+                name: "team-slack".to_string(),
+                outcome: HandlerOutcome::Error("team-slack: timed out".to_string()),
+                duration: Duration::from_secs(5),
+            },
+        ];
+
+        assert_eq!(
+            format_outcome_table(&timed),
+            "HANDLER           TIME  RESULT\n\
+             desktop         12.3ms  ok\n\
+             team-slack    5000.0ms  error: team-slack: timed out\n"
+        );
+        assert_eq!(format_outcome_table(&[]), "No handlers configured.\n");
+    }
+
+    #[test]
+    fn test_suppression_summary_records_reasons() {
+        let suppressed = |name: &str, reason| TimedOutcome {
+            name: name.to_string(),
+            outcome: HandlerOutcome::Suppressed {
+                reason,
+                message: format!("{}: skipped", name),
+            },
+            duration: Duration::ZERO,
+        };
+        let timed = vec![
+            suppressed("heartbeat", boopifier::SuppressionReason::Cooldown),
+            TimedOutcome {
+                name: "desktop".to_string(),
+                outcome: HandlerOutcome::Success,
+                duration: Duration::ZERO,
+            },
+            suppressed("phone", boopifier::SuppressionReason::ChannelGroup),
+        ];
+
+        assert_eq!(
+            suppression_summary(&timed).as_deref(),
+            Some("heartbeat (cooldown), phone (channel_group)")
+        );
+        assert_eq!(suppression_summary(&timed[1..2]), None);
+        assert!(format_outcome_table(&timed).contains("suppressed (cooldown): heartbeat: skipped\n"));
+    }
+
+    #[tokio::test]
+    async fn test_replay_dry_run() {
+        let config = Config::from_json(
+            r#"{
+                "handlers": [
+                    {"name": "done", "type": "worklog", "match_rules": {"hook_event_name": "Stop"}, "config": {}},
+                    {"name": "bash", "type": "worklog", "match_rules": {"tool_name": "Bash"}, "config": {}}
+                ],
+                "on_no_match": {"name": "fallback", "type": "worklog", "config": {}}
+            }"#,
+        )
+        .unwrap();
+        let log = concat!(
+            "{\"hook_event_name\": \"Stop\"}\n",
+            "\n",
+            "{\"timestamp\": \"2026-01-01\", \"event\": {\"hook_event_name\": \"PreToolUse\", \"tool_name\": \"Bash\"}}\n",
+            "{\"hook_event_name\": \"Notification\"}\n",
+            "not json\n",
+        );
+
+        let (report, all_ok) = replay(io::Cursor::new(log), &config, &HandlerRegistry::new(), true).await;
+        assert!(!all_ok);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines[..3],
+            [
+                "line 1: Stop -> done",
+                "line 3: PreToolUse -> bash",
+                "line 4: Notification -> fallback",
+            ]
+        );
+        assert!(lines[3].starts_with("line 5: invalid event:"));
+        assert!(report.ends_with("\n3 event(s) replayed (dry run), 3 fired at least one handler\n"));
+    }
+
+    #[test]
+    fn test_hook_error_response_modes() {
+        let response = hook_error_response(false, "bad config");
+        assert_eq!(
+            response,
+            json!({"continue": true, "systemMessage": "Boopifier warning: bad config"})
+        );
+
+        let response = hook_error_response(true, "bad config");
+        assert_eq!(
+            response,
+            json!({
+                "continue": false,
+                "stopReason": "Boopifier error: bad config",
+                "systemMessage": "Boopifier error: bad config"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stalled_stdin_times_out() {
+        let mut inputs = InputStream::spawn(StalledReader, false);
+        let input = inputs.next(Some(Duration::from_millis(50))).await;
+        assert!(matches!(input, Input::TimedOut));
+    }
+
+    #[tokio::test]
+    async fn test_read_input_within_timeout() {
+        let reader = io::Cursor::new(b"  {\"hook_event_name\": \"Stop\"}".to_vec());
+        let mut inputs = InputStream::spawn(reader, false);
+        let input = inputs.next(Some(Duration::from_secs(5))).await;
+        assert!(matches!(input, Input::Event(event) if event.hook_name() == Some("Stop")));
+        assert!(matches!(inputs.next(None).await, Input::Empty));
+
+        let mut inputs = InputStream::spawn(io::Cursor::new(b" \n".to_vec()), false);
+        assert!(matches!(inputs.next(None).await, Input::Empty));
+    }
+
+    #[tokio::test]
+    async fn test_reads_several_events() {
+        let reader = io::Cursor::new(b"{\"n\": 1}\n\n{\n  \"n\": 2\n}\n{\"n\": 3} {oops".to_vec());
+        let mut inputs = InputStream::spawn(reader, false);
+        for n in 1..=3 {
+            assert!(matches!(inputs.next(None).await, Input::Event(event) if event.data["n"] == n));
+        }
+        assert!(matches!(inputs.next(None).await, Input::InvalidJson(_)));
+    }
+
+    #[tokio::test]
+    async fn test_single_event_rejects_trailing_input() {
+        let mut inputs = InputStream::spawn(io::Cursor::new(b"{\"n\": 1} {oops".to_vec()), true);
+        assert!(matches!(inputs.next(None).await, Input::InvalidJson(e) if e.contains("trailing characters")));
+
+        let mut inputs = InputStream::spawn(io::Cursor::new(b"{\"n\": 1}\n".to_vec()), true);
+        assert!(matches!(inputs.next(None).await, Input::Event(event) if event.data["n"] == 1));
+    }
+
+    #[test]
+    fn test_background_args() {
+        let cli = Cli::parse_from(["boopifier", "--debug-stderr", "--offline", "--pretty", "--profile"]);
+        assert_eq!(
+            background_args(&cli, Path::new("/etc/boopifier.json")),
+            ["--background", "--config", "/etc/boopifier.json", "--debug", "--offline"]
+        );
 
-    if let Ok(json_str) = serde_json::to_string(&response) {
-        println!("{}", json_str);
+        let cli = Cli::parse_from(["boopifier", "--lenient"]);
+        assert_eq!(background_args(&cli, Path::new("b.json")), ["--background", "--config", "b.json", "--lenient"]);
     }
 }
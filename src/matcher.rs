@@ -2,11 +2,29 @@
 //!
 //! This module provides functionality to match events against configured rules.
 
-use crate::config::{MatchRules, MatchType};
+use crate::config::{MatchRules, MatchType, RegexFlags};
 use crate::event::Event;
-use regex::Regex;
+use chrono::{DateTime, Datelike, FixedOffset, Local, Timelike};
+use once_cell::sync::Lazy;
+use regex::{Regex, RegexBuilder};
+use semver::{Version, VersionReq};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+/// Returns true if `version` satisfies the semver range `range` (e.g. `">=1.2.0, <2"`).
+///
+/// A leading `v` on the version is ignored, and unparseable versions never match.
+/// Only values with an operator (`=`, `>`, `<`, `~`, `^`) or a `*` wildcard are
+/// ranges; others (like `"SessionStart"` or `"123"`) are compared exactly, so
+/// other string rules keep working alongside version ranges.
+fn semver_match(version: &str, range: &str) -> bool {
+    let is_range = range.contains(['=', '>', '<', '~', '^', '*']);
+    let Some(requirement) = is_range.then(|| VersionReq::parse(range).ok()).flatten() else {
+        return version == range;
+    };
+    Version::parse(version.trim().trim_start_matches('v')).is_ok_and(|version| requirement.matches(&version))
+}
 
 /// Checks if an event matches the given rules.
 ///
@@ -14,7 +32,7 @@ use std::collections::HashMap;
 ///
 /// * `event` - The event to match against
 /// * `rules` - The matching rules (None matches all events)
-/// * `match_type` - The type of matching to use for strings (exact or regex)
+/// * `match_type` - The type of matching to use for strings (exact, regex or semver)
 ///
 /// # Examples
 ///
@@ -32,6 +50,80 @@ use std::collections::HashMap;
 /// assert!(matches(&event, &Some(MatchRules::Simple(rules)), &MatchType::Exact));
 /// ```
 pub fn matches(event: &Event, rules: &Option<MatchRules>, match_type: &MatchType) -> bool {
+    matches_with_options(event, rules, match_type, &MatchOptions::default())
+}
+
+/// Optional matching behaviors that relax the default strict comparison.
+#[derive(Debug, Clone, Default)]
+pub struct MatchOptions {
+    /// Compare numeric strings against numbers (e.g. `"1"` matches `1`)
+    pub coerce_numbers: bool,
+
+    /// Flags applied to every regex in the rules (e.g. case-insensitivity)
+    pub regex_flags: RegexFlags,
+
+    /// Rules that decide `{"tool_failed": ...}` instead of [`Event::tool_failed`]
+    pub tool_failed_when: Option<MatchRules>,
+
+    /// Time used for `$time.*` fields instead of the current local time
+    pub now: Option<DateTime<FixedOffset>>,
+}
+
+/// Rule key that matches on [`Event::tool_failed`] rather than an event field.
+const TOOL_FAILED: &str = "tool_failed";
+
+/// Rule key that falls back to [`Event::tool_name`] for events with no `tool_name` field.
+const TOOL_NAME: &str = "tool_name";
+
+/// Prefix of the virtual wall-clock fields, e.g. `$time.hour`.
+const TIME_PREFIX: &str = "$time.";
+
+/// Compiled regexes by pattern; `None` marks a pattern that doesn't compile.
+type CompiledPatterns = HashMap<String, Option<Regex>>;
+
+/// Rule regexes compiled so far, by flags and pattern.
+static REGEX_CACHE: Lazy<Mutex<HashMap<RegexFlags, CompiledPatterns>>> = Lazy::new(Default::default);
+
+/// Named regex groups bound by `{"regex": ..., "bind": true}` rules.
+pub type Captures = HashMap<String, String>;
+
+/// Checks if an event matches the given rules using the provided options.
+///
+/// Behaves like [`matches`] but allows opting into looser comparisons.
+pub fn matches_with_options(
+    event: &Event,
+    rules: &Option<MatchRules>,
+    match_type: &MatchType,
+    options: &MatchOptions,
+) -> bool {
+    matches_with_captures(event, rules, match_type, options).is_some()
+}
+
+/// Checks if an event matches, returning the bound regex captures on success.
+///
+/// Rule values of the form `{"regex": "...", "bind": true}` match string fields
+/// against the regex (regardless of `match_type`) and bind its named groups,
+/// which templates can use as `{{capture.name}}`. With `bind`, every named group
+/// must have participated in the match.
+///
+/// Returns `None` if the event does not match.
+pub fn matches_with_captures(
+    event: &Event,
+    rules: &Option<MatchRules>,
+    match_type: &MatchType,
+    options: &MatchOptions,
+) -> Option<Captures> {
+    let mut captures = Captures::new();
+    matches_into(event, rules, match_type, options, &mut captures).then_some(captures)
+}
+
+fn matches_into(
+    event: &Event,
+    rules: &Option<MatchRules>,
+    match_type: &MatchType,
+    options: &MatchOptions,
+    captures: &mut Captures,
+) -> bool {
     match rules {
         None => true, // No rules means match all
         Some(MatchRules::Simple(simple_rules)) => {
@@ -55,29 +147,53 @@ pub fn matches(event: &Event, rules: &Option<MatchRules>, match_type: &MatchType
                     .and_then(|v| v.as_object())
                     .map(|o| o.iter().map(|(k, v)| (k.clone(), v.clone())).collect());
 
-                matches_complex(event, all.as_ref(), any.as_ref(), not.as_ref(), match_type)
+                matches_complex(event, all.as_ref(), any.as_ref(), not.as_ref(), match_type, options, captures)
             } else {
-                matches_simple(event, simple_rules, match_type)
+                matches_simple(event, simple_rules, match_type, options, captures)
             }
         }
         Some(MatchRules::Complex { all, any, not }) => {
-            matches_complex(event, all.as_ref(), any.as_ref(), not.as_ref(), match_type)
+            matches_complex(event, all.as_ref(), any.as_ref(), not.as_ref(), match_type, options, captures)
         }
     }
 }
 
-fn matches_simple(event: &Event, rules: &HashMap<String, Value>, match_type: &MatchType) -> bool {
+fn matches_simple(
+    event: &Event,
+    rules: &HashMap<String, Value>,
+    match_type: &MatchType,
+    options: &MatchOptions,
+    captures: &mut Captures,
+) -> bool {
     for (key, expected_value) in rules {
         // Support nested keys with dot notation (e.g., "tool.name")
         let actual_value = if key.contains('.') {
-            event.get_nested_str(key).map(|s| Value::String(s.to_string()))
+            event.get_nested(key)
         } else {
-            event.data.get(key).cloned()
+            event.data.get(key)
+        };
+
+        // `tool_failed`, `tool_name` and `$time.*` are computed unless the event has a real field by that name
+        let computed;
+        let actual_value = match actual_value {
+            None if key == TOOL_NAME => {
+                computed = event.tool_name().map(Value::from);
+                computed.as_ref()
+            }
+            None if key == TOOL_FAILED => {
+                computed = tool_failed(event, options).map(Value::Bool);
+                computed.as_ref()
+            }
+            None if key.starts_with(TIME_PREFIX) => {
+                computed = time_field(&key[TIME_PREFIX.len()..], options);
+                computed.as_ref()
+            }
+            other => other,
         };
 
         match actual_value {
             Some(actual) => {
-                if !values_match(&actual, expected_value, match_type) {
+                if !values_match(actual, expected_value, match_type, options, captures) {
                     return false;
                 }
             }
@@ -87,12 +203,44 @@ fn matches_simple(event: &Event, rules: &HashMap<String, Value>, match_type: &Ma
     true
 }
 
+/// Whether the event's tool failed, using the handler's `tool_failed_when` rules if set.
+fn tool_failed(event: &Event, options: &MatchOptions) -> Option<bool> {
+    match &options.tool_failed_when {
+        // A `tool_failed` key inside the override falls back to the built-in heuristic
+        Some(_) => {
+            let inner = MatchOptions {
+                coerce_numbers: options.coerce_numbers,
+                regex_flags: options.regex_flags,
+                tool_failed_when: None,
+                now: options.now,
+            };
+            Some(matches_with_options(event, &options.tool_failed_when, &MatchType::Exact, &inner))
+        }
+        None => event.tool_failed(),
+    }
+}
+
+/// Value of a `$time.*` field: `hour` (0-23), `minute` (0-59) or `weekday` (`"Mon"`-`"Sun"`).
+///
+/// Uses the local time at match time unless `options.now` is set.
+fn time_field(field: &str, options: &MatchOptions) -> Option<Value> {
+    let now = options.now.unwrap_or_else(|| Local::now().fixed_offset());
+    match field {
+        "hour" => Some(Value::from(now.hour())),
+        "minute" => Some(Value::from(now.minute())),
+        "weekday" => Some(Value::String(now.weekday().to_string())),
+        _ => None,
+    }
+}
+
 fn matches_complex(
     event: &Event,
     all: Option<&Vec<HashMap<String, Value>>>,
     any: Option<&Vec<HashMap<String, Value>>>,
     not: Option<&HashMap<String, Value>>,
     match_type: &MatchType,
+    options: &MatchOptions,
+    captures: &mut Captures,
 ) -> bool {
     // Complex rules must have at least one field set
     if all.is_none() && any.is_none() && not.is_none() {
@@ -102,7 +250,7 @@ fn matches_complex(
     // "all" - all conditions must match
     if let Some(all_rules) = all {
         for rule in all_rules {
-            if !matches_simple(event, rule, match_type) {
+            if !matches_simple(event, rule, match_type, options, captures) {
                 return false;
             }
         }
@@ -112,7 +260,10 @@ fn matches_complex(
     if let Some(any_rules) = any {
         let mut matched = false;
         for rule in any_rules {
-            if matches_simple(event, rule, match_type) {
+            // Only keep captures from the branch that matched
+            let mut branch = Captures::new();
+            if matches_simple(event, rule, match_type, options, &mut branch) {
+                captures.extend(branch);
                 matched = true;
                 break;
             }
@@ -124,7 +275,7 @@ fn matches_complex(
 
     // "not" - condition must not match
     if let Some(not_rules) = not {
-        if matches_simple(event, not_rules, match_type) {
+        if matches_simple(event, not_rules, match_type, options, &mut Captures::new()) {
             return false;
         }
     }
@@ -132,19 +283,59 @@ fn matches_complex(
     true
 }
 
-fn values_match(actual: &Value, expected: &Value, match_type: &MatchType) -> bool {
+fn values_match(
+    actual: &Value,
+    expected: &Value,
+    match_type: &MatchType,
+    options: &MatchOptions,
+    captures: &mut Captures,
+) -> bool {
     match (actual, expected) {
+        // Membership: {"in": [...]} matches if any listed value matches
+        (_, Value::Object(e)) if e.len() == 1 && e.get("in").is_some_and(Value::is_array) => {
+            let candidates = e["in"].as_array().into_iter().flatten();
+            for candidate in candidates {
+                // Only keep captures from the candidate that matched
+                let mut branch = Captures::new();
+                if values_match(actual, candidate, match_type, options, &mut branch) {
+                    captures.extend(branch);
+                    return true;
+                }
+            }
+            false
+        }
+
+        // Length rule object: {"len_gt": 3}, optionally combined with len_lt/len_eq
+        (_, Value::Object(e)) if is_length_rule(e) => length_rule_match(actual, e),
+
+        // Comparison rule object: {"gte": 9, "lt": 17}
+        (_, Value::Object(e)) if is_comparison_rule(e) => comparison_rule_match(actual, e, options),
+
+        // Substring rule object: {"contains": "error"}, optionally with startswith/endswith
+        (_, Value::Object(e)) if is_substring_rule(e) => substring_rule_match(actual, e),
+
+        // Regex rule object: {"regex": "...", "bind": true}
+        (Value::String(a), Value::Object(e)) if e.contains_key("regex") => {
+            regex_rule_match(a, e, options.regex_flags, captures)
+        }
+
         // String matching - exact or regex
         (Value::String(a), Value::String(e)) => {
             match match_type {
                 MatchType::Exact => a == e,
                 MatchType::Regex => {
-                    // Try to compile regex, fall back to exact match on error
-                    Regex::new(e).map(|re| re.is_match(a)).unwrap_or(false)
+                    // An invalid regex never matches
+                    compile_regex(e, options.regex_flags).is_some_and(|re| re.is_match(a))
                 }
+                MatchType::Semver => semver_match(a, e),
             }
         }
         (Value::Number(a), Value::Number(e)) => a == e,
+
+        // Numeric strings vs numbers (opt-in)
+        (Value::String(s), Value::Number(n)) | (Value::Number(n), Value::String(s)) if options.coerce_numbers => {
+            numeric_string_eq(s, n)
+        }
         (Value::Bool(a), Value::Bool(e)) => a == e,
 
         // Array: check if expected array is subset of actual
@@ -152,7 +343,7 @@ fn values_match(actual: &Value, expected: &Value, match_type: &MatchType) -> boo
 
         // Object: recursive matching
         (Value::Object(a), Value::Object(e)) => {
-            e.iter().all(|(k, ev)| a.get(k).is_some_and(|av| values_match(av, ev, match_type)))
+            e.iter().all(|(k, ev)| a.get(k).is_some_and(|av| values_match(av, ev, match_type, options, captures)))
         }
 
         // Type mismatch
@@ -160,6 +351,228 @@ fn values_match(actual: &Value, expected: &Value, match_type: &MatchType) -> boo
     }
 }
 
+/// Comparison keys of a length rule such as `{"len_gt": 3}`.
+const LENGTH_OPERATORS: [&str; 3] = ["len_eq", "len_gt", "len_lt"];
+
+/// Returns true for rule objects made up only of length comparisons.
+fn is_length_rule(rule: &serde_json::Map<String, Value>) -> bool {
+    !rule.is_empty() && rule.keys().all(|k| LENGTH_OPERATORS.contains(&k.as_str()))
+}
+
+/// Compares the element count of an array, or the character count of a string,
+/// against every bound in a length rule. Other values never match.
+fn length_rule_match(actual: &Value, rule: &serde_json::Map<String, Value>) -> bool {
+    let len = match actual {
+        Value::Array(items) => items.len() as u64,
+        Value::String(s) => s.chars().count() as u64,
+        _ => return false,
+    };
+
+    rule.iter().all(|(op, bound)| {
+        let Some(bound) = bound.as_u64() else {
+            return false;
+        };
+        match op.as_str() {
+            "len_eq" => len == bound,
+            "len_gt" => len > bound,
+            "len_lt" => len < bound,
+            _ => false,
+        }
+    })
+}
+
+/// Keys of a substring rule such as `{"contains": "error"}`.
+const SUBSTRING_OPERATORS: [&str; 3] = ["contains", "startswith", "endswith"];
+
+/// Returns true for rule objects made up only of substring checks.
+fn is_substring_rule(rule: &serde_json::Map<String, Value>) -> bool {
+    !rule.is_empty() && rule.keys().all(|k| SUBSTRING_OPERATORS.contains(&k.as_str()))
+}
+
+/// Checks a string against every part of a substring rule, case-sensitively.
+/// Other values, and non-string parts, never match.
+fn substring_rule_match(actual: &Value, rule: &serde_json::Map<String, Value>) -> bool {
+    let Value::String(actual) = actual else {
+        return false;
+    };
+
+    rule.iter().all(|(op, part)| {
+        let Some(part) = part.as_str() else {
+            return false;
+        };
+        match op.as_str() {
+            "contains" => actual.contains(part),
+            "startswith" => actual.starts_with(part),
+            "endswith" => actual.ends_with(part),
+            _ => false,
+        }
+    })
+}
+
+/// Keys of a numeric comparison rule such as `{"gte": 9, "lt": 17}`.
+const COMPARISON_OPERATORS: [&str; 4] = ["gt", "gte", "lt", "lte"];
+
+/// Returns true for rule objects made up only of numeric comparisons.
+fn is_comparison_rule(rule: &serde_json::Map<String, Value>) -> bool {
+    !rule.is_empty() && rule.keys().all(|k| COMPARISON_OPERATORS.contains(&k.as_str()))
+}
+
+/// Compares a number against every bound in a comparison rule.
+///
+/// Numeric strings count as numbers only with `coerce_numbers`; other values never match.
+fn comparison_rule_match(actual: &Value, rule: &serde_json::Map<String, Value>, options: &MatchOptions) -> bool {
+    let value = match actual {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) if options.coerce_numbers => s.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+    let Some(value) = value else {
+        return false;
+    };
+
+    rule.iter().all(|(op, bound)| {
+        let Some(bound) = bound.as_f64() else {
+            return false;
+        };
+        match op.as_str() {
+            "gt" => value > bound,
+            "gte" => value >= bound,
+            "lt" => value < bound,
+            "lte" => value <= bound,
+            _ => false,
+        }
+    })
+}
+
+/// Compiles a rule's regex with the handler's `match_flags`, once per pattern.
+///
+/// Returns `None` for an invalid pattern. Configs with one fail to load (see
+/// [`check_regexes`]), so only lenient loads get here with one.
+fn compile_regex(pattern: &str, flags: RegexFlags) -> Option<Regex> {
+    let mut cache = REGEX_CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+    let patterns = cache.entry(flags).or_default();
+    if let Some(compiled) = patterns.get(pattern) {
+        return compiled.clone();
+    }
+
+    let compiled = build_regex(pattern, flags).ok();
+    patterns.insert(pattern.to_string(), compiled.clone());
+    compiled
+}
+
+fn build_regex(pattern: &str, flags: RegexFlags) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(flags.case_insensitive)
+        .multi_line(flags.multi_line)
+        .dot_matches_new_line(flags.dot_matches_new_line)
+        .ignore_whitespace(flags.ignore_whitespace)
+        .build()
+}
+
+/// Checks that every regex in `rules` compiles with `flags`.
+///
+/// Covers `{"regex": ...}` rule objects, and plain string values when
+/// `match_type` is regex, including those inside `all`/`any`/`not` and `in` lists.
+///
+/// # Errors
+///
+/// Returns a message naming the first pattern that doesn't compile.
+pub fn check_regexes(rules: &Option<MatchRules>, match_type: &MatchType, flags: RegexFlags) -> Result<(), String> {
+    fn check_rules(rules: &HashMap<String, Value>, match_type: &MatchType, flags: RegexFlags) -> Result<(), String> {
+        for (key, value) in rules {
+            match (key.as_str(), value) {
+                // A complex rule deserialized as simple (see `matches_into`)
+                ("all" | "any", Value::Array(branches)) => {
+                    for branch in branches {
+                        if let Ok(branch) = serde_json::from_value(branch.clone()) {
+                            check_rules(&branch, match_type, flags)?;
+                        }
+                    }
+                }
+                ("not", Value::Object(_)) => {
+                    if let Ok(branch) = serde_json::from_value(value.clone()) {
+                        check_rules(&branch, match_type, flags)?;
+                    }
+                }
+                _ => check_value(value, match_type, flags)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn check_value(value: &Value, match_type: &MatchType, flags: RegexFlags) -> Result<(), String> {
+        let pattern = match value {
+            Value::Object(e) if e.len() == 1 && e.get("in").is_some_and(Value::is_array) => {
+                return e["in"].as_array().into_iter().flatten().try_for_each(|v| check_value(v, match_type, flags));
+            }
+            Value::Object(e) if is_length_rule(e) || is_comparison_rule(e) || is_substring_rule(e) => return Ok(()),
+            Value::Object(e) if e.contains_key("regex") => match e.get("regex").and_then(Value::as_str) {
+                Some(pattern) => pattern,
+                None => return Ok(()),
+            },
+            Value::Object(e) => return e.values().try_for_each(|v| check_value(v, match_type, flags)),
+            Value::String(pattern) if *match_type == MatchType::Regex => pattern,
+            _ => return Ok(()),
+        };
+        build_regex(pattern, flags)
+            .map(drop)
+            .map_err(|e| format!("invalid regex '{}' in match rules: {}", pattern, e))
+    }
+
+    match rules {
+        None => Ok(()),
+        Some(MatchRules::Simple(rules)) => check_rules(rules, match_type, flags),
+        Some(MatchRules::Complex { all, any, not }) => all
+            .iter()
+            .chain(any)
+            .flatten()
+            .chain(not)
+            .try_for_each(|rules| check_rules(rules, match_type, flags)),
+    }
+}
+
+/// Matches a string against a `{"regex": ..., "bind": bool}` rule.
+///
+/// An invalid regex never matches. When `bind` is set, all named groups must
+/// have matched and are added to `captures`.
+fn regex_rule_match(
+    actual: &str,
+    rule: &serde_json::Map<String, Value>,
+    flags: RegexFlags,
+    captures: &mut Captures,
+) -> bool {
+    let Some(re) = rule
+        .get("regex")
+        .and_then(|v| v.as_str())
+        .and_then(|pattern| compile_regex(pattern, flags))
+    else {
+        return false;
+    };
+    let Some(caps) = re.captures(actual) else {
+        return false;
+    };
+
+    if rule.get("bind").and_then(|v| v.as_bool()).unwrap_or(false) {
+        for name in re.capture_names().flatten() {
+            let Some(m) = caps.name(name) else {
+                return false;
+            };
+            captures.insert(name.to_string(), m.as_str().to_string());
+        }
+    }
+    true
+}
+
+/// Compares a numeric string against a JSON number.
+///
+/// Returns false if the string does not parse as a number.
+fn numeric_string_eq(s: &str, n: &serde_json::Number) -> bool {
+    match (s.trim().parse::<f64>(), n.as_f64()) {
+        (Ok(parsed), Some(num)) => parsed == num,
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,6 +605,18 @@ mod tests {
         assert!(matches(&event, &Some(MatchRules::Simple(rules)), &MatchType::Exact));
     }
 
+    #[test]
+    fn test_nested_array_index_match() {
+        let event = Event::from_json(r#"{"tool_input": {"files": ["src/main.rs", "README.md"]}}"#).unwrap();
+        let rule = |key: &str, value: Value| Some(MatchRules::Simple(HashMap::from([(key.to_string(), value)])));
+
+        assert!(matches(&event, &rule("tool_input.files.0", json!("src/main.rs")), &MatchType::Exact));
+        assert!(matches(&event, &rule("tool_input.files.1", json!(".*\\.md$")), &MatchType::Regex));
+        assert!(!matches(&event, &rule("tool_input.files.0", json!("README.md")), &MatchType::Exact));
+        // Out of bounds behaves like a missing field
+        assert!(!matches(&event, &rule("tool_input.files.2", json!("src/main.rs")), &MatchType::Exact));
+    }
+
     #[test]
     fn test_no_rules_matches_all() {
         let event = Event::from_json(r#"{"anything": "goes"}"#).unwrap();
@@ -376,6 +801,105 @@ mod test_regex_matching {
         assert!(matches(&event, &Some(rules), &MatchType::Regex));
     }
 
+    #[test]
+    fn test_regex_case_insensitive_flag() {
+        let mut rules = HashMap::new();
+        rules.insert("message".to_string(), json!("^permission"));
+        let rules = Some(MatchRules::Simple(rules));
+        let options = MatchOptions {
+            regex_flags: RegexFlags {
+                case_insensitive: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        for message in ["Permission needed", "permission needed", "PERMISSION needed"] {
+            let event = Event::from_json(&json!({ "message": message }).to_string()).unwrap();
+            assert!(matches_with_options(&event, &rules, &MatchType::Regex, &options), "{}", message);
+        }
+
+        // Without the flag, regex and exact matching stay case-sensitive
+        let event = Event::from_json(r#"{"message": "Permission needed"}"#).unwrap();
+        assert!(!matches(&event, &rules, &MatchType::Regex));
+        let event = Event::from_json(r#"{"message": "PERMISSION"}"#).unwrap();
+        let mut exact = HashMap::new();
+        exact.insert("message".to_string(), json!("permission"));
+        assert!(!matches_with_options(&event, &Some(MatchRules::Simple(exact)), &MatchType::Exact, &options));
+    }
+
+    #[test]
+    fn test_regex_flags_apply_to_regex_rules() {
+        let mut rules = HashMap::new();
+        rules.insert("message".to_string(), json!({"regex": "use (?P<tool>bash)", "bind": true}));
+        let rules = Some(MatchRules::Simple(rules));
+        let event = Event::from_json(r#"{"message": "Claude wants to use Bash"}"#).unwrap();
+
+        let options = MatchOptions {
+            regex_flags: RegexFlags {
+                case_insensitive: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let captures = matches_with_captures(&event, &rules, &MatchType::Exact, &options).unwrap();
+        assert_eq!(captures["tool"], "Bash");
+        assert!(matches_with_captures(&event, &rules, &MatchType::Exact, &MatchOptions::default()).is_none());
+    }
+
+    #[test]
+    fn test_regex_compiled_once_per_pattern() {
+        let pattern = "^cached-(?P<n>\\d+)$";
+        let mut rules = HashMap::new();
+        rules.insert("message".to_string(), json!(pattern));
+        let rules = Some(MatchRules::Simple(rules));
+
+        for i in 0..500 {
+            let event = Event::from_json(&json!({ "message": format!("cached-{}", i) }).to_string()).unwrap();
+            assert!(matches(&event, &rules, &MatchType::Regex));
+        }
+        let event = Event::from_json(r#"{"message": "cached-x"}"#).unwrap();
+        assert!(!matches(&event, &rules, &MatchType::Regex));
+
+        let cache = REGEX_CACHE.lock().unwrap();
+        let compiled = &cache[&RegexFlags::default()][pattern];
+        assert_eq!(compiled.as_ref().map(Regex::as_str), Some(pattern));
+    }
+
+    #[test]
+    fn test_invalid_regex_never_matches() {
+        let mut rules = HashMap::new();
+        rules.insert("message".to_string(), json!("(unclosed"));
+        let rules = Some(MatchRules::Simple(rules));
+        let event = Event::from_json(r#"{"message": "(unclosed"}"#).unwrap();
+
+        assert!(!matches(&event, &rules, &MatchType::Regex));
+        assert!(!matches(&event, &rules, &MatchType::Regex));
+        assert!(REGEX_CACHE.lock().unwrap()[&RegexFlags::default()]["(unclosed"].is_none());
+    }
+
+    #[test]
+    fn test_check_regexes() {
+        let flags = RegexFlags::default();
+        let rules = |value: Value| Some(serde_json::from_value::<MatchRules>(value).unwrap());
+
+        let valid = rules(json!({"message": "^use", "count": {"gte": 2}, "tool_name": {"in": ["Bash", "Re.d"]}}));
+        assert!(check_regexes(&valid, &MatchType::Regex, flags).is_ok());
+        // Plain strings are only patterns with the regex match type
+        let exact = rules(json!({"message": "(unclosed", "title": {"contains": "(x"}}));
+        assert!(check_regexes(&exact, &MatchType::Exact, flags).is_ok());
+
+        for invalid in [
+            json!({"message": {"regex": "(unclosed", "bind": true}}),
+            json!({"any": [{"hook_event_name": "Stop"}, {"message": {"regex": "(unclosed"}}]}),
+            json!({"not": {"tool_input": {"command": {"regex": "(unclosed"}}}}),
+        ] {
+            let error = check_regexes(&rules(invalid.clone()), &MatchType::Exact, flags).unwrap_err();
+            assert!(error.starts_with("invalid regex '(unclosed' in match rules: "), "{}", invalid);
+        }
+        assert!(check_regexes(&exact, &MatchType::Regex, flags).is_err());
+    }
+
     #[test]
     fn test_exact_match_still_works() {
         let event = Event::from_json(r#"{"status": "success"}"#).unwrap();
@@ -385,3 +909,540 @@ mod test_regex_matching {
         assert!(matches(&event, &Some(MatchRules::Simple(rules)), &MatchType::Exact));
     }
 }
+
+#[cfg(test)]
+mod test_number_coercion {
+    use super::*;
+    use serde_json::json;
+
+    fn coerce() -> MatchOptions {
+        MatchOptions {
+            coerce_numbers: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_string_matches_number_with_coercion() {
+        let event = Event::from_json(r#"{"exit_code": "1"}"#).unwrap();
+
+        let mut rules = HashMap::new();
+        rules.insert("exit_code".to_string(), json!(1));
+        assert!(matches_with_options(&event, &Some(MatchRules::Simple(rules)), &MatchType::Exact, &coerce()));
+    }
+
+    #[test]
+    fn test_number_matches_string_with_coercion() {
+        let event = Event::from_json(r#"{"exit_code": 1}"#).unwrap();
+
+        let mut rules = HashMap::new();
+        rules.insert("exit_code".to_string(), json!("1.0"));
+        assert!(matches_with_options(&event, &Some(MatchRules::Simple(rules)), &MatchType::Exact, &coerce()));
+    }
+
+    #[test]
+    fn test_strict_by_default() {
+        let event = Event::from_json(r#"{"exit_code": "1"}"#).unwrap();
+
+        let mut rules = HashMap::new();
+        rules.insert("exit_code".to_string(), json!(1));
+        assert!(!matches(&event, &Some(MatchRules::Simple(rules)), &MatchType::Exact));
+    }
+
+    #[test]
+    fn test_invalid_coercion_no_match() {
+        let event = Event::from_json(r#"{"exit_code": "one"}"#).unwrap();
+
+        let mut rules = HashMap::new();
+        rules.insert("exit_code".to_string(), json!(1));
+        assert!(!matches_with_options(&event, &Some(MatchRules::Simple(rules)), &MatchType::Exact, &coerce()));
+    }
+}
+
+#[cfg(test)]
+mod test_regex_bind {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(value: Value) -> Option<MatchRules> {
+        Some(serde_json::from_value(value).unwrap())
+    }
+
+    #[test]
+    fn test_bind_named_capture() {
+        let event = Event::from_json(r#"{"message": "Claude needs your permission to use Bash"}"#).unwrap();
+        let rules = rules(json!({"message": {"regex": "use (?P<tool>\\w+)", "bind": true}}));
+
+        let captures = matches_with_captures(&event, &rules, &MatchType::Exact, &MatchOptions::default()).unwrap();
+        assert_eq!(captures.get("tool").map(String::as_str), Some("Bash"));
+    }
+
+    #[test]
+    fn test_regex_rule_without_bind() {
+        let event = Event::from_json(r#"{"message": "use Edit"}"#).unwrap();
+        let rules = rules(json!({"message": {"regex": "use (?P<tool>\\w+)"}}));
+
+        let captures = matches_with_captures(&event, &rules, &MatchType::Exact, &MatchOptions::default()).unwrap();
+        assert!(captures.is_empty());
+    }
+
+    #[test]
+    fn test_bind_requires_named_group() {
+        let event = Event::from_json(r#"{"message": "use"}"#).unwrap();
+        let rules = rules(json!({"message": {"regex": "use( (?P<tool>\\w+))?", "bind": true}}));
+        assert!(!matches(&event, &rules, &MatchType::Exact));
+
+        let event = Event::from_json(r#"{"message": "nothing here"}"#).unwrap();
+        assert!(!matches(&event, &rules, &MatchType::Exact));
+    }
+
+    #[test]
+    fn test_any_keeps_only_matching_branch_captures() {
+        let event = Event::from_json(r#"{"hook_event_name": "Notification", "message": "use Read"}"#).unwrap();
+        let rules = rules(json!({
+            "any": [
+                {"hook_event_name": "Stop", "message": {"regex": "(?P<tool>\\w+)", "bind": true}},
+                {"message": {"regex": "use (?P<verb>\\w+)", "bind": true}}
+            ]
+        }));
+
+        let captures = matches_with_captures(&event, &rules, &MatchType::Exact, &MatchOptions::default()).unwrap();
+        assert_eq!(captures.get("verb").map(String::as_str), Some("Read"));
+        assert!(!captures.contains_key("tool"));
+    }
+}
+
+#[cfg(test)]
+mod test_in_operator {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(value: Value) -> Option<MatchRules> {
+        Some(serde_json::from_value(value).unwrap())
+    }
+
+    #[test]
+    fn test_in_strings() {
+        let rules = rules(json!({"hook_event_name": {"in": ["Stop", "SubagentStop"]}}));
+
+        for name in ["Stop", "SubagentStop"] {
+            let event = Event::from_json(&format!(r#"{{"hook_event_name": "{}"}}"#, name)).unwrap();
+            assert!(matches(&event, &rules, &MatchType::Exact));
+        }
+        let event = Event::from_json(r#"{"hook_event_name": "Notification"}"#).unwrap();
+        assert!(!matches(&event, &rules, &MatchType::Exact));
+    }
+
+    #[test]
+    fn test_in_numbers() {
+        let rules = rules(json!({"exit_code": {"in": [1, 2, 127]}}));
+
+        let event = Event::from_json(r#"{"exit_code": 127}"#).unwrap();
+        assert!(matches(&event, &rules, &MatchType::Exact));
+        let event = Event::from_json(r#"{"exit_code": 0}"#).unwrap();
+        assert!(!matches(&event, &rules, &MatchType::Exact));
+
+        // Numeric strings only match with coercion enabled
+        let event = Event::from_json(r#"{"exit_code": "2"}"#).unwrap();
+        assert!(!matches(&event, &rules, &MatchType::Exact));
+        let options = MatchOptions {
+            coerce_numbers: true,
+            ..Default::default()
+        };
+        assert!(matches_with_options(&event, &rules, &MatchType::Exact, &options));
+    }
+
+    #[test]
+    fn test_in_respects_regex_match_type() {
+        let rules = rules(json!({"message": {"in": ["^permission", "idle$"]}}));
+        let event = Event::from_json(r#"{"message": "Claude is idle"}"#).unwrap();
+        assert!(matches(&event, &rules, &MatchType::Regex));
+        assert!(!matches(&event, &rules, &MatchType::Exact));
+    }
+
+    #[test]
+    fn test_array_subset_semantics_unchanged() {
+        let rules = rules(json!({"tags": ["a", "b"]}));
+        let event = Event::from_json(r#"{"tags": ["a", "b", "c"]}"#).unwrap();
+        assert!(matches(&event, &rules, &MatchType::Exact));
+        let event = Event::from_json(r#"{"tags": "a"}"#).unwrap();
+        assert!(!matches(&event, &rules, &MatchType::Exact));
+    }
+}
+
+#[cfg(test)]
+mod test_length_operators {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(value: Value) -> Option<MatchRules> {
+        Some(serde_json::from_value(value).unwrap())
+    }
+
+    fn event_with_files(count: usize) -> Event {
+        let files: Vec<String> = (0..count).map(|i| format!("src/file{}.rs", i)).collect();
+        Event::from_json(&json!({"tool_name": "Edit", "tool_input": {"files": files}}).to_string()).unwrap()
+    }
+
+    /// Which of 0..6 touched files match `rule` on `tool_input.files`.
+    fn matching_counts(rule: Value) -> Vec<usize> {
+        let rules = rules(json!({ "tool_input.files": rule }));
+        (0..6)
+            .filter(|&n| matches(&event_with_files(n), &rules, &MatchType::Exact))
+            .collect()
+    }
+
+    #[test]
+    fn test_nested_array_length() {
+        assert_eq!(matching_counts(json!({"len_gt": 3})), vec![4, 5]);
+        assert_eq!(matching_counts(json!({"len_lt": 2})), vec![0, 1]);
+        assert_eq!(matching_counts(json!({"len_eq": 0})), vec![0]);
+        assert_eq!(matching_counts(json!({"len_gt": 1, "len_lt": 4})), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_string_length_counts_chars() {
+        let rules = rules(json!({"prompt": {"len_eq": 3}}));
+        let event = Event::from_json(r#"{"prompt": "héé"}"#).unwrap();
+        assert!(matches(&event, &rules, &MatchType::Exact));
+    }
+
+    #[test]
+    fn test_length_of_other_values_never_matches() {
+        let field_rules = rules(json!({"field": {"len_lt": 100}}));
+        for value in [json!(5), json!(true), json!({"a": 1}), Value::Null] {
+            let event = Event::from_json(&json!({ "field": value }).to_string()).unwrap();
+            assert!(!matches(&event, &field_rules, &MatchType::Exact), "{}", value);
+        }
+
+        // Invalid bounds and missing fields don't match either
+        assert!(matching_counts(json!({"len_gt": -1})).is_empty());
+        let rules = rules(json!({"tool_input.missing": {"len_eq": 0}}));
+        assert!(!matches(&event_with_files(0), &rules, &MatchType::Exact));
+    }
+
+    #[test]
+    fn test_nested_fields_keep_their_type() {
+        let rules = rules(json!({"tool.exit_code": 0}));
+        let event = Event::from_json(r#"{"tool": {"exit_code": 0}}"#).unwrap();
+        assert!(matches(&event, &rules, &MatchType::Exact));
+    }
+}
+
+#[cfg(test)]
+mod test_tool_failed {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(value: Value) -> Option<MatchRules> {
+        Some(serde_json::from_value(value).unwrap())
+    }
+
+    #[test]
+    fn test_tool_name_across_event_shapes() {
+        let rules = rules(json!({"tool_name": "Bash"}));
+        for event in [
+            json!({"tool_name": "Bash"}),
+            json!({"tool": {"name": "Bash"}}),
+            json!({"tool": "Bash"}),
+        ] {
+            let event = Event::from_json(&event.to_string()).unwrap();
+            assert!(matches(&event, &rules, &MatchType::Exact), "{:?}", event.data);
+        }
+
+        // A real `tool_name` field wins, and events with no tool don't match
+        let event = Event::from_json(r#"{"tool_name": "Read", "tool": "Bash"}"#).unwrap();
+        assert!(!matches(&event, &rules, &MatchType::Exact));
+        assert!(!matches(&Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap(), &rules, &MatchType::Exact));
+    }
+
+    #[test]
+    fn test_tool_failed_sugar() {
+        let failed = rules(json!({"hook_event_name": "PostToolUse", "tool_failed": true}));
+        let succeeded = rules(json!({"tool_failed": false}));
+
+        let event = Event::from_json(r#"{"hook_event_name": "PostToolUse", "tool_response": {"exit_code": 1}}"#).unwrap();
+        assert!(matches(&event, &failed, &MatchType::Exact));
+        assert!(!matches(&event, &succeeded, &MatchType::Exact));
+
+        let event = Event::from_json(r#"{"hook_event_name": "PostToolUse", "tool_response": {"exit_code": 0}}"#).unwrap();
+        assert!(!matches(&event, &failed, &MatchType::Exact));
+        assert!(matches(&event, &succeeded, &MatchType::Exact));
+
+        // Events without tool results match neither
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+        assert!(!matches(&event, &succeeded, &MatchType::Exact));
+
+        // A real `tool_failed` field takes precedence
+        let event = Event::from_json(r#"{"tool_failed": false, "exit_code": 1}"#).unwrap();
+        assert!(matches(&event, &succeeded, &MatchType::Exact));
+    }
+
+    #[test]
+    fn test_tool_failed_override() {
+        let options = MatchOptions {
+            tool_failed_when: rules(json!({"tool_response.result": "FAIL"})),
+            ..Default::default()
+        };
+        let failed = rules(json!({"tool_failed": true}));
+
+        let event = Event::from_json(r#"{"tool_response": {"result": "FAIL"}}"#).unwrap();
+        assert!(matches_with_options(&event, &failed, &MatchType::Exact, &options));
+        assert!(!matches(&event, &failed, &MatchType::Exact));
+
+        // The override replaces the built-in heuristic
+        let event = Event::from_json(r#"{"tool_response": {"result": "ok", "exit_code": 1}}"#).unwrap();
+        assert!(!matches_with_options(&event, &failed, &MatchType::Exact, &options));
+
+        // ... unless it refers back to it
+        let options = MatchOptions {
+            tool_failed_when: rules(json!({"any": [{"tool_failed": true}, {"tool_response.result": "FAIL"}]})),
+            ..Default::default()
+        };
+        assert!(matches_with_options(&event, &failed, &MatchType::Exact, &options));
+    }
+}
+
+#[cfg(test)]
+mod test_time_fields {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(value: Value) -> Option<MatchRules> {
+        Some(serde_json::from_value(value).unwrap())
+    }
+
+    /// Options with the clock fixed at the given RFC 3339 time.
+    fn at(time: &str) -> MatchOptions {
+        MatchOptions {
+            now: Some(DateTime::parse_from_rfc3339(time).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_working_hours() {
+        let rules = rules(json!({
+            "$time.hour": {"gte": 9, "lt": 17},
+            "$time.weekday": {"in": ["Mon", "Tue", "Wed", "Thu", "Fri"]}
+        }));
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+
+        // 2025-06-02 is a Monday
+        assert!(matches_with_options(&event, &rules, &MatchType::Exact, &at("2025-06-02T09:00:00+02:00")));
+        assert!(matches_with_options(&event, &rules, &MatchType::Exact, &at("2025-06-02T16:59:00+02:00")));
+        assert!(!matches_with_options(&event, &rules, &MatchType::Exact, &at("2025-06-02T17:00:00+02:00")));
+        assert!(!matches_with_options(&event, &rules, &MatchType::Exact, &at("2025-06-02T08:59:00+02:00")));
+        assert!(!matches_with_options(&event, &rules, &MatchType::Exact, &at("2025-06-07T12:00:00+02:00")));
+    }
+
+    #[test]
+    fn test_time_fields_in_complex_rules() {
+        let rules = rules(json!({"any": [{"$time.minute": 0}, {"$time.weekday": "Sun"}]}));
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+
+        assert!(matches_with_options(&event, &rules, &MatchType::Exact, &at("2025-06-03T10:00:00Z")));
+        assert!(matches_with_options(&event, &rules, &MatchType::Exact, &at("2025-06-08T10:30:00Z")));
+        assert!(!matches_with_options(&event, &rules, &MatchType::Exact, &at("2025-06-03T10:30:00Z")));
+    }
+
+    #[test]
+    fn test_unknown_time_field_and_event_field_precedence() {
+        let event = Event::from_json(r#"{"$time": {"hour": 3}}"#).unwrap();
+        let options = at("2025-06-02T12:00:00Z");
+        assert!(matches_with_options(&event, &rules(json!({"$time.hour": 3})), &MatchType::Exact, &options));
+
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+        assert!(!matches_with_options(&event, &rules(json!({"$time.second": 0})), &MatchType::Exact, &options));
+    }
+
+    #[test]
+    fn test_comparison_rules() {
+        let rules = rules(json!({"duration_ms": {"gt": 1000, "lte": 5000.5}}));
+        for (duration, expected) in [(json!(1000), false), (json!(1001), true), (json!(5000.5), true), (json!(6000), false)] {
+            let event = Event::from_json(&json!({ "duration_ms": duration }).to_string()).unwrap();
+            assert_eq!(matches(&event, &rules, &MatchType::Exact), expected, "{}", duration);
+        }
+
+        // Numeric strings need coerce_numbers; other types never match
+        let event = Event::from_json(r#"{"duration_ms": "2000"}"#).unwrap();
+        assert!(!matches(&event, &rules, &MatchType::Exact));
+        let options = MatchOptions {
+            coerce_numbers: true,
+            ..Default::default()
+        };
+        assert!(matches_with_options(&event, &rules, &MatchType::Exact, &options));
+        let event = Event::from_json(r#"{"duration_ms": [2000]}"#).unwrap();
+        assert!(!matches(&event, &rules, &MatchType::Exact));
+    }
+
+    #[test]
+    fn test_comparison_boundaries() {
+        // (operator, matches below / at / above the 5000 boundary)
+        let cases = [
+            ("gt", [false, false, true]),
+            ("gte", [false, true, true]),
+            ("lt", [true, false, false]),
+            ("lte", [true, true, false]),
+        ];
+        for (op, expected) in cases {
+            let rules = rules(json!({"duration_ms": {op: 5000}}));
+            for (duration, expected) in [4999, 5000, 5001].into_iter().zip(expected) {
+                let event = Event::from_json(&json!({ "duration_ms": duration }).to_string()).unwrap();
+                // Bounds are compared as numbers whatever the match type
+                for match_type in [MatchType::Exact, MatchType::Regex] {
+                    assert_eq!(matches(&event, &rules, &match_type), expected, "{} {} {:?}", op, duration, match_type);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_comparisons_in_complex_rules() {
+        let rules = rules(json!({
+            "all": [{"hook_event_name": "PostToolUse"}, {"duration_ms": {"gte": 5000}}],
+            "any": [{"tool_name": "Bash"}, {"exit_code": {"gt": 0}}],
+            "not": {"duration_ms": {"gt": 60000}}
+        }));
+        let event = |duration: u64, tool: &str, exit_code: i64| {
+            let event = json!({
+                "hook_event_name": "PostToolUse",
+                "duration_ms": duration,
+                "tool_name": tool,
+                "exit_code": exit_code
+            });
+            Event::from_json(&event.to_string()).unwrap()
+        };
+
+        assert!(matches(&event(5000, "Bash", 0), &rules, &MatchType::Regex));
+        assert!(matches(&event(9000, "Read", 2), &rules, &MatchType::Exact));
+        assert!(!matches(&event(4999, "Bash", 0), &rules, &MatchType::Exact));
+        assert!(!matches(&event(9000, "Read", 0), &rules, &MatchType::Exact));
+        assert!(!matches(&event(60001, "Bash", 0), &rules, &MatchType::Exact));
+    }
+}
+
+#[cfg(test)]
+mod test_semver_matching {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(value: Value) -> Option<MatchRules> {
+        Some(serde_json::from_value(value).unwrap())
+    }
+
+    fn event(version: &str) -> Event {
+        Event::from_json(&json!({"hook_event_name": "SessionStart", "version": version}).to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_range_matches() {
+        let rules = rules(json!({"version": ">=1.2.0"}));
+        assert!(matches(&event("1.2.0"), &rules, &MatchType::Semver));
+        assert!(matches(&event("2.0.1"), &rules, &MatchType::Semver));
+        assert!(matches(&event("v1.10.3"), &rules, &MatchType::Semver));
+        assert!(!matches(&event("1.1.9"), &rules, &MatchType::Semver));
+        assert!(!matches(&event("1.2.0-beta.1"), &rules, &MatchType::Semver));
+
+        let rules = self::rules(json!({"version": ">=1.2, <2", "hook_event_name": "SessionStart"}));
+        assert!(matches(&event("1.9.9"), &rules, &MatchType::Semver));
+        assert!(!matches(&event("2.0.0"), &rules, &MatchType::Semver));
+
+        // Other rule kinds keep working alongside ranges
+        let rules = self::rules(json!({"version": {"in": ["^1.4", "^3"]}}));
+        assert!(matches(&event("3.1.0"), &rules, &MatchType::Semver));
+        assert!(!matches(&event("2.1.0"), &rules, &MatchType::Semver));
+    }
+
+    #[test]
+    fn test_invalid_versions_never_match() {
+        let rules = rules(json!({"version": ">=1.2.0"}));
+        assert!(!matches(&event("latest"), &rules, &MatchType::Semver));
+        assert!(!matches(&event("1.2"), &rules, &MatchType::Semver));
+        assert!(!matches(&event(""), &rules, &MatchType::Semver));
+
+        // Values that aren't ranges are compared exactly
+        let not_a_range = self::rules(json!({"version": ">=one"}));
+        assert!(!matches(&event("1.2.0"), &not_a_range, &MatchType::Semver));
+        assert!(matches(&event(">=one"), &not_a_range, &MatchType::Semver));
+
+        // A bare version isn't a caret range
+        let bare = self::rules(json!({"version": "123"}));
+        assert!(matches(&event("123"), &bare, &MatchType::Semver));
+        assert!(!matches(&event("123.4.0"), &bare, &MatchType::Semver));
+        let bare = self::rules(json!({"version": "1.2.0"}));
+        assert!(!matches(&event("1.9.0"), &bare, &MatchType::Semver));
+        assert!(matches(&event("1.9.0"), &self::rules(json!({"version": "^1.2.0"})), &MatchType::Semver));
+    }
+
+    #[test]
+    fn test_match_type_deserializes() {
+        let match_type: MatchType = serde_json::from_value(json!("semver")).unwrap();
+        assert_eq!(match_type, MatchType::Semver);
+    }
+}
+
+#[cfg(test)]
+mod test_substring_operators {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(value: Value) -> Option<MatchRules> {
+        Some(serde_json::from_value(value).unwrap())
+    }
+
+    fn event(message: &str) -> Event {
+        Event::from_json(&json!({"hook_event_name": "Notification", "message": message}).to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_substring_positions() {
+        let contains = rules(json!({"message": {"contains": "error"}}));
+        let startswith = rules(json!({"message": {"startswith": "error"}}));
+        let endswith = rules(json!({"message": {"endswith": "error"}}));
+
+        // (message, contains, startswith, endswith)
+        let cases = [
+            ("error in build", true, true, false),
+            ("build error found", true, false, false),
+            ("build failed with error", true, false, true),
+            ("build passed", false, false, false),
+            ("Error in build", false, false, false),
+        ];
+        for (message, in_message, at_start, at_end) in cases {
+            let event = event(message);
+            assert_eq!(matches(&event, &contains, &MatchType::Exact), in_message, "{}", message);
+            assert_eq!(matches(&event, &startswith, &MatchType::Exact), at_start, "{}", message);
+            assert_eq!(matches(&event, &endswith, &MatchType::Exact), at_end, "{}", message);
+        }
+    }
+
+    #[test]
+    fn test_substring_rules_combine() {
+        let rules = rules(json!({"message": {"startswith": "Claude", "contains": "permission", "endswith": "Bash"}}));
+        assert!(matches(&event("Claude needs your permission to use Bash"), &rules, &MatchType::Exact));
+        assert!(!matches(&event("Claude needs your permission to use Edit"), &rules, &MatchType::Exact));
+
+        // Substring parts are literal text, even in regex mode
+        let rules = self::rules(json!({"message": {"contains": "(1/3)"}}));
+        assert!(matches(&event("step (1/3) done"), &rules, &MatchType::Regex));
+
+        // Non-strings never match
+        let event = Event::from_json(r#"{"message": ["error"], "code": 500}"#).unwrap();
+        assert!(!matches(&event, &self::rules(json!({"message": {"contains": "error"}})), &MatchType::Exact));
+        assert!(!matches(&event, &self::rules(json!({"code": {"contains": "5"}})), &MatchType::Exact));
+    }
+
+    #[test]
+    fn test_substring_rules_in_complex_rules() {
+        let rules = rules(json!({
+            "all": [{"hook_event_name": "Notification"}],
+            "any": [{"message": {"contains": "permission"}}, {"message": {"endswith": "?"}}],
+            "not": {"message": {"startswith": "[test]"}}
+        }));
+        assert!(matches(&event("Claude needs your permission"), &rules, &MatchType::Exact));
+        assert!(matches(&event("Continue?"), &rules, &MatchType::Exact));
+        assert!(!matches(&event("[test] permission check"), &rules, &MatchType::Exact));
+        assert!(!matches(&event("Claude is waiting"), &rules, &MatchType::Exact));
+    }
+}
@@ -0,0 +1,161 @@
+//! Host and port parsing shared by network handlers.
+//!
+//! Handlers accept hosts as plain names, IPv4 addresses, or IPv6 literals with
+//! or without brackets (`::1`, `[::1]`), optionally followed by a port.
+
+use std::io;
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Splits `host[:port]` into the host and an optional port.
+///
+/// IPv6 literals may be bracketed (`[::1]:2525`); an unbracketed IPv6 literal
+/// (`::1`) is treated as a host without a port. Brackets are removed from the
+/// returned host.
+///
+/// # Examples
+///
+/// ```
+/// use boopifier::net::split_host_port;
+///
+/// assert_eq!(split_host_port("smtp.example.com:587"), ("smtp.example.com", Some(587)));
+/// assert_eq!(split_host_port("[::1]:1025"), ("::1", Some(1025)));
+/// assert_eq!(split_host_port("::1"), ("::1", None));
+/// ```
+pub fn split_host_port(s: &str) -> (&str, Option<u16>) {
+    let s = s.trim();
+
+    if let Some(rest) = s.strip_prefix('[') {
+        if let Some((host, after)) = rest.split_once(']') {
+            let port = after.strip_prefix(':').and_then(|p| p.parse().ok());
+            return (host, port);
+        }
+    }
+
+    // A bare IPv6 literal contains several colons and no port
+    if s.parse::<IpAddr>().is_ok() {
+        return (s, None);
+    }
+
+    match s.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => match port.parse() {
+            Ok(port) => (host, Some(port)),
+            Err(_) => (s, None),
+        },
+        _ => (s, None),
+    }
+}
+
+/// Returns true if `host` refers to the local machine.
+///
+/// Recognizes `localhost` (and `*.localhost`), any IPv4 `127.0.0.0/8` address,
+/// and IPv6 `::1`, with or without brackets.
+pub fn is_loopback_host(host: &str) -> bool {
+    let host = host
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_end_matches('.');
+
+    if host.eq_ignore_ascii_case("localhost") || host.to_ascii_lowercase().ends_with(".localhost") {
+        return true;
+    }
+
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(v6)) => v6.is_loopback() || v6.to_ipv4_mapped().is_some_and(|v4| v4.is_loopback()),
+        Ok(ip) => ip.is_loopback(),
+        Err(_) => false,
+    }
+}
+
+/// Normalizes `host` for comparison with other hosts.
+///
+/// Removes brackets and a trailing dot, lowercases names, and writes IP
+/// literals in their canonical form, so `[0:0::1]` and `::1` compare equal.
+pub fn normalize_host(host: &str) -> String {
+    let host = host
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_end_matches('.');
+
+    match host.parse::<IpAddr>() {
+        Ok(ip) => ip.to_string(),
+        Err(_) => host.to_ascii_lowercase(),
+    }
+}
+
+/// Default time allowed for a [`probe_tcp`] connection.
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Checks that a TCP connection to `host:port` can be opened within `timeout`.
+///
+/// Used by `boopifier doctor` to probe servers; the connection is closed
+/// immediately without sending anything.
+///
+/// # Errors
+///
+/// Returns the connection error, or `TimedOut` if no connection was made in time.
+pub async fn probe_tcp(host: &str, port: u16, timeout: Duration) -> io::Result<()> {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    match tokio::time::timeout(timeout, tokio::net::TcpStream::connect((host, port))).await {
+        Ok(stream) => stream.map(drop),
+        Err(_) => Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("no response within {}ms", timeout.as_millis()),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_probe_tcp() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(probe_tcp("127.0.0.1", port, Duration::from_secs(2)).await.is_ok());
+
+        drop(listener);
+        assert!(probe_tcp("127.0.0.1", port, Duration::from_secs(2)).await.is_err());
+    }
+
+    #[test]
+    fn test_split_host_port() {
+        assert_eq!(split_host_port("smtp.example.com"), ("smtp.example.com", None));
+        assert_eq!(split_host_port("smtp.example.com:587"), ("smtp.example.com", Some(587)));
+        assert_eq!(split_host_port("127.0.0.1:1025"), ("127.0.0.1", Some(1025)));
+        assert_eq!(split_host_port("[::1]"), ("::1", None));
+        assert_eq!(split_host_port("[::1]:2525"), ("::1", Some(2525)));
+        assert_eq!(split_host_port("::1"), ("::1", None));
+        assert_eq!(split_host_port("fe80::1"), ("fe80::1", None));
+        assert_eq!(split_host_port("host:notaport"), ("host:notaport", None));
+    }
+
+    #[test]
+    fn test_normalize_host() {
+        assert_eq!(normalize_host("Hooks.Example.COM."), "hooks.example.com");
+        assert_eq!(normalize_host("[::1]"), "::1");
+        assert_eq!(normalize_host("0:0:0:0:0:0:0:1"), "::1");
+        assert_eq!(normalize_host("[FE80::1]"), "fe80::1");
+        assert_eq!(normalize_host("127.0.0.1"), "127.0.0.1");
+    }
+
+    #[test]
+    fn test_is_loopback_host() {
+        assert!(is_loopback_host("localhost"));
+        assert!(is_loopback_host("LOCALHOST"));
+        assert!(is_loopback_host("mail.localhost"));
+        assert!(is_loopback_host("127.0.0.1"));
+        assert!(is_loopback_host("127.1.2.3"));
+        assert!(is_loopback_host("::1"));
+        assert!(is_loopback_host("[::1]"));
+        assert!(is_loopback_host("0:0:0:0:0:0:0:1"));
+        assert!(is_loopback_host("::ffff:127.0.0.1"));
+
+        assert!(!is_loopback_host("smtp.example.com"));
+        assert!(!is_loopback_host("10.0.0.1"));
+        assert!(!is_loopback_host("[2001:db8::1]"));
+    }
+}
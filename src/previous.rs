@@ -0,0 +1,188 @@
+//! The previous event of each session, for `{{prev.field}}` template placeholders.
+//!
+//! `boopifier serve` keeps each session's last event in memory
+//! ([`PreviousEvents`]). One-shot runs can't, so they persist it to a small
+//! file per session instead ([`load_persisted`] and [`persist`]), readable only
+//! by the user, and only when the config uses `{{prev.*}}`. Either way, a
+//! `SessionEnd` event forgets its session; persisted sessions that never end
+//! are pruned after [`MAX_AGE`].
+
+use crate::error::Result;
+use crate::event::Event;
+use crate::handlers::write_private;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// How long a persisted previous event is kept without its session sending another.
+pub const MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// In-memory previous events by session id, for long-running mode.
+#[derive(Debug, Default)]
+pub struct PreviousEvents {
+    events: Mutex<HashMap<String, Arc<Value>>>,
+}
+
+impl PreviousEvents {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `event` as its session's latest, returning the event it replaces.
+    ///
+    /// Events without a `session_id` are neither recorded nor given a previous event.
+    pub fn swap(&self, event: &Event) -> Option<Arc<Value>> {
+        let session = event.get_str("session_id")?;
+        let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        if is_session_end(event) {
+            return events.remove(session);
+        }
+        events.insert(session.to_string(), Arc::new(event.as_value()))
+    }
+}
+
+/// Directory one-shot runs persist previous events to.
+pub fn default_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".claude/boopifier-last-events")
+}
+
+/// Reads the persisted previous event for `event`'s session, if there is one.
+pub fn load_persisted(dir: &Path, event: &Event) -> Option<Arc<Value>> {
+    let contents = fs::read_to_string(session_file(dir, event)?).ok()?;
+    serde_json::from_str(&contents).ok().map(Arc::new)
+}
+
+/// Persists `event` as its session's latest, or forgets the session on `SessionEnd`.
+///
+/// Files of other sessions not written to for [`MAX_AGE`] are removed.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written or removed.
+pub fn persist(dir: &Path, event: &Event) -> Result<()> {
+    let Some(path) = session_file(dir, event) else {
+        return Ok(());
+    };
+    prune(dir, MAX_AGE);
+    if is_session_end(event) {
+        return match fs::remove_file(&path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        };
+    }
+    write_private(&path, event.as_value().to_string())
+}
+
+/// Removes persisted events older than `max_age`, ignoring errors.
+fn prune(dir: &Path, max_age: Duration) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let stale = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() > max_age);
+        if stale && path.extension().is_some_and(|ext| ext == "json") {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// File holding the previous event of `event`'s session.
+///
+/// `None` when the event has no `session_id`, or one that isn't safe as a file name.
+fn session_file(dir: &Path, event: &Event) -> Option<PathBuf> {
+    let session = event.get_str("session_id")?;
+    let safe = !session.is_empty() && session.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    safe.then(|| dir.join(format!("{}.json", session)))
+}
+
+fn is_session_end(event: &Event) -> bool {
+    event.hook_name() == Some("SessionEnd")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(json: &str) -> Event {
+        Event::from_json(json).unwrap()
+    }
+
+    #[test]
+    fn test_swap_per_session() {
+        let previous = PreviousEvents::new();
+        let first = event(r#"{"session_id": "a", "status": "running"}"#);
+        let other = event(r#"{"session_id": "b", "status": "idle"}"#);
+        let second = event(r#"{"session_id": "a", "status": "done"}"#);
+
+        assert_eq!(previous.swap(&first), None);
+        assert_eq!(previous.swap(&other), None);
+        assert_eq!(previous.swap(&second).unwrap()["status"], "running");
+
+        // Sessions end, and events without one are never tracked
+        let end = event(r#"{"session_id": "a", "hook_event_name": "SessionEnd"}"#);
+        assert_eq!(previous.swap(&end).unwrap()["status"], "done");
+        assert_eq!(previous.swap(&first), None);
+        assert_eq!(previous.swap(&event(r#"{"status": "x"}"#)), None);
+    }
+
+    #[test]
+    fn test_persisted_per_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = event(r#"{"session_id": "a-1", "status": "running"}"#);
+
+        assert_eq!(load_persisted(dir.path(), &first), None);
+        persist(dir.path(), &first).unwrap();
+        let second = event(r#"{"session_id": "a-1", "status": "done"}"#);
+        assert_eq!(load_persisted(dir.path(), &second).unwrap()["status"], "running");
+        assert_eq!(load_persisted(dir.path(), &event(r#"{"session_id": "b"}"#)), None);
+
+        persist(dir.path(), &event(r#"{"session_id": "a-1", "hook_event_name": "SessionEnd"}"#)).unwrap();
+        assert_eq!(load_persisted(dir.path(), &second), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_persisted_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        persist(dir.path(), &event(r#"{"session_id": "s", "prompt": "secret"}"#)).unwrap();
+        let mode = fs::metadata(dir.path().join("s.json")).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_prune_old_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("old.json");
+        fs::write(&old, "{}").unwrap();
+        let week_ago = SystemTime::now() - MAX_AGE - Duration::from_secs(60);
+        fs::File::options().write(true).open(&old).unwrap().set_modified(week_ago).unwrap();
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        persist(dir.path(), &event(r#"{"session_id": "new"}"#)).unwrap();
+        assert!(!old.exists());
+        assert!(dir.path().join("new.json").exists());
+        assert!(dir.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn test_unsafe_session_ids_not_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let escape = event(r#"{"session_id": "../escape", "status": "x"}"#);
+        persist(dir.path(), &escape).unwrap();
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+        assert_eq!(load_persisted(dir.path(), &escape), None);
+    }
+}
@@ -0,0 +1,274 @@
+//! Secret resolution for configuration values.
+//!
+//! Config strings may contain `{{scheme.arg}}` placeholders that are replaced with
+//! secrets when the config is loaded. The built-in [`DefaultSecretResolver`] handles
+//! `env`, `file` and `dotenv`; embedders can implement [`SecretResolver`] to add
+//! their own schemes (e.g. a secrets manager) and pass it to
+//! [`Config::load_with_resolver`](crate::config::Config::load_with_resolver).
+
+use crate::credential_cache::CredentialCache;
+use crate::error::{NotificationError, Result};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+
+/// Resolves `{{scheme.arg}}` secret placeholders.
+pub trait SecretResolver {
+    /// Whether this resolver handles `scheme`.
+    ///
+    /// Placeholders with unsupported schemes (such as `{{capture.tool}}`) are left
+    /// untouched for template rendering at dispatch time.
+    fn supports(&self, scheme: &str) -> bool;
+
+    /// Resolves the secret for `scheme` and `arg` (everything after the first `.`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the secret cannot be found or read.
+    fn resolve(&self, scheme: &str, arg: &str) -> Result<String>;
+}
+
+/// Resolver for the built-in schemes:
+///
+/// - `{{env.VAR_NAME}}` - Environment variables
+/// - `{{file.path/to/file}}` - Read from file (trimmed; optionally cached)
+/// - `{{dotenv.path/to/.env:KEY}}` - Read one key from a dotenv file
+#[derive(Default)]
+pub struct DefaultSecretResolver {
+    cache: Option<CredentialCache>,
+}
+
+impl DefaultSecretResolver {
+    /// Creates a resolver that reads `{{file.*}}` secrets directly.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a resolver that caches `{{file.*}}` secrets in `cache`.
+    pub fn with_cache(cache: Option<CredentialCache>) -> Self {
+        Self { cache }
+    }
+}
+
+impl SecretResolver for DefaultSecretResolver {
+    fn supports(&self, scheme: &str) -> bool {
+        matches!(scheme, "env" | "file" | "dotenv")
+    }
+
+    fn resolve(&self, scheme: &str, arg: &str) -> Result<String> {
+        match scheme {
+            "env" => std::env::var(arg).map_err(|_| {
+                NotificationError::InvalidConfig(format!("Environment variable not found: {}", arg))
+            }),
+            "file" => {
+                let read_file = || read_secret_file(arg).map(|content| content.trim().to_string());
+                match &self.cache {
+                    Some(cache) => cache.get_or_fetch(&format!("file:{}", arg), read_file),
+                    None => read_file(),
+                }
+            }
+            "dotenv" => {
+                let (file_path, key) = arg.rsplit_once(':').ok_or_else(|| {
+                    NotificationError::InvalidConfig(format!(
+                        "Dotenv secret must be {{{{dotenv.path:KEY}}}}, got: {}",
+                        arg
+                    ))
+                })?;
+                let content = read_secret_file(file_path)?;
+                parse_dotenv_value(&content, key).ok_or_else(|| {
+                    NotificationError::InvalidConfig(format!("Key {} not found in {}", key, file_path))
+                })
+            }
+            _ => Err(NotificationError::InvalidConfig(format!(
+                "Unsupported secret scheme: {}",
+                scheme
+            ))),
+        }
+    }
+}
+
+/// Wraps a resolver so each distinct `{{scheme.arg}}` is resolved at most once.
+///
+/// Lives for a single config load, so handlers sharing a secret read it once and
+/// all see the same value even if the file changes mid-load.
+pub(crate) struct MemoizedResolver<'a> {
+    inner: &'a dyn SecretResolver,
+    resolved: RefCell<HashMap<String, String>>,
+}
+
+impl<'a> MemoizedResolver<'a> {
+    pub(crate) fn new(inner: &'a dyn SecretResolver) -> Self {
+        Self {
+            inner,
+            resolved: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl SecretResolver for MemoizedResolver<'_> {
+    fn supports(&self, scheme: &str) -> bool {
+        self.inner.supports(scheme)
+    }
+
+    fn resolve(&self, scheme: &str, arg: &str) -> Result<String> {
+        let key = format!("{}.{}", scheme, arg);
+        if let Some(value) = self.resolved.borrow().get(&key) {
+            return Ok(value.clone());
+        }
+        let value = self.inner.resolve(scheme, arg)?;
+        self.resolved.borrow_mut().insert(key, value.clone());
+        Ok(value)
+    }
+}
+
+/// Replaces every `{{scheme.arg}}` placeholder whose scheme `resolver` supports.
+///
+/// Other `{{...}}` placeholders are kept as-is, and resolved values are not
+/// scanned again.
+///
+/// # Errors
+///
+/// Returns the first error from `resolver`.
+pub fn resolve_placeholders(s: &str, resolver: &dyn SecretResolver) -> Result<String> {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(start) = rest.find("{{") {
+        result.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+
+        let Some(end) = after_open.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let inner = &after_open[..end];
+
+        // Unbalanced `{{`: keep it and rescan from the next opening brace
+        if let Some(nested) = inner.find("{{") {
+            result.push_str(&rest[start..start + 2 + nested]);
+            rest = &after_open[nested..];
+            continue;
+        }
+
+        match inner.split_once('.') {
+            Some((scheme, arg)) if resolver.supports(scheme) => {
+                result.push_str(&resolver.resolve(scheme, arg)?);
+            }
+            _ => {
+                result.push_str("{{");
+                result.push_str(inner);
+                result.push_str("}}");
+            }
+        }
+        rest = &after_open[end + 2..];
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+fn read_secret_file(path: &str) -> Result<String> {
+    let expanded_path = shellexpand::tilde(path);
+    fs::read_to_string(expanded_path.as_ref())
+        .map_err(|e| NotificationError::InvalidConfig(format!("Failed to read file {}: {}", path, e)))
+}
+
+/// Looks up `key` in dotenv-style `KEY=value` content.
+///
+/// Blank lines, `#` comments and a leading `export` are ignored. Values are
+/// trimmed, and matching single or double quotes around them are removed.
+fn parse_dotenv_value(content: &str, key: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (name, value) = line.split_once('=')?;
+        if name.trim() != key {
+            return None;
+        }
+
+        let value = value.trim();
+        let unquoted = ['"', '\'']
+            .iter()
+            .find_map(|&q| value.strip_prefix(q)?.strip_suffix(q))
+            .unwrap_or(value);
+        Some(unquoted.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Resolver that only knows a fixed `vault` scheme.
+    struct StaticResolver;
+
+    impl SecretResolver for StaticResolver {
+        fn supports(&self, scheme: &str) -> bool {
+            scheme == "vault"
+        }
+
+        fn resolve(&self, _scheme: &str, arg: &str) -> Result<String> {
+            Ok(format!("<{}>", arg))
+        }
+    }
+
+    #[test]
+    fn test_resolve_placeholders_leaves_other_templates() {
+        let resolved = resolve_placeholders(
+            "{{vault.a}} {{hook_event_name}} {{capture.tool}} {{env.HOME}} {{vault.b/c}}",
+            &StaticResolver,
+        )
+        .unwrap();
+        assert_eq!(
+            resolved,
+            "<a> {{hook_event_name}} {{capture.tool}} {{env.HOME}} <b/c>"
+        );
+    }
+
+    #[test]
+    fn test_resolve_placeholders_unbalanced() {
+        assert_eq!(
+            resolve_placeholders("{{ {{vault.x}} {{open", &StaticResolver).unwrap(),
+            "{{ <x> {{open"
+        );
+    }
+
+    /// Counts how often each secret reaches the wrapped resolver.
+    #[derive(Default)]
+    struct CountingResolver {
+        calls: RefCell<Vec<String>>,
+    }
+
+    impl SecretResolver for CountingResolver {
+        fn supports(&self, scheme: &str) -> bool {
+            scheme == "vault"
+        }
+
+        fn resolve(&self, _scheme: &str, arg: &str) -> Result<String> {
+            self.calls.borrow_mut().push(arg.to_string());
+            Ok(format!("<{}>", arg))
+        }
+    }
+
+    #[test]
+    fn test_memoized_resolver_resolves_once() {
+        let counting = CountingResolver::default();
+        let memoized = MemoizedResolver::new(&counting);
+
+        let first = resolve_placeholders("{{vault.a}} {{vault.b}} {{vault.a}}", &memoized).unwrap();
+        let second = resolve_placeholders("{{vault.a}}", &memoized).unwrap();
+        assert_eq!(first, "<a> <b> <a>");
+        assert_eq!(second, "<a>");
+        assert_eq!(*counting.calls.borrow(), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_resolved_values_not_rescanned() {
+        std::env::set_var("TEST_SECRETS_NESTED", "{{env.TEST_SECRETS_NESTED}}");
+        let resolved = resolve_placeholders("{{env.TEST_SECRETS_NESTED}}", &DefaultSecretResolver::new()).unwrap();
+        assert_eq!(resolved, "{{env.TEST_SECRETS_NESTED}}");
+    }
+}
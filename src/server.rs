@@ -0,0 +1,718 @@
+//! Long-running server mode.
+//!
+//! `boopifier serve` accepts events as JSON `POST /event` requests (and, on Unix,
+//! as newline-delimited JSON on a local socket) and runs each through the
+//! configured handlers, so a `forward` handler on another machine can deliver
+//! notifications here. Handlers and their connections stay warm between events,
+//! and the config file is reloaded when it changes. Only built with the `server`
+//! feature.
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::handlers::HandlerRegistry;
+use crate::hooks::{format_response, HandlerOutcome};
+use crate::net;
+use crate::previous::PreviousEvents;
+use crate::{dispatch_event_timed, Event};
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::Router;
+use serde_json::{json, Value};
+use std::fs;
+use std::future::Future;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+
+/// Default port for `boopifier serve`.
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// Environment variable holding the bearer token clients must send.
+pub const TOKEN_ENV: &str = "BOOPIFIER_SERVE_TOKEN";
+
+/// Event server: the config and handlers that received events run through.
+///
+/// # Examples
+///
+/// ```no_run
+/// use boopifier::server::Server;
+/// use boopifier::{Config, HandlerRegistry};
+///
+/// # async fn run() -> std::io::Result<()> {
+/// let path = ".claude/boopifier.json";
+/// let listener = tokio::net::TcpListener::bind("127.0.0.1:7878").await?;
+/// Server::new(Config::load(path).unwrap(), HandlerRegistry::new())
+///     .with_token(Some("s3cret".to_string()))
+///     .with_reload(path, None)
+///     .serve(listener, async {
+///         let _ = tokio::signal::ctrl_c().await;
+///     })
+///     .await
+/// # }
+/// ```
+pub struct Server {
+    config: RwLock<Arc<Config>>,
+    registry: HandlerRegistry,
+    /// Last event of each session, for `{{prev.field}}` placeholders
+    previous: PreviousEvents,
+    token: Option<String>,
+    pretty: bool,
+    reload: Option<ConfigReload>,
+    #[cfg(unix)]
+    socket: Option<(PathBuf, std::os::unix::net::UnixListener)>,
+}
+
+/// Where to reload the config from when the file changes.
+struct ConfigReload {
+    path: PathBuf,
+    project_dir: Option<String>,
+    /// Modification time of the currently loaded file
+    modified: Mutex<Option<SystemTime>>,
+}
+
+impl Server {
+    /// Creates a server that accepts events without authentication.
+    pub fn new(config: Config, registry: HandlerRegistry) -> Self {
+        Self {
+            config: RwLock::new(Arc::new(config)),
+            registry,
+            previous: PreviousEvents::new(),
+            token: None,
+            pretty: false,
+            reload: None,
+            #[cfg(unix)]
+            socket: None,
+        }
+    }
+
+    /// Requires `Authorization: Bearer <token>` on every HTTP request when set.
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token.filter(|t| !t.is_empty());
+        self
+    }
+
+    /// Pretty-prints HTTP reply bodies. Socket replies always stay on one line.
+    pub fn with_pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Reloads the config from `path` whenever the file's modification time changes.
+    ///
+    /// `project_dir` selects the project overrides applied after each reload. If a
+    /// reload fails, the previous config stays in use.
+    pub fn with_reload(mut self, path: impl Into<PathBuf>, project_dir: Option<String>) -> Self {
+        let path = path.into();
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        self.reload = Some(ConfigReload {
+            path,
+            project_dir,
+            modified: Mutex::new(modified),
+        });
+        self
+    }
+
+    /// Also accepts newline-delimited JSON events on a Unix socket at `path`.
+    ///
+    /// Each line gets a one-line JSON reply. The socket is only protected by its
+    /// file permissions, so the token does not apply to it. A stale socket file
+    /// left by a previous run is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket cannot be bound.
+    #[cfg(unix)]
+    pub fn with_socket(mut self, path: impl Into<PathBuf>) -> io::Result<Self> {
+        use std::os::unix::fs::FileTypeExt;
+
+        let path = path.into();
+        if fs::symlink_metadata(&path).is_ok_and(|m| m.file_type().is_socket()) {
+            fs::remove_file(&path)?;
+        }
+        let listener = std::os::unix::net::UnixListener::bind(&path)?;
+        listener.set_nonblocking(true)?;
+        self.socket = Some((path, listener));
+        Ok(self)
+    }
+
+    /// Serves requests on `listener` until `shutdown` completes.
+    ///
+    /// On shutdown the server stops accepting connections and waits for events
+    /// already being handled to finish.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if accepting connections fails.
+    pub async fn serve<F>(mut self, listener: TcpListener, shutdown: F) -> io::Result<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        #[cfg(unix)]
+        let socket = self.socket.take();
+        let server = Arc::new(self);
+        let (stop_tx, mut stop_rx) = watch::channel(false);
+
+        #[cfg(unix)]
+        let socket = match socket {
+            Some((path, listener)) => {
+                let listener = tokio::net::UnixListener::from_std(listener)?;
+                let task = tokio::spawn(serve_socket(server.clone(), listener, stop_rx.clone()));
+                Some((path, task))
+            }
+            None => None,
+        };
+
+        let router = Router::new()
+            .route("/event", post(receive_event))
+            .with_state(server.clone());
+        let http = axum::serve(listener, router).with_graceful_shutdown(async move {
+            let _ = stop_rx.wait_for(|stop| *stop).await;
+        });
+
+        // Relay the caller's signal to every listener
+        tokio::spawn(async move {
+            shutdown.await;
+            let _ = stop_tx.send(true);
+        });
+        let result = http.await;
+
+        #[cfg(unix)]
+        if let Some((path, task)) = socket {
+            let _ = task.await;
+            let _ = fs::remove_file(path);
+        }
+
+        result
+    }
+
+    /// Returns true if the request carries the configured token (or none is required).
+    fn authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(token) = &self.token else {
+            return true;
+        };
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()))
+    }
+
+    /// Checks the request could not have come from a web page, returning the
+    /// status and message to reject it with otherwise.
+    ///
+    /// Browsers can send cross-origin `POST`s only with form or `text/plain`
+    /// bodies, so JSON is required. Without a token, the `Host` header must also
+    /// name a loopback address, which a DNS-rebound page cannot forge.
+    fn check_origin(&self, headers: &HeaderMap) -> std::result::Result<(), (StatusCode, &'static str)> {
+        let is_json = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(';').next())
+            .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case("application/json"));
+        if !is_json {
+            return Err((StatusCode::UNSUPPORTED_MEDIA_TYPE, "Content-Type must be application/json"));
+        }
+
+        if self.token.is_none() {
+            let loopback = headers
+                .get(header::HOST)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|host| net::is_loopback_host(net::split_host_port(host).0));
+            if !loopback {
+                return Err((StatusCode::FORBIDDEN, "Host must be a loopback address when no token is set"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the current config, first reloading it if the file changed.
+    fn current_config(&self) -> Arc<Config> {
+        if let Some(reload) = &self.reload {
+            if let Err(e) = self.reload_if_changed(reload) {
+                eprintln!("boopifier: keeping previous config: {}", e);
+            }
+        }
+        self.config.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn reload_if_changed(&self, reload: &ConfigReload) -> Result<()> {
+        let mut modified = reload.modified.lock().unwrap_or_else(|e| e.into_inner());
+        let current = fs::metadata(&reload.path).and_then(|m| m.modified()).ok();
+        if current == *modified {
+            return Ok(());
+        }
+        // Don't retry a broken file until it changes again
+        *modified = current;
+
+        let mut config = Config::load(&reload.path)?;
+        if let Some(project_dir) = &reload.project_dir {
+            config.apply_overrides(project_dir);
+        }
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(config);
+        Ok(())
+    }
+
+    /// Builds a JSON HTTP response, pretty-printed if configured.
+    fn reply(&self, status: StatusCode, body: &Value) -> Response {
+        let content_type = [(header::CONTENT_TYPE, "application/json")];
+        (status, content_type, format_response(body, self.pretty)).into_response()
+    }
+
+    /// Parses and dispatches one event, describing the result as JSON.
+    async fn process(&self, body: &str) -> (StatusCode, Value) {
+        let mut event = match Event::from_json(body) {
+            Ok(event) => event,
+            Err(e) => return (StatusCode::BAD_REQUEST, json!({ "error": format!("invalid event JSON: {}", e) })),
+        };
+        event.context.previous = self.previous.swap(&event);
+
+        let config = self.current_config();
+        match dispatch_event_timed(&event, &config, &self.registry).await {
+            Ok(timed) => {
+                let outcomes: Vec<Value> = timed
+                    .iter()
+                    .map(|t| outcome_json(&t.name, &t.outcome))
+                    .collect();
+                (StatusCode::OK, json!({ "outcomes": outcomes }))
+            }
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, json!({ "error": e.to_string() })),
+        }
+    }
+}
+
+/// `POST /event`: dispatches the JSON body and reports each handler's outcome.
+async fn receive_event(State(server): State<Arc<Server>>, headers: HeaderMap, body: String) -> Response {
+    if !server.authorized(&headers) {
+        let error = json!({ "error": "missing or invalid bearer token" });
+        return server.reply(StatusCode::UNAUTHORIZED, &error);
+    }
+    if let Err((status, message)) = server.check_origin(&headers) {
+        return server.reply(status, &json!({ "error": message }));
+    }
+
+    let (status, body) = server.process(&body).await;
+    server.reply(status, &body)
+}
+
+/// Accepts socket connections until shutdown, then waits for open ones to finish.
+#[cfg(unix)]
+async fn serve_socket(server: Arc<Server>, listener: tokio::net::UnixListener, mut stop: watch::Receiver<bool>) {
+    let mut connections = tokio::task::JoinSet::new();
+    let connection_stop = stop.clone();
+
+    loop {
+        tokio::select! {
+            _ = stop.wait_for(|stop| *stop) => break,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    connections.spawn(handle_connection(server.clone(), stream, connection_stop.clone()));
+                }
+                Err(e) => eprintln!("boopifier: socket accept failed: {}", e),
+            },
+        }
+    }
+
+    while connections.join_next().await.is_some() {}
+}
+
+/// Replies to each newline-delimited event on a connection with one JSON line.
+///
+/// The connection closes at EOF or on shutdown; an event already read is still
+/// handled first.
+#[cfg(unix)]
+async fn handle_connection(server: Arc<Server>, stream: tokio::net::UnixStream, mut stop: watch::Receiver<bool>) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    loop {
+        let line = tokio::select! {
+            _ = stop.wait_for(|stop| *stop) => return,
+            line = lines.next_line() => match line {
+                Ok(Some(line)) => line,
+                _ => return,
+            },
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (_, reply) = server.process(&line).await;
+        if writer.write_all(format!("{}\n", reply).as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Describes a handler outcome as `{"handler", "status", "message"}`, plus
+/// `"reason"` for suppressed handlers.
+fn outcome_json(name: &str, outcome: &HandlerOutcome) -> Value {
+    let (status, message) = match outcome {
+        HandlerOutcome::SuppressOutput(outcome) => return outcome_json(name, outcome),
+        HandlerOutcome::Success => ("success", None),
+        HandlerOutcome::SystemMessage(msg) | HandlerOutcome::Block(msg) | HandlerOutcome::AdditionalContext(msg) => {
+            ("success", Some(msg.as_str()))
+        }
+        HandlerOutcome::Error(msg) => ("error", Some(msg.as_str())),
+        HandlerOutcome::Suppressed { reason, message } => {
+            return json!({ "handler": name, "status": "suppressed", "reason": reason, "message": message });
+        }
+        HandlerOutcome::Interactive(_) => ("interactive", None),
+    };
+    json!({ "handler": name, "status": status, "message": message })
+}
+
+/// Compares two byte strings without exiting early on the first difference.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::forward::ForwardHandler;
+    use crate::handlers::{Handler, HandlerResult};
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use tokio::sync::{oneshot, Notify};
+
+    const RECORD_CONFIG: &str = r#"{"handlers": [{"name": "record", "type": "recording", "config": {}}]}"#;
+
+    /// Records the `hook_event_name` (or rendered `label`) of each event it handles, after an optional delay.
+    struct RecordingHandler {
+        received: Arc<Mutex<Vec<String>>>,
+        started: Arc<Notify>,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl Handler for RecordingHandler {
+        fn handler_type(&self) -> &str {
+            "recording"
+        }
+
+        async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+            self.started.notify_one();
+            tokio::time::sleep(self.delay).await;
+            let name = match config.get("label").and_then(Value::as_str) {
+                Some(label) => crate::template::render(label, event),
+                None => event.hook_name().unwrap_or_default().to_string(),
+            };
+            self.received.lock().unwrap().push(name);
+            Ok(())
+        }
+    }
+
+    /// A test server plus what its recording handler has seen.
+    struct TestServer {
+        url: String,
+        received: Arc<Mutex<Vec<String>>>,
+        started: Arc<Notify>,
+        stop: oneshot::Sender<()>,
+        task: tokio::task::JoinHandle<io::Result<()>>,
+    }
+
+    async fn spawn_server(configure: impl FnOnce(Server) -> Server, delay: Duration) -> TestServer {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let started = Arc::new(Notify::new());
+        let mut registry = HandlerRegistry::new();
+        registry.register(Box::new(RecordingHandler {
+            received: received.clone(),
+            started: started.clone(),
+            delay,
+        }));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/event", listener.local_addr().unwrap());
+        let server = configure(Server::new(Config::from_json(RECORD_CONFIG).unwrap(), registry));
+        let (stop, stopped) = oneshot::channel();
+        let task = tokio::spawn(server.serve(listener, async {
+            let _ = stopped.await;
+        }));
+
+        TestServer {
+            url,
+            received,
+            started,
+            stop,
+            task,
+        }
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"s3cret", b"s3cret"));
+        assert!(!constant_time_eq(b"s3cret", b"s3crex"));
+        assert!(!constant_time_eq(b"s3cret", b"s3cret2"));
+    }
+
+    #[test]
+    fn test_outcome_json_records_suppression_reason() {
+        let outcome = HandlerOutcome::Suppressed {
+            reason: crate::SuppressionReason::NoDevice,
+            message: "sound: no audio output device".to_string(),
+        };
+        assert_eq!(
+            outcome_json("sound", &outcome),
+            json!({
+                "handler": "sound",
+                "status": "suppressed",
+                "reason": "no_device",
+                "message": "sound: no audio output device"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forward_round_trip() {
+        let server = spawn_server(|s| s.with_token(Some("s3cret".to_string())), Duration::ZERO).await;
+        let event = Event::from_json(r#"{"hook_event_name": "Stop", "session_id": "abc"}"#).unwrap();
+
+        let mut config = HashMap::new();
+        config.insert("url".to_string(), json!(server.url));
+        config.insert("token".to_string(), json!("s3cret"));
+        ForwardHandler.handle(&event, &config).await.unwrap();
+        assert_eq!(*server.received.lock().unwrap(), vec!["Stop".to_string()]);
+
+        // A wrong token is rejected before any handler runs
+        config.insert("token".to_string(), json!("guess"));
+        let error = ForwardHandler.handle(&event, &config).await.unwrap_err().to_string();
+        assert!(error.contains("401"), "{}", error);
+        assert_eq!(server.received.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reports_outcomes_and_rejects_bad_json() {
+        let server = spawn_server(|s| s, Duration::ZERO).await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(&server.url)
+            .header("content-type", "application/json")
+            .body(r#"{"hook_event_name": "Notification"}"#)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(
+            body,
+            json!({"outcomes": [{"handler": "record", "status": "success", "message": null}]})
+        );
+
+        let response = client.post(&server.url).header("content-type", "application/json").body("not json").send().await.unwrap();
+        assert_eq!(response.status(), 400);
+        assert_eq!(response.headers()["content-type"], "application/json");
+        assert_eq!(*server.received.lock().unwrap(), vec!["Notification".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_browser_requests() {
+        let server = spawn_server(|s| s, Duration::ZERO).await;
+        let client = reqwest::Client::new();
+        let event = r#"{"hook_event_name": "Stop"}"#;
+
+        // A cross-origin form or text/plain POST never reaches the handlers
+        for content_type in [None, Some("text/plain"), Some("application/x-www-form-urlencoded")] {
+            let mut request = client.post(&server.url).body(event);
+            if let Some(content_type) = content_type {
+                request = request.header("content-type", content_type);
+            }
+            assert_eq!(request.send().await.unwrap().status(), 415, "{:?}", content_type);
+        }
+
+        // Without a token, a DNS-rebound name in Host is refused
+        let response = client
+            .post(&server.url)
+            .header("content-type", "application/json")
+            .header("host", "attacker.example:7878")
+            .body(event)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+        assert!(server.received.lock().unwrap().is_empty());
+
+        let response = client
+            .post(&server.url)
+            .header("content-type", "application/json; charset=utf-8")
+            .header("host", "localhost:7878")
+            .body(event)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        // With a token, any Host is accepted from a client that has it
+        let server = spawn_server(|s| s.with_token(Some("s3cret".to_string())), Duration::ZERO).await;
+        let response = client
+            .post(&server.url)
+            .bearer_auth("s3cret")
+            .header("content-type", "application/json")
+            .header("host", "desktop.lan:7878")
+            .body(event)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_previous_event_per_session() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = HandlerRegistry::new();
+        registry.register(Box::new(RecordingHandler {
+            received: received.clone(),
+            started: Arc::new(Notify::new()),
+            delay: Duration::ZERO,
+        }));
+        let config = Config::from_json(
+            r#"{"handlers": [{"name": "record", "type": "recording", "config": {"label": "{{prev.status}} -> {{status}}"}}]}"#,
+        )
+        .unwrap();
+        let server = Server::new(config, registry);
+
+        for body in [
+            r#"{"session_id": "a", "status": "running"}"#,
+            r#"{"session_id": "b", "status": "idle"}"#,
+            r#"{"session_id": "a", "status": "done"}"#,
+            r#"{"session_id": "a", "hook_event_name": "SessionEnd", "status": "ended"}"#,
+            r#"{"session_id": "a", "status": "restarted"}"#,
+        ] {
+            assert_eq!(server.process(body).await.0, StatusCode::OK);
+        }
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![
+                "{{prev.status}} -> running",
+                "{{prev.status}} -> idle",
+                "running -> done",
+                "done -> ended",
+                "{{prev.status}} -> restarted",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pretty_replies() {
+        let server = spawn_server(|s| s.with_pretty(true), Duration::ZERO).await;
+
+        let response = reqwest::Client::new()
+            .post(&server.url)
+            .header("content-type", "application/json")
+            .body(r#"{"hook_event_name": "Stop"}"#)
+            .send()
+            .await
+            .unwrap();
+        let text = response.text().await.unwrap();
+        assert!(text.lines().count() > 1, "{}", text);
+        assert_eq!(
+            serde_json::from_str::<Value>(&text).unwrap(),
+            json!({"outcomes": [{"handler": "record", "status": "success", "message": null}]})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reloads_changed_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("boopifier.json");
+        fs::write(&path, RECORD_CONFIG).unwrap();
+
+        let server = spawn_server(|s| s.with_reload(&path, None), Duration::ZERO).await;
+        let client = reqwest::Client::new();
+        let post = || async {
+            let response = client
+                .post(&server.url)
+                .header("content-type", "application/json")
+                .body(r#"{"hook_event_name": "Stop"}"#)
+                .send()
+                .await
+                .unwrap();
+            response.json::<Value>().await.unwrap()
+        };
+        assert_eq!(post().await["outcomes"][0]["handler"], "record");
+
+        // Set the time explicitly so the change is seen on coarse-grained filesystems
+        let bump_mtime = |secs| {
+            let file = fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(SystemTime::now() + Duration::from_secs(secs)).unwrap();
+        };
+        fs::write(&path, RECORD_CONFIG.replace("\"record\"", "\"renamed\"")).unwrap();
+        bump_mtime(10);
+        assert_eq!(post().await["outcomes"][0]["handler"], "renamed");
+
+        // A broken file keeps the last good config
+        fs::write(&path, "{ not json").unwrap();
+        bump_mtime(20);
+        assert_eq!(post().await["outcomes"][0]["handler"], "renamed");
+    }
+
+    #[tokio::test]
+    async fn test_graceful_shutdown_finishes_in_flight_events() {
+        let server = spawn_server(|s| s, Duration::from_millis(200)).await;
+
+        let url = server.url.clone();
+        let request = tokio::spawn(async move {
+            reqwest::Client::new()
+                .post(url)
+                .header("content-type", "application/json")
+                .body(r#"{"hook_event_name": "Stop"}"#)
+                .send()
+                .await
+                .map(|r| r.status())
+        });
+        server.started.notified().await;
+        server.stop.send(()).unwrap();
+
+        assert_eq!(request.await.unwrap().unwrap(), 200);
+        server.task.await.unwrap().unwrap();
+        assert_eq!(*server.received.lock().unwrap(), vec!["Stop".to_string()]);
+
+        // Nothing is listening any more
+        assert!(reqwest::Client::new().post(&server.url).body("{}").send().await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket_ndjson() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("boopifier.sock");
+        // A stale socket file from a previous run is replaced
+        std::os::unix::net::UnixListener::bind(&socket).unwrap();
+
+        let server = spawn_server(|s| s.with_socket(&socket).unwrap(), Duration::ZERO).await;
+        let stream = tokio::net::UnixStream::connect(&socket).await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        writer
+            .write_all(b"{\"hook_event_name\": \"Stop\"}\n\nnot json\n{\"hook_event_name\": \"Notification\"}\n")
+            .await
+            .unwrap();
+        writer.shutdown().await.unwrap();
+
+        let mut lines = BufReader::new(reader).lines();
+        let mut replies = Vec::new();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            replies.push(serde_json::from_str::<Value>(&line).unwrap());
+        }
+        assert_eq!(replies.len(), 3);
+        assert_eq!(replies[0]["outcomes"][0]["status"], "success");
+        assert!(replies[1]["error"].as_str().unwrap().contains("invalid event JSON"));
+        assert_eq!(
+            *server.received.lock().unwrap(),
+            vec!["Stop".to_string(), "Notification".to_string()]
+        );
+
+        server.stop.send(()).unwrap();
+        server.task.await.unwrap().unwrap();
+        assert!(!socket.exists());
+    }
+}
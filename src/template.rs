@@ -0,0 +1,490 @@
+//! Template rendering for handler configuration values.
+//!
+//! Handlers use `{{field_name}}` placeholders in their config strings, which are
+//! substituted with top-level event fields at dispatch time.
+//!
+//! `{{transcript_tail:N}}` expands to the last `N` lines of the session
+//! transcript named by the event's `transcript_path`, and `{{prev.field}}`
+//! to a field of the session's previous event.
+
+use crate::error::{NotificationError, Result};
+use crate::event::Event;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Bytes read per step when scanning a transcript backwards for its tail.
+const TAIL_CHUNK_BYTES: u64 = 8 * 1024;
+
+/// Extra values available to templates beyond the event's own fields.
+///
+/// Filled in by the dispatcher; rendered via reserved placeholders such as
+/// `{{override.pattern}}`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TemplateContext {
+    /// `path_pattern` of the project override that was applied, if any
+    pub override_pattern: Option<String>,
+
+    /// Named regex groups bound by the handler's match rules
+    pub captures: HashMap<String, String>,
+
+    /// Values pulled out of the event by the handler's `extract` pointers
+    pub extracted: HashMap<String, Value>,
+
+    /// The session's previous event, for `{{prev.field}}` placeholders
+    pub previous: Option<Arc<Value>>,
+
+    /// Name of the handler the event is delivered to
+    pub handler_name: Option<String>,
+}
+
+impl TemplateContext {
+    /// Resolves each `name -> JSON Pointer` in `extract` against `event`.
+    ///
+    /// Pointers that don't resolve are skipped, leaving `{{name}}` unbound.
+    pub fn extract(event: &Event, extract: &HashMap<String, String>) -> HashMap<String, Value> {
+        if extract.is_empty() {
+            return HashMap::new();
+        }
+
+        let data = event.as_value();
+        extract
+            .iter()
+            .filter_map(|(name, pointer)| Some((name.clone(), data.pointer(pointer)?.clone())))
+            .collect()
+    }
+}
+
+/// Renders a template string by substituting `{{field_name}}` placeholders.
+///
+/// String values are inserted as-is; other JSON values use their JSON encoding.
+/// Names bound by the handler's `extract` pointers work like top-level fields,
+/// and dotted paths reach nested values, with numeric segments indexing arrays
+/// (`{{tool_input.files.0}}`).
+/// `{{override.pattern}}` is replaced with the matched override's pattern (or
+/// an empty string), `{{capture.name}}` with a bound regex capture,
+/// `{{prev.field}}` with a field of the session's previous event (see
+/// [`previous`](crate::previous)), and `{{transcript_tail:N}}` with the transcript's last `N` lines (empty if the
+/// transcript is missing or unreadable). Other placeholders without a matching
+/// value are left untouched. Substituted values are inserted verbatim, so a
+/// `{{...}}` inside an event field is never expanded.
+///
+/// # Examples
+///
+/// ```
+/// use boopifier::event::Event;
+/// use boopifier::template::render;
+///
+/// let event = Event::from_json(r#"{"tool": "bash", "code": 0}"#).unwrap();
+/// assert_eq!(render("{{tool}} exited {{code}}", &event), "bash exited 0");
+/// ```
+pub fn render(template: &str, event: &Event) -> String {
+    render_escaped(template, event, str::to_string)
+}
+
+/// Like [`render`], but passes every substituted value through `escape` first.
+///
+/// Placeholders left untouched and the template's own text are not escaped.
+///
+/// # Examples
+///
+/// ```
+/// use boopifier::event::Event;
+/// use boopifier::template::render_escaped;
+///
+/// let event = Event::from_json(r#"{"tool": "<bash>"}"#).unwrap();
+/// assert_eq!(render_escaped("<b>{{tool}}</b>", &event, |s| s.replace('<', "&lt;")), "<b>&lt;bash></b>");
+/// ```
+pub fn render_escaped(template: &str, event: &Event, escape: impl Fn(&str) -> String) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    // Single left-to-right scan: each `}}` closes the nearest `{{` before it, and
+    // substituted values are copied verbatim, never scanned again
+    while let Some(close) = rest.find("}}") {
+        let Some(open) = rest[..close].rfind("{{") else {
+            result.push_str(&rest[..close + 2]);
+            rest = &rest[close + 2..];
+            continue;
+        };
+        result.push_str(&rest[..open]);
+
+        let name = &rest[open + 2..close];
+        match resolve_placeholder(name, event) {
+            Some(value) => result.push_str(&escape(&value)),
+            None => result.push_str(&rest[open..close + 2]),
+        }
+        rest = &rest[close + 2..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Looks up the value of a single `{{name}}` placeholder.
+///
+/// Extracted names win over top-level fields of the same name, which in turn win
+/// over the reserved namespaces and dotted paths. Returns `None` for placeholders
+/// that should be left untouched.
+fn resolve_placeholder(name: &str, event: &Event) -> Option<String> {
+    if let Some(value) = event.context.extracted.get(name).or_else(|| event.data.get(name)) {
+        return Some(value_text(value));
+    }
+
+    if name == "override.pattern" {
+        return Some(event.context.override_pattern.clone().unwrap_or_default());
+    }
+    if let Some(capture) = name.strip_prefix("capture.") {
+        return event.context.captures.get(capture).cloned();
+    }
+    if name.starts_with("override.") {
+        return None;
+    }
+    if let Some(path) = name.strip_prefix("prev.") {
+        return value_at_path(event.context.previous.as_deref()?, path).map(value_text);
+    }
+    if let Some(count) = name.strip_prefix("transcript_tail:") {
+        let n = count.trim().parse::<usize>().ok()?;
+        return Some(transcript_tail(event, n));
+    }
+
+    // Dotted paths into nested objects and arrays (e.g. `{{tool_input.files.0}}`)
+    if name.contains('.') {
+        return event.get_nested(name).map(value_text);
+    }
+
+    None
+}
+
+/// Follows a dotted path into `value`, with numeric segments indexing arrays.
+fn value_at_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |current, part| match current {
+        Value::Array(items) => items.get(part.parse::<usize>().ok()?),
+        other => other.get(part),
+    })
+}
+
+/// Strings are inserted as-is; other JSON values use their JSON encoding.
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Returns the last `n` lines of the event's transcript, joined with newlines.
+///
+/// Empty if the event has no `transcript_path` or the transcript can't be read.
+fn transcript_tail(event: &Event, n: usize) -> String {
+    let Some(path) = event.get_str("transcript_path") else {
+        return String::new();
+    };
+
+    let path = shellexpand::tilde(path);
+    read_tail_lines(Path::new(path.as_ref()), n)
+        .map(|lines| lines.join("\n"))
+        .unwrap_or_default()
+}
+
+/// Reads the last `n` lines of the file at `path`.
+///
+/// Reads backwards from the end in chunks, so only the tail of a long
+/// transcript is loaded. A trailing newline does not count as an empty line.
+fn read_tail_lines(path: &Path, n: usize) -> io::Result<Vec<String>> {
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut pos = len;
+    let mut buf = Vec::new();
+
+    // `n` line breaks before the trailing one mean the last `n` lines are complete
+    while pos > 0 {
+        let newlines = buf.iter().filter(|&&b| b == b'\n').count();
+        let trailing = usize::from(buf.last() == Some(&b'\n'));
+        if newlines.saturating_sub(trailing) >= n {
+            break;
+        }
+
+        let step = TAIL_CHUNK_BYTES.min(pos);
+        pos -= step;
+        let mut chunk = vec![0; step as usize];
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buf);
+        buf = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let lines: Vec<&str> = text.lines().collect();
+    let skip = lines.len().saturating_sub(n);
+    Ok(lines[skip..].iter().map(|l| l.to_string()).collect())
+}
+
+/// Reads the handler's `body_file` (if configured) and renders it against the event.
+///
+/// Returns `Ok(None)` when no `body_file` is set, so handlers can fall back to
+/// their inline body setting. The path is tilde-expanded.
+///
+/// # Errors
+///
+/// Returns `NotificationError::InvalidConfig` if the file cannot be read.
+pub fn render_body_file(config: &HashMap<String, Value>, event: &Event) -> Result<Option<String>> {
+    let Some(path) = config.get("body_file").and_then(|v| v.as_str()) else {
+        return Ok(None);
+    };
+
+    let expanded = shellexpand::tilde(path);
+    let template = fs::read_to_string(expanded.as_ref()).map_err(|e| {
+        NotificationError::InvalidConfig(format!("Failed to read body_file {}: {}", path, e))
+    })?;
+
+    Ok(Some(render(&template, event)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_strings_and_values() {
+        let event = Event::from_json(r#"{"tool": "bash", "ok": true}"#).unwrap();
+        assert_eq!(render("{{tool}}: {{ok}}", &event), "bash: true");
+    }
+
+    #[test]
+    fn test_render_nested_paths() {
+        let event = Event::from_json(
+            r#"{"tool_input": {"files": ["src/main.rs", {"path": "b.rs"}], "timeout": 30}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            render("{{tool_input.files.0}}, {{tool_input.files.1.path}} ({{tool_input.timeout}}s)", &event),
+            "src/main.rs, b.rs (30s)"
+        );
+        assert_eq!(render("{{tool_input.files.1}}", &event), r#"{"path":"b.rs"}"#);
+
+        // Unresolved paths stay as written
+        assert_eq!(
+            render("{{tool_input.files.5}} {{tool_input.missing}} {{ok", &event),
+            "{{tool_input.files.5}} {{tool_input.missing}} {{ok"
+        );
+    }
+
+    #[test]
+    fn test_render_override_pattern() {
+        let mut event = Event::from_json(r#"{"message": "done"}"#).unwrap();
+        assert_eq!(render("[{{override.pattern}}] {{message}}", &event), "[] done");
+
+        event.context.override_pattern = Some("/home/user/work/*".to_string());
+        assert_eq!(
+            render("[{{override.pattern}}] {{message}}", &event),
+            "[/home/user/work/*] done"
+        );
+    }
+
+    #[test]
+    fn test_render_captures() {
+        let mut event = Event::from_json(r#"{"message": "use Bash"}"#).unwrap();
+        event.context.captures.insert("tool".to_string(), "Bash".to_string());
+        assert_eq!(
+            render("Approve {{capture.tool}}? {{capture.other}}", &event),
+            "Approve Bash? {{capture.other}}"
+        );
+    }
+
+    #[test]
+    fn test_render_previous_event() {
+        let mut event = Event::from_json(r#"{"status": "failed"}"#).unwrap();
+        let template = "{{prev.status}} -> {{status}} ({{prev.tool_input.files.0}}, {{prev.missing}})";
+
+        // No previous event yet: the placeholders stay as written
+        assert_eq!(render(template, &event), "{{prev.status}} -> failed ({{prev.tool_input.files.0}}, {{prev.missing}})");
+
+        event.context.previous = Some(Arc::new(serde_json::json!({"status": "passing", "tool_input": {"files": ["a.rs"]}})));
+        assert_eq!(render(template, &event), "passing -> failed (a.rs, {{prev.missing}})");
+    }
+
+    #[test]
+    fn test_extract_pointers() {
+        let mut event = Event::from_json(
+            r#"{"tool_input": {"command": "cargo test", "args": ["-q"]}, "cmd": "top-level"}"#,
+        )
+        .unwrap();
+        let extract: HashMap<String, String> = [
+            ("cmd", "/tool_input/command"),
+            ("first_arg", "/tool_input/args/0"),
+            ("missing", "/tool_input/timeout"),
+            ("invalid", "tool_input"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        event.context.extracted = TemplateContext::extract(&event, &extract);
+        assert_eq!(event.context.extracted.len(), 2);
+
+        assert_eq!(
+            render("{{cmd}} {{first_arg}} {{missing}} {{invalid}}", &event),
+            "cargo test -q {{missing}} {{invalid}}"
+        );
+    }
+
+    #[test]
+    fn test_render_body_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("body.txt");
+        fs::write(&path, "Tool {{tool}} finished\n").unwrap();
+
+        let event = Event::from_json(r#"{"tool": "bash"}"#).unwrap();
+        let mut config = HashMap::new();
+        assert_eq!(render_body_file(&config, &event).unwrap(), None);
+
+        config.insert("body_file".to_string(), Value::String(path.display().to_string()));
+        assert_eq!(
+            render_body_file(&config, &event).unwrap(),
+            Some("Tool bash finished\n".to_string())
+        );
+
+        config.insert(
+            "body_file".to_string(),
+            Value::String(dir.path().join("missing.txt").display().to_string()),
+        );
+        assert!(matches!(
+            render_body_file(&config, &event),
+            Err(NotificationError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_render_transcript_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transcript.jsonl");
+        let lines: Vec<String> = (1..=2000).map(|i| format!(r#"{{"line": {}}}"#, i)).collect();
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+        let event = Event::from_json(
+            &serde_json::json!({"hook_event_name": "Stop", "transcript_path": path}).to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            render("{{hook_event_name}}:\n{{transcript_tail:2}}", &event),
+            "Stop:\n{\"line\": 1999}\n{\"line\": 2000}"
+        );
+        assert_eq!(render("[{{transcript_tail:0}}]", &event), "[]");
+        assert_eq!(render("{{transcript_tail:x}}", &event), "{{transcript_tail:x}}");
+
+        // Asking for more lines than exist returns the whole file
+        fs::write(&path, "first\nsecond").unwrap();
+        assert_eq!(render("{{transcript_tail:5}}", &event), "first\nsecond");
+    }
+
+    #[test]
+    fn test_render_transcript_tail_missing() {
+        let event = Event::from_json(r#"{"transcript_path": "/nonexistent/transcript.jsonl"}"#).unwrap();
+        assert_eq!(render("tail: [{{transcript_tail:5}}]", &event), "tail: []");
+
+        let event = Event::from_json(r#"{"hook_event_name": "Stop"}"#).unwrap();
+        assert_eq!(render("tail: [{{transcript_tail:5}}]", &event), "tail: []");
+    }
+
+    #[test]
+    fn test_read_tail_lines_across_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("long.txt");
+        let long_line = "x".repeat(TAIL_CHUNK_BYTES as usize * 2);
+        fs::write(&path, format!("a\n{}\nb\n", long_line)).unwrap();
+
+        assert_eq!(read_tail_lines(&path, 2).unwrap(), vec![long_line.clone(), "b".to_string()]);
+        assert_eq!(read_tail_lines(&path, 3).unwrap(), vec!["a".to_string(), long_line, "b".to_string()]);
+    }
+
+    /// The replace-per-field renderer `render` used before the single-pass scan,
+    /// minus transcript tails, kept as a reference for equivalence tests.
+    fn render_by_replacement(template: &str, event: &Event) -> String {
+        let mut result = template.to_string();
+        for (key, value) in event.context.extracted.iter().chain(&event.data) {
+            result = result.replace(&format!("{{{{{}}}}}", key), &value_text(value));
+        }
+
+        let mut nested = String::new();
+        let mut rest = result.as_str();
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            nested.push_str(&rest[..start]);
+            let path = &after[..end];
+            let value = (path.contains('.') && !path.starts_with("override.") && !path.starts_with("capture."))
+                .then(|| event.get_nested(path))
+                .flatten();
+            match value {
+                Some(value) => nested.push_str(&value_text(value)),
+                None => nested.push_str(&format!("{{{{{}}}}}", path)),
+            }
+            rest = &after[end + 2..];
+        }
+        nested.push_str(rest);
+        result = nested;
+
+        let pattern = event.context.override_pattern.as_deref().unwrap_or("");
+        result = result.replace("{{override.pattern}}", pattern);
+        for (name, value) in &event.context.captures {
+            result = result.replace(&format!("{{{{capture.{}}}}}", name), value);
+        }
+        result
+    }
+
+    #[test]
+    fn test_render_matches_replacement_renderer() {
+        let mut event = Event::from_json(
+            r#"{"tool": "bash", "code": 0, "ok": true, "tool_input": {"files": ["a.rs", {"path": "b.rs"}]},
+                "message": "x < y", "empty": ""}"#,
+        )
+        .unwrap();
+        event.context.override_pattern = Some("~/work/*".to_string());
+        event.context.captures.insert("name".to_string(), "Bash".to_string());
+        event.context.extracted.insert("tool".to_string(), Value::String("extracted".to_string()));
+
+        let templates = [
+            "",
+            "no placeholders",
+            "{{tool}} exited {{code}} ({{ok}})",
+            "{{tool}}{{tool}}{{empty}}|{{message}}",
+            "{{tool_input.files.0}} {{tool_input.files.1.path}} {{tool_input.files.1}} {{tool_input.files.9}}",
+            "[{{override.pattern}}] {{override.other}} {{capture.name}} {{capture.missing}}",
+            "{{missing}} {{ tool }} {{tool",
+            "{{{tool}}} }}{{code}}{{ {{",
+            "{{tool_input}}",
+            "héllo {{message}} wörld {{code}}",
+        ];
+        for template in templates {
+            assert_eq!(render(template, &event), render_by_replacement(template, &event), "{:?}", template);
+        }
+
+        // Many fields and a long template still render the same
+        let data: serde_json::Map<String, Value> =
+            (0..200).map(|i| (format!("field_{}", i), Value::from(i))).collect();
+        let event = Event::from_json(&Value::Object(data).to_string()).unwrap();
+        let template: String = (0..200).map(|i| format!("{{{{field_{}}}}} and {{{{none_{}}}}} ", i, i)).collect();
+        assert_eq!(render(&template, &event), render_by_replacement(&template, &event));
+    }
+
+    #[test]
+    fn test_render_does_not_expand_substituted_values() {
+        let event = Event::from_json(r#"{"message": "see {{secret}}", "secret": "hunter2"}"#).unwrap();
+        assert_eq!(render("{{message}}", &event), "see {{secret}}");
+    }
+
+    #[test]
+    fn test_render_leaves_unknown_placeholders() {
+        let event = Event::from_json(r#"{"tool": "bash"}"#).unwrap();
+        assert_eq!(render("{{tool}} {{missing}}", &event), "bash {{missing}}");
+    }
+}
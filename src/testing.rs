@@ -0,0 +1,353 @@
+//! Test utilities for asserting how a config routes events.
+//!
+//! [`TestHarness`] runs events through the real dispatch path (match rules,
+//! variants, overrides, channel groups, escalations) with every built-in
+//! handler type replaced by a recorder, so nothing is actually sent. Each
+//! [`Dispatch`] reports which handlers fired and the config they were called
+//! with, which can be rendered against the event the handler saw.
+//!
+//! Available in the crate's own tests, and to other crates with the `testing`
+//! feature.
+//!
+//! ```
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> anyhow::Result<()> {
+//! use boopifier::testing::TestHarness;
+//!
+//! let harness = TestHarness::from_json(
+//!     r#"{"handlers": [
+//!         {"name": "bash", "type": "desktop", "match_rules": {"tool_name": "Bash"},
+//!          "config": {"body": "Ran {{tool_input.command}}"}},
+//!         {"name": "edits", "type": "desktop", "match_rules": {"tool_name": "Edit"}, "config": {}}
+//!     ]}"#,
+//! )?;
+//!
+//! let dispatch = harness.run(r#"{"tool_name": "Bash", "tool_input": {"command": "ls"}}"#).await?;
+//! dispatch.assert_fired(&["bash"]).assert_rendered("bash", "body", "Ran ls");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::config::{Config, HandlerConfig};
+use crate::error::NotificationError;
+use crate::event::Event;
+use crate::handlers::{Handler, HandlerRegistry, HandlerResult};
+use crate::hooks::HandlerOutcome;
+use crate::{build_hook_response, dispatch_event_with_options, hook_from_event, template, ProcessOptions, TimedOutcome};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Config key the harness adds to each handler's config to learn which handler a call is for.
+const NAME_KEY: &str = "__test_harness_handler";
+
+/// Runs events through a config with recording handlers in place of the built-in ones.
+///
+/// Handler types the built-in registry doesn't know fail with an unknown
+/// handler type error, as they would outside of tests.
+#[derive(Clone)]
+pub struct TestHarness {
+    config: Config,
+    failing: HashSet<String>,
+    options: ProcessOptions,
+}
+
+impl TestHarness {
+    /// Creates a harness for `config`.
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            failing: HashSet::new(),
+            options: ProcessOptions::default(),
+        }
+    }
+
+    /// Creates a harness for a JSON config.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config is invalid.
+    pub fn from_json(json: &str) -> crate::Result<Self> {
+        Config::from_json(json).map(Self::new)
+    }
+
+    /// Applies the config's project overrides for `project_path`.
+    pub fn project(mut self, project_path: &str) -> Self {
+        self.config.apply_overrides(project_path);
+        self
+    }
+
+    /// Makes the named handler fail, e.g. to exercise channel group fallbacks.
+    pub fn fail(mut self, handler_name: &str) -> Self {
+        self.failing.insert(handler_name.to_string());
+        self
+    }
+
+    /// Dispatches with options, e.g. `ignore_match` as `boopifier test-all` does.
+    pub fn options(mut self, options: ProcessOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Runs one event, returning what each handler was called with.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event JSON is invalid.
+    pub async fn run(&self, event_json: &str) -> anyhow::Result<Dispatch> {
+        let event = Event::from_json(event_json)?;
+
+        let mut config = self.config.clone();
+        config.handlers.iter_mut().chain(config.on_no_match.as_mut()).for_each(tag_handler);
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = HandlerRegistry::new();
+        let types: Vec<String> = registry.list_types().into_iter().map(str::to_string).collect();
+        for handler_type in types {
+            registry.register(Box::new(RecordingHandler {
+                handler_type,
+                failing: self.failing.clone(),
+                calls: calls.clone(),
+            }));
+        }
+
+        let outcomes = dispatch_event_with_options(&event, &config, &registry, &self.options).await?;
+        let response = hook_from_event(&event)
+            .ok()
+            .map(|hook| build_hook_response(hook.as_ref(), &plain_outcomes(&outcomes), &config));
+
+        // Calls run concurrently; report them in dispatch order
+        let mut calls = std::mem::take(&mut *calls.lock().unwrap_or_else(|e| e.into_inner()));
+        calls.sort_by_key(|call: &Call| outcomes.iter().position(|o| o.name == call.name));
+
+        Ok(Dispatch {
+            calls,
+            outcomes,
+            response,
+        })
+    }
+}
+
+/// Adds the handler's name to its config so the recorder can report it.
+fn tag_handler(handler: &mut HandlerConfig) {
+    handler.config.insert(NAME_KEY.to_string(), Value::String(handler.name.clone()));
+}
+
+fn plain_outcomes(timed: &[TimedOutcome]) -> Vec<HandlerOutcome> {
+    timed.iter().map(|t| t.outcome.clone()).collect()
+}
+
+/// A recorded call to a handler.
+#[derive(Debug, Clone)]
+pub struct Call {
+    /// Configured handler name
+    pub name: String,
+    /// Handler type the config asked for
+    pub handler_type: String,
+    /// Config the handler was called with, after any variant was merged in
+    pub config: HashMap<String, Value>,
+    /// Event the handler saw, including its regex captures and extracted values
+    pub event: Event,
+}
+
+impl Call {
+    /// Renders the string config value `key` against the call's event.
+    ///
+    /// `None` if the config has no string under `key`.
+    pub fn rendered(&self, key: &str) -> Option<String> {
+        let value = self.config.get(key)?.as_str()?;
+        Some(template::render(value, &self.event))
+    }
+}
+
+/// What happened when the harness ran an event.
+#[derive(Debug)]
+pub struct Dispatch {
+    /// Handler calls, in dispatch order
+    pub calls: Vec<Call>,
+    /// Outcome of every selected handler, including ones that didn't run
+    pub outcomes: Vec<TimedOutcome>,
+    /// Hook response for the event, if it named a known hook type
+    pub response: Option<Value>,
+}
+
+impl Dispatch {
+    /// Names of the handlers that were called, in dispatch order.
+    pub fn fired(&self) -> Vec<&str> {
+        self.calls.iter().map(|call| call.name.as_str()).collect()
+    }
+
+    /// The call to the named handler.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handler wasn't called.
+    #[track_caller]
+    pub fn call(&self, handler_name: &str) -> &Call {
+        self.calls
+            .iter()
+            .find(|call| call.name == handler_name)
+            .unwrap_or_else(|| panic!("handler '{}' was not called (fired: {:?})", handler_name, self.fired()))
+    }
+
+    /// The named handler's outcome.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handler wasn't selected for the event.
+    #[track_caller]
+    pub fn outcome(&self, handler_name: &str) -> &HandlerOutcome {
+        self.outcomes
+            .iter()
+            .find(|timed| timed.name == handler_name)
+            .map(|timed| &timed.outcome)
+            .unwrap_or_else(|| panic!("handler '{}' has no outcome", handler_name))
+    }
+
+    /// Asserts that exactly these handlers were called, in this order.
+    #[track_caller]
+    pub fn assert_fired(&self, expected: &[&str]) -> &Self {
+        assert_eq!(self.fired(), expected, "handlers fired");
+        self
+    }
+
+    /// Asserts that the named handler's `key` renders to `expected`.
+    #[track_caller]
+    pub fn assert_rendered(&self, handler_name: &str, key: &str, expected: &str) -> &Self {
+        let rendered = self.call(handler_name).rendered(key);
+        assert_eq!(rendered.as_deref(), Some(expected), "'{}' of handler '{}'", key, handler_name);
+        self
+    }
+
+    /// Asserts that the hook response is `expected`.
+    #[track_caller]
+    pub fn assert_response(&self, expected: Value) -> &Self {
+        assert_eq!(self.response.as_ref(), Some(&expected), "hook response");
+        self
+    }
+}
+
+/// Stands in for a built-in handler type, recording every call.
+struct RecordingHandler {
+    handler_type: String,
+    failing: HashSet<String>,
+    calls: Arc<Mutex<Vec<Call>>>,
+}
+
+#[async_trait]
+impl Handler for RecordingHandler {
+    fn handler_type(&self) -> &str {
+        &self.handler_type
+    }
+
+    async fn handle(&self, event: &Event, config: &HashMap<String, Value>) -> HandlerResult<()> {
+        let mut config = config.clone();
+        let name = match config.remove(NAME_KEY) {
+            Some(Value::String(name)) => name,
+            _ => String::new(),
+        };
+        let fail = self.failing.contains(&name);
+
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).push(Call {
+            name,
+            handler_type: self.handler_type.clone(),
+            config,
+            event: event.clone(),
+        });
+
+        match fail {
+            true => Err(NotificationError::SendFailed("failed by the test harness".to_string())),
+            false => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_routing_and_variants() {
+        let harness = TestHarness::from_json(
+            r#"{"handlers": [
+                {"name": "failures", "type": "webhook",
+                 "match_rules": {
+                     "tool_name": "Bash",
+                     "tool_response.stderr": {"regex": "error: (?P<reason>.+)", "bind": true}
+                 },
+                 "config": {"url": "https://hooks.example.com", "text": "Failed: {{capture.reason}}"}},
+                {"name": "bash", "type": "desktop", "match_rules": {"tool_name": "Bash"},
+                 "config": {"summary": "Bash", "body": "{{tool_input.command}}"},
+                 "variants": [{"match_rules": {"tool_input.command": {"contains": "cargo"}},
+                               "config": {"summary": "Cargo"}}]}
+            ]}"#,
+        )
+        .unwrap();
+
+        let event = r#"{"tool_name": "Bash", "tool_input": {"command": "cargo build"}, "tool_response": {"stderr": "error: E0308"}}"#;
+        let dispatch = harness.run(event).await.unwrap();
+        dispatch
+            .assert_fired(&["failures", "bash"])
+            .assert_rendered("failures", "text", "Failed: E0308")
+            .assert_rendered("bash", "summary", "Cargo")
+            .assert_rendered("bash", "body", "cargo build");
+        assert_eq!(dispatch.call("failures").handler_type, "webhook");
+        assert!(!dispatch.call("bash").config.contains_key(NAME_KEY));
+
+        let dispatch = harness.run(r#"{"tool_name": "Read"}"#).await.unwrap();
+        dispatch.assert_fired(&[]);
+    }
+
+    #[tokio::test]
+    async fn test_overrides_and_fallback() {
+        let harness = TestHarness::from_json(
+            r#"{
+                "handlers": [{"name": "base", "type": "desktop", "match_rules": {"tool_name": "Bash"}, "config": {}}],
+                "overrides": [{"path_pattern": "/work/*", "handlers": [
+                    {"name": "work", "type": "webhook", "match_rules": {"tool_name": "Bash"},
+                     "config": {"text": "{{tool_name}} in {{override.pattern}}"}}
+                ]}],
+                "on_no_match": {"name": "fallback", "type": "noop", "config": {}}
+            }"#,
+        )
+        .unwrap();
+
+        let dispatch = harness.run(r#"{"tool_name": "Bash"}"#).await.unwrap();
+        dispatch.assert_fired(&["base"]);
+
+        let work = harness.clone().project("/work/api");
+        let dispatch = work.run(r#"{"tool_name": "Bash"}"#).await.unwrap();
+        dispatch.assert_fired(&["work"]).assert_rendered("work", "text", "Bash in /work/*");
+
+        let dispatch = work.run(r#"{"tool_name": "Edit"}"#).await.unwrap();
+        dispatch.assert_fired(&["fallback"]);
+    }
+
+    #[tokio::test]
+    async fn test_failures_and_response() {
+        let harness = TestHarness::from_json(
+            r#"{"handlers": [
+                {"name": "desktop", "type": "desktop", "channel_group": "alert", "config": {}},
+                {"name": "phone", "type": "ntfy", "channel_group": "alert", "config": {"topic": "claude"}},
+                {"name": "typo", "type": "dekstop", "match_rules": {"tool_name": "Bash"}, "config": {}}
+            ]}"#,
+        )
+        .unwrap()
+        .fail("desktop");
+
+        let dispatch = harness
+            .run(r#"{"hook_event_name": "Notification", "message": "Waiting"}"#)
+            .await
+            .unwrap();
+        dispatch.assert_fired(&["desktop", "phone"]).assert_response(json!({}));
+        assert!(matches!(dispatch.outcome("desktop"), HandlerOutcome::Error(_)));
+        assert!(dispatch.outcome("phone").is_success());
+
+        // Unknown handler types fail as they would outside of tests
+        let dispatch = harness.run(r#"{"tool_name": "Bash"}"#).await.unwrap();
+        dispatch.assert_fired(&[]);
+        assert!(matches!(dispatch.outcome("typo"), HandlerOutcome::Error(e) if e.contains("Unknown handler type")));
+    }
+}
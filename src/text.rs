@@ -0,0 +1,304 @@
+//! Text helpers shared by notification handlers.
+
+use serde_json::{Map, Value};
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Ellipsis appended to truncated text.
+pub const ELLIPSIS: char = '…';
+
+/// Truncates `s` to at most `max` characters, ending with an ellipsis when cut.
+///
+/// Counts Unicode scalar values rather than bytes, so multibyte characters
+/// (emoji, CJK, etc.) are never split.
+///
+/// # Examples
+///
+/// ```
+/// use boopifier::text::truncate_chars;
+///
+/// assert_eq!(truncate_chars("hello world", 8), "hello w…");
+/// assert_eq!(truncate_chars("short", 8), "short");
+/// ```
+pub fn truncate_chars(s: &str, max: usize) -> String {
+    if max == 0 {
+        return String::new();
+    }
+
+    // Find the byte offset of the (max+1)th char; if there isn't one, no cut is needed
+    match s.char_indices().nth(max) {
+        None => s.to_string(),
+        Some(_) => {
+            let keep = s.char_indices().nth(max - 1).map(|(i, _)| i).unwrap_or(0);
+            let mut truncated = s[..keep].to_string();
+            truncated.push(ELLIPSIS);
+            truncated
+        }
+    }
+}
+
+/// Maximum message length, in characters, for each service handlers deliver to.
+///
+/// Handlers truncate to these limits so oversized messages are shortened
+/// visibly instead of being rejected (or silently cut) by the service.
+/// A handler's `max_body_length` config overrides its entry.
+pub const SERVICE_LIMITS: &[(&str, usize)] = &[
+    ("desktop", 1_000),
+    ("discord", 2_000),
+    ("ntfy", 4_096),
+    ("signal", 2_000),
+    ("slack", 40_000),
+    ("sms", 160),
+];
+
+/// Looks up the message length limit for `service` in [`SERVICE_LIMITS`].
+pub fn service_limit(service: &str) -> Option<usize> {
+    SERVICE_LIMITS
+        .iter()
+        .find(|(name, _)| *name == service)
+        .map(|&(_, limit)| limit)
+}
+
+/// Splits `s` into consecutive chunks of at most `max` characters.
+///
+/// Used to send long text as several SMS segments. Like [`truncate_chars`],
+/// this never splits a multibyte character. Returns no chunks for empty text.
+///
+/// # Examples
+///
+/// ```
+/// use boopifier::text::split_chars;
+///
+/// assert_eq!(split_chars("abcdefg", 3), vec!["abc", "def", "g"]);
+/// ```
+pub fn split_chars(s: &str, max: usize) -> Vec<String> {
+    if max == 0 {
+        return Vec::new();
+    }
+
+    let chars: Vec<char> = s.chars().collect();
+    chars.chunks(max).map(|chunk| chunk.iter().collect()).collect()
+}
+
+/// Removes terminal escape sequences and control characters from `s`.
+///
+/// Event fields often carry terminal output. Its color codes show up as
+/// garbage in a notification, and in a terminal (a shell prompt, say) an
+/// escape sequence could retitle the window or rewrite what's on screen.
+/// ANSI escape sequences (CSI, OSC and the like) are removed whole; other
+/// control characters, including `\r`, are dropped. Newlines and tabs are kept.
+///
+/// # Examples
+///
+/// ```
+/// use boopifier::text::strip_control;
+///
+/// assert_eq!(strip_control("\x1b[31merror\x1b[0m: failed\x07"), "error: failed");
+/// assert_eq!(strip_control("\x1b]0;pwned\x07done\n"), "done\n");
+/// ```
+pub fn strip_control(s: &str) -> String {
+    let mut stripped = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' | '\t' => stripped.push(c),
+            '\u{1b}' => skip_escape(&mut chars),
+            // 8-bit forms of CSI, and of OSC and the other string sequences
+            '\u{9b}' => skip_csi(&mut chars),
+            '\u{90}' | '\u{98}' | '\u{9d}' | '\u{9e}' | '\u{9f}' => skip_control_string(&mut chars),
+            c if c.is_control() => {}
+            c => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+/// Skips the rest of an escape sequence whose ESC was just read.
+fn skip_escape(chars: &mut Peekable<Chars<'_>>) {
+    match chars.next() {
+        Some('[') => skip_csi(chars),
+        // OSC, DCS, SOS, PM and APC run until a string terminator
+        Some(']' | 'P' | 'X' | '^' | '_') => skip_control_string(chars),
+        // Intermediate bytes, then one final byte (e.g. `ESC ( B`)
+        Some(' '..='/') => {
+            while chars.next_if(|c| (' '..='/').contains(c)).is_some() {}
+            chars.next();
+        }
+        // Everything else is a two-character sequence such as `ESC c`
+        _ => {}
+    }
+}
+
+/// Skips the parameters and final byte of a CSI sequence (e.g. `31m`).
+///
+/// A malformed sequence ends at the first character that can't be part of it,
+/// which is kept.
+fn skip_csi(chars: &mut Peekable<Chars<'_>>) {
+    while chars.next_if(|c| (' '..='?').contains(c)).is_some() {}
+    chars.next_if(|c| ('@'..='~').contains(c));
+}
+
+/// Skips a control string up to and including its terminator (BEL or ST).
+fn skip_control_string(chars: &mut Peekable<Chars<'_>>) {
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{7}' | '\u{9c}' => return,
+            '\u{1b}' if chars.next_if_eq(&'\\').is_some() => return,
+            _ => {}
+        }
+    }
+}
+
+/// Reduces a locale such as `de_DE.UTF-8` to its lowercase language code (`de`).
+///
+/// Returns `None` for the `C`/`POSIX` locales and anything without a
+/// language code.
+///
+/// # Examples
+///
+/// ```
+/// use boopifier::text::language_code;
+///
+/// assert_eq!(language_code("de_DE.UTF-8").as_deref(), Some("de"));
+/// assert_eq!(language_code("pt-BR").as_deref(), Some("pt"));
+/// assert_eq!(language_code("C.UTF-8"), None);
+/// ```
+pub fn language_code(locale: &str) -> Option<String> {
+    let code = locale
+        .split(['.', '@'])
+        .next()?
+        .split(['_', '-'])
+        .next()?
+        .to_ascii_lowercase();
+
+    let valid = !code.is_empty() && code.chars().all(|c| c.is_ascii_alphabetic());
+    (valid && code != "c" && code != "posix").then_some(code)
+}
+
+/// Picks the entry of `templates` for the language of `locale`.
+///
+/// Keys are language codes such as `en` or `de`, matched case-insensitively.
+/// Falls back to the `default` entry when the language has none (or no locale
+/// is given), and returns `None` if there is no `default` either.
+pub fn select_localized<'a>(templates: &'a Map<String, Value>, locale: Option<&str>) -> Option<&'a Value> {
+    locale
+        .and_then(language_code)
+        .and_then(|code| {
+            templates
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(&code))
+                .map(|(_, value)| value)
+        })
+        .or_else(|| templates.get("default"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_no_truncation_needed() {
+        assert_eq!(truncate_chars("abc", 3), "abc");
+        assert_eq!(truncate_chars("", 3), "");
+    }
+
+    #[test]
+    fn test_truncates_ascii() {
+        assert_eq!(truncate_chars("abcdef", 4), "abc…");
+        assert_eq!(truncate_chars("abcdef", 1), "…");
+        assert_eq!(truncate_chars("abcdef", 0), "");
+    }
+
+    #[test]
+    fn test_multibyte_at_boundary() {
+        // Each emoji is 4 bytes; byte slicing at 5 would panic
+        let s = "🎉🎉🎉🎉";
+        assert_eq!(truncate_chars(s, 4), s);
+        assert_eq!(truncate_chars(s, 3), "🎉🎉…");
+        assert_eq!(truncate_chars("héllo wörld", 6), "héllo…");
+        assert_eq!(truncate_chars("日本語のテキスト", 4), "日本語…");
+    }
+
+    #[test]
+    fn test_result_never_exceeds_max() {
+        let s = "ñ".repeat(50);
+        for max in 0..60 {
+            assert!(truncate_chars(&s, max).chars().count() <= max);
+        }
+    }
+
+    #[test]
+    fn test_service_limits() {
+        assert_eq!(service_limit("discord"), Some(2_000));
+        assert_eq!(service_limit("sms"), Some(160));
+        assert_eq!(service_limit("carrier-pigeon"), None);
+    }
+
+    #[test]
+    fn test_split_chars() {
+        assert_eq!(split_chars("", 3), Vec::<String>::new());
+        assert_eq!(split_chars("abcdef", 3), vec!["abc", "def"]);
+        assert_eq!(split_chars("🎉🎉🎉", 2), vec!["🎉🎉", "🎉"]);
+        assert_eq!(split_chars("abc", 0), Vec::<String>::new());
+
+        let sms = "x".repeat(161);
+        let segments = split_chars(&sms, 160);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1], "x");
+    }
+
+    #[test]
+    fn test_strip_control() {
+        assert_eq!(strip_control("plain text, ünïcode 🎉"), "plain text, ünïcode 🎉");
+        assert_eq!(strip_control("line one\n\tline two\r\n"), "line one\n\tline two\n");
+
+        // SGR colors, cursor movement and erasing
+        assert_eq!(strip_control("\x1b[1;31mFAILED\x1b[0m tests"), "FAILED tests");
+        assert_eq!(strip_control("50%\x1b[2K\x1b[1G100%"), "50%100%");
+        assert_eq!(strip_control("\x1b[?25lhidden cursor\x1b[?25h"), "hidden cursor");
+
+        // Window titles and hyperlinks, terminated by BEL or ST
+        assert_eq!(strip_control("\x1b]0;owned\x07title"), "title");
+        assert_eq!(strip_control("\x1b]8;;https://evil.example\x1b\\link\x1b]8;;\x1b\\"), "link");
+        assert_eq!(strip_control("\x1bPq#0;2;0;0;0\x1b\\sixel"), "sixel");
+
+        // Two-character and charset sequences, and 8-bit forms
+        assert_eq!(strip_control("\x1bcreset \x1b(Bascii"), "reset ascii");
+        assert_eq!(strip_control("\u{9b}31mred\u{9d}0;title\u{9c}"), "red");
+
+        // Other control characters, and truncated sequences
+        assert_eq!(strip_control("a\x00b\x08c\x7fd\u{85}e"), "abcde");
+        assert_eq!(strip_control("done\x1b"), "done");
+        assert_eq!(strip_control("\x1b[31"), "");
+        assert_eq!(strip_control("\x1b[31\nnext"), "\nnext");
+    }
+
+    #[test]
+    fn test_language_code() {
+        assert_eq!(language_code("de_DE.UTF-8").as_deref(), Some("de"));
+        assert_eq!(language_code("sr_RS@latin").as_deref(), Some("sr"));
+        assert_eq!(language_code("EN").as_deref(), Some("en"));
+        assert_eq!(language_code("fr").as_deref(), Some("fr"));
+        assert_eq!(language_code("C"), None);
+        assert_eq!(language_code("POSIX"), None);
+        assert_eq!(language_code(""), None);
+        assert_eq!(language_code("{{env.LANG}}"), None);
+    }
+
+    #[test]
+    fn test_select_localized() {
+        let templates = json!({"en": "Done", "DE": "Fertig", "default": "OK"});
+        let templates = templates.as_object().unwrap();
+
+        assert_eq!(select_localized(templates, Some("de_AT.UTF-8")), Some(&json!("Fertig")));
+        assert_eq!(select_localized(templates, Some("en_US")), Some(&json!("Done")));
+        assert_eq!(select_localized(templates, Some("ja_JP.UTF-8")), Some(&json!("OK")));
+        assert_eq!(select_localized(templates, Some("C")), Some(&json!("OK")));
+        assert_eq!(select_localized(templates, None), Some(&json!("OK")));
+
+        let templates = json!({"en": "Done"});
+        assert_eq!(select_localized(templates.as_object().unwrap(), Some("de")), None);
+    }
+}
@@ -0,0 +1,263 @@
+//! End-to-end tests running the `boopifier` binary.
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::fs;
+use std::io::Read;
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_config_from_stdin_with_event_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let status = dir.path().join("status");
+    let event = dir.path().join("event.json");
+    fs::write(&event, r#"{"hook_event_name": "Stop"}"#).unwrap();
+
+    let config = serde_json::json!({
+        "handlers": [{
+            "name": "status",
+            "type": "status_file",
+            "config": {"file": status.display().to_string(), "status": "done: {{hook_event_name}}"}
+        }]
+    });
+
+    cargo_bin_cmd!("boopifier")
+        .args(["--config", "-", "--event-file"])
+        .arg(&event)
+        .write_stdin(config.to_string())
+        .assert()
+        .success()
+        .stdout("{}\n");
+
+    assert_eq!(fs::read_to_string(&status).unwrap(), "done: Stop\n");
+}
+
+#[test]
+fn test_config_from_stdin_requires_event_file() {
+    cargo_bin_cmd!("boopifier")
+        .args(["--config", "-"])
+        .write_stdin(r#"{"handlers": []}"#)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--event-file"));
+}
+
+#[test]
+fn test_fire_and_forget_returns_before_handlers_finish() {
+    let dir = tempfile::tempdir().unwrap();
+    let status = dir.path().join("status");
+
+    // A webhook endpoint that accepts the request but never answers
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+    let config_path = dir.path().join("boopifier.json");
+    let config = serde_json::json!({
+        "fire_and_forget": true,
+        "response_extra": {"suppressOutput": true},
+        "handlers": [
+            {"name": "slow", "type": "webhook", "config": {"url": url}},
+            {"name": "status", "type": "status_file", "config": {"file": status.display().to_string(), "status": "done"}}
+        ]
+    });
+    fs::write(&config_path, config.to_string()).unwrap();
+
+    // The hook answers while the webhook request is still pending
+    cargo_bin_cmd!("boopifier")
+        .arg("--config")
+        .arg(&config_path)
+        .write_stdin(r#"{"hook_event_name": "Stop"}"#)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout("{\"suppressOutput\":true}\n");
+
+    // ...and the detached sender still delivers
+    let (mut stream, _) = listener.accept().unwrap();
+    stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+    let mut request = [0; 16];
+    stream.read_exact(&mut request).unwrap();
+    assert!(request.starts_with(b"POST /hook"));
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !status.exists() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(fs::read_to_string(&status).unwrap(), "done\n");
+}
+
+#[test]
+fn test_previous_event_persisted_between_runs() {
+    let dir = tempfile::tempdir().unwrap();
+    let status = dir.path().join("status");
+    let config_path = dir.path().join("boopifier.json");
+    let config = serde_json::json!({
+        "handlers": [{
+            "name": "status",
+            "type": "status_file",
+            "config": {"file": status.display().to_string(), "status": "{{prev.hook_event_name}} -> {{hook_event_name}}"}
+        }]
+    });
+    fs::write(&config_path, config.to_string()).unwrap();
+
+    let run = |event: &str| {
+        cargo_bin_cmd!("boopifier")
+            .env("HOME", dir.path())
+            .arg("--config")
+            .arg(&config_path)
+            .write_stdin(event.to_string())
+            .assert()
+            .success();
+        fs::read_to_string(&status).unwrap()
+    };
+
+    assert_eq!(
+        run(r#"{"hook_event_name": "UserPromptSubmit", "session_id": "s1"}"#),
+        "{{prev.hook_event_name}} -> UserPromptSubmit\n"
+    );
+    assert_eq!(run(r#"{"hook_event_name": "Stop", "session_id": "s1"}"#), "UserPromptSubmit -> Stop\n");
+
+    // Each session has its own previous event
+    assert_eq!(run(r#"{"hook_event_name": "Stop", "session_id": "s2"}"#), "{{prev.hook_event_name}} -> Stop\n");
+}
+
+#[test]
+fn test_several_events_on_stdin() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("boopifier.json");
+    let config = serde_json::json!({
+        "handlers": [{
+            "name": "keep-going",
+            "type": "noop",
+            "match_rules": {"hook_event_name": "Stop"},
+            "block_reason": "Finish {{session_id}}",
+            "config": {}
+        }]
+    });
+    fs::write(&config_path, config.to_string()).unwrap();
+
+    let output = cargo_bin_cmd!("boopifier")
+        .env("HOME", dir.path())
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--multi-event")
+        .write_stdin("{\"hook_event_name\": \"Notification\"}\n{\"hook_event_name\": \"Stop\", \"session_id\": \"s1\"}\n{oops")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    // One response per event, in order
+    let responses: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses, [serde_json::json!({}), serde_json::json!({"decision": "block", "reason": "Finish s1"})]);
+}
+
+#[test]
+fn test_single_event_rejects_trailing_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("boopifier.json");
+    fs::write(&config_path, r#"{"handlers": []}"#).unwrap();
+
+    cargo_bin_cmd!("boopifier")
+        .env("HOME", dir.path())
+        .arg("--config")
+        .arg(&config_path)
+        .write_stdin("{\"hook_event_name\": \"Stop\"} {oops")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Invalid JSON: trailing characters after the event"));
+}
+
+#[test]
+fn test_answers_first_event_without_waiting_for_eof() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("boopifier.json");
+    fs::write(&config_path, r#"{"handlers": []}"#).unwrap();
+
+    // Stdin stays open, as it may when Claude Code runs the hook
+    let mut child = Command::new(env!("CARGO_BIN_EXE_boopifier"))
+        .env("HOME", dir.path())
+        .arg("--config")
+        .arg(&config_path)
+        .args(["--stdin-timeout-ms", "0"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(br#"{"hook_event_name": "Notification"}"#).unwrap();
+    stdin.flush().unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while child.try_wait().unwrap().is_none() {
+        if Instant::now() > deadline {
+            child.kill().unwrap();
+            panic!("boopifier waited for EOF after a complete event");
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    let mut stdout = String::new();
+    child.stdout.take().unwrap().read_to_string(&mut stdout).unwrap();
+    assert_eq!(stdout.trim(), "{}");
+    drop(stdin);
+}
+
+#[test]
+fn test_unknown_hook_continues_even_when_fail_closed() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("boopifier.json");
+    fs::write(&config_path, r#"{"handlers": []}"#).unwrap();
+
+    cargo_bin_cmd!("boopifier")
+        .env("HOME", dir.path())
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--fail-closed")
+        .write_stdin(r#"{"hook_event_name": "SomeFutureHook"}"#)
+        .assert()
+        .success()
+        .stdout("{}\n");
+}
+
+#[test]
+fn test_validate_reports_problems() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("boopifier.yaml");
+
+    fs::write(
+        &config_path,
+        "handlers:\n  - {name: slack, type: webhook, config: {url: 'https://hooks.example.com/x'}}\n",
+    )
+    .unwrap();
+    cargo_bin_cmd!("boopifier")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--validate")
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with(": OK\n"));
+
+    fs::write(
+        &config_path,
+        "handlers:\n  - {name: slack, type: webhook, config: {}}\n  - {name: typo, type: deskop, config: {}}\n",
+    )
+    .unwrap();
+    cargo_bin_cmd!("boopifier")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--validate")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("2 problem(s)"))
+        .stdout(predicate::str::contains("slack (webhook): Invalid configuration: Webhook handler requires 'url'"))
+        .stdout(predicate::str::contains("typo (deskop): unknown handler type 'deskop'"));
+}